@@ -1,4 +1,5 @@
 use crate::keybindings::KeybindingsConfig;
+use crate::services::provider::ProviderKind;
 use crate::services::remote::RemoteProfile;
 use crate::ui::theme::{Theme, DEFAULT_THEME_NAME};
 use serde::{Deserialize, Serialize};
@@ -63,6 +64,54 @@ impl Default for ThemeSettings {
     }
 }
 
+/// AI-drafted commit messages in the git screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitDraftSettings {
+    /// AI provider: "claude" or "codex"
+    #[serde(default = "default_commit_draft_provider")]
+    pub provider: String,
+    /// Style instructions sent with the staged diff
+    #[serde(default = "default_commit_draft_style")]
+    pub style: String,
+}
+
+fn default_commit_draft_provider() -> String {
+    ProviderKind::Claude.as_str().to_string()
+}
+
+fn default_commit_draft_style() -> String {
+    "Write the commit message in the Conventional Commits format: a subject line \
+     `type(scope): summary` of at most 72 characters in the imperative mood, \
+     a blank line, then a short body explaining what changed and why. \
+     Use one of the types feat, fix, refactor, perf, docs, test, build, ci, chore."
+        .to_string()
+}
+
+impl Default for CommitDraftSettings {
+    fn default() -> Self {
+        Self {
+            provider: default_commit_draft_provider(),
+            style: default_commit_draft_style(),
+        }
+    }
+}
+
+impl CommitDraftSettings {
+    /// Configured provider, falling back to Claude for unknown names
+    pub fn provider_kind(&self) -> ProviderKind {
+        ProviderKind::from_str(&self.provider).unwrap_or(ProviderKind::Claude)
+    }
+}
+
+/// Language server started by the file editor for one language
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LspServerSettings {
+    /// Executable, looked up in PATH
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -85,7 +134,7 @@ pub struct Settings {
     /// Active panel index
     #[serde(default)]
     pub active_panel_index: usize,
-    /// DIFF compare method: "content", "modified_time", "content_and_time"
+    /// DIFF compare method: "content", "modified_time", "content_and_time", "size", "hash"
     #[serde(default = "default_diff_compare_method")]
     pub diff_compare_method: String,
     /// Remote server profiles for SSH/SFTP connections
@@ -97,6 +146,14 @@ pub struct Settings {
     /// Encryption split size in MB (0 = no split)
     #[serde(default = "default_encrypt_split_size")]
     pub encrypt_split_size: u64,
+    /// AI commit message drafts (git screen)
+    #[serde(default)]
+    pub commit_draft: CommitDraftSettings,
+    /// Language servers of the file editor, keyed by LSP language id
+    /// Example: {"rust": {"command": "rust-analyzer"},
+    ///           "python": {"command": "pylsp", "args": ["-v"]}}
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub lsp_servers: HashMap<String, LspServerSettings>,
 }
 
 impl Default for Settings {
@@ -143,6 +200,8 @@ impl Default for Settings {
             remote_profiles: Vec::new(),
             keybindings: KeybindingsConfig::default(),
             encrypt_split_size: default_encrypt_split_size(),
+            commit_draft: CommitDraftSettings::default(),
+            lsp_servers: HashMap::new(),
         }
     }
 }
@@ -158,6 +217,11 @@ impl Settings {
         Self::config_dir().map(|d| d.join("themes"))
     }
 
+    /// Returns the user grammar directory path (~/.remotecc/syntaxes)
+    pub fn syntaxes_dir() -> Option<PathBuf> {
+        Self::config_dir().map(|d| d.join("syntaxes"))
+    }
+
     /// Returns the config file path (~/.remotecc/settings.json)
     pub fn config_path() -> Option<PathBuf> {
         Self::config_dir().map(|d| d.join("settings.json"))
@@ -206,6 +270,13 @@ impl Settings {
             }
         }
 
+        // Create ~/.remotecc/syntaxes/
+        if let Some(syntaxes_dir) = Self::syntaxes_dir() {
+            if !syntaxes_dir.exists() {
+                let _ = fs::create_dir_all(&syntaxes_dir);
+            }
+        }
+
         // Create default settings.json if not exists
         if let Some(config_path) = Self::config_path() {
             if !config_path.exists() {
@@ -301,6 +301,7 @@ pub enum PanelAction {
     GoHomeDir,
     Refresh,
     GitLogDiff,
+    FileHistory,
     StartDiff,
     ClosePanel,
     #[serde(rename = "ai_screen")]
@@ -518,6 +519,10 @@ pub fn default_panel_keybindings() -> HashMap<PanelAction, Vec<String>> {
         PanelAction::GitLogDiff,
         vec!["//Git log diff".into(), "7".into()],
     );
+    m.insert(
+        PanelAction::FileHistory,
+        vec!["//Git history of file".into(), "l".into()],
+    );
     m.insert(
         PanelAction::StartDiff,
         vec!["//Start diff".into(), "8".into()],
@@ -574,6 +579,13 @@ pub enum EditorAction {
     DeleteLine,
     DuplicateLine,
     SelectNextOccurrence,
+    SelectAllOccurrences,
+    AddCursorAbove,
+    AddCursorBelow,
+    BlockSelectUp,
+    BlockSelectDown,
+    BlockSelectLeft,
+    BlockSelectRight,
     SelectLine,
     ToggleComment,
     Indent,
@@ -590,6 +602,24 @@ pub enum EditorAction {
     GoToFileEnd,
     MoveLineUp,
     MoveLineDown,
+    FileHistory,
+    ConvertEncoding,
+    ConvertLineEnding,
+    ToggleFinalNewline,
+    OpenFile,
+    NextBuffer,
+    PrevBuffer,
+    BufferList,
+    CloseBuffer,
+    SplitHorizontal,
+    SplitVertical,
+    FocusOtherSplit,
+    CloseSplit,
+    LspHover,
+    LspDefinition,
+    LspCompletion,
+    LspFormat,
+    NextDiagnostic,
     Exit,
 }
 
@@ -621,6 +651,10 @@ pub fn default_editor_keybindings() -> HashMap<EditorAction, Vec<String>> {
         EditorAction::SelectNextOccurrence,
         vec!["//Select next occurrence".into(), "ctrl+d".into()],
     );
+    m.insert(
+        EditorAction::SelectAllOccurrences,
+        vec!["//Select all occurrences".into(), "ctrl+shift+l".into()],
+    );
     m.insert(
         EditorAction::SelectLine,
         vec!["//Select line".into(), "ctrl+l".into()],
@@ -728,10 +762,125 @@ pub fn default_editor_keybindings() -> HashMap<EditorAction, Vec<String>> {
         vec!["//Move line down".into(), "alt+down".into()],
     );
 
+    // Multiple cursors & block selection
+    m.insert(
+        EditorAction::AddCursorAbove,
+        vec!["//Add cursor above".into(), "ctrl+alt+up".into()],
+    );
+    m.insert(
+        EditorAction::AddCursorBelow,
+        vec!["//Add cursor below".into(), "ctrl+alt+down".into()],
+    );
+    m.insert(
+        EditorAction::BlockSelectUp,
+        vec!["//Extend block selection up".into(), "alt+shift+up".into()],
+    );
+    m.insert(
+        EditorAction::BlockSelectDown,
+        vec![
+            "//Extend block selection down".into(),
+            "alt+shift+down".into(),
+        ],
+    );
+    m.insert(
+        EditorAction::BlockSelectLeft,
+        vec![
+            "//Extend block selection left".into(),
+            "alt+shift+left".into(),
+        ],
+    );
+    m.insert(
+        EditorAction::BlockSelectRight,
+        vec![
+            "//Extend block selection right".into(),
+            "alt+shift+right".into(),
+        ],
+    );
+
+    // Git
+    m.insert(
+        EditorAction::FileHistory,
+        vec!["//Git history of file".into(), "alt+h".into()],
+    );
+
+    // Encoding
+    m.insert(
+        EditorAction::ConvertEncoding,
+        vec!["//Convert to next encoding".into(), "alt+e".into()],
+    );
+    m.insert(
+        EditorAction::ConvertLineEnding,
+        vec!["//Convert line endings (LF/CRLF/CR)".into(), "alt+l".into()],
+    );
+    m.insert(
+        EditorAction::ToggleFinalNewline,
+        vec!["//Toggle newline at end of file".into(), "alt+n".into()],
+    );
+
+    // Buffers & splits
+    m.insert(
+        EditorAction::OpenFile,
+        vec!["//Open file in a new buffer".into(), "ctrl+o".into()],
+    );
+    m.insert(
+        EditorAction::NextBuffer,
+        vec!["//Next buffer".into(), "ctrl+pagedown".into()],
+    );
+    m.insert(
+        EditorAction::PrevBuffer,
+        vec!["//Previous buffer".into(), "ctrl+pageup".into()],
+    );
+    m.insert(
+        EditorAction::BufferList,
+        vec!["//List open buffers".into(), "alt+b".into()],
+    );
+    m.insert(
+        EditorAction::CloseBuffer,
+        vec!["//Close buffer".into(), "alt+w".into()],
+    );
+    m.insert(
+        EditorAction::SplitHorizontal,
+        vec!["//Split editor top and bottom".into(), "alt+s".into()],
+    );
+    m.insert(
+        EditorAction::SplitVertical,
+        vec!["//Split editor side by side".into(), "alt+v".into()],
+    );
+    m.insert(
+        EditorAction::FocusOtherSplit,
+        vec!["//Focus the other split".into(), "alt+o".into()],
+    );
+    m.insert(
+        EditorAction::CloseSplit,
+        vec!["//Close the other split".into(), "alt+q".into()],
+    );
+
+    // Language server
+    m.insert(
+        EditorAction::LspHover,
+        vec!["//Show type and docs at cursor".into(), "alt+i".into()],
+    );
+    m.insert(
+        EditorAction::LspDefinition,
+        vec!["//Go to definition".into(), "f12".into()],
+    );
+    m.insert(
+        EditorAction::LspCompletion,
+        vec!["//Complete word".into(), "ctrl+space".into()],
+    );
+    m.insert(
+        EditorAction::LspFormat,
+        vec!["//Format document".into(), "alt+f".into()],
+    );
+    m.insert(
+        EditorAction::NextDiagnostic,
+        vec!["//Go to next diagnostic".into(), "f8".into()],
+    );
+
     // Exit
     m.insert(
         EditorAction::Exit,
-        vec!["//Close editor".into(), "esc".into()],
+        vec!["//Close editor (all buffers)".into(), "esc".into()],
     );
 
     m
@@ -889,6 +1038,19 @@ pub enum DiffFileViewAction {
     GoEnd,
     NextChange,
     PrevChange,
+    ToggleMergeView,
+    TakeLeft,
+    TakeRight,
+    TakeBoth,
+    TakeBase,
+    EditChunk,
+    Save,
+    ToggleIgnoreWhitespace,
+    ToggleIgnoreCase,
+    ToggleInlineMode,
+    CopyToRight,
+    CopyToLeft,
+    ExportPatch,
 }
 
 pub fn default_diff_file_view_keybindings() -> HashMap<DiffFileViewAction, Vec<String>> {
@@ -929,6 +1091,76 @@ pub fn default_diff_file_view_keybindings() -> HashMap<DiffFileViewAction, Vec<S
         DiffFileViewAction::PrevChange,
         vec!["//Previous change".into(), "shift+n".into(), "p".into()],
     );
+
+    // Three-way merge
+    m.insert(
+        DiffFileViewAction::ToggleMergeView,
+        vec![
+            "//Toggle merged result / side-by-side diff".into(),
+            "m".into(),
+        ],
+    );
+    m.insert(
+        DiffFileViewAction::TakeLeft,
+        vec!["//Take left side for current hunk".into(), "l".into()],
+    );
+    m.insert(
+        DiffFileViewAction::TakeRight,
+        vec!["//Take right side for current hunk".into(), "r".into()],
+    );
+    m.insert(
+        DiffFileViewAction::TakeBoth,
+        vec!["//Take both sides for current hunk".into(), "b".into()],
+    );
+    m.insert(
+        DiffFileViewAction::TakeBase,
+        vec!["//Take base for current hunk".into(), "o".into()],
+    );
+    m.insert(
+        DiffFileViewAction::EditChunk,
+        vec![
+            "//Edit current hunk of the merged result".into(),
+            "enter".into(),
+        ],
+    );
+    m.insert(
+        DiffFileViewAction::Save,
+        vec![
+            "//Save merged result or edited files".into(),
+            "ctrl+s".into(),
+        ],
+    );
+
+    // Comparison options
+    m.insert(
+        DiffFileViewAction::ToggleIgnoreWhitespace,
+        vec!["//Toggle ignore whitespace".into(), "w".into()],
+    );
+    m.insert(
+        DiffFileViewAction::ToggleIgnoreCase,
+        vec!["//Toggle ignore case".into(), "i".into()],
+    );
+    m.insert(
+        DiffFileViewAction::ToggleInlineMode,
+        vec![
+            "//Toggle word / character highlighting in changed lines".into(),
+            "c".into(),
+        ],
+    );
+
+    // Hunk editing
+    m.insert(
+        DiffFileViewAction::CopyToRight,
+        vec!["//Copy current hunk to right".into(), ">".into()],
+    );
+    m.insert(
+        DiffFileViewAction::CopyToLeft,
+        vec!["//Copy current hunk to left".into(), "<".into()],
+    );
+    m.insert(
+        DiffFileViewAction::ExportPatch,
+        vec!["//Export as unified patch".into(), "e".into()],
+    );
     m
 }
 
@@ -955,6 +1187,13 @@ pub enum DiffScreenAction {
     CollapseAll,
     Open,
     Close,
+    SyncToRight,
+    SyncToLeft,
+    DeleteExtra,
+    MirrorToRight,
+    MirrorToLeft,
+    ExportPatch,
+    ExportReport,
 }
 
 pub fn default_diff_screen_keybindings() -> HashMap<DiffScreenAction, Vec<String>> {
@@ -1042,6 +1281,47 @@ pub fn default_diff_screen_keybindings() -> HashMap<DiffScreenAction, Vec<String
         vec!["//Return to file panel".into(), "esc".into()],
     );
 
+    // Sync
+    m.insert(
+        DiffScreenAction::SyncToRight,
+        vec![
+            "//Copy selected differences left to right".into(),
+            ">".into(),
+        ],
+    );
+    m.insert(
+        DiffScreenAction::SyncToLeft,
+        vec![
+            "//Copy selected differences right to left".into(),
+            "<".into(),
+        ],
+    );
+    m.insert(
+        DiffScreenAction::DeleteExtra,
+        vec!["//Delete selected one-side-only items".into(), "x".into()],
+    );
+    m.insert(
+        DiffScreenAction::MirrorToRight,
+        vec!["//Mirror whole tree left to right".into(), "}".into()],
+    );
+    m.insert(
+        DiffScreenAction::MirrorToLeft,
+        vec!["//Mirror whole tree right to left".into(), "{".into()],
+    );
+
+    // Export
+    m.insert(
+        DiffScreenAction::ExportPatch,
+        vec![
+            "//Export selected differences (or whole tree) as unified patch".into(),
+            "p".into(),
+        ],
+    );
+    m.insert(
+        DiffScreenAction::ExportReport,
+        vec!["//Export comparison as HTML report".into(), "r".into()],
+    );
+
     m
 }
 
@@ -1067,6 +1347,11 @@ pub enum ViewerAction {
     ToggleWrap,
     ToggleHex,
     GotoLine,
+    ToggleBlame,
+    FileHistory,
+    Follow,
+    Filter,
+    CycleEncoding,
 }
 
 pub fn default_viewer_keybindings() -> HashMap<ViewerAction, Vec<String>> {
@@ -1139,6 +1424,30 @@ pub fn default_viewer_keybindings() -> HashMap<ViewerAction, Vec<String>> {
         ViewerAction::GotoLine,
         vec!["//Go to line".into(), "ctrl+g".into(), ":".into()],
     );
+    m.insert(
+        ViewerAction::ToggleBlame,
+        vec!["//Toggle git blame".into(), "a".into()],
+    );
+    m.insert(
+        ViewerAction::FileHistory,
+        vec!["//Git history of file".into(), "l".into()],
+    );
+    m.insert(
+        ViewerAction::Follow,
+        vec![
+            "//Follow appends (tail -f)".into(),
+            "f".into(),
+            "shift+f".into(),
+        ],
+    );
+    m.insert(
+        ViewerAction::Filter,
+        vec!["//Show only lines matching a regex".into(), "&".into()],
+    );
+    m.insert(
+        ViewerAction::CycleEncoding,
+        vec!["//Reopen with next encoding".into(), "shift+e".into()],
+    );
     m
 }
 
@@ -1066,6 +1066,18 @@ fn run_app<B: ratatui::backend::Backend>(
                 .as_ref()
                 .map(|s| s.is_comparing)
                 .unwrap_or(false);
+        let is_git_job_running = app.current_screen == Screen::GitScreen
+            && app
+                .git_screen_state
+                .as_ref()
+                .map(|s| s.is_job_running() || s.is_drafting())
+                .unwrap_or(false);
+        let is_viewer_busy = app.current_screen == Screen::FileViewer
+            && app
+                .viewer_state
+                .as_ref()
+                .map(|s| s.is_busy())
+                .unwrap_or(false);
         let is_dedup_active = app.current_screen == Screen::DedupScreen
             && app
                 .dedup_screen_state
@@ -1088,6 +1100,8 @@ fn run_app<B: ratatui::backend::Backend>(
             || is_file_info_calculating
             || is_image_loading
             || is_diff_comparing
+            || is_git_job_running
+            || is_viewer_busy
         {
             Duration::from_millis(100) // Fast polling for spinner animation
         } else {
@@ -1105,6 +1119,15 @@ fn run_app<B: ratatui::backend::Backend>(
             }
         }
 
+        // Swap files, on-disk change checks and language servers of the editor's buffers
+        if app.current_screen == Screen::FileEditor {
+            if let Some(ref mut state) = app.editor_state {
+                app.editor_buffers.autosave(state);
+                app.editor_buffers
+                    .lsp_tick(state, &app.settings.lsp_servers, app.theme.syntax);
+            }
+        }
+
         // Poll for file info calculation if on FileInfo screen
         if app.current_screen == Screen::FileInfo {
             if let Some(ref mut state) = app.file_info_state {
@@ -1142,9 +1165,28 @@ fn run_app<B: ratatui::backend::Backend>(
             }
         }
 
+        // Poll for streamed fetch/pull/push output if on GitScreen
+        if app.current_screen == Screen::GitScreen {
+            if let Some(ref mut state) = app.git_screen_state {
+                state.poll();
+            }
+        }
+
+        // Poll for line indexing and search of a large file in the viewer
+        if app.current_screen == Screen::FileViewer {
+            if let Some(ref mut state) = app.viewer_state {
+                state.poll_background();
+            }
+        }
+
         // Poll for remote spinner completion
         app.poll_remote_spinner();
 
+        // Apply finished background git status refreshes
+        for panel in app.panels.iter_mut() {
+            panel.poll_git_status();
+        }
+
         // Check for theme file changes (hot-reload, only in design mode)
         if app.design_mode && app.theme_watch_state.check_for_changes() {
             app.reload_theme();
@@ -1197,6 +1239,7 @@ fn run_app<B: ratatui::backend::Backend>(
                             crate::services::file_ops::FileOperationType::Download => "Downloaded",
                             crate::services::file_ops::FileOperationType::Encrypt => "Encrypted",
                             crate::services::file_ops::FileOperationType::Decrypt => "Decrypted",
+                            crate::services::file_ops::FileOperationType::Sync => "Synced",
                         };
                         let total = result.success_count + result.failure_count;
                         if result.failure_count == 0 {
@@ -1259,8 +1302,7 @@ fn run_app<B: ratatui::backend::Backend>(
                                             panel_index,
                                             remote_path,
                                         });
-                                    app.editor_state = Some(editor);
-                                    app.current_screen = Screen::FileEditor;
+                                    app.open_editor(editor);
                                 }
                                 Err(e) => {
                                     app.show_message(&format!("Cannot open file: {}", e));
@@ -1290,6 +1332,13 @@ fn run_app<B: ratatui::backend::Backend>(
                     }
                 }
             } else {
+                // Sync from the diff screen: compare again to show the new state
+                if app.current_screen == Screen::DiffScreen {
+                    if let Some(ref mut state) = app.diff_state {
+                        state.message = progress_message.clone();
+                        state.start_comparison();
+                    }
+                }
                 if let Some(msg) = progress_message {
                     app.show_message(&msg);
                 }
@@ -1357,10 +1406,19 @@ fn run_app<B: ratatui::backend::Backend>(
                             }
                         }
                         Screen::FileViewer => {
-                            ui::file_viewer::handle_input(app, key.code, key.modifiers);
+                            // File history dialog
+                            if app.dialog.is_some() {
+                                ui::dialogs::handle_dialog_input(app, key.code, key.modifiers);
+                            } else {
+                                ui::file_viewer::handle_input(app, key.code, key.modifiers);
+                            }
                         }
                         Screen::FileEditor => {
-                            ui::file_editor::handle_input(app, key.code, key.modifiers);
+                            if app.dialog.is_some() {
+                                ui::dialogs::handle_dialog_input(app, key.code, key.modifiers);
+                            } else {
+                                ui::file_editor::handle_input(app, key.code, key.modifiers);
+                            }
                         }
                         Screen::FileInfo => {
                             ui::file_info::handle_input(app, key.code, key.modifiers);
@@ -1427,10 +1485,19 @@ fn run_app<B: ratatui::backend::Backend>(
                             }
                         }
                         Screen::DiffScreen => {
-                            ui::diff_screen::handle_input(app, key.code, key.modifiers);
+                            // Sync confirmation and progress dialogs
+                            if app.dialog.is_some() {
+                                ui::dialogs::handle_dialog_input(app, key.code, key.modifiers);
+                            } else {
+                                ui::diff_screen::handle_input(app, key.code, key.modifiers);
+                            }
                         }
                         Screen::DiffFileView => {
-                            ui::diff_file_view::handle_input(app, key.code, key.modifiers);
+                            if app.dialog.is_some() {
+                                ui::dialogs::handle_dialog_input(app, key.code, key.modifiers);
+                            } else {
+                                ui::diff_file_view::handle_input(app, key.code, key.modifiers);
+                            }
                         }
                         Screen::GitScreen => {
                             ui::git_screen::handle_input(app, key.code, key.modifiers);
@@ -1486,6 +1553,11 @@ fn run_app<B: ratatui::backend::Backend>(
                         _ => {}
                     }
                 }
+                Event::Mouse(mouse)
+                    if app.current_screen == Screen::FileEditor && app.dialog.is_none() =>
+                {
+                    ui::file_editor::handle_mouse(app, mouse);
+                }
                 _ => {}
             }
         }
@@ -1583,6 +1655,7 @@ fn handle_panel_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) ->
             PanelAction::GoHomeDir => app.goto_home(),
             PanelAction::Refresh => app.refresh_panels(),
             PanelAction::GitLogDiff => app.show_git_log_diff_dialog(),
+            PanelAction::FileHistory => app.show_file_history_dialog(),
             PanelAction::StartDiff => app.start_diff(),
             PanelAction::ClosePanel => app.close_panel(),
             PanelAction::AIScreen => app.show_ai_screen(),
@@ -0,0 +1,401 @@
+//! Comparison helpers for the folder diff: ignore rules and the digest cache.
+//!
+//! [`IgnoreRules`] understands the gitignore pattern syntax and is read from
+//! `~/.remotecc/diffignore` and a `.diffignore` file at each compared root.
+//! [`DigestCache`] remembers SHA-256 digests keyed by path, size and modification
+//! time in `~/.remotecc/diff_digests.json`, so hash comparisons of unchanged trees
+//! do not have to read every file again.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Settings;
+
+/// Name of the per-root ignore file
+pub const IGNORE_FILE_NAME: &str = ".diffignore";
+
+const READ_BUF_SIZE: usize = 64 * 1024; // 64KB
+
+/// Cache entries unused for this long are dropped when the cache is saved
+const CACHE_MAX_AGE_SECS: i64 = 30 * 24 * 60 * 60;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Ignore rules
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Ordered list of gitignore-style patterns; the last matching pattern wins
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Parse the contents of an ignore file. Invalid patterns are skipped.
+    pub fn parse(text: &str) -> Self {
+        IgnoreRules {
+            rules: text.lines().filter_map(parse_rule).collect(),
+        }
+    }
+
+    /// Load the global ignore file followed by `.diffignore` in each root
+    pub fn load(roots: &[&Path]) -> Self {
+        let mut rules = IgnoreRules::default();
+        let global = Settings::config_dir().map(|d| d.join("diffignore"));
+        let files = global
+            .into_iter()
+            .chain(roots.iter().map(|root| root.join(IGNORE_FILE_NAME)));
+        for file in files {
+            if let Ok(text) = fs::read_to_string(&file) {
+                rules.rules.extend(IgnoreRules::parse(&text).rules);
+            }
+        }
+        rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// True if any pattern only applies to directories (callers then need the entry type)
+    pub fn has_dir_only_rules(&self) -> bool {
+        self.rules.iter().any(|r| r.dir_only)
+    }
+
+    /// Check a '/'-separated path relative to the compared root
+    pub fn is_ignored(&self, relative_path: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            if rule.regex.is_match(relative_path) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_rule(line: &str) -> Option<IgnoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    // A slash anywhere but the end anchors the pattern to the root
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+    let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+    let regex = Regex::new(&format!("{}{}$", prefix, glob_to_regex(pattern))).ok()?;
+    Some(IgnoreRule {
+        regex,
+        negated,
+        dir_only,
+    })
+}
+
+/// Translate a gitignore glob (`*`, `?`, `[...]`, `**`) into a regex body
+fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_segment_start = i == 0 || chars[i - 1] == '/';
+                match chars.get(i + 2) {
+                    Some('/') if at_segment_start => {
+                        out.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                    None if at_segment_start => {
+                        out.push_str(".*");
+                        i += 2;
+                    }
+                    _ => {
+                        out.push_str("[^/]*");
+                        i += 2;
+                    }
+                }
+            }
+            '*' => {
+                out.push_str("[^/]*");
+                i += 1;
+            }
+            '?' => {
+                out.push_str("[^/]");
+                i += 1;
+            }
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    out.push('[');
+                    let mut j = i + 1;
+                    if matches!(chars[j], '!' | '^') {
+                        out.push('^');
+                        j += 1;
+                    }
+                    for &c in &chars[j..end] {
+                        if matches!(c, '\\' | '[' | ']' | '&' | '~') {
+                            out.push('\\');
+                        }
+                        out.push(c);
+                    }
+                    out.push(']');
+                    i = end + 1;
+                }
+                None => {
+                    out.push_str("\\[");
+                    i += 1;
+                }
+            },
+            '\\' if i + 1 < chars.len() => {
+                out.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+            }
+            c => {
+                out.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Index of the `]` closing the character class opened at `start`
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start + 1;
+    if matches!(chars.get(j), Some('!') | Some('^')) {
+        j += 1;
+    }
+    // A ']' right after the opening bracket is a literal member
+    if chars.get(j) == Some(&']') {
+        j += 1;
+    }
+    (j..chars.len()).find(|&k| chars[k] == ']')
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Digest cache
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DigestEntry {
+    size: u64,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+    digest: String,
+    /// Unix time of the last lookup that used this entry
+    used: i64,
+}
+
+/// SHA-256 digests of files, reused while a file's size and modification time are unchanged
+#[derive(Debug, Default)]
+pub struct DigestCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, DigestEntry>,
+    dirty: bool,
+}
+
+impl DigestCache {
+    /// Returns the cache file path (~/.remotecc/diff_digests.json)
+    pub fn default_path() -> Option<PathBuf> {
+        Settings::config_dir().map(|d| d.join("diff_digests.json"))
+    }
+
+    /// Load the cache stored at `path`; a missing or unreadable file starts empty
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        DigestCache {
+            path: Some(path),
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// A cache that is never written to disk
+    pub fn in_memory() -> Self {
+        DigestCache::default()
+    }
+
+    /// SHA-256 of the file at `path`, from the cache when size and mtime still match
+    pub fn digest(&mut self, path: &Path) -> Option<String> {
+        let metadata = fs::metadata(path).ok()?;
+        let (mtime_secs, mtime_nanos) = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| (d.as_secs() as i64, d.subsec_nanos()))
+            .unwrap_or((0, 0));
+        let key = path.to_string_lossy().to_string();
+        let now = unix_now();
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if entry.size == metadata.len()
+                && entry.mtime_secs == mtime_secs
+                && entry.mtime_nanos == mtime_nanos
+            {
+                if entry.used != now {
+                    entry.used = now;
+                    self.dirty = true;
+                }
+                return Some(entry.digest.clone());
+            }
+        }
+
+        let digest = file_digest(path).ok()?;
+        self.entries.insert(
+            key,
+            DigestEntry {
+                size: metadata.len(),
+                mtime_secs,
+                mtime_nanos,
+                digest: digest.clone(),
+                used: now,
+            },
+        );
+        self.dirty = true;
+        Some(digest)
+    }
+
+    /// Write the cache back if it changed, dropping entries unused for 30 days
+    pub fn save(&mut self) -> io::Result<()> {
+        let path = match self.path {
+            Some(ref path) if self.dirty => path.clone(),
+            _ => return Ok(()),
+        };
+        let cutoff = unix_now() - CACHE_MAX_AGE_SECS;
+        self.entries.retain(|_, e| e.used >= cutoff);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(&self.entries).map_err(io::Error::other)?;
+        // Write to a sibling file first so an interrupted save never truncates the cache
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Hex-encoded SHA-256 of a file's contents
+pub fn file_digest(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; READ_BUF_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_basename_and_dir_only() {
+        let rules = IgnoreRules::parse("# build output\nnode_modules/\n*.log\n\n!keep.log\n");
+        assert!(rules.is_ignored("node_modules", true));
+        assert!(rules.is_ignored("web/node_modules", true));
+        assert!(!rules.is_ignored("node_modules", false));
+        assert!(rules.is_ignored("debug.log", false));
+        assert!(rules.is_ignored("a/b/trace.log", false));
+        assert!(!rules.is_ignored("a/keep.log", false));
+        assert!(!rules.is_ignored("log", false));
+        assert!(rules.has_dir_only_rules());
+    }
+
+    #[test]
+    fn test_ignore_anchored_and_double_star() {
+        let rules = IgnoreRules::parse("/target\ndoc/*.txt\na/**/b\n**/gen/out\nlogs/**\n");
+        assert!(rules.is_ignored("target", true));
+        assert!(!rules.is_ignored("crates/target", true));
+        assert!(rules.is_ignored("doc/notes.txt", false));
+        assert!(!rules.is_ignored("doc/sub/notes.txt", false));
+        assert!(rules.is_ignored("a/b", false));
+        assert!(rules.is_ignored("a/x/y/b", false));
+        assert!(rules.is_ignored("gen/out", false));
+        assert!(rules.is_ignored("src/gen/out", false));
+        assert!(rules.is_ignored("logs/2024/app", false));
+        assert!(!rules.is_ignored("logs", true));
+    }
+
+    #[test]
+    fn test_ignore_classes_and_escapes() {
+        let rules = IgnoreRules::parse("file[0-9].txt\n[!a]?.md\n\\#literal\nx[.rs\n");
+        assert!(rules.is_ignored("file3.txt", false));
+        assert!(!rules.is_ignored("fileX.txt", false));
+        assert!(rules.is_ignored("bc.md", false));
+        assert!(!rules.is_ignored("ab.md", false));
+        assert!(rules.is_ignored("#literal", false));
+        assert!(rules.is_ignored("x[.rs", false));
+    }
+
+    #[test]
+    fn test_digest_cache_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.bin");
+        fs::write(&file, b"hello").unwrap();
+        let cache_path = dir.path().join("cache.json");
+
+        let mut cache = DigestCache::load(cache_path.clone());
+        let first = cache.digest(&file).unwrap();
+        assert_eq!(first, file_digest(&file).unwrap());
+        cache.save().unwrap();
+
+        // A fresh load returns the stored digest without reading the file
+        let mut reloaded = DigestCache::load(cache_path.clone());
+        assert_eq!(reloaded.entries.len(), 1);
+        let key = file.to_string_lossy().to_string();
+        if let Some(entry) = reloaded.entries.get_mut(&key) {
+            entry.digest = "cached".to_string();
+        }
+        assert_eq!(reloaded.digest(&file).as_deref(), Some("cached"));
+
+        // A size change invalidates the entry
+        fs::write(&file, b"hello, world").unwrap();
+        let updated = reloaded.digest(&file).unwrap();
+        assert_eq!(updated, file_digest(&file).unwrap());
+        assert_ne!(updated, first);
+    }
+}
@@ -0,0 +1,508 @@
+//! Export of folder/file comparisons: unified patches and HTML reports.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+use crate::ui::diff_file_view::{compute_line_matches, DiffOptions};
+use crate::ui::diff_screen::{DiffState, DiffStatus, MergeStatus};
+use crate::utils::format::format_size;
+
+/// Context lines around each hunk, as in `diff -u`
+pub const CONTEXT_LINES: usize = 3;
+
+/// One file (or one-side directory) to include in a patch.
+/// A directory present on only one side is walked and emitted file by file.
+#[derive(Debug, Clone)]
+pub struct PatchTarget {
+    pub relative_path: String,
+    pub left: Option<PathBuf>,
+    pub right: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Unified diff of two texts with `old_label`/`new_label` as file names.
+/// Returns an empty string when the texts are identical.
+pub fn unified_diff(
+    old_label: &str,
+    new_label: &str,
+    old: &str,
+    new: &str,
+    context: usize,
+) -> String {
+    let old_lines: Vec<String> = old.split_inclusive('\n').map(String::from).collect();
+    let new_lines: Vec<String> = new.split_inclusive('\n').map(String::from).collect();
+    // Lines keep their terminator, so a missing final newline counts as a change
+    let matches = compute_line_matches(&old_lines, &new_lines, &DiffOptions::default());
+
+    let mut ops = Vec::with_capacity(old_lines.len().max(new_lines.len()));
+    let (mut i, mut j) = (0usize, 0usize);
+    for &(mi, mj) in matches
+        .iter()
+        .chain(std::iter::once(&(old_lines.len(), new_lines.len())))
+    {
+        ops.extend((i..mi).map(Op::Delete));
+        ops.extend((j..mj).map(Op::Insert));
+        if mi < old_lines.len() {
+            ops.push(Op::Equal(mi));
+        }
+        i = mi + 1;
+        j = mj + 1;
+    }
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose context would overlap into one hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    let mut start = changes[0].saturating_sub(context);
+    let mut last = changes[0];
+    for &c in &changes[1..] {
+        if c - last > 2 * context {
+            hunks.push((start, (last + context + 1).min(ops.len())));
+            start = c - context;
+        }
+        last = c;
+    }
+    hunks.push((start, (last + context + 1).min(ops.len())));
+
+    // Old/new line positions before each op
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut old_pos, mut new_pos) = (0usize, 0usize);
+    for op in &ops {
+        positions.push((old_pos, new_pos));
+        match op {
+            Op::Equal(_) => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Op::Delete(_) => old_pos += 1,
+            Op::Insert(_) => new_pos += 1,
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (s, e) in hunks {
+        let hunk = &ops[s..e];
+        let old_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let (old_start, new_start) = positions[s];
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            hunk_range(old_start, old_count),
+            hunk_range(new_start, new_count)
+        );
+        for op in hunk {
+            let (prefix, line) = match *op {
+                Op::Equal(k) => (' ', &old_lines[k]),
+                Op::Delete(k) => ('-', &old_lines[k]),
+                Op::Insert(k) => ('+', &new_lines[k]),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// Hunk header range: "start,count" (1-based), or "start" when count is 1
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+/// Read a file as patchable text; None for binary or non-UTF-8 content
+fn read_text(path: &Path) -> std::io::Result<Option<String>> {
+    let data = fs::read(path)?;
+    if data[..data.len().min(8192)].contains(&0) {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(data).ok())
+}
+
+/// Patch section for a single file pair (either side may be missing).
+pub fn file_patch(
+    relative_path: &str,
+    left: Option<&Path>,
+    right: Option<&Path>,
+) -> std::io::Result<String> {
+    let old_label = match left {
+        Some(_) => format!("a/{}", relative_path),
+        None => "/dev/null".to_string(),
+    };
+    let new_label = match right {
+        Some(_) => format!("b/{}", relative_path),
+        None => "/dev/null".to_string(),
+    };
+    let old = match left {
+        Some(p) => read_text(p)?,
+        None => Some(String::new()),
+    };
+    let new = match right {
+        Some(p) => read_text(p)?,
+        None => Some(String::new()),
+    };
+    match (old, new) {
+        (Some(old), Some(new)) => Ok(unified_diff(
+            &old_label,
+            &new_label,
+            &old,
+            &new,
+            CONTEXT_LINES,
+        )),
+        _ => Ok(format!(
+            "Binary files {} and {} differ\n",
+            old_label, new_label
+        )),
+    }
+}
+
+/// Files under a one-side directory, sorted, as (relative_path, path).
+/// Symlinks are skipped so link loops cannot recurse forever.
+fn walk_files(relative_path: &str, dir: &Path, out: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| (e.file_name().to_string_lossy().into_owned(), e.path()))
+        .collect();
+    children.sort();
+    for (name, path) in children {
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let rel = format!("{}/{}", relative_path, name);
+        if meta.file_type().is_symlink() {
+            continue;
+        } else if meta.is_dir() {
+            walk_files(&rel, &path, out);
+        } else {
+            out.push((rel, path));
+        }
+    }
+}
+
+/// Build one patch from all targets. Returns the patch and the number of files in it.
+pub fn build_patch(targets: &[PatchTarget]) -> std::io::Result<(String, usize)> {
+    let mut patch = String::new();
+    let mut files = 0usize;
+    let mut push = |section: String| {
+        if !section.is_empty() {
+            patch.push_str(&section);
+            files += 1;
+        }
+    };
+    for target in targets {
+        match (&target.left, &target.right) {
+            (Some(dir), None) if dir.is_dir() => {
+                let mut found = Vec::new();
+                walk_files(&target.relative_path, dir, &mut found);
+                for (rel, path) in found {
+                    push(file_patch(&rel, Some(&path), None)?);
+                }
+            }
+            (None, Some(dir)) if dir.is_dir() => {
+                let mut found = Vec::new();
+                walk_files(&target.relative_path, dir, &mut found);
+                for (rel, path) in found {
+                    push(file_patch(&rel, None, Some(&path))?);
+                }
+            }
+            (left, right) => push(file_patch(
+                &target.relative_path,
+                left.as_deref(),
+                right.as_deref(),
+            )?),
+        }
+    }
+    Ok((patch, files))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// HTML report
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn status_label(status: DiffStatus) -> (&'static str, &'static str) {
+    match status {
+        DiffStatus::Same | DiffStatus::DirSame => ("Same", "same"),
+        DiffStatus::Modified | DiffStatus::DirModified => ("Modified", "modified"),
+        DiffStatus::LeftOnly => ("Left only", "left"),
+        DiffStatus::RightOnly => ("Right only", "right"),
+    }
+}
+
+fn merge_label(status: MergeStatus) -> &'static str {
+    match status {
+        MergeStatus::Unchanged => "Unchanged",
+        MergeStatus::ChangedLeft => "Changed left",
+        MergeStatus::ChangedRight => "Changed right",
+        MergeStatus::ChangedBoth => "Changed both",
+        MergeStatus::Conflict => "Conflict",
+    }
+}
+
+const REPORT_STYLE: &str = "\
+body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;margin:24px;color:#222}
+h1{font-size:20px}
+table{border-collapse:collapse;width:100%;font-size:13px}
+th,td{border:1px solid #ddd;padding:4px 8px;text-align:left;white-space:nowrap}
+th{background:#f3f3f3}
+td.num{text-align:right}
+.meta td{border:none;padding:2px 8px}
+.summary span{display:inline-block;margin-right:16px;padding:2px 8px;border-radius:3px}
+.same{color:#666}
+.modified{background:#fff4d6}
+.left{background:#ffe3e3}
+.right{background:#e2f5e2}
+.conflict{color:#b00020;font-weight:bold}
+.dir{font-weight:bold}";
+
+/// Render a self-contained HTML report of the directory comparison.
+pub fn html_report(state: &DiffState, generated: DateTime<Local>) -> String {
+    let mut html = String::new();
+    let left = state.left_root.display().to_string();
+    let right = state.right_root.display().to_string();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Directory comparison: {} vs {}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n",
+        html_escape(&left),
+        html_escape(&right),
+        REPORT_STYLE
+    );
+    html.push_str("<h1>Directory comparison</h1>\n<table class=\"meta\">\n");
+    let mut meta = vec![("Left", left.clone()), ("Right", right.clone())];
+    if let Some(ref base) = state.base_root {
+        meta.push(("Base", base.display().to_string()));
+    }
+    meta.push((
+        "Compare method",
+        state.compare_method.display_name().to_string(),
+    ));
+    meta.push((
+        "Generated",
+        generated.format("%Y-%m-%d %H:%M:%S").to_string(),
+    ));
+    for (key, value) in meta {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            key,
+            html_escape(&value)
+        );
+    }
+    html.push_str("</table>\n");
+
+    // Summary over files only (directory statuses aggregate their children)
+    let files = state.all_entries.iter().filter(|e| !e.is_directory);
+    let mut counts = [0usize; 4];
+    for entry in files {
+        let idx = match entry.status {
+            DiffStatus::Same | DiffStatus::DirSame => 0,
+            DiffStatus::Modified | DiffStatus::DirModified => 1,
+            DiffStatus::LeftOnly => 2,
+            DiffStatus::RightOnly => 3,
+        };
+        counts[idx] += 1;
+    }
+    let _ = writeln!(
+        html,
+        "<p class=\"summary\"><span class=\"same\">Same: {}</span>\
+         <span class=\"modified\">Modified: {}</span>\
+         <span class=\"left\">Left only: {}</span>\
+         <span class=\"right\">Right only: {}</span></p>",
+        counts[0], counts[1], counts[2], counts[3]
+    );
+
+    let three_way = state.base_root.is_some();
+    html.push_str("<table>\n<tr><th>Path</th><th>Status</th>");
+    if three_way {
+        html.push_str("<th>Merge</th>");
+    }
+    html.push_str(
+        "<th>Left size</th><th>Left modified</th><th>Right size</th><th>Right modified</th></tr>\n",
+    );
+    for entry in &state.all_entries {
+        let (label, class) = status_label(entry.status);
+        let name = if entry.is_directory {
+            format!("{}/", entry.relative_path)
+        } else {
+            entry.relative_path.clone()
+        };
+        let _ = write!(
+            html,
+            "<tr class=\"{}\"><td class=\"{}\" style=\"padding-left:{}px\">{}</td><td>{}</td>",
+            class,
+            if entry.is_directory { "dir" } else { "" },
+            8 + entry.depth * 16,
+            html_escape(&name),
+            label
+        );
+        if three_way {
+            let merge = state
+                .merge_status
+                .get(&entry.relative_path)
+                .copied()
+                .unwrap_or(MergeStatus::Unchanged);
+            let _ = write!(
+                html,
+                "<td class=\"{}\">{}</td>",
+                if merge == MergeStatus::Conflict {
+                    "conflict"
+                } else {
+                    ""
+                },
+                merge_label(merge)
+            );
+        }
+        for info in [&entry.left, &entry.right] {
+            match info {
+                Some(info) => {
+                    let size = if info.is_directory {
+                        String::new()
+                    } else {
+                        format_size(info.size)
+                    };
+                    let _ = write!(
+                        html,
+                        "<td class=\"num\">{}</td><td>{}</td>",
+                        size,
+                        info.modified.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+                None => html.push_str("<td></td><td></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=20)
+            .filter(|&i| i != 18)
+            .map(|i| match i {
+                3 => "three\n".to_string(),
+                _ => format!("{}\n", i),
+            })
+            .collect();
+        let patch = unified_diff("a/f", "b/f", &old, &new, 3);
+        assert_eq!(
+            patch,
+            "--- a/f\n+++ b/f\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -15,6 +15,5 @@\n 15\n 16\n 17\n-18\n 19\n 20\n"
+        );
+        assert!(unified_diff("a/f", "b/f", &old, &old, 3).is_empty());
+    }
+
+    #[test]
+    fn test_unified_diff_missing_newline_and_new_file() {
+        let patch = unified_diff("a/f", "b/f", "x\ny", "x\ny\n", 3);
+        assert_eq!(
+            patch,
+            "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n x\n-y\n\\ No newline at end of file\n+y\n"
+        );
+        let patch = unified_diff("/dev/null", "b/f", "", "a\nb\n", 3);
+        assert_eq!(patch, "--- /dev/null\n+++ b/f\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+    }
+
+    #[test]
+    fn test_build_patch_walks_one_side_dirs() {
+        let tmp = TempDir::new().unwrap();
+        let left = tmp.path().join("left");
+        let right = tmp.path().join("right");
+        fs::create_dir_all(left.join("gone/sub")).unwrap();
+        fs::create_dir_all(&right).unwrap();
+        fs::write(left.join("gone/sub/a.txt"), "a\n").unwrap();
+        fs::write(left.join("same.txt"), "s\n").unwrap();
+        fs::write(right.join("same.txt"), "s\n").unwrap();
+        fs::write(left.join("bin"), [0u8, 1, 2]).unwrap();
+        fs::write(right.join("bin"), [0u8, 1, 3]).unwrap();
+
+        let targets = vec![
+            PatchTarget {
+                relative_path: "bin".to_string(),
+                left: Some(left.join("bin")),
+                right: Some(right.join("bin")),
+            },
+            PatchTarget {
+                relative_path: "gone".to_string(),
+                left: Some(left.join("gone")),
+                right: None,
+            },
+            PatchTarget {
+                relative_path: "same.txt".to_string(),
+                left: Some(left.join("same.txt")),
+                right: Some(right.join("same.txt")),
+            },
+        ];
+        let (patch, files) = build_patch(&targets).unwrap();
+        assert_eq!(files, 2);
+        assert_eq!(
+            patch,
+            "Binary files a/bin and b/bin differ\n\
+             --- a/gone/sub/a.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n"
+        );
+    }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(
+            html_escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
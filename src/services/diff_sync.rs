@@ -0,0 +1,851 @@
+//! Synchronisation between the two sides of a folder diff.
+//!
+//! A [`SyncPlan`] is built from the diff screen's entries and lists the copy and
+//! delete operations needed to bring one side in line with the other. The plan
+//! can be summarised as a dry run before it is executed, and execution reports
+//! progress with the same [`ProgressMessage`] stream as `copy_files_with_progress`.
+//! Either side may be a directory on an SFTP server.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use crate::services::file_ops::{self, ProgressMessage};
+use crate::services::remote::{RemoteProfile, SftpSession};
+use crate::utils::format::format_size;
+
+/// One side of a folder diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncSide {
+    Left,
+    Right,
+}
+
+impl SyncSide {
+    pub fn other(self) -> SyncSide {
+        match self {
+            SyncSide::Left => SyncSide::Right,
+            SyncSide::Right => SyncSide::Left,
+        }
+    }
+
+    fn label(self) -> char {
+        match self {
+            SyncSide::Left => 'L',
+            SyncSide::Right => 'R',
+        }
+    }
+}
+
+/// Kind of sync requested from the diff screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Copy selected new/modified items from left to right
+    CopyToRight,
+    /// Copy selected new/modified items from right to left
+    CopyToLeft,
+    /// Delete selected items that exist on one side only
+    DeleteExtra,
+    /// Make the whole right tree identical to the left tree
+    MirrorToRight,
+    /// Make the whole left tree identical to the right tree
+    MirrorToLeft,
+}
+
+impl SyncMode {
+    pub fn display_name(&self) -> &str {
+        match self {
+            SyncMode::CopyToRight => "Copy left → right",
+            SyncMode::CopyToLeft => "Copy right → left",
+            SyncMode::DeleteExtra => "Delete extra",
+            SyncMode::MirrorToRight => "Mirror left → right",
+            SyncMode::MirrorToLeft => "Mirror right → left",
+        }
+    }
+
+    /// Side whose content is copied over the other side (None for DeleteExtra)
+    pub fn source(&self) -> Option<SyncSide> {
+        match self {
+            SyncMode::CopyToRight | SyncMode::MirrorToRight => Some(SyncSide::Left),
+            SyncMode::CopyToLeft | SyncMode::MirrorToLeft => Some(SyncSide::Right),
+            SyncMode::DeleteExtra => None,
+        }
+    }
+
+    /// Mirror modes cover the whole tree and delete extras on the target side
+    pub fn is_mirror(&self) -> bool {
+        matches!(self, SyncMode::MirrorToRight | SyncMode::MirrorToLeft)
+    }
+}
+
+/// Root directory of one side of the sync
+#[derive(Debug, Clone)]
+pub enum SyncRoot {
+    Local(PathBuf),
+    Remote {
+        profile: RemoteProfile,
+        path: String,
+    },
+}
+
+impl SyncRoot {
+    pub fn is_remote(&self) -> bool {
+        matches!(self, SyncRoot::Remote { .. })
+    }
+}
+
+/// A single planned operation, relative to the sync roots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOp {
+    /// Copy `relative_path` from side `from` to the other side, replacing what is there
+    Copy {
+        from: SyncSide,
+        relative_path: String,
+        is_dir: bool,
+        size: u64,
+    },
+    /// Delete `relative_path` from `side`
+    Delete {
+        side: SyncSide,
+        relative_path: String,
+        is_dir: bool,
+    },
+}
+
+/// Ordered list of sync operations between two roots
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub mode: SyncMode,
+    pub left: SyncRoot,
+    pub right: SyncRoot,
+    pub ops: Vec<SyncOp>,
+}
+
+impl SyncPlan {
+    pub fn new(mode: SyncMode, left: SyncRoot, right: SyncRoot) -> Self {
+        Self {
+            mode,
+            left,
+            right,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn copy_count(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, SyncOp::Copy { .. }))
+            .count()
+    }
+
+    pub fn delete_count(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, SyncOp::Delete { .. }))
+            .count()
+    }
+
+    /// Bytes of the planned file copies (directory contents are counted at execution)
+    pub fn copy_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                SyncOp::Copy { size, .. } => *size,
+                SyncOp::Delete { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Dry-run summary: a totals line followed by at most `max_items` operations.
+    /// `+ R: path` overwrites/creates on the right, `- L: path` deletes on the left.
+    pub fn summary_lines(&self, max_items: usize) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Copy {} item(s) ({}), delete {} item(s)",
+            self.copy_count(),
+            format_size(self.copy_bytes()),
+            self.delete_count()
+        )];
+        for op in self.ops.iter().take(max_items) {
+            let line = match op {
+                SyncOp::Copy {
+                    from,
+                    relative_path,
+                    is_dir,
+                    ..
+                } => format!(
+                    "+ {}: {}{}",
+                    from.other().label(),
+                    relative_path,
+                    if *is_dir { "/" } else { "" }
+                ),
+                SyncOp::Delete {
+                    side,
+                    relative_path,
+                    is_dir,
+                } => format!(
+                    "- {}: {}{}",
+                    side.label(),
+                    relative_path,
+                    if *is_dir { "/" } else { "" }
+                ),
+            };
+            lines.push(line);
+        }
+        if self.ops.len() > max_items {
+            lines.push(format!("... and {} more", self.ops.len() - max_items));
+        }
+        lines
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Execution
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// File-level unit of work produced by expanding directory copies
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SyncJob {
+    MakeDir {
+        side: SyncSide,
+        relative_path: String,
+    },
+    CopyFile {
+        from: SyncSide,
+        relative_path: String,
+        size: u64,
+    },
+    Delete {
+        side: SyncSide,
+        relative_path: String,
+        is_dir: bool,
+    },
+}
+
+/// Listing entry of a sync root: (name, is_dir, size)
+type ListEntry = (String, bool, u64);
+
+/// A sync root plus the connection used to reach it
+struct Endpoint {
+    root: SyncRoot,
+    session: Option<SftpSession>,
+}
+
+impl Endpoint {
+    fn connect(root: SyncRoot) -> Result<Self, String> {
+        let session = match root {
+            SyncRoot::Remote { ref profile, .. } => {
+                Some(SftpSession::connect(profile).map_err(|e| e.to_string())?)
+            }
+            SyncRoot::Local(_) => None,
+        };
+        Ok(Self { root, session })
+    }
+
+    fn local_path(&self, relative_path: &str) -> Option<PathBuf> {
+        match self.root {
+            SyncRoot::Local(ref root) => Some(join_local(root, relative_path)),
+            SyncRoot::Remote { .. } => None,
+        }
+    }
+
+    fn remote_path(&self, relative_path: &str) -> String {
+        match self.root {
+            SyncRoot::Remote { ref path, .. } => join_remote(path, relative_path),
+            SyncRoot::Local(ref root) => join_local(root, relative_path).display().to_string(),
+        }
+    }
+
+    fn session(&self) -> Result<&SftpSession, String> {
+        self.session
+            .as_ref()
+            .ok_or_else(|| "Not connected".to_string())
+    }
+
+    fn list(&self, relative_path: &str) -> Result<Vec<ListEntry>, String> {
+        if let Some(dir) = self.local_path(relative_path) {
+            let mut result = Vec::new();
+            for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+                let entry = entry.map_err(|e| e.to_string())?;
+                let metadata = fs::symlink_metadata(entry.path()).map_err(|e| e.to_string())?;
+                result.push((
+                    entry.file_name().to_string_lossy().to_string(),
+                    metadata.is_dir(),
+                    metadata.len(),
+                ));
+            }
+            return Ok(result);
+        }
+        let entries = self
+            .session()?
+            .list_dir(&self.remote_path(relative_path))
+            .map_err(|e| e.to_string())?;
+        Ok(entries
+            .into_iter()
+            .map(|e| (e.name, e.is_directory && !e.is_symlink, e.size))
+            .collect())
+    }
+
+    fn make_dir(&self, relative_path: &str) -> Result<(), String> {
+        if let Some(dir) = self.local_path(relative_path) {
+            return fs::create_dir_all(dir).map_err(|e| e.to_string());
+        }
+        let session = self.session()?;
+        let path = self.remote_path(relative_path);
+        if session.dir_exists(&path) {
+            return Ok(());
+        }
+        session.mkdir(&path).map_err(|e| e.to_string())
+    }
+
+    fn delete(&self, relative_path: &str, is_dir: bool) -> Result<(), String> {
+        if let Some(path) = self.local_path(relative_path) {
+            if fs::symlink_metadata(&path).is_err() {
+                return Ok(());
+            }
+            return file_ops::delete_file(&path).map_err(|e| e.to_string());
+        }
+        self.session()?
+            .remove(&self.remote_path(relative_path), is_dir)
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn join_local(root: &Path, relative_path: &str) -> PathBuf {
+    if relative_path.is_empty() {
+        root.to_path_buf()
+    } else {
+        root.join(relative_path)
+    }
+}
+
+fn join_remote(root: &str, relative_path: &str) -> String {
+    if relative_path.is_empty() {
+        root.to_string()
+    } else {
+        format!("{}/{}", root.trim_end_matches('/'), relative_path)
+    }
+}
+
+fn file_name_of(relative_path: &str) -> String {
+    relative_path
+        .rsplit('/')
+        .next()
+        .unwrap_or(relative_path)
+        .to_string()
+}
+
+/// Expand the plan's operations into file-level jobs, walking copied directories
+fn expand_jobs(
+    ops: &[SyncOp],
+    left: &Endpoint,
+    right: &Endpoint,
+    cancel_flag: &AtomicBool,
+) -> Result<Vec<SyncJob>, String> {
+    let mut jobs = Vec::new();
+    for op in ops {
+        match op {
+            SyncOp::Copy {
+                from,
+                relative_path,
+                is_dir: false,
+                size,
+            } => jobs.push(SyncJob::CopyFile {
+                from: *from,
+                relative_path: relative_path.clone(),
+                size: *size,
+            }),
+            SyncOp::Copy {
+                from,
+                relative_path,
+                is_dir: true,
+                ..
+            } => {
+                let source = match from {
+                    SyncSide::Left => left,
+                    SyncSide::Right => right,
+                };
+                expand_dir(source, *from, relative_path, cancel_flag, &mut jobs)?;
+            }
+            SyncOp::Delete {
+                side,
+                relative_path,
+                is_dir,
+            } => jobs.push(SyncJob::Delete {
+                side: *side,
+                relative_path: relative_path.clone(),
+                is_dir: *is_dir,
+            }),
+        }
+    }
+    Ok(jobs)
+}
+
+fn expand_dir(
+    source: &Endpoint,
+    from: SyncSide,
+    relative_path: &str,
+    cancel_flag: &AtomicBool,
+    jobs: &mut Vec<SyncJob>,
+) -> Result<(), String> {
+    if cancel_flag.load(Ordering::Relaxed) {
+        return Err("Cancelled".to_string());
+    }
+    jobs.push(SyncJob::MakeDir {
+        side: from.other(),
+        relative_path: relative_path.to_string(),
+    });
+    let mut children = source.list(relative_path)?;
+    children.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, is_dir, size) in children {
+        let child = format!("{}/{}", relative_path, name);
+        if is_dir {
+            expand_dir(source, from, &child, cancel_flag, jobs)?;
+        } else {
+            jobs.push(SyncJob::CopyFile {
+                from,
+                relative_path: child,
+                size,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Copy one file between endpoints, reporting byte progress through `on_progress`
+fn copy_file_between<F>(
+    source: &Endpoint,
+    target: &Endpoint,
+    relative_path: &str,
+    size: u64,
+    cancel_flag: &Arc<AtomicBool>,
+    on_progress: F,
+) -> Result<(), String>
+where
+    F: Fn(u64, u64),
+{
+    match (
+        source.local_path(relative_path),
+        target.local_path(relative_path),
+    ) {
+        (Some(src), Some(dest)) => {
+            prepare_local_dest(&dest)?;
+            if fs::symlink_metadata(&src).is_ok_and(|m| m.is_symlink()) {
+                return copy_symlink(&src, &dest).map_err(|e| e.to_string());
+            }
+            file_ops::copy_file_with_progress(&src, &dest, cancel_flag, on_progress)
+                .map_err(|e| e.to_string())?;
+            preserve_mtime(source, target, relative_path)
+        }
+        (Some(src), None) => target
+            .session()?
+            .upload_file_with_progress(
+                &src.display().to_string(),
+                &target.remote_path(relative_path),
+                size,
+                cancel_flag,
+                on_progress,
+            )
+            .map_err(|e| e.to_string())
+            .and_then(|_| preserve_mtime(source, target, relative_path)),
+        (None, Some(dest)) => {
+            prepare_local_dest(&dest)?;
+            source
+                .session()?
+                .download_file_with_progress(
+                    &source.remote_path(relative_path),
+                    &dest.display().to_string(),
+                    size,
+                    cancel_flag,
+                    on_progress,
+                )
+                .map_err(|e| e.to_string())?;
+            preserve_mtime(source, target, relative_path)
+        }
+        (None, None) => Err("Copying between two remote directories is not supported".into()),
+    }
+}
+
+/// Make way for a file copied to local `dest`: create its parent directory and
+/// remove a symlink there, so the copy replaces the link instead of writing
+/// through it to a file that may lie outside the compared tree
+fn prepare_local_dest(dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    if fs::symlink_metadata(dest).is_ok_and(|m| m.is_symlink()) {
+        fs::remove_file(dest).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Give the copied file the source's modification time so the next comparison
+/// (which may compare by time) sees both sides as the same
+fn preserve_mtime(source: &Endpoint, target: &Endpoint, relative_path: &str) -> Result<(), String> {
+    let mtime = match source.local_path(relative_path) {
+        Some(src) => {
+            let modified = fs::metadata(src)
+                .and_then(|m| m.modified())
+                .map_err(|e| e.to_string())?;
+            modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        }
+        None => source
+            .session()?
+            .mtime(&source.remote_path(relative_path))
+            .map_err(|e| e.to_string())?,
+    };
+    match target.local_path(relative_path) {
+        Some(dest) => {
+            let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime.max(0) as u64);
+            fs::File::options()
+                .write(true)
+                .open(dest)
+                .and_then(|f| f.set_modified(time))
+                .map_err(|e| e.to_string())
+        }
+        None => target
+            .session()?
+            .set_mtime(&target.remote_path(relative_path), mtime)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    let link_target = fs::read_link(src)?;
+    if fs::symlink_metadata(dest).is_ok() {
+        file_ops::delete_file(dest)?;
+    }
+    std::os::unix::fs::symlink(link_target, dest)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::copy(src, dest).map(|_| ())
+}
+
+/// Execute a sync plan with progress reporting.
+/// Sends the same ProgressMessage sequence as `copy_files_with_progress`.
+pub fn execute_sync_with_progress(
+    plan: SyncPlan,
+    cancel_flag: Arc<AtomicBool>,
+    progress_tx: Sender<ProgressMessage>,
+) {
+    let SyncPlan {
+        left, right, ops, ..
+    } = plan;
+
+    if left.is_remote() || right.is_remote() {
+        let _ = progress_tx.send(ProgressMessage::Preparing(
+            "Connecting to remote host...".to_string(),
+        ));
+    }
+    let endpoints = Endpoint::connect(left).and_then(|l| Endpoint::connect(right).map(|r| (l, r)));
+    let (left, right) = match endpoints {
+        Ok(pair) => pair,
+        Err(e) => {
+            let _ = progress_tx.send(ProgressMessage::Error("".to_string(), e));
+            let _ = progress_tx.send(ProgressMessage::Completed(0, ops.len()));
+            return;
+        }
+    };
+
+    let _ = progress_tx.send(ProgressMessage::Preparing(
+        "Calculating file sizes...".to_string(),
+    ));
+    let jobs = match expand_jobs(&ops, &left, &right, &cancel_flag) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            let _ = progress_tx.send(ProgressMessage::Error("".to_string(), e));
+            let _ = progress_tx.send(ProgressMessage::Completed(0, ops.len()));
+            return;
+        }
+    };
+    let total_files = jobs
+        .iter()
+        .filter(|j| !matches!(j, SyncJob::MakeDir { .. }))
+        .count();
+    let total_bytes: u64 = jobs
+        .iter()
+        .map(|j| match j {
+            SyncJob::CopyFile { size, .. } => *size,
+            _ => 0,
+        })
+        .sum();
+
+    let _ = progress_tx.send(ProgressMessage::PrepareComplete);
+
+    let endpoint = |side: SyncSide| match side {
+        SyncSide::Left => &left,
+        SyncSide::Right => &right,
+    };
+
+    let mut success_count = 0;
+    let mut failure_count = 0;
+    let mut completed_files: usize = 0;
+    let mut completed_bytes: u64 = 0;
+
+    for job in &jobs {
+        if cancel_flag.load(Ordering::Relaxed) {
+            break;
+        }
+        match job {
+            SyncJob::MakeDir {
+                side,
+                relative_path,
+            } => {
+                // A file or symlink may occupy the path on the target side
+                let target = endpoint(*side);
+                if let Some(path) = target.local_path(relative_path) {
+                    if fs::symlink_metadata(&path).is_ok_and(|m| !m.is_dir()) {
+                        let _ = fs::remove_file(&path);
+                    }
+                }
+                if let Err(e) = target.make_dir(relative_path) {
+                    failure_count += 1;
+                    let _ =
+                        progress_tx.send(ProgressMessage::Error(file_name_of(relative_path), e));
+                }
+            }
+            SyncJob::CopyFile {
+                from,
+                relative_path,
+                size,
+            } => {
+                let filename = file_name_of(relative_path);
+                let _ = progress_tx.send(ProgressMessage::FileStarted(filename.clone()));
+                let file_completed_bytes = completed_bytes;
+                let result = copy_file_between(
+                    endpoint(*from),
+                    endpoint(from.other()),
+                    relative_path,
+                    *size,
+                    &cancel_flag,
+                    |copied, total| {
+                        let _ = progress_tx.send(ProgressMessage::FileProgress(copied, total));
+                        let _ = progress_tx.send(ProgressMessage::TotalProgress(
+                            completed_files,
+                            total_files,
+                            file_completed_bytes + copied,
+                            total_bytes,
+                        ));
+                    },
+                );
+                if cancel_flag.load(Ordering::Relaxed) {
+                    break;
+                }
+                completed_files += 1;
+                completed_bytes += size;
+                match result {
+                    Ok(()) => {
+                        success_count += 1;
+                        let _ = progress_tx.send(ProgressMessage::FileCompleted(filename));
+                    }
+                    Err(e) => {
+                        failure_count += 1;
+                        let _ = progress_tx.send(ProgressMessage::Error(filename, e));
+                    }
+                }
+            }
+            SyncJob::Delete {
+                side,
+                relative_path,
+                is_dir,
+            } => {
+                let filename = file_name_of(relative_path);
+                let _ = progress_tx.send(ProgressMessage::FileStarted(filename.clone()));
+                completed_files += 1;
+                match endpoint(*side).delete(relative_path, *is_dir) {
+                    Ok(()) => {
+                        success_count += 1;
+                        let _ = progress_tx.send(ProgressMessage::FileCompleted(filename));
+                    }
+                    Err(e) => {
+                        failure_count += 1;
+                        let _ = progress_tx.send(ProgressMessage::Error(filename, e));
+                    }
+                }
+            }
+        }
+        let _ = progress_tx.send(ProgressMessage::TotalProgress(
+            completed_files,
+            total_files,
+            completed_bytes,
+            total_bytes,
+        ));
+    }
+
+    let _ = progress_tx.send(ProgressMessage::Completed(success_count, failure_count));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    fn run(plan: SyncPlan) -> Vec<ProgressMessage> {
+        let (tx, rx) = mpsc::channel();
+        execute_sync_with_progress(plan, Arc::new(AtomicBool::new(false)), tx);
+        rx.try_iter().collect()
+    }
+
+    fn completed(messages: &[ProgressMessage]) -> Option<(usize, usize)> {
+        messages.iter().find_map(|m| match m {
+            ProgressMessage::Completed(s, f) => Some((*s, *f)),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_copy_and_delete_local() {
+        let left = TempDir::new().expect("temp dir");
+        let right = TempDir::new().expect("temp dir");
+        fs::create_dir_all(left.path().join("sub/deep")).expect("mkdir");
+        fs::write(left.path().join("sub/deep/a.txt"), "aaa").expect("write");
+        fs::write(left.path().join("b.txt"), "new").expect("write");
+        fs::write(right.path().join("b.txt"), "old content").expect("write");
+        fs::write(right.path().join("extra.txt"), "x").expect("write");
+
+        let mut plan = SyncPlan::new(
+            SyncMode::MirrorToRight,
+            SyncRoot::Local(left.path().to_path_buf()),
+            SyncRoot::Local(right.path().to_path_buf()),
+        );
+        plan.ops = vec![
+            SyncOp::Copy {
+                from: SyncSide::Left,
+                relative_path: "sub".to_string(),
+                is_dir: true,
+                size: 0,
+            },
+            SyncOp::Copy {
+                from: SyncSide::Left,
+                relative_path: "b.txt".to_string(),
+                is_dir: false,
+                size: 3,
+            },
+            SyncOp::Delete {
+                side: SyncSide::Right,
+                relative_path: "extra.txt".to_string(),
+                is_dir: false,
+            },
+        ];
+
+        let messages = run(plan);
+        assert_eq!(completed(&messages), Some((3, 0)));
+        assert_eq!(
+            fs::read_to_string(right.path().join("sub/deep/a.txt")).expect("read"),
+            "aaa"
+        );
+        assert_eq!(
+            fs::read_to_string(right.path().join("b.txt")).expect("read"),
+            "new"
+        );
+        assert!(!right.path().join("extra.txt").exists());
+        assert!(messages
+            .iter()
+            .any(|m| matches!(m, ProgressMessage::TotalProgress(3, 3, 6, 6))));
+    }
+
+    #[test]
+    fn test_copy_replaces_directory_with_file() {
+        let left = TempDir::new().expect("temp dir");
+        let right = TempDir::new().expect("temp dir");
+        fs::write(left.path().join("item"), "file").expect("write");
+        fs::create_dir_all(right.path().join("item/child")).expect("mkdir");
+
+        let mut plan = SyncPlan::new(
+            SyncMode::CopyToRight,
+            SyncRoot::Local(left.path().to_path_buf()),
+            SyncRoot::Local(right.path().to_path_buf()),
+        );
+        plan.ops = vec![
+            SyncOp::Delete {
+                side: SyncSide::Right,
+                relative_path: "item".to_string(),
+                is_dir: true,
+            },
+            SyncOp::Copy {
+                from: SyncSide::Left,
+                relative_path: "item".to_string(),
+                is_dir: false,
+                size: 4,
+            },
+        ];
+
+        let messages = run(plan);
+        assert_eq!(completed(&messages), Some((2, 0)));
+        assert_eq!(
+            fs::read_to_string(right.path().join("item")).expect("read"),
+            "file"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prepare_local_dest_replaces_symlink() {
+        let dir = TempDir::new().expect("temp dir");
+        let outside = TempDir::new().expect("temp dir");
+        let target = outside.path().join("target.txt");
+        fs::write(&target, "keep").expect("write");
+        let dest = dir.path().join("sub/link.txt");
+        fs::create_dir_all(dir.path().join("sub")).expect("mkdir");
+        std::os::unix::fs::symlink(&target, &dest).expect("symlink");
+
+        prepare_local_dest(&dest).expect("prepare");
+        assert!(fs::symlink_metadata(&dest).is_err());
+        fs::write(&dest, "new").expect("write");
+        assert_eq!(fs::read_to_string(&target).expect("read"), "keep");
+
+        // A missing parent directory is created
+        let nested = dir.path().join("a/b/c.txt");
+        prepare_local_dest(&nested).expect("prepare");
+        assert!(dir.path().join("a/b").is_dir());
+    }
+
+    #[test]
+    fn test_summary_lines() {
+        let mut plan = SyncPlan::new(
+            SyncMode::CopyToLeft,
+            SyncRoot::Local(PathBuf::from("/l")),
+            SyncRoot::Local(PathBuf::from("/r")),
+        );
+        plan.ops = vec![
+            SyncOp::Copy {
+                from: SyncSide::Right,
+                relative_path: "a/b.txt".to_string(),
+                is_dir: false,
+                size: 2048,
+            },
+            SyncOp::Copy {
+                from: SyncSide::Right,
+                relative_path: "dir".to_string(),
+                is_dir: true,
+                size: 0,
+            },
+            SyncOp::Delete {
+                side: SyncSide::Right,
+                relative_path: "old".to_string(),
+                is_dir: false,
+            },
+        ];
+
+        let lines = plan.summary_lines(2);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Copy 2 item(s)"));
+        assert!(lines[0].ends_with("delete 1 item(s)"));
+        assert_eq!(lines[1], "+ L: a/b.txt");
+        assert_eq!(lines[2], "+ L: dir/");
+        assert_eq!(lines[3], "... and 1 more");
+    }
+}
@@ -16,6 +16,7 @@ pub enum FileOperationType {
     Download,
     Encrypt,
     Decrypt,
+    Sync,
 }
 
 /// Progress message for file operations
@@ -0,0 +1,349 @@
+//! Git status of files shown in the panels.
+//!
+//! [`RepoStatus`] is one `git status` run of a whole repository, with the
+//! status of each directory aggregated from its children. [`GitStatusWatcher`]
+//! keeps the latest result for a panel and refreshes it in a background thread,
+//! at most once per [`REFRESH_INTERVAL`] unless the index changed, so moving
+//! around a large repository does not run `git status` on every directory change.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A cached status is reused for this long when the index did not change
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Status of a file or (aggregated) directory. Later variants win when a
+/// directory holds children with different states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GitFileStatus {
+    Ignored,
+    Untracked,
+    Staged,
+    Modified,
+    Conflicted,
+}
+
+impl GitFileStatus {
+    /// One-character badge shown next to the file name
+    pub fn badge(self) -> char {
+        match self {
+            GitFileStatus::Ignored => '!',
+            GitFileStatus::Untracked => '?',
+            GitFileStatus::Staged => 'S',
+            GitFileStatus::Modified => 'M',
+            GitFileStatus::Conflicted => 'U',
+        }
+    }
+
+    /// Map the two-letter code of `git status --porcelain`
+    fn from_code(x: char, y: char) -> Self {
+        match (x, y) {
+            ('?', '?') => GitFileStatus::Untracked,
+            ('!', '!') => GitFileStatus::Ignored,
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => GitFileStatus::Conflicted,
+            (_, ' ') => GitFileStatus::Staged,
+            _ => GitFileStatus::Modified,
+        }
+    }
+}
+
+/// Status of every changed, untracked or ignored path of one repository
+#[derive(Debug, Clone, Default)]
+pub struct RepoStatus {
+    /// Paths relative to the repository root
+    files: HashMap<PathBuf, GitFileStatus>,
+    /// Highest status found below each directory (ignored entries excluded)
+    dirs: HashMap<PathBuf, GitFileStatus>,
+}
+
+impl RepoStatus {
+    /// Parse `git status --porcelain=v1 -z --ignored`
+    pub fn parse(output: &[u8]) -> Self {
+        let mut status = RepoStatus::default();
+        let text = String::from_utf8_lossy(output);
+        let mut entries = text.split('\0');
+        while let Some(entry) = entries.next() {
+            let mut chars = entry.chars();
+            let (x, y) = match (chars.next(), chars.next(), chars.next()) {
+                (Some(x), Some(y), Some(' ')) => (x, y),
+                _ => continue,
+            };
+            // Renames and copies are followed by their source path
+            if matches!(x, 'R' | 'C') || matches!(y, 'R' | 'C') {
+                entries.next();
+            }
+            let path = PathBuf::from(entry[3..].trim_end_matches('/'));
+            let file_status = GitFileStatus::from_code(x, y);
+            if file_status != GitFileStatus::Ignored {
+                for dir in path.ancestors().skip(1) {
+                    if dir.as_os_str().is_empty() {
+                        break;
+                    }
+                    let current = status.dirs.entry(dir.to_path_buf()).or_insert(file_status);
+                    *current = (*current).max(file_status);
+                }
+            }
+            status.files.insert(path, file_status);
+        }
+        status
+    }
+
+    /// Status of `path` (relative to the root). Entries of untracked or ignored
+    /// directories apply to everything inside them.
+    pub fn status_of(&self, path: &Path, is_dir: bool) -> Option<GitFileStatus> {
+        if let Some(&status) = self.files.get(path) {
+            return Some(status);
+        }
+        if is_dir {
+            if let Some(&status) = self.dirs.get(path) {
+                return Some(status);
+            }
+        }
+        path.ancestors()
+            .skip(1)
+            .take_while(|dir| !dir.as_os_str().is_empty())
+            .find_map(|dir| match self.files.get(dir) {
+                Some(&s) if matches!(s, GitFileStatus::Untracked | GitFileStatus::Ignored) => {
+                    Some(s)
+                }
+                _ => None,
+            })
+    }
+}
+
+/// Run `git status` for the repository at `root`
+pub fn load_repo_status(root: &Path) -> Option<RepoStatus> {
+    let output = Command::new("git")
+        .arg("-c")
+        .arg(format!("safe.directory={}", root.to_string_lossy()))
+        .arg("-C")
+        .arg(root)
+        .args(["status", "--porcelain=v1", "-z", "--ignored"])
+        // A read-only status must not take the index lock from other git commands
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(RepoStatus::parse(&output.stdout))
+}
+
+/// Root of the working tree containing `path`, found by looking for `.git`
+/// without running git
+pub fn find_repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Modification time of the repository's index, which changes on staging,
+/// commits and checkouts. `.git` may be a file pointing elsewhere (worktrees).
+fn index_mtime(root: &Path) -> Option<SystemTime> {
+    let dot_git = root.join(".git");
+    let git_dir = if dot_git.is_file() {
+        let content = fs::read_to_string(&dot_git).ok()?;
+        let target = content.strip_prefix("gitdir:")?.trim();
+        root.join(target)
+    } else {
+        dot_git
+    };
+    fs::metadata(git_dir.join("index"))
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+struct Snapshot {
+    root: PathBuf,
+    status: RepoStatus,
+    loaded_at: Instant,
+    index_mtime: Option<SystemTime>,
+    /// Set by `invalidate`; the status is still shown until the refresh arrives
+    stale: bool,
+}
+
+/// Latest git status of the repository a panel is in, refreshed in the background
+#[derive(Default)]
+pub struct GitStatusWatcher {
+    snapshot: Option<Snapshot>,
+    pending: Option<(PathBuf, Option<SystemTime>, Receiver<Option<RepoStatus>>)>,
+}
+
+impl std::fmt::Debug for GitStatusWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitStatusWatcher")
+            .field("root", &self.snapshot.as_ref().map(|s| &s.root))
+            .field("pending", &self.pending.is_some())
+            .finish()
+    }
+}
+
+impl GitStatusWatcher {
+    /// Status for a panel showing `dir`: the cached one when it belongs to
+    /// the same repository, starting a refresh when it is missing or stale.
+    /// Returns the repository root and its status, if any.
+    pub fn lookup(&mut self, dir: &Path) -> Option<(&Path, &RepoStatus)> {
+        let root = match find_repo_root(dir) {
+            Some(r) => r,
+            None => {
+                self.snapshot = None;
+                self.pending = None;
+                return None;
+            }
+        };
+        let mtime = index_mtime(&root);
+        let fresh = self.snapshot.as_ref().is_some_and(|s| {
+            s.root == root
+                && !s.stale
+                && s.index_mtime == mtime
+                && s.loaded_at.elapsed() < REFRESH_INTERVAL
+        });
+        let pending_same_root = self.pending.as_ref().is_some_and(|(r, _, _)| *r == root);
+        if !fresh && !pending_same_root {
+            self.spawn_refresh(root.clone(), mtime);
+        }
+        if self.snapshot.as_ref().is_some_and(|s| s.root != root) {
+            self.snapshot = None;
+        }
+        self.snapshot
+            .as_ref()
+            .map(|s| (s.root.as_path(), &s.status))
+    }
+
+    /// Root of the repository of the cached status
+    pub fn root(&self) -> Option<&Path> {
+        self.snapshot.as_ref().map(|s| s.root.as_path())
+    }
+
+    fn spawn_refresh(&mut self, root: PathBuf, mtime: Option<SystemTime>) {
+        let (tx, rx) = mpsc::channel();
+        let thread_root = root.clone();
+        thread::spawn(move || {
+            let _ = tx.send(load_repo_status(&thread_root));
+        });
+        self.pending = Some((root, mtime, rx));
+    }
+
+    /// Take a finished background refresh. Returns true when a new status arrived.
+    pub fn poll(&mut self) -> bool {
+        let result = match self.pending {
+            Some((_, _, ref rx)) => match rx.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => None,
+            },
+            None => return false,
+        };
+        let (root, index_mtime, _) = match self.pending.take() {
+            Some(p) => p,
+            None => return false,
+        };
+        match result {
+            Some(status) => {
+                self.snapshot = Some(Snapshot {
+                    root,
+                    status,
+                    loaded_at: Instant::now(),
+                    index_mtime,
+                    stale: false,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Make the next lookup reload the status, e.g. after files were changed
+    pub fn invalidate(&mut self) {
+        if let Some(ref mut snapshot) = self.snapshot {
+            snapshot.stale = true;
+        }
+    }
+}
+
+/// New git repository in a temporary directory, for tests. A missing git
+/// fails the test instead of quietly skipping it.
+#[cfg(test)]
+pub fn temp_repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let init = Command::new("git")
+        .arg("-C")
+        .arg(dir.path())
+        .args(["init", "-q"])
+        .status();
+    assert!(
+        init.is_ok_and(|s| s.success()),
+        "git init failed; these tests need git"
+    );
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_aggregate() {
+        let output = concat!(
+            " M src/main.rs\0",
+            "M  src/ui/panel.rs\0",
+            "UU src/ui/app/mod.rs\0",
+            "R  docs/new.md\0docs/old.md\0",
+            "?? notes/\0",
+            "!! target/\0",
+        );
+        let status = RepoStatus::parse(output.as_bytes());
+        let of = |p: &str, dir: bool| status.status_of(Path::new(p), dir);
+
+        assert_eq!(of("src/main.rs", false), Some(GitFileStatus::Modified));
+        assert_eq!(of("src/ui/panel.rs", false), Some(GitFileStatus::Staged));
+        assert_eq!(of("docs/new.md", false), Some(GitFileStatus::Staged));
+        assert_eq!(of("docs/old.md", false), None);
+        assert_eq!(of("src/lib.rs", false), None);
+
+        // Directories take the strongest status below them
+        assert_eq!(of("src", true), Some(GitFileStatus::Conflicted));
+        assert_eq!(of("src/ui", true), Some(GitFileStatus::Conflicted));
+        assert_eq!(of("docs", true), Some(GitFileStatus::Staged));
+
+        // Untracked and ignored directories cover their contents
+        assert_eq!(of("notes", true), Some(GitFileStatus::Untracked));
+        assert_eq!(of("notes/todo.txt", false), Some(GitFileStatus::Untracked));
+        assert_eq!(of("target/debug", true), Some(GitFileStatus::Ignored));
+        assert_eq!(of("other", true), None);
+    }
+
+    #[test]
+    fn test_watcher_refreshes_in_background() {
+        let dir = temp_repo();
+        let root = dir.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/new.txt"), "x").unwrap();
+
+        let mut watcher = GitStatusWatcher::default();
+        // Nothing cached yet; a refresh runs in the background
+        assert!(watcher.lookup(&root.join("sub")).is_none());
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !watcher.poll() {
+            assert!(Instant::now() < deadline, "git status did not finish");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let (found_root, status) = watcher.lookup(&root.join("sub")).unwrap();
+        assert_eq!(found_root, root);
+        assert_eq!(
+            status.status_of(Path::new("sub/new.txt"), false),
+            Some(GitFileStatus::Untracked)
+        );
+        // Still fresh: no second refresh was started
+        assert!(watcher.pending.is_none());
+        watcher.invalidate();
+        assert!(watcher.lookup(root).is_some());
+        assert!(watcher.pending.is_some());
+    }
+}
@@ -0,0 +1,691 @@
+//! Paged access to files too large to read into memory.
+//!
+//! [`LargeFile`] keeps only the file handle and a sparse line index: the byte
+//! offset of every [`CHECKPOINT_LINES`]-th line, built by a background thread.
+//! A line is read by seeking to the checkpoint before it and scanning forward,
+//! so a multi-GB log costs a few MB of index and only the visible page is ever
+//! held as text. [`SearchJob`] and [`FilterJob`] scan the file in the
+//! background the same way, streaming results back as they are found. A file
+//! that grows (a log being written) is indexed further with [`LargeFile::extend`].
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+use regex::Regex;
+
+/// Distance in lines between two indexed offsets
+pub const CHECKPOINT_LINES: usize = 256;
+
+/// Longer lines are cut when read; the rest of the line is skipped
+pub const MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// A search stops after this many matches
+pub const MAX_SEARCH_MATCHES: usize = 100_000;
+
+/// Bytes read per step by the indexer and the search
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug)]
+enum IndexUpdate {
+    /// Checkpoints found in the bytes scanned since the last update
+    Progress {
+        checkpoints: Vec<u64>,
+        /// Complete (newline-terminated) lines so far
+        lines: usize,
+        bytes: u64,
+        /// Offset just after the last newline
+        complete_bytes: u64,
+    },
+    /// The whole file was scanned; `lines` includes a last unterminated line
+    Done {
+        lines: usize,
+    },
+    Error(String),
+}
+
+/// A file read in pages through a background-built line index
+#[derive(Debug)]
+pub struct LargeFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// Offset of line `i * CHECKPOINT_LINES`
+    checkpoints: Vec<u64>,
+    /// Number of lines indexed so far
+    lines: usize,
+    /// Lines ended by a newline, and the offset just after the last of them
+    complete_lines: usize,
+    complete_bytes: u64,
+    indexed_bytes: u64,
+    index_rx: Option<Receiver<IndexUpdate>>,
+    index_error: Option<String>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl LargeFile {
+    /// Open `path` and start indexing its lines in the background
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut large = Self {
+            path: path.to_path_buf(),
+            file,
+            size: 0,
+            checkpoints: vec![0],
+            lines: 0,
+            complete_lines: 0,
+            complete_bytes: 0,
+            indexed_bytes: 0,
+            index_rx: None,
+            index_error: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        large.spawn_index(size);
+        Ok(large)
+    }
+
+    /// Index the bytes between the current size and `new_size`
+    fn spawn_index(&mut self, new_size: u64) {
+        let (tx, rx) = mpsc::channel();
+        let thread_path = self.path.clone();
+        let thread_cancel = self.cancel.clone();
+        let (start, lines_before) = (self.complete_bytes, self.complete_lines);
+        thread::spawn(move || {
+            let result = index_lines(
+                &thread_path,
+                start..new_size,
+                lines_before,
+                &thread_cancel,
+                |update| tx.send(update).is_ok(),
+            );
+            let update = match result {
+                Ok(lines) => IndexUpdate::Done { lines },
+                Err(e) => IndexUpdate::Error(e.to_string()),
+            };
+            let _ = tx.send(update);
+        });
+        self.size = new_size;
+        self.index_rx = Some(rx);
+    }
+
+    /// Index data appended since the file was opened. Returns false while
+    /// the previous indexing is still running; call again later.
+    pub fn extend(&mut self, new_size: u64) -> bool {
+        if self.is_indexing() || new_size <= self.size {
+            return false;
+        }
+        self.spawn_index(new_size);
+        true
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Lines known so far; the final count once indexing is done
+    pub fn line_count(&self) -> usize {
+        self.lines
+    }
+
+    pub fn is_indexing(&self) -> bool {
+        self.index_rx.is_some()
+    }
+
+    /// Bytes covered by the index
+    pub fn indexed_bytes(&self) -> u64 {
+        self.indexed_bytes
+    }
+
+    /// Number of newline-terminated lines, and the offset where the line
+    /// after them starts
+    pub fn complete_lines(&self) -> (usize, u64) {
+        (self.complete_lines, self.complete_bytes)
+    }
+
+    pub fn index_error(&self) -> Option<&str> {
+        self.index_error.as_deref()
+    }
+
+    /// Take index updates from the background thread. Returns true when the
+    /// index grew or finished.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(ref rx) = self.index_rx {
+            match rx.try_recv() {
+                Ok(IndexUpdate::Progress {
+                    checkpoints,
+                    lines,
+                    bytes,
+                    complete_bytes,
+                }) => {
+                    self.checkpoints.extend(checkpoints);
+                    self.lines = lines;
+                    self.complete_lines = lines;
+                    self.complete_bytes = complete_bytes;
+                    self.indexed_bytes = bytes;
+                    changed = true;
+                }
+                Ok(IndexUpdate::Done { lines }) => {
+                    self.lines = lines;
+                    self.indexed_bytes = self.size;
+                    self.index_rx = None;
+                    changed = true;
+                }
+                Ok(IndexUpdate::Error(e)) => {
+                    self.index_error = Some(e);
+                    self.index_rx = None;
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.index_rx = None;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Read up to `count` lines starting at line `first`, returning each
+    /// line's byte offset with its text. Only indexed lines can be read.
+    pub fn read_lines(&mut self, first: usize, count: usize) -> io::Result<Vec<(u64, String)>> {
+        let mut result = Vec::new();
+        let end = first.saturating_add(count).min(self.lines);
+        if first >= end {
+            return Ok(result);
+        }
+        let checkpoint = first / CHECKPOINT_LINES;
+        let mut offset = self.checkpoints.get(checkpoint).copied().unwrap_or(0);
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut reader = BufReader::with_capacity(64 * 1024, &mut self.file);
+        let mut buf = Vec::new();
+        for line in checkpoint * CHECKPOINT_LINES..end {
+            let read = read_line_capped(&mut reader, &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            if line >= first {
+                result.push((offset, line_text(&buf)));
+            }
+            offset += read;
+        }
+        Ok(result)
+    }
+
+    /// Read `len` bytes at `offset` (fewer at the end of the file)
+    pub fn read_bytes(&mut self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::with_capacity(len);
+        (&mut self.file).take(len as u64).read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Line containing byte `offset`, once the index has reached it
+    pub fn line_at_offset(&mut self, offset: u64) -> io::Result<Option<usize>> {
+        let offset = offset.min(self.size);
+        if self.is_indexing() && offset >= self.indexed_bytes {
+            return Ok(None);
+        }
+        let checkpoint = self
+            .checkpoints
+            .partition_point(|&c| c <= offset)
+            .saturating_sub(1);
+        let start = self.checkpoints.get(checkpoint).copied().unwrap_or(0);
+        let mut reader = BufReader::new(&mut self.file);
+        reader.seek(SeekFrom::Start(start))?;
+        let newlines = count_newlines(reader.take(offset - start))?;
+        let line = checkpoint * CHECKPOINT_LINES + newlines;
+        Ok(Some(line.min(self.lines.saturating_sub(1))))
+    }
+}
+
+impl Drop for LargeFile {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Scan `range` of `path`, which starts a line preceded by `lines_before`
+/// lines, reporting checkpoints through `report` (which returns false once
+/// nobody listens). Returns the line count.
+fn index_lines(
+    path: &Path,
+    range: Range<u64>,
+    lines_before: usize,
+    cancel: &AtomicBool,
+    mut report: impl FnMut(IndexUpdate) -> bool,
+) -> io::Result<usize> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(range.start))?;
+    let mut reader = file.take(range.end.saturating_sub(range.start));
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut offset = range.start;
+    let mut complete_bytes = range.start;
+    let mut lines = lines_before;
+    let mut last_byte = b'\n';
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let mut checkpoints = Vec::new();
+        for (i, _) in chunk[..read]
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| b == b'\n')
+        {
+            lines += 1;
+            complete_bytes = offset + i as u64 + 1;
+            if lines.is_multiple_of(CHECKPOINT_LINES) {
+                checkpoints.push(complete_bytes);
+            }
+        }
+        offset += read as u64;
+        last_byte = chunk[read - 1];
+        let update = IndexUpdate::Progress {
+            checkpoints,
+            lines,
+            bytes: offset,
+            complete_bytes,
+        };
+        if !report(update) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+        }
+    }
+    // Like `str::lines`, a last line without a newline still counts
+    if last_byte != b'\n' {
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+fn count_newlines(mut reader: impl Read) -> io::Result<usize> {
+    let mut chunk = vec![0u8; CHUNK_SIZE.min(64 * 1024)];
+    let mut count = 0;
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(count),
+            Ok(n) => count += chunk[..n].iter().filter(|&&b| b == b'\n').count(),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read one line into `buf` (without the newline), keeping at most
+/// [`MAX_LINE_BYTES`] of it. Returns the bytes consumed, 0 at the end.
+fn read_line_capped(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> io::Result<u64> {
+    buf.clear();
+    let mut consumed = 0u64;
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(b) => b,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if available.is_empty() {
+            return Ok(consumed);
+        }
+        let (used, found) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (available.len(), false),
+        };
+        let content = if found { used - 1 } else { used };
+        let room = MAX_LINE_BYTES.saturating_sub(buf.len());
+        buf.extend_from_slice(&available[..content.min(room)]);
+        reader.consume(used);
+        consumed += used as u64;
+        if found {
+            return Ok(consumed);
+        }
+    }
+}
+
+/// Text of a raw line: lossy UTF-8 without the `\r` of CRLF endings
+fn line_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[derive(Debug)]
+enum ScanUpdate<T> {
+    Found { items: Vec<T>, scanned: u64 },
+    Done { truncated: bool },
+    Error(String),
+}
+
+/// Line-by-line scan of a byte range of a file in a background thread,
+/// streaming what it finds: regex matches for a search, whole lines for a filter
+#[derive(Debug)]
+pub struct ScanJob<T> {
+    rx: Option<Receiver<ScanUpdate<T>>>,
+    cancel: Arc<AtomicBool>,
+    /// Bytes to scan
+    len: u64,
+    scanned: u64,
+    truncated: bool,
+    error: Option<String>,
+}
+
+/// Search matches as `(line, char_start, char_end)`
+pub type SearchJob = ScanJob<(usize, usize, usize)>;
+
+/// Matching lines as `(line, text)`
+pub type FilterJob = ScanJob<(usize, String)>;
+
+impl SearchJob {
+    /// Search the first `size` bytes of `path` line by line for `regex`
+    pub fn start(path: &Path, size: u64, regex: Regex) -> Self {
+        Self::spawn(path, 0..size, 0, move |line_idx, line, found| {
+            for mat in regex.find_iter(line) {
+                let char_start = line[..mat.start()].chars().count();
+                let char_end = char_start + mat.as_str().chars().count();
+                found.push((line_idx, char_start, char_end));
+            }
+        })
+    }
+}
+
+impl FilterJob {
+    /// Collect the lines matching `regex` in `range` of `path`, numbering
+    /// them from `first_line` (the line starting at `range.start`)
+    pub fn start(path: &Path, range: Range<u64>, first_line: usize, regex: Regex) -> Self {
+        Self::spawn(path, range, first_line, move |line_idx, line, found| {
+            if regex.is_match(line) {
+                found.push((line_idx, line.to_string()));
+            }
+        })
+    }
+}
+
+impl<T: Send + 'static> ScanJob<T> {
+    fn spawn(
+        path: &Path,
+        range: Range<u64>,
+        first_line: usize,
+        matcher: impl FnMut(usize, &str, &mut Vec<T>) + Send + 'static,
+    ) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let thread_path = path.to_path_buf();
+        let thread_cancel = cancel.clone();
+        let len = range.end.saturating_sub(range.start);
+        thread::spawn(move || {
+            let result = scan_lines(
+                &thread_path,
+                range,
+                first_line,
+                &thread_cancel,
+                matcher,
+                |update| tx.send(update).is_ok(),
+            );
+            let update = match result {
+                Ok(truncated) => ScanUpdate::Done { truncated },
+                Err(e) => ScanUpdate::Error(e.to_string()),
+            };
+            let _ = tx.send(update);
+        });
+        Self {
+            rx: Some(rx),
+            cancel,
+            len,
+            scanned: 0,
+            truncated: false,
+            error: None,
+        }
+    }
+
+    /// Take what was found since the last poll
+    pub fn poll(&mut self) -> Vec<T> {
+        let mut found = Vec::new();
+        while let Some(ref rx) = self.rx {
+            match rx.try_recv() {
+                Ok(ScanUpdate::Found { items, scanned }) => {
+                    found.extend(items);
+                    self.scanned = scanned;
+                }
+                Ok(ScanUpdate::Done { truncated }) => {
+                    self.truncated = truncated;
+                    self.scanned = self.len;
+                    self.rx = None;
+                }
+                Ok(ScanUpdate::Error(e)) => {
+                    self.error = Some(e);
+                    self.rx = None;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.rx = None,
+            }
+        }
+        found
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Share of the range scanned, 0-100
+    pub fn percent(&self) -> u64 {
+        (self.scanned * 100).checked_div(self.len).unwrap_or(100)
+    }
+
+    /// True when the scan stopped at [`MAX_SEARCH_MATCHES`]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl<T> Drop for ScanJob<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Feed each line of `range` to `matcher`, reporting its findings about once
+/// per [`CHUNK_SIZE`]. Returns true when it stopped at [`MAX_SEARCH_MATCHES`].
+fn scan_lines<T>(
+    path: &Path,
+    range: Range<u64>,
+    first_line: usize,
+    cancel: &AtomicBool,
+    mut matcher: impl FnMut(usize, &str, &mut Vec<T>),
+    mut report: impl FnMut(ScanUpdate<T>) -> bool,
+) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(range.start))?;
+    let len = range.end.saturating_sub(range.start);
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, file.take(len));
+    let mut buf = Vec::new();
+    let mut items = Vec::new();
+    let mut total = 0usize;
+    let mut scanned = 0u64;
+    let mut reported = 0u64;
+    let mut line_idx = first_line;
+    loop {
+        let read = read_line_capped(&mut reader, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        scanned += read;
+        let before = items.len();
+        matcher(line_idx, &line_text(&buf), &mut items);
+        total += items.len() - before;
+        line_idx += 1;
+        if total >= MAX_SEARCH_MATCHES || scanned - reported >= CHUNK_SIZE as u64 {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            let update = ScanUpdate::Found {
+                items: std::mem::take(&mut items),
+                scanned,
+            };
+            if !report(update) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            reported = scanned;
+            if total >= MAX_SEARCH_MATCHES {
+                return Ok(true);
+            }
+        }
+    }
+    if !items.is_empty() {
+        report(ScanUpdate::Found { items, scanned });
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn wait_indexed(file: &mut LargeFile) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while file.is_indexing() {
+            file.poll();
+            assert!(Instant::now() < deadline, "indexing did not finish");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_index_and_read_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.log");
+        let mut content = String::new();
+        for i in 0..1000 {
+            content.push_str(&format!("line {}\r\n", i));
+        }
+        content.push_str("last");
+        std::fs::write(&path, &content).unwrap();
+
+        let mut file = LargeFile::open(&path).unwrap();
+        wait_indexed(&mut file);
+        assert_eq!(file.line_count(), 1001);
+        assert_eq!(file.checkpoints.len(), 1 + 1000 / CHECKPOINT_LINES);
+
+        let page = file.read_lines(510, 3).unwrap();
+        let texts: Vec<&str> = page.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(texts, ["line 510", "line 511", "line 512"]);
+        let offset = content.find("line 511\r\n").unwrap() as u64;
+        assert_eq!(page[1].0, offset);
+        assert_eq!(file.line_at_offset(offset).unwrap(), Some(511));
+        assert_eq!(file.line_at_offset(offset + 4).unwrap(), Some(511));
+        assert_eq!(file.line_at_offset(u64::MAX).unwrap(), Some(1000));
+
+        // Reading past the end stops at the last line
+        let tail = file.read_lines(999, 10).unwrap();
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[1].1, "last");
+        assert_eq!(file.read_bytes(0, 6).unwrap(), b"line 0");
+    }
+
+    #[test]
+    fn test_long_lines_are_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wide.log");
+        let long = "x".repeat(MAX_LINE_BYTES * 3);
+        std::fs::write(&path, format!("{}\nafter\n", long)).unwrap();
+
+        let mut file = LargeFile::open(&path).unwrap();
+        wait_indexed(&mut file);
+        assert_eq!(file.line_count(), 2);
+        let page = file.read_lines(0, 2).unwrap();
+        assert_eq!(page[0].1.len(), MAX_LINE_BYTES);
+        assert_eq!(page[1], (long.len() as u64 + 1, "after".to_string()));
+    }
+
+    #[test]
+    fn test_search_job_streams_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("search.log");
+        let mut content = String::new();
+        for i in 0..5000 {
+            let level = if i % 1000 == 7 { "ERROR" } else { "info" };
+            content.push_str(&format!("{} é event {}\n", level, i));
+        }
+        std::fs::write(&path, &content).unwrap();
+
+        let regex = Regex::new("ERROR").unwrap();
+        let mut job = SearchJob::start(&path, content.len() as u64, regex);
+        let mut matches = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while job.is_running() {
+            matches.extend(job.poll());
+            assert!(Instant::now() < deadline, "search did not finish");
+            thread::sleep(Duration::from_millis(5));
+        }
+        matches.extend(job.poll());
+        assert_eq!(job.percent(), 100);
+        assert!(!job.truncated());
+        let lines: Vec<usize> = matches.iter().map(|m| m.0).collect();
+        assert_eq!(lines, [7, 1007, 2007, 3007, 4007]);
+        assert_eq!((matches[0].1, matches[0].2), (0, 5));
+    }
+
+    #[test]
+    fn test_extend_indexes_appended_lines() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("growing.log");
+        let mut content: String = (0..300).map(|i| format!("{}\n", i)).collect();
+        content.push_str("par");
+        std::fs::write(&path, &content).unwrap();
+
+        let mut file = LargeFile::open(&path).unwrap();
+        wait_indexed(&mut file);
+        assert_eq!(file.line_count(), 301);
+        let (lines, start) = file.complete_lines();
+        assert_eq!((lines, start), (300, content.len() as u64 - 3));
+
+        let mut out = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        out.write_all(b"tial\nnext\n").unwrap();
+        let size = std::fs::metadata(&path).unwrap().len();
+        assert!(file.extend(size));
+        assert!(!file.extend(size));
+        wait_indexed(&mut file);
+        assert_eq!(file.line_count(), 302);
+        let tail = file.read_lines(299, 5).unwrap();
+        let texts: Vec<&str> = tail.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(texts, ["299", "partial", "next"]);
+
+        // Filtering just the appended part numbers lines from where it starts
+        let regex = Regex::new("^(partial|next)$").unwrap();
+        let mut job = FilterJob::start(&path, start..size, lines, regex);
+        let mut found = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while job.is_running() {
+            found.extend(job.poll());
+            assert!(Instant::now() < deadline, "filter did not finish");
+            thread::sleep(Duration::from_millis(5));
+        }
+        found.extend(job.poll());
+        assert_eq!(
+            found,
+            [(300, "partial".to_string()), (301, "next".to_string())]
+        );
+    }
+}
@@ -0,0 +1,967 @@
+//! Language Server Protocol client of the file editor.
+//!
+//! A server configured for a language in `Settings::lsp_servers` runs as a
+//! child process speaking JSON-RPC over its stdin and stdout. Its messages are
+//! read on a thread and turned into [`LspEvent`]s by [`LspClient::poll`];
+//! messages to it are written on another, so a server that stops reading never
+//! blocks the UI. Documents are kept in step by sending their full text on
+//! every change.
+//!
+//! Positions are exchanged in UTF-16 code units, the protocol's default;
+//! [`utf16_col`] and [`char_col`] convert them from and to the editor's
+//! character columns.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::config::LspServerSettings;
+
+/// Request whose response the editor waits for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LspRequest {
+    Hover,
+    Definition,
+    Completion,
+    Formatting,
+}
+
+/// Diagnostic severity, most severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    fn from_lsp(value: &Value) -> Self {
+        match value.as_u64() {
+            Some(2) => Severity::Warning,
+            Some(3) => Severity::Information,
+            Some(4) => Severity::Hint,
+            _ => Severity::Error,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Information => "info",
+            Severity::Hint => "hint",
+        }
+    }
+}
+
+/// Zero-based line and UTF-16 column in a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub start: Position,
+    pub end: Position,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: Position,
+    pub end: Position,
+    pub new_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub detail: Option<String>,
+    /// Text matched against the typed prefix
+    pub filter_text: String,
+    pub insert_text: String,
+    /// Replacement of a range around the cursor, instead of the typed prefix
+    pub edit: Option<TextEdit>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub position: Position,
+}
+
+/// Something a server sent; responses carry the URI of the document asked about
+#[derive(Debug, Clone, PartialEq)]
+pub enum LspEvent {
+    Diagnostics {
+        uri: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+    Hover {
+        uri: String,
+        text: String,
+    },
+    Definition {
+        uri: String,
+        location: Option<Location>,
+    },
+    Completion {
+        uri: String,
+        items: Vec<CompletionItem>,
+    },
+    Formatting {
+        uri: String,
+        edits: Vec<TextEdit>,
+    },
+    /// Error response or message of the server worth showing
+    Message(String),
+    /// The server process ended
+    Exited,
+}
+
+/// Write one message with its `Content-Length` header
+pub fn write_message(w: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(w, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    w.flush()
+}
+
+/// Read one message; `None` at the end of the stream
+pub fn read_message(r: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    let length = loop {
+        let mut header = String::new();
+        if r.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            match length {
+                Some(n) => break n,
+                None => continue,
+            }
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    };
+    let mut body = vec![0; length];
+    r.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(io::Error::other)
+}
+
+/// `file://` URI of a path, percent-encoding what is not allowed in it
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", b));
+        }
+    }
+    uri
+}
+
+/// Path of a `file://` URI
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let bytes = rest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(
+        String::from_utf8_lossy(&decoded).into_owned(),
+    ))
+}
+
+/// UTF-16 column of character column `col` in `line`
+pub fn utf16_col(line: &str, col: usize) -> usize {
+    line.chars().take(col).map(char::len_utf16).sum()
+}
+
+/// Character column of UTF-16 column `units` in `line`
+pub fn char_col(line: &str, units: usize) -> usize {
+    let mut used = 0;
+    for (i, c) in line.chars().enumerate() {
+        if used >= units {
+            return i;
+        }
+        used += c.len_utf16();
+    }
+    line.chars().count()
+}
+
+/// `text` with `edits` applied, all of them relative to the original text
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> String {
+    let starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset = |p: Position| match starts.get(p.line) {
+        Some(&start) => {
+            let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+            let line = &text[start..end];
+            start
+                + line
+                    .chars()
+                    .take(char_col(line, p.character))
+                    .map(char::len_utf8)
+                    .sum::<usize>()
+        }
+        None => text.len(),
+    };
+
+    // From the end, so earlier offsets stay valid; edits at the same position
+    // end up in the order they were given
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|e| e.start);
+    let mut out = text.to_string();
+    for edit in sorted.into_iter().rev() {
+        let start = offset(edit.start);
+        let end = offset(edit.end).max(start);
+        out.replace_range(start..end, &edit.new_text);
+    }
+    out
+}
+
+/// Directory a server is started in: the file's repository, or its directory
+pub fn workspace_root(file: &Path) -> PathBuf {
+    let dir = file.parent().unwrap_or(Path::new("/"));
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+fn parse_position(v: &Value) -> Position {
+    Position {
+        line: v["line"].as_u64().unwrap_or(0) as usize,
+        character: v["character"].as_u64().unwrap_or(0) as usize,
+    }
+}
+
+fn parse_text_edit(v: &Value) -> Option<TextEdit> {
+    // A completion's InsertReplaceEdit has two ranges; insert leaves the rest
+    // of the word alone
+    let range = [&v["range"], &v["insert"]]
+        .into_iter()
+        .find(|r| r.is_object())?;
+    Some(TextEdit {
+        start: parse_position(&range["start"]),
+        end: parse_position(&range["end"]),
+        new_text: v["newText"].as_str()?.to_string(),
+    })
+}
+
+fn parse_diagnostics(params: &Value) -> Vec<Diagnostic> {
+    let Some(list) = params["diagnostics"].as_array() else {
+        return Vec::new();
+    };
+    list.iter()
+        .map(|d| {
+            let message = d["message"].as_str().unwrap_or_default();
+            Diagnostic {
+                start: parse_position(&d["range"]["start"]),
+                end: parse_position(&d["range"]["end"]),
+                severity: Severity::from_lsp(&d["severity"]),
+                message: match d["source"].as_str() {
+                    Some(source) => format!("{}: {}", source, message),
+                    None => message.to_string(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Text of a hover: a string, a MarkupContent or MarkedStrings
+fn parse_hover(result: &Value) -> String {
+    fn part(v: &Value) -> &str {
+        v.as_str()
+            .or_else(|| v["value"].as_str())
+            .unwrap_or_default()
+    }
+    let contents = &result["contents"];
+    let text = match contents.as_array() {
+        Some(parts) => parts
+            .iter()
+            .map(part)
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        None => part(contents).to_string(),
+    };
+    text.trim().to_string()
+}
+
+/// First target of a Location, Location[] or LocationLink[]
+fn parse_location(result: &Value) -> Option<Location> {
+    let first = match result.as_array() {
+        Some(list) => list.first()?,
+        None => result,
+    };
+    let uri = first["uri"]
+        .as_str()
+        .or_else(|| first["targetUri"].as_str())?;
+    let range = if first["range"].is_object() {
+        &first["range"]
+    } else {
+        &first["targetSelectionRange"]
+    };
+    Some(Location {
+        path: uri_to_path(uri)?,
+        position: parse_position(&range["start"]),
+    })
+}
+
+/// Drop the tab stops and placeholders of a snippet, keeping placeholder text
+fn strip_snippet(snippet: &str) -> String {
+    static TAB_STOP: OnceLock<Option<Regex>> = OnceLock::new();
+    let re = TAB_STOP.get_or_init(|| Regex::new(r"\$\{\d+:([^}]*)\}|\$\{\d+\}|\$\d+").ok());
+    match re {
+        Some(re) => re.replace_all(snippet, "$1").into_owned(),
+        None => snippet.to_string(),
+    }
+}
+
+fn parse_completion(result: &Value) -> Vec<CompletionItem> {
+    let list = result
+        .as_array()
+        .or_else(|| result["items"].as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    list.iter()
+        .filter_map(|item| {
+            let label = item["label"].as_str()?.to_string();
+            let snippet = item["insertTextFormat"].as_u64() == Some(2);
+            let unsnip = |s: &str| {
+                if snippet {
+                    strip_snippet(s)
+                } else {
+                    s.to_string()
+                }
+            };
+            let mut edit = parse_text_edit(&item["textEdit"]);
+            if let Some(ref mut edit) = edit {
+                edit.new_text = unsnip(&edit.new_text);
+            }
+            Some(CompletionItem {
+                detail: item["detail"].as_str().map(str::to_string),
+                filter_text: item["filterText"].as_str().unwrap_or(&label).to_string(),
+                insert_text: unsnip(item["insertText"].as_str().unwrap_or(&label)),
+                edit,
+                label,
+            })
+        })
+        .collect()
+}
+
+/// One running language server
+#[derive(Debug)]
+pub struct LspClient {
+    /// Taken on drop by the thread that waits for the server to exit
+    child: Option<Child>,
+    /// Messages for the writer thread
+    outgoing: Sender<Value>,
+    messages: Receiver<Value>,
+    next_id: u64,
+    /// Requests waiting for a response
+    pending: HashMap<u64, PendingRequest>,
+    /// Messages held back until the server answers `initialize`
+    queued: Option<Vec<Value>>,
+    /// Documents opened on the server, with the version last sent
+    documents: HashMap<String, i64>,
+    exited: bool,
+}
+
+/// A request sent to the server, with the document it is about
+#[derive(Debug)]
+struct PendingRequest {
+    kind: LspRequest,
+    uri: String,
+    /// Document version the request was made against
+    version: Option<i64>,
+}
+
+/// Id of the `initialize` request; later requests count up from 1
+const INITIALIZE_ID: u64 = 0;
+
+impl LspClient {
+    /// Start `server` in `root` and send it `initialize`
+    pub fn start(server: &LspServerSettings, root: &Path) -> io::Result<Self> {
+        let mut child = Command::new(&server.command)
+            .args(&server.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(io::Error::other("no stdio pipes"));
+        };
+
+        let (tx, messages) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                if tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+        let (outgoing, rx) = mpsc::channel::<Value>();
+        std::thread::spawn(move || {
+            let mut stdin = stdin;
+            while let Ok(message) = rx.recv() {
+                if write_message(&mut stdin, &message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let client = Self {
+            child: Some(child),
+            outgoing,
+            messages,
+            next_id: INITIALIZE_ID + 1,
+            pending: HashMap::new(),
+            queued: Some(Vec::new()),
+            documents: HashMap::new(),
+            exited: false,
+        };
+        let root_uri = path_to_uri(root);
+        let root_name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let params = json!({
+            "processId": std::process::id(),
+            "clientInfo": { "name": "remotecc" },
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": root_name }],
+            "capabilities": {
+                "general": { "positionEncodings": ["utf-16"] },
+                "workspace": { "workspaceFolders": true, "configuration": true },
+                "textDocument": {
+                    "synchronization": { "didSave": true },
+                    "hover": { "contentFormat": ["plaintext", "markdown"] },
+                    "definition": {},
+                    "completion": { "completionItem": { "snippetSupport": false } },
+                    "formatting": {},
+                    "publishDiagnostics": {}
+                }
+            }
+        });
+        client.write(json!({
+            "jsonrpc": "2.0",
+            "id": INITIALIZE_ID,
+            "method": "initialize",
+            "params": params,
+        }));
+        Ok(client)
+    }
+
+    /// Hand `message` to the writer thread; lost if the server is gone
+    fn write(&self, message: Value) {
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Send now, or once the server is initialized
+    fn send(&mut self, message: Value) {
+        match self.queued {
+            Some(ref mut queued) => queued.push(message),
+            None => self.write(message),
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    pub fn is_open(&self, uri: &str) -> bool {
+        self.documents.contains_key(uri)
+    }
+
+    pub fn did_open(&mut self, uri: &str, language_id: &str, text: &str) {
+        self.documents.insert(uri.to_string(), 1);
+        self.notify(
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": uri, "languageId": language_id, "version": 1, "text": text
+            } }),
+        );
+    }
+
+    /// Send the document's whole new text
+    pub fn did_change(&mut self, uri: &str, text: &str) {
+        let Some(version) = self.documents.get_mut(uri) else {
+            return;
+        };
+        *version += 1;
+        let version = *version;
+        self.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": version },
+                "contentChanges": [{ "text": text }]
+            }),
+        );
+    }
+
+    pub fn did_save(&mut self, uri: &str) {
+        if self.is_open(uri) {
+            self.notify(
+                "textDocument/didSave",
+                json!({ "textDocument": { "uri": uri } }),
+            );
+        }
+    }
+
+    pub fn did_close(&mut self, uri: &str) {
+        if self.documents.remove(uri).is_some() {
+            self.notify(
+                "textDocument/didClose",
+                json!({ "textDocument": { "uri": uri } }),
+            );
+        }
+    }
+
+    fn request(&mut self, kind: LspRequest, uri: &str, method: &str, params: Value) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let version = self.documents.get(uri).copied();
+        self.pending.insert(
+            id,
+            PendingRequest {
+                kind,
+                uri: uri.to_string(),
+                version,
+            },
+        );
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+    }
+
+    /// Ask about `position`: hover, definition or completion
+    pub fn request_at(&mut self, kind: LspRequest, uri: &str, position: Position) {
+        let method = match kind {
+            LspRequest::Hover => "textDocument/hover",
+            LspRequest::Definition => "textDocument/definition",
+            LspRequest::Completion => "textDocument/completion",
+            LspRequest::Formatting => return,
+        };
+        let params = json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": position.line, "character": position.character }
+        });
+        self.request(kind, uri, method, params);
+    }
+
+    /// Ask for the edits formatting the whole document
+    pub fn request_formatting(&mut self, uri: &str, tab_size: usize, insert_spaces: bool) {
+        let params = json!({
+            "textDocument": { "uri": uri },
+            "options": { "tabSize": tab_size, "insertSpaces": insert_spaces }
+        });
+        self.request(
+            LspRequest::Formatting,
+            uri,
+            "textDocument/formatting",
+            params,
+        );
+    }
+
+    /// Messages received since the last call
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+        loop {
+            match self.messages.try_recv() {
+                Ok(message) => self.handle(message, &mut events),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if !self.exited {
+                        self.exited = true;
+                        events.push(LspEvent::Exited);
+                    }
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    fn handle(&mut self, message: Value, events: &mut Vec<LspEvent>) {
+        let method = message["method"].as_str();
+        match (method, message.get("id")) {
+            // A request of the server: answer it so the server does not wait
+            (Some(method), Some(id)) => {
+                let result = if method == "workspace/configuration" {
+                    let items = message["params"]["items"].as_array().map_or(0, Vec::len);
+                    Value::Array(vec![Value::Null; items])
+                } else {
+                    Value::Null
+                };
+                self.write(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let params = &message["params"];
+                events.push(LspEvent::Diagnostics {
+                    uri: params["uri"].as_str().unwrap_or_default().to_string(),
+                    diagnostics: parse_diagnostics(params),
+                });
+            }
+            // Errors and warnings only; the rest is progress chatter
+            (Some("window/showMessage"), None) => {
+                let params = &message["params"];
+                if params["type"].as_u64().is_some_and(|t| t <= 2) {
+                    if let Some(text) = params["message"].as_str() {
+                        events.push(LspEvent::Message(text.to_string()));
+                    }
+                }
+            }
+            (None, Some(id)) => self.handle_response(id.as_u64(), &message, events),
+            _ => {}
+        }
+    }
+
+    fn handle_response(&mut self, id: Option<u64>, message: &Value, events: &mut Vec<LspEvent>) {
+        let error = message["error"]["message"].as_str();
+        if id == Some(INITIALIZE_ID) && self.queued.is_some() {
+            if let Some(error) = error {
+                events.push(LspEvent::Message(format!("Initialize failed: {}", error)));
+            }
+            self.write(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+            for queued in self.queued.take().unwrap_or_default() {
+                self.write(queued);
+            }
+            return;
+        }
+        let Some(PendingRequest { kind, uri, version }) =
+            id.and_then(|id| self.pending.remove(&id))
+        else {
+            return;
+        };
+        if let Some(error) = error {
+            events.push(LspEvent::Message(error.to_string()));
+            return;
+        }
+        // Edits are offsets into the text formatted; they would corrupt a changed one
+        if kind == LspRequest::Formatting && self.documents.get(&uri).copied() != version {
+            events.push(LspEvent::Message(
+                "Text changed before formatting arrived; format again".to_string(),
+            ));
+            return;
+        }
+        let result = &message["result"];
+        events.push(match kind {
+            LspRequest::Hover => LspEvent::Hover {
+                uri,
+                text: parse_hover(result),
+            },
+            LspRequest::Definition => LspEvent::Definition {
+                uri,
+                location: parse_location(result),
+            },
+            LspRequest::Completion => LspEvent::Completion {
+                uri,
+                items: parse_completion(result),
+            },
+            LspRequest::Formatting => LspEvent::Formatting {
+                uri,
+                edits: result
+                    .as_array()
+                    .map(|edits| edits.iter().filter_map(parse_text_edit).collect())
+                    .unwrap_or_default(),
+            },
+        });
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let id = self.next_id;
+        self.write(json!({ "jsonrpc": "2.0", "id": id, "method": "shutdown" }));
+        self.write(json!({ "jsonrpc": "2.0", "method": "exit" }));
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        // Give the server a moment to exit on its own, off the UI thread
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + Duration::from_millis(200);
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+    }
+}
+
+/// Running language servers, one per language id
+#[derive(Debug, Default)]
+pub struct LspManager {
+    clients: HashMap<String, LspClient>,
+    /// Languages whose server failed to start or exited; not retried
+    failed: HashSet<String>,
+}
+
+impl LspManager {
+    /// Server of `language`, started in the [`workspace_root`] of `file` on
+    /// first use. `Ok(None)` when none is configured or it already failed;
+    /// `Err` when it cannot be started.
+    pub fn client(
+        &mut self,
+        language: &str,
+        servers: &HashMap<String, LspServerSettings>,
+        file: &Path,
+    ) -> Result<Option<&mut LspClient>, String> {
+        if self.failed.contains(language) {
+            return Ok(None);
+        }
+        let Some(server) = servers.get(language) else {
+            return Ok(None);
+        };
+        if !self.clients.contains_key(language) {
+            match LspClient::start(server, &workspace_root(file)) {
+                Ok(client) => {
+                    self.clients.insert(language.to_string(), client);
+                }
+                Err(e) => {
+                    self.failed.insert(language.to_string());
+                    return Err(format!("Cannot start {}: {}", server.command, e));
+                }
+            }
+        }
+        Ok(self.clients.get_mut(language))
+    }
+
+    /// Server of `language` if it is running
+    pub fn running(&mut self, language: &str) -> Option<&mut LspClient> {
+        self.clients.get_mut(language)
+    }
+
+    /// Events of every server; one that exited is dropped for good
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+        let mut exited = Vec::new();
+        for (language, client) in &mut self.clients {
+            for event in client.poll() {
+                if event == LspEvent::Exited {
+                    exited.push(language.clone());
+                    events.push(LspEvent::Message(format!(
+                        "Language server for {} exited",
+                        language
+                    )));
+                } else {
+                    events.push(event);
+                }
+            }
+        }
+        for language in exited {
+            self.clients.remove(&language);
+            self.failed.insert(language);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_framing() {
+        let mut buf = Vec::new();
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "result": "한글" });
+        write_message(&mut buf, &message).unwrap();
+        write_message(&mut buf, &json!({ "jsonrpc": "2.0", "method": "exit" })).unwrap();
+
+        let mut reader = io::Cursor::new(buf);
+        assert_eq!(read_message(&mut reader).unwrap(), Some(message));
+        assert_eq!(
+            read_message(&mut reader).unwrap().unwrap()["method"],
+            "exit"
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_uris_and_utf16_columns() {
+        let path = Path::new("/tmp/my dir/파일#1.rs");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///tmp/my%20dir/%ED%8C%8C%EC%9D%BC%231.rs");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+        assert_eq!(uri_to_path("https://example.com"), None);
+
+        // '😀' is two UTF-16 units, '가' one
+        let line = "a😀가b";
+        assert_eq!(utf16_col(line, 2), 3);
+        assert_eq!(utf16_col(line, 4), 5);
+        assert_eq!(char_col(line, 3), 2);
+        assert_eq!(char_col(line, 5), 4);
+        assert_eq!(char_col(line, 99), 4);
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let at = |line, character| Position { line, character };
+        let text = "fn main(){\n  let x=1;\n}\n";
+        let edits = vec![
+            TextEdit {
+                start: at(0, 9),
+                end: at(0, 9),
+                new_text: " ".into(),
+            },
+            TextEdit {
+                start: at(1, 0),
+                end: at(1, 2),
+                new_text: "    ".into(),
+            },
+            TextEdit {
+                start: at(1, 7),
+                end: at(1, 8),
+                new_text: " = ".into(),
+            },
+        ];
+        assert_eq!(
+            apply_text_edits(text, &edits),
+            "fn main() {\n    let x = 1;\n}\n"
+        );
+
+        // Positions past the end append; same-position inserts keep their order
+        let edits = vec![
+            TextEdit {
+                start: at(9, 0),
+                end: at(9, 0),
+                new_text: "a".into(),
+            },
+            TextEdit {
+                start: at(9, 0),
+                end: at(9, 0),
+                new_text: "b".into(),
+            },
+        ];
+        assert_eq!(apply_text_edits("x", &edits), "xab");
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let hover =
+            json!({ "contents": [{ "language": "rust", "value": "fn main()" }, "Entry point"] });
+        assert_eq!(parse_hover(&hover), "fn main()\n\nEntry point");
+        let hover = json!({ "contents": { "kind": "markdown", "value": " text " } });
+        assert_eq!(parse_hover(&hover), "text");
+
+        let link = json!([{
+            "targetUri": "file:///src/lib.rs",
+            "targetRange": { "start": { "line": 1, "character": 0 }, "end": { "line": 9, "character": 1 } },
+            "targetSelectionRange": { "start": { "line": 3, "character": 7 }, "end": { "line": 3, "character": 9 } }
+        }]);
+        assert_eq!(
+            parse_location(&link),
+            Some(Location {
+                path: PathBuf::from("/src/lib.rs"),
+                position: Position {
+                    line: 3,
+                    character: 7
+                },
+            })
+        );
+        assert_eq!(parse_location(&json!([])), None);
+
+        let completion = json!({ "isIncomplete": false, "items": [
+            { "label": "push", "detail": "fn(&mut self, T)",
+              "insertText": "push(${1:value})$0", "insertTextFormat": 2 },
+            { "label": "len" }
+        ] });
+        let items = parse_completion(&completion);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].insert_text, "push(value)");
+        assert_eq!(items[0].detail.as_deref(), Some("fn(&mut self, T)"));
+        assert_eq!(items[1].insert_text, "len");
+        assert_eq!(items[1].filter_text, "len");
+
+        let diagnostics = parse_diagnostics(&json!({ "diagnostics": [{
+            "range": { "start": { "line": 2, "character": 4 }, "end": { "line": 2, "character": 5 } },
+            "severity": 2, "source": "rustc", "message": "unused variable"
+        }] }));
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].start.line, 2);
+        assert_eq!(diagnostics[0].message, "rustc: unused variable");
+    }
+
+    #[test]
+    fn test_stale_formatting_is_dropped() {
+        let server = LspServerSettings {
+            command: "sleep".into(),
+            args: vec!["5".into()],
+        };
+        let mut client = LspClient::start(&server, Path::new("/")).unwrap();
+        client.queued = None;
+        let uri = "file:///a.rs";
+        client.did_open(uri, "rust", "fn a(){}");
+        let response = |id: u64| json!({ "jsonrpc": "2.0", "id": id, "result": [] });
+
+        let mut events = Vec::new();
+        client.request_formatting(uri, 4, true);
+        client.did_change(uri, "fn a(){} x");
+        client.handle(response(client.next_id - 1), &mut events);
+        assert!(matches!(events.as_slice(), [LspEvent::Message(_)]));
+
+        events.clear();
+        client.request_formatting(uri, 4, true);
+        client.handle(response(client.next_id - 1), &mut events);
+        assert!(matches!(events.as_slice(), [LspEvent::Formatting { .. }]));
+    }
+
+    #[test]
+    fn test_server_not_reading_does_not_block() {
+        // `sleep` never reads its stdin, so the pipe fills up
+        let server = LspServerSettings {
+            command: "sleep".into(),
+            args: vec!["5".into()],
+        };
+        let mut client = LspClient::start(&server, Path::new("/")).unwrap();
+        client.queued = None;
+        let started = Instant::now();
+        let uri = "file:///big.txt";
+        client.did_open(uri, "plaintext", "");
+        let text = "x".repeat(1 << 20);
+        for _ in 0..4 {
+            client.did_change(uri, &text);
+        }
+        drop(client);
+        assert!(started.elapsed() < Duration::from_millis(150));
+    }
+}
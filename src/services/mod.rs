@@ -2,11 +2,18 @@ pub mod claude;
 pub mod codex;
 pub mod codex_tmux_wrapper;
 pub mod dedup;
+pub mod diff_compare;
+pub mod diff_export;
+pub mod diff_sync;
 pub mod discord;
 pub mod file_ops;
+pub mod git_status;
+pub mod large_file;
+pub mod lsp;
 pub mod process;
 pub mod provider;
 pub mod provider_exec;
 pub mod remote;
 pub mod remote_transfer;
+pub mod swap;
 pub mod tmux_wrapper;
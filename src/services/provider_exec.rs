@@ -1,3 +1,7 @@
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+use crate::services::claude::{CancelToken, StreamMessage};
 use crate::services::provider::ProviderKind;
 use crate::services::{claude, codex};
 
@@ -9,3 +13,31 @@ pub async fn execute_simple(provider: ProviderKind, prompt: String) -> Result<St
     .await
     .map_err(|e| format!("Task join error: {}", e))?
 }
+
+/// Stream a one-off, tool-less request (no session) to the provider.
+/// Blocking — run it on its own thread and read the messages from `sender`'s receiver.
+pub fn execute_streaming(
+    provider: ProviderKind,
+    prompt: &str,
+    system_prompt: &str,
+    working_dir: &str,
+    sender: Sender<StreamMessage>,
+    cancel_token: Option<Arc<CancelToken>>,
+) -> Result<(), String> {
+    let no_tools: Vec<String> = Vec::new();
+    let run = match provider {
+        ProviderKind::Claude => claude::execute_command_streaming,
+        ProviderKind::Codex => codex::execute_command_streaming,
+    };
+    run(
+        prompt,
+        None,
+        working_dir,
+        sender,
+        Some(system_prompt),
+        Some(&no_tools),
+        cancel_token,
+        None,
+        None,
+    )
+}
@@ -638,6 +638,96 @@ impl SftpSession {
         })
     }
 
+    /// Upload local file with progress callback and cancellation support
+    pub fn upload_file_with_progress<F>(
+        &self,
+        local_path: &str,
+        remote_path: &str,
+        file_size: u64,
+        cancel_flag: &std::sync::atomic::AtomicBool,
+        on_progress: F,
+    ) -> AppResult<u64>
+    where
+        F: Fn(u64, u64),
+    {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or(AppError::Ssh("Not connected".to_string()))?;
+        let remote_path = remote_path.to_string();
+        let local_path = local_path.to_string();
+
+        self.runtime.block_on(async {
+            use tokio::io::AsyncWriteExt;
+
+            let mut local_file = std::fs::File::open(&local_path)?;
+
+            let mut remote_file = sftp
+                .create(&remote_path)
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to create '{}': {}", remote_path, e)))?;
+
+            let mut buf = vec![0u8; 64 * 1024];
+            let mut total = 0u64;
+            loop {
+                if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
+                    drop(remote_file);
+                    let _ = sftp.remove_file(&remote_path).await;
+                    return Err(AppError::Other("Cancelled".to_string()));
+                }
+                let n = std::io::Read::read(&mut local_file, &mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                remote_file.write_all(&buf[..n]).await.map_err(|e| {
+                    AppError::Ssh(format!("Failed to write '{}': {}", remote_path, e))
+                })?;
+                total += n as u64;
+                on_progress(total, file_size);
+            }
+            remote_file
+                .shutdown()
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to close '{}': {}", remote_path, e)))?;
+            Ok(total)
+        })
+    }
+
+    /// Modification time of a remote path (seconds since epoch)
+    pub fn mtime(&self, path: &str) -> AppResult<i64> {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or(AppError::Ssh("Not connected".to_string()))?;
+        let path = path.to_string();
+
+        self.runtime.block_on(async {
+            let attrs = sftp
+                .metadata(&path)
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to stat '{}': {}", path, e)))?;
+            Ok(attrs.mtime.unwrap_or(0) as i64)
+        })
+    }
+
+    /// Set access and modification time of a remote path (seconds since epoch)
+    pub fn set_mtime(&self, path: &str, mtime: i64) -> AppResult<()> {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or(AppError::Ssh("Not connected".to_string()))?;
+        let path = path.to_string();
+
+        self.runtime.block_on(async {
+            let mut attrs = russh_sftp::protocol::FileAttributes::empty();
+            attrs.atime = Some(mtime as u32);
+            attrs.mtime = Some(mtime as u32);
+            sftp.set_metadata(&path, attrs)
+                .await
+                .map_err(|e| AppError::Ssh(format!("Failed to set time on '{}': {}", path, e)))
+        })
+    }
+
     /// Disconnect from remote host
     pub fn disconnect(&mut self) {
         // Drop SFTP first, then SSH
@@ -0,0 +1,179 @@
+//! Swap files of the file editor, for recovering unsaved changes after a
+//! crash.
+//!
+//! While a buffer has unsaved changes its lines are written to
+//! `~/.remotecc/swap/`, one JSON [`SwapFile`] per edited file, named after a
+//! hash of the file's path. The swap is removed when the buffer is saved or
+//! closed, so one that is still there when the file is opened again was left
+//! by a session that ended without doing either, unless the process that
+//! wrote it is still running and editing the file.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwapFile {
+    /// File the changes belong to
+    pub path: PathBuf,
+    /// Process that wrote the swap
+    pub pid: u32,
+    /// Local time of the last write, for display
+    pub saved_at: String,
+    pub lines: Vec<String>,
+}
+
+impl SwapFile {
+    /// Whether another process that is still running wrote the swap, i.e. the
+    /// file is open in a second instance rather than left by a crash
+    pub fn owner_alive(&self) -> bool {
+        if self.pid == std::process::id() {
+            return false;
+        }
+        #[cfg(unix)]
+        {
+            let Ok(pid) = libc::pid_t::try_from(self.pid) else {
+                return false;
+            };
+            if pid <= 0 {
+                return false;
+            }
+            // SAFETY: signal 0 sends nothing, it only checks that the process exists
+            #[allow(unsafe_code)]
+            let result = unsafe { libc::kill(pid, 0) };
+            result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
+    }
+}
+
+/// `~/.remotecc/swap`
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".remotecc").join("swap"))
+}
+
+/// Swap file of `file` in `dir`
+pub fn swap_path(dir: &Path, file: &Path) -> PathBuf {
+    let file = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+    let hash = Sha256::digest(file.to_string_lossy().as_bytes());
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    dir.join(format!("{}-{}.swp", &hex::encode(hash)[..16], name))
+}
+
+/// Copy of the swapped lines handed to the diff view
+pub fn recovered_path(dir: &Path, file: &Path) -> PathBuf {
+    swap_path(dir, file).with_extension("recovered")
+}
+
+/// Write the unsaved `lines` of `file`, replacing the previous swap
+pub fn write(dir: &Path, file: &Path, lines: &[String]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let swap = SwapFile {
+        path: file.to_path_buf(),
+        pid: std::process::id(),
+        saved_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        lines: lines.to_vec(),
+    };
+    let json = serde_json::to_vec(&swap).map_err(io::Error::other)?;
+
+    // Written aside and renamed, so a crash mid-write keeps the previous swap
+    let path = swap_path(dir, file);
+    let tmp = path.with_extension("swp.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // The swap holds the file's text: keep it private from the start
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut out = options.open(&tmp)?;
+    out.write_all(&json)?;
+    drop(out);
+    fs::rename(&tmp, &path)
+}
+
+/// Swap left for `file`, if any
+pub fn read(dir: &Path, file: &Path) -> Option<SwapFile> {
+    let data = fs::read(swap_path(dir, file)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Delete the swap of `file` and the copy made for the diff view
+pub fn remove(dir: &Path, file: &Path) {
+    let _ = fs::remove_file(swap_path(dir, file));
+    let _ = fs::remove_file(recovered_path(dir, file));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_read_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let swap_dir = dir.path().join("swap");
+        let file = dir.path().join("notes.txt");
+        assert!(read(&swap_dir, &file).is_none());
+
+        let lines = vec!["first".to_string(), "second".to_string()];
+        write(&swap_dir, &file, &lines).unwrap();
+        let swap = read(&swap_dir, &file).unwrap();
+        assert_eq!(swap.path, file);
+        assert_eq!(swap.lines, lines);
+        assert_eq!(swap.pid, std::process::id());
+        assert!(swap_path(&swap_dir, &file)
+            .to_string_lossy()
+            .ends_with("-notes.txt.swp"));
+
+        // Another file with the same name has its own swap
+        let other = dir.path().join("sub").join("notes.txt");
+        assert_ne!(swap_path(&swap_dir, &file), swap_path(&swap_dir, &other));
+        assert!(read(&swap_dir, &other).is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(swap_path(&swap_dir, &file))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        remove(&swap_dir, &file);
+        assert!(read(&swap_dir, &file).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_owner_alive() {
+        let mut swap = SwapFile {
+            path: PathBuf::from("/tmp/notes.txt"),
+            pid: std::process::id(),
+            saved_at: String::new(),
+            lines: Vec::new(),
+        };
+        // Our own swap is never "another instance"
+        assert!(!swap.owner_alive());
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        swap.pid = child.id();
+        assert!(swap.owner_alive());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!swap.owner_alive());
+    }
+}
@@ -2,6 +2,7 @@ use super::*;
 
 use std::fs;
 
+use crate::ui::diff_file_view::ReturnScreen;
 use crate::ui::file_editor::EditorState;
 use crate::ui::file_info::FileInfoState;
 use crate::ui::file_viewer::ViewerState;
@@ -287,8 +288,7 @@ impl App {
                     editor.set_syntax_colors(self.theme.syntax);
                     match editor.load_file(&path) {
                         Ok(_) => {
-                            self.editor_state = Some(editor);
-                            self.current_screen = Screen::FileEditor;
+                            self.open_editor(editor);
                         }
                         Err(e) => {
                             self.show_message(&format!("Cannot open file: {}", e));
@@ -426,6 +426,31 @@ impl App {
         });
     }
 
+    /// Ask for the output path of a diff export, prefilled with `file_name`
+    /// in the active panel's directory (home directory for remote panels).
+    pub fn show_diff_export_dialog(&mut self, file_name: &str, message: &str) {
+        let panel = self.active_panel();
+        let dir = if panel.is_remote() {
+            dirs::home_dir().unwrap_or_default()
+        } else {
+            panel.path.clone()
+        };
+        let input = dir.join(file_name).display().to_string();
+        let len = input.chars().count();
+        let name_start = len - file_name.chars().count();
+        let stem_end = name_start + file_name.rfind('.').unwrap_or(file_name.len());
+        self.dialog = Some(Dialog {
+            dialog_type: DialogType::DiffExport,
+            input,
+            cursor_pos: stem_end,
+            message: message.to_string(),
+            completion: None,
+            selected_button: 0,
+            selection: Some((name_start, stem_end)),
+            use_md5: false,
+        });
+    }
+
     pub fn show_rename_dialog(&mut self) {
         let panel = self.active_panel();
         if let Some(file) = panel.current_file() {
@@ -586,7 +611,9 @@ impl App {
             self.show_message("Not a git repository");
             return;
         }
-        self.git_screen_state = Some(crate::ui::git_screen::GitScreenState::new(path));
+        let mut state = crate::ui::git_screen::GitScreenState::new(path);
+        state.draft_settings = self.settings.commit_draft.clone();
+        self.git_screen_state = Some(state);
         self.current_screen = Screen::GitScreen;
     }
 
@@ -647,6 +674,148 @@ impl App {
         });
     }
 
+    /// Show the git history of the file under the cursor
+    pub fn show_file_history_dialog(&mut self) {
+        if self.active_panel().is_remote() {
+            self.show_message("Git history is not available for remote files");
+            return;
+        }
+        let panel = self.active_panel();
+        let path = match panel.current_file() {
+            Some(file) if !file.is_directory => panel.path.join(&file.name),
+            _ => {
+                self.show_message("Select a file to show its history");
+                return;
+            }
+        };
+        self.show_file_history(path);
+    }
+
+    /// Show the git history of `path` over the current screen
+    pub fn show_file_history(&mut self, path: PathBuf) {
+        let (dir, name) = match (path.parent(), path.file_name()) {
+            (Some(d), Some(n)) => (d.to_path_buf(), n.to_string_lossy().into_owned()),
+            _ => return,
+        };
+        let repo_path = match crate::ui::git_screen::get_repo_root(&dir) {
+            Some(r) => r,
+            None => {
+                self.show_message("Not a git repository");
+                return;
+            }
+        };
+        let revisions = match crate::ui::git_screen::get_file_history(&dir, &name) {
+            Ok(r) if !r.is_empty() => r,
+            Ok(_) => {
+                self.show_message("No git history for this file");
+                return;
+            }
+            Err(e) => {
+                self.show_message(e.lines().next().unwrap_or("git log failed"));
+                return;
+            }
+        };
+        self.file_history_state = Some(FileHistoryState {
+            repo_path,
+            file_path: path,
+            revisions,
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_height: 20,
+            return_screen: self.current_screen,
+        });
+        self.open_file_history_dialog();
+    }
+
+    fn open_file_history_dialog(&mut self) {
+        self.dialog = Some(Dialog {
+            dialog_type: DialogType::FileHistory,
+            input: String::new(),
+            cursor_pos: 0,
+            message: String::new(),
+            completion: None,
+            selected_button: 0,
+            selection: None,
+            use_md5: false,
+        });
+    }
+
+    /// Open the selected revision of the file history in the viewer,
+    /// or diff it against the working copy
+    pub fn open_file_history_revision(&mut self, diff: bool) {
+        let (revision, file_path) = match self.file_history_state {
+            Some(ref state) => match state.revisions.get(state.selected_index) {
+                Some(r) => (
+                    crate::ui::git_screen::GitRevision {
+                        repo: state.repo_path.clone(),
+                        hash: r.entry.hash.clone(),
+                        path: r.path.clone(),
+                    },
+                    state.file_path.clone(),
+                ),
+                None => return,
+            },
+            None => return,
+        };
+        let extracted = match crate::ui::git_screen::extract_revision(&revision) {
+            Ok(p) => p,
+            Err(e) => {
+                self.show_message(e.lines().next().unwrap_or("Cannot read revision"));
+                return;
+            }
+        };
+        self.dialog = None;
+
+        if diff {
+            let name = file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let title = format!("{} ({} \u{2194} working copy)", name, revision.hash);
+            self.enter_diff_file_view(extracted, file_path, title, ReturnScreen::FileHistory);
+            return;
+        }
+        let mut viewer = ViewerState::new();
+        viewer.set_syntax_colors(self.theme.syntax);
+        match viewer.load_file(&extracted) {
+            Ok(_) => {
+                viewer.revision = Some(revision);
+                self.viewer_state = Some(viewer);
+                self.current_screen = Screen::FileViewer;
+            }
+            Err(e) => {
+                self.show_message(&format!("Cannot read file: {}", e));
+                self.open_file_history_dialog();
+            }
+        }
+    }
+
+    /// Go back to the history dialog after viewing or diffing a revision.
+    /// Returns false when no file history is open.
+    pub fn return_to_file_history(&mut self) -> bool {
+        let (mut screen, file_path) = match self.file_history_state {
+            Some(ref state) => (state.return_screen, state.file_path.clone()),
+            None => return false,
+        };
+        let showing_revision = self
+            .viewer_state
+            .as_ref()
+            .is_some_and(|v| v.revision.is_some());
+        if screen == Screen::FileViewer && showing_revision {
+            // The revision replaced the working copy in the viewer
+            let mut viewer = ViewerState::new();
+            viewer.set_syntax_colors(self.theme.syntax);
+            if viewer.load_file(&file_path).is_ok() {
+                self.viewer_state = Some(viewer);
+            } else {
+                screen = Screen::FilePanel;
+            }
+        }
+        self.current_screen = screen;
+        self.open_file_history_dialog();
+        true
+    }
+
     #[allow(dead_code)]
     pub fn show_advanced_search_dialog(&mut self) {
         self.advanced_search_state.active = true;
@@ -680,8 +849,7 @@ impl App {
             editor.set_syntax_colors(self.theme.syntax);
             match editor.load_file(&path) {
                 Ok(_) => {
-                    self.editor_state = Some(editor);
-                    self.current_screen = Screen::FileEditor;
+                    self.open_editor(editor);
                 }
                 Err(e) => {
                     self.show_message(&format!("Cannot open file: {}", e));
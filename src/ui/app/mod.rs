@@ -11,6 +11,7 @@ use std::path::{Path, PathBuf};
 
 use crate::config::Settings;
 use crate::keybindings::Keybindings;
+use crate::ui::editor_workspace::EditorBuffers;
 use crate::ui::file_editor::EditorState;
 use crate::ui::file_info::FileInfoState;
 use crate::ui::file_viewer::ViewerState;
@@ -63,6 +64,8 @@ pub struct App {
 
     // File editor state (새로운 고급 상태)
     pub editor_state: Option<EditorState>,
+    /// Editor buffers besides `editor_state`, and the split layout
+    pub editor_buffers: EditorBuffers,
 
     // File editor state (레거시 호환용 - 제거 예정)
     #[allow(dead_code)]
@@ -165,6 +168,7 @@ pub struct App {
 
     // Git log diff state
     pub git_log_diff_state: Option<GitLogDiffState>,
+    pub file_history_state: Option<FileHistoryState>,
 
     // Pending remote download → open action
     pub pending_remote_open: Option<PendingRemoteOpen>,
@@ -192,6 +196,7 @@ impl App {
             // 새로운 고급 상태
             viewer_state: None,
             editor_state: None,
+            editor_buffers: EditorBuffers::default(),
 
             // 레거시 호환용
             viewer_lines: Vec::new(),
@@ -247,6 +252,7 @@ impl App {
             git_screen_state: None,
             dedup_screen_state: None,
             git_log_diff_state: None,
+            file_history_state: None,
             pending_remote_open: None,
             remote_spinner: None,
         }
@@ -298,6 +304,7 @@ impl App {
             // 새로운 고급 상태
             viewer_state: None,
             editor_state: None,
+            editor_buffers: EditorBuffers::default(),
 
             // 레거시 호환용
             viewer_lines: Vec::new(),
@@ -353,6 +360,7 @@ impl App {
             git_screen_state: None,
             dedup_screen_state: None,
             git_log_diff_state: None,
+            file_history_state: None,
             pending_remote_open: None,
             remote_spinner: None,
         }
@@ -428,6 +436,12 @@ impl App {
         // Update diff compare method
         self.settings.diff_compare_method = new_settings.diff_compare_method;
 
+        // Update commit draft provider and style
+        self.settings.commit_draft = new_settings.commit_draft;
+
+        // Update editor language servers (running ones keep going until the editor closes)
+        self.settings.lsp_servers = new_settings.lsp_servers;
+
         // Update keybindings
         self.keybindings = crate::keybindings::Keybindings::from_config(&new_settings.keybindings);
         self.settings.keybindings = new_settings.keybindings;
@@ -439,6 +453,20 @@ impl App {
         self.show_message("Settings reloaded");
         true
     }
+    /// Show `editor` in the file editor, as another buffer when the editor
+    /// is already open
+    pub fn open_editor(&mut self, editor: EditorState) {
+        if self.current_screen == Screen::FileEditor {
+            if let Some(ref mut focused) = self.editor_state {
+                self.editor_buffers.open(focused, editor);
+                return;
+            }
+        }
+        self.editor_buffers.close_all();
+        self.editor_state = Some(editor);
+        self.current_screen = Screen::FileEditor;
+    }
+
     pub fn is_settings_file(path: &std::path::Path) -> bool {
         if let Some(config_path) = Settings::config_path() {
             path == config_path
@@ -1311,6 +1339,8 @@ impl App {
                 }
                 // If remote_ctx is temporarily taken by background thread, skip
             } else {
+                // Files may have changed behind git's back
+                panel.git_status.invalidate();
                 panel.load_files();
             }
         }
@@ -1516,6 +1546,28 @@ mod tests {
         cleanup_temp_dir(&temp_dir);
     }
 
+    #[test]
+    fn test_marked_files_keep_mark_order() {
+        let mut marks = MarkedFiles::new();
+        assert!(marks.insert("main".to_string()));
+        assert!(marks.insert("feature".to_string()));
+        assert!(!marks.insert("main".to_string()));
+        assert!(marks.insert("merge-base".to_string()));
+        assert_eq!(marks.len(), 3);
+        assert!(marks.contains("feature"));
+
+        // Unmarking and marking again moves a name to the end
+        assert!(marks.remove("main"));
+        assert!(!marks.remove("main"));
+        marks.insert("main".to_string());
+        let order: Vec<&str> = marks.iter().map(String::as_str).collect();
+        assert_eq!(order, vec!["feature", "merge-base", "main"]);
+
+        marks.clear();
+        assert!(marks.is_empty());
+        assert_eq!(marks.iter().count(), 0);
+    }
+
     // ========== App tests ==========
 
     #[test]
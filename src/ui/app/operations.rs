@@ -1,15 +1,18 @@
 use super::*;
 
 use std::collections::HashSet;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Instant;
 
+use crate::services::diff_sync::SyncSide;
 use crate::services::file_ops::{self, FileOperationType, ProgressMessage};
 use crate::services::remote;
 use crate::services::remote_transfer;
+use crate::ui::diff_file_view::ReturnScreen;
 use crate::ui::file_editor::EditorState;
 
 impl App {
@@ -17,19 +20,61 @@ impl App {
     /// With 2 panels: immediately enter diff screen
     /// With 3+ panels: first call selects first panel, second call selects second panel
     pub fn start_diff(&mut self) {
-        if self.panels.iter().any(|p| p.is_remote()) {
-            self.show_message("Diff is not supported for remote panels");
+        // Priority: if exactly 2 directories are selected in active panel, diff them
+        // (selection-based diffs read both trees locally)
+        let panel = &self.panels[self.active_panel_index];
+        if panel.is_remote() && !panel.selected_files.is_empty() {
+            self.show_message("Diff of selected items is not supported for remote panels");
+            return;
+        }
+        // In mark order, which decides the roles of a three-way merge
+        let marked = |want_dirs: bool| -> Vec<PathBuf> {
+            panel
+                .selected_files
+                .iter()
+                .filter(|name| {
+                    panel
+                        .files
+                        .iter()
+                        .any(|f| &f.name == *name && f.is_directory == want_dirs)
+                })
+                .map(|name| panel.path.join(name))
+                .collect()
+        };
+        let selected_dirs = marked(true);
+        if selected_dirs.len() == 3 {
+            let (base, left, right) = merge_roles(selected_dirs);
+            let roles = merge_roles_message(&base, &left, &right);
+            self.panels[self.active_panel_index].selected_files.clear();
+            self.enter_merge_diff_screen(base, left, right);
+            if let Some(state) = self.diff_state.as_mut() {
+                state.message = Some(roles);
+            }
+            return;
+        }
+
+        let selected_files = marked(false);
+        if selected_files.len() == 3 {
+            let (base, left, right) = merge_roles(selected_files);
+            let file_name = left
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let roles = merge_roles_message(&base, &left, &right);
+            self.panels[self.active_panel_index].selected_files.clear();
+            self.enter_merge_file_view(
+                base,
+                left,
+                right,
+                file_name,
+                ReturnScreen::Screen(Screen::FilePanel),
+            );
+            if let Some(state) = self.diff_file_view_state.as_mut() {
+                state.message = Some(roles);
+            }
             return;
         }
 
-        // Priority: if exactly 2 directories are selected in active panel, diff them
-        let panel = &self.panels[self.active_panel_index];
-        let selected_dirs: Vec<PathBuf> = panel
-            .files
-            .iter()
-            .filter(|f| f.is_directory && panel.selected_files.contains(&f.name))
-            .map(|f| panel.path.join(&f.name))
-            .collect();
         if selected_dirs.len() == 2 {
             let left = selected_dirs[0].clone();
             let right = selected_dirs[1].clone();
@@ -45,9 +90,7 @@ impl App {
 
         if self.panels.len() == 2 {
             // 2 panels: immediate diff
-            let left = self.panels[0].path.clone();
-            let right = self.panels[1].path.clone();
-            self.enter_diff_screen(left, right);
+            self.enter_panel_diff_screen(0, 1);
         } else {
             // 3+ panels: 2-stage selection
             if let Some(first) = self.diff_first_panel {
@@ -57,10 +100,8 @@ impl App {
                     self.show_message("Select a different panel for diff");
                     return;
                 }
-                let left = self.panels[first].path.clone();
-                let right = self.panels[second].path.clone();
                 self.diff_first_panel = None;
-                self.enter_diff_screen(left, right);
+                self.enter_panel_diff_screen(first, second);
             } else {
                 // First selection
                 self.diff_first_panel = Some(self.active_panel_index);
@@ -100,16 +141,129 @@ impl App {
         self.current_screen = Screen::DiffScreen;
     }
 
-    /// Enter file content diff view from the diff screen
+    /// Enter diff screen comparing the current directories of two panels.
+    /// One of them may be a remote (SFTP) panel.
+    fn enter_panel_diff_screen(&mut self, left_index: usize, right_index: usize) {
+        let left = self.panels[left_index].path.clone();
+        let right = self.panels[right_index].path.clone();
+        let left_profile = self.panels[left_index]
+            .remote_ctx
+            .as_ref()
+            .map(|ctx| ctx.profile.clone());
+        let right_profile = self.panels[right_index]
+            .remote_ctx
+            .as_ref()
+            .map(|ctx| ctx.profile.clone());
+        if self.panels[left_index].is_remote() && self.panels[right_index].is_remote() {
+            self.show_message("Diff between two remote panels is not supported");
+            return;
+        }
+        let remote = match (left_profile, right_profile) {
+            (Some(profile), None) => Some((SyncSide::Left, profile)),
+            (None, Some(profile)) => Some((SyncSide::Right, profile)),
+            _ => None,
+        };
+        let Some((side, profile)) = remote else {
+            if self.panels[left_index].is_remote() || self.panels[right_index].is_remote() {
+                self.show_message("Remote panel is not connected");
+                return;
+            }
+            self.enter_diff_screen(left, right);
+            return;
+        };
+
+        let compare_method =
+            crate::ui::diff_screen::parse_compare_method(&self.settings.diff_compare_method);
+        let sort_by = self.active_panel().sort_by;
+        let sort_order = self.active_panel().sort_order;
+        let mut state = crate::ui::diff_screen::DiffState::new_remote(
+            left,
+            right,
+            side,
+            profile,
+            compare_method,
+            sort_by,
+            sort_order,
+        );
+        state.start_comparison();
+        self.diff_state = Some(state);
+        self.current_screen = Screen::DiffScreen;
+    }
+
+    /// Enter three-way diff screen comparing `left` and `right` against `base`
+    pub fn enter_merge_diff_screen(&mut self, base: PathBuf, left: PathBuf, right: PathBuf) {
+        if left == right || base == left || base == right {
+            self.show_message("All three paths must be different");
+            return;
+        }
+        let compare_method =
+            crate::ui::diff_screen::parse_compare_method(&self.settings.diff_compare_method);
+        let sort_by = self.active_panel().sort_by;
+        let sort_order = self.active_panel().sort_order;
+        let mut state = crate::ui::diff_screen::DiffState::new_three_way(
+            base,
+            left,
+            right,
+            compare_method,
+            sort_by,
+            sort_order,
+        );
+        state.start_comparison();
+        self.diff_state = Some(state);
+        self.current_screen = Screen::DiffScreen;
+    }
+
+    /// Enter three-way file merge view; the merged result is saved to `left_path`
+    pub fn enter_merge_file_view(
+        &mut self,
+        base_path: PathBuf,
+        left_path: PathBuf,
+        right_path: PathBuf,
+        file_name: String,
+        return_screen: ReturnScreen,
+    ) {
+        let mut state = crate::ui::diff_file_view::DiffFileViewState::new_merge(
+            base_path, left_path, right_path, file_name,
+        );
+        state.return_screen = return_screen;
+        self.diff_file_view_state = Some(state);
+        self.current_screen = Screen::DiffFileView;
+    }
+
+    /// Enter three-way file merge view saving the merged result to `output_path`
+    pub fn enter_merge_file_view_into(
+        &mut self,
+        base_path: PathBuf,
+        left_path: PathBuf,
+        right_path: PathBuf,
+        output_path: PathBuf,
+        file_name: String,
+        return_screen: ReturnScreen,
+    ) {
+        let mut state = crate::ui::diff_file_view::DiffFileViewState::new_merge_into(
+            base_path,
+            left_path,
+            right_path,
+            output_path,
+            file_name,
+        );
+        state.return_screen = return_screen;
+        self.diff_file_view_state = Some(state);
+        self.current_screen = Screen::DiffFileView;
+    }
+
+    /// Enter file content diff view
     pub fn enter_diff_file_view(
         &mut self,
         left_path: PathBuf,
         right_path: PathBuf,
         file_name: String,
+        return_screen: ReturnScreen,
     ) {
-        self.diff_file_view_state = Some(crate::ui::diff_file_view::DiffFileViewState::new(
-            left_path, right_path, file_name,
-        ));
+        let mut state =
+            crate::ui::diff_file_view::DiffFileViewState::new(left_path, right_path, file_name);
+        state.return_screen = return_screen;
+        self.diff_file_view_state = Some(state);
         self.current_screen = Screen::DiffFileView;
     }
 
@@ -248,6 +402,93 @@ impl App {
         });
     }
 
+    /// Run the sync plan confirmed in the diff screen's dry-run dialog
+    pub fn execute_diff_sync(&mut self) {
+        let plan = match self.diff_state.as_mut().and_then(|s| s.pending_sync.take()) {
+            Some(p) => p,
+            None => return,
+        };
+
+        let mut progress = FileOperationProgress::new(FileOperationType::Sync);
+        progress.is_active = true;
+        let cancel_flag = progress.cancel_flag.clone();
+
+        let (tx, rx) = mpsc::channel();
+        progress.receiver = Some(rx);
+
+        thread::spawn(move || {
+            crate::services::diff_sync::execute_sync_with_progress(plan, cancel_flag, tx);
+        });
+
+        self.file_operation_progress = Some(progress);
+        self.dialog = Some(Dialog {
+            dialog_type: DialogType::Progress,
+            input: String::new(),
+            cursor_pos: 0,
+            message: String::new(),
+            completion: None,
+            selected_button: 0,
+            selection: None,
+            use_md5: false,
+        });
+    }
+
+    /// Write the export requested from the diff screens to `input`.
+    /// In the folder diff a `.html`/`.htm` path produces the HTML report,
+    /// anything else a unified patch; the file diff always exports a patch.
+    pub fn execute_diff_export(&mut self, input: &str) {
+        use crate::services::diff_export;
+
+        let path = PathBuf::from(input.trim());
+        let path = if path.is_absolute() {
+            path
+        } else {
+            self.active_panel().path.join(path)
+        };
+        let is_html = path
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"))
+            .unwrap_or(false);
+
+        if self.current_screen == Screen::DiffFileView {
+            let Some(state) = self.diff_file_view_state.as_mut() else {
+                return;
+            };
+            state.message = Some(match state.unified_patch() {
+                None => "Binary files cannot be exported as a patch".to_string(),
+                Some(patch) if patch.is_empty() => "No differences to export".to_string(),
+                Some(patch) => match fs::write(&path, patch) {
+                    Ok(()) => format!("Exported patch to {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                },
+            });
+            return;
+        }
+
+        let Some(state) = self.diff_state.as_mut() else {
+            return;
+        };
+        let result = if is_html {
+            let report = diff_export::html_report(state, chrono::Local::now());
+            fs::write(&path, report).map(|()| format!("Exported report to {}", path.display()))
+        } else if state.remote.is_some() {
+            Ok("Patch export is not available for remote panels".to_string())
+        } else {
+            diff_export::build_patch(&state.patch_targets()).and_then(|(patch, files)| {
+                if files == 0 {
+                    return Ok("No differences to export".to_string());
+                }
+                fs::write(&path, patch)?;
+                Ok(format!(
+                    "Exported patch of {} file(s) to {}",
+                    files,
+                    path.display()
+                ))
+            })
+        };
+        state.message = Some(result.unwrap_or_else(|e| format!("Export failed: {}", e)));
+    }
+
     pub fn execute_dedup(&mut self) {
         let path = self.active_panel().path.clone();
         self.dedup_screen_state = Some(crate::ui::dedup_screen::DedupScreenState::new(path));
@@ -1383,8 +1624,7 @@ impl App {
                 editor.set_syntax_colors(self.theme.syntax);
                 match editor.load_file(&path) {
                     Ok(_) => {
-                        self.editor_state = Some(editor);
-                        self.current_screen = Screen::FileEditor;
+                        self.open_editor(editor);
                     }
                     Err(e) => {
                         self.show_message(&format!("File created but cannot open: {}", e));
@@ -2411,3 +2651,29 @@ impl App {
         }
     }
 }
+
+/// Roles of three items selected for a three-way diff, given in the order
+/// they were marked: the first is the base, the second the left side (where a
+/// file merge is saved) and the third the right side.
+fn merge_roles(paths: Vec<PathBuf>) -> (PathBuf, PathBuf, PathBuf) {
+    let mut paths = paths.into_iter();
+    let base = paths.next().unwrap_or_default();
+    let left = paths.next().unwrap_or_default();
+    let right = paths.next().unwrap_or_default();
+    (base, left, right)
+}
+
+/// Tells which selected item got which role
+fn merge_roles_message(base: &Path, left: &Path, right: &Path) -> String {
+    let name = |p: &Path| {
+        p.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    format!(
+        "Base: {}  Left: {}  Right: {}",
+        name(base),
+        name(left),
+        name(right)
+    )
+}
@@ -1,18 +1,72 @@
 use chrono::{DateTime, Local};
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::services::git_status::GitStatusWatcher;
 use crate::services::remote::{self, ConnectionStatus, RemoteContext, SftpFileEntry};
 
 use super::state::*;
 
+/// Names of the marked files, remembered in the order they were marked
+#[derive(Debug, Clone, Default)]
+pub struct MarkedFiles {
+    names: HashSet<String>,
+    order: Vec<String>,
+}
+
+impl MarkedFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `name`; false when it already was
+    pub fn insert(&mut self, name: String) -> bool {
+        if !self.names.insert(name.clone()) {
+            return false;
+        }
+        self.order.push(name);
+        true
+    }
+
+    /// Unmark `name`; false when it wasn't marked
+    pub fn remove(&mut self, name: &str) -> bool {
+        if !self.names.remove(name) {
+            return false;
+        }
+        self.order.retain(|n| n != name);
+        true
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    pub fn clear(&mut self) {
+        self.names.clear();
+        self.order.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Marked names, first marked first
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.order.iter()
+    }
+}
+
 #[derive(Debug)]
 pub struct PanelState {
     pub path: PathBuf,
     pub files: Vec<FileItem>,
     pub selected_index: usize,
-    pub selected_files: HashSet<String>,
+    pub selected_files: MarkedFiles,
     pub sort_by: SortBy,
     pub sort_order: SortOrder,
     pub scroll_offset: usize,
@@ -23,6 +77,8 @@ pub struct PanelState {
     pub remote_ctx: Option<Box<RemoteContext>>,
     /// Cached remote display info (user, host, port) — survives while remote_ctx is temporarily taken
     pub remote_display: Option<(String, String, u16)>,
+    /// Git status of the repository the panel is in
+    pub git_status: GitStatusWatcher,
 }
 
 impl PanelState {
@@ -35,7 +91,7 @@ impl PanelState {
             path: valid_path,
             files: Vec::new(),
             selected_index: 0,
-            selected_files: HashSet::new(),
+            selected_files: MarkedFiles::new(),
             sort_by: SortBy::Name,
             sort_order: SortOrder::Asc,
             scroll_offset: 0,
@@ -44,6 +100,7 @@ impl PanelState {
             disk_available: 0,
             remote_ctx: None,
             remote_display: None,
+            git_status: GitStatusWatcher::default(),
         };
         state.load_files();
         state
@@ -62,7 +119,7 @@ impl PanelState {
             path: valid_path,
             files: Vec::new(),
             selected_index: 0,
-            selected_files: HashSet::new(),
+            selected_files: MarkedFiles::new(),
             sort_by,
             sort_order,
             scroll_offset: 0,
@@ -71,6 +128,7 @@ impl PanelState {
             disk_available: 0,
             remote_ctx: None,
             remote_display: None,
+            git_status: GitStatusWatcher::default(),
         };
         state.load_files();
         state
@@ -118,6 +176,7 @@ impl PanelState {
                 size: 0,
                 modified: Local::now(),
                 permissions: String::new(),
+                git_status: None,
             });
         }
 
@@ -178,6 +237,7 @@ impl PanelState {
                     size,
                     modified,
                     permissions,
+                    git_status: None,
                 })
             }));
 
@@ -187,9 +247,41 @@ impl PanelState {
         }
 
         self.finalize_load();
+        self.apply_git_status();
         self.update_disk_info();
     }
 
+    /// Set each item's git status from the cached status of the panel's repository.
+    /// A stale cache is shown as is while a refresh runs in the background.
+    fn apply_git_status(&mut self) {
+        match self.git_status.lookup(&self.path) {
+            Some((root, status)) => {
+                let rel_dir = self.path.strip_prefix(root).unwrap_or(Path::new(""));
+                for file in self.files.iter_mut() {
+                    file.git_status = if file.name == ".." {
+                        None
+                    } else {
+                        status.status_of(&rel_dir.join(&file.name), file.is_directory)
+                    };
+                }
+            }
+            None => {
+                for file in self.files.iter_mut() {
+                    file.git_status = None;
+                }
+            }
+        }
+    }
+
+    /// Apply a finished background git status refresh. Returns true when the items changed.
+    pub fn poll_git_status(&mut self) -> bool {
+        if self.is_remote() || !self.git_status.poll() {
+            return false;
+        }
+        self.apply_git_status();
+        true
+    }
+
     fn load_files_remote(&mut self) {
         self.files.clear();
 
@@ -205,6 +297,7 @@ impl PanelState {
                 size: 0,
                 modified: Local::now(),
                 permissions: String::new(),
+                git_status: None,
             });
         }
 
@@ -226,6 +319,7 @@ impl PanelState {
                         size: if entry.is_directory { 0 } else { entry.size },
                         modified: entry.modified,
                         permissions: entry.permissions,
+                        git_status: None,
                     })
                     .collect();
 
@@ -267,6 +361,7 @@ impl PanelState {
                 size: 0,
                 modified: Local::now(),
                 permissions: String::new(),
+                git_status: None,
             });
         }
 
@@ -280,6 +375,7 @@ impl PanelState {
                 size: if entry.is_directory { 0 } else { entry.size },
                 modified: entry.modified,
                 permissions: entry.permissions,
+                git_status: None,
             })
             .collect();
 
@@ -405,6 +501,7 @@ impl PanelState {
                     size: 0,
                     modified: Local::now(),
                     permissions: String::new(),
+                    git_status: None,
                 });
             }
             self.sort_items(&mut items);
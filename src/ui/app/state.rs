@@ -183,6 +183,7 @@ pub enum DialogType {
     ExtensionHandlerError,
     BinaryFileHandler,
     GitLogDiff,
+    FileHistory,
     /// Remote connection dialog - enter auth info for new server
     RemoteConnect,
     /// Remote profile save prompt - ask to save after successful connect
@@ -190,6 +191,10 @@ pub enum DialogType {
     EncryptConfirm,
     DecryptConfirm,
     DedupConfirm,
+    /// Folder diff sync confirmation showing the dry-run summary
+    DiffSyncConfirm,
+    /// Output path prompt for patch / HTML report export from the diff screens
+    DiffExport,
 }
 
 /// Settings dialog state
@@ -241,6 +246,8 @@ impl SettingsState {
             "content".to_string(),
             "modified_time".to_string(),
             "content_and_time".to_string(),
+            "size".to_string(),
+            "hash".to_string(),
         ];
         let diff_method_index = diff_methods
             .iter()
@@ -584,6 +591,21 @@ pub struct GitLogDiffState {
     pub visible_height: usize,
 }
 
+/// State for the git history dialog of a single file
+#[derive(Debug, Clone)]
+pub struct FileHistoryState {
+    /// Repository root
+    pub repo_path: PathBuf,
+    /// Working copy of the file
+    pub file_path: PathBuf,
+    pub revisions: Vec<crate::ui::git_screen::FileRevision>,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    pub visible_height: usize,
+    /// Screen the dialog was opened over, returned to after viewing a revision
+    pub return_screen: Screen,
+}
+
 /// Clipboard operation type for Ctrl+C/X/V operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClipboardOperation {
@@ -782,6 +804,8 @@ pub struct FileItem {
     pub modified: DateTime<Local>,
     #[allow(dead_code)]
     pub permissions: String,
+    /// Git status (aggregated for directories); None outside a repository or when clean
+    pub git_status: Option<crate::services::git_status::GitFileStatus>,
 }
 
 /// Parse sort_by string from settings to SortBy enum
@@ -13,12 +13,12 @@ use unicode_width::UnicodeWidthChar;
 
 use crate::keybindings::GotoAction;
 use crate::services::file_ops::FileOperationType;
-use crate::utils::format::{safe_prefix, safe_suffix};
+use crate::utils::format::{safe_prefix, safe_suffix, truncate_with_ellipsis};
 
 use super::{
     app::{
-        fuzzy_match, App, ConflictResolution, ConflictState, Dialog, DialogType, GitLogDiffState,
-        PathCompletion, RemoteConnectState, SettingsState,
+        fuzzy_match, App, ConflictResolution, ConflictState, Dialog, DialogType, FileHistoryState,
+        GitLogDiffState, PathCompletion, RemoteConnectState, SettingsState,
     },
     theme::Theme,
 };
@@ -278,6 +278,15 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
             CONFIRM_DIALOG_HEIGHT,
         ),
         DialogType::DedupConfirm => (60, 10, 10),
+        DialogType::DiffSyncConfirm => {
+            let w = area
+                .width
+                .saturating_sub(DIALOG_MARGIN)
+                .clamp(DIALOG_MIN_WIDTH, 80);
+            // summary lines + blank + buttons + blank + 2 border + top padding
+            let h = dialog.message.lines().count() as u16 + 6;
+            (w, h, h)
+        }
         DialogType::ExtensionHandlerError => {
             // Error dialog: wider to accommodate error messages, taller for multi-line
             (65, 8, 8)
@@ -312,6 +321,13 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
             SIMPLE_INPUT_HEIGHT,
             SIMPLE_INPUT_HEIGHT,
         ),
+        DialogType::DiffExport => (
+            area.width
+                .saturating_sub(DIALOG_MARGIN)
+                .clamp(DIALOG_MIN_WIDTH, 80),
+            SIMPLE_INPUT_HEIGHT,
+            SIMPLE_INPUT_HEIGHT,
+        ),
         DialogType::EncryptConfirm => (SIMPLE_DIALOG_WIDTH, 7, 7),
         DialogType::Progress => (
             SIMPLE_DIALOG_WIDTH,
@@ -348,7 +364,7 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
             let max_height = base_height + 4; // max 5 input lines
             (dialog_width, height, max_height)
         }
-        DialogType::GitLogDiff => {
+        DialogType::GitLogDiff | DialogType::FileHistory => {
             let w = area.width.saturating_sub(6).max(70).min(100);
             let h = area.height.saturating_sub(6).max(15).min(30);
             (w, h, h)
@@ -420,6 +436,9 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
         DialogType::DedupConfirm => {
             draw_dedup_confirm_dialog(frame, dialog, dialog_area, theme);
         }
+        DialogType::DiffSyncConfirm => {
+            draw_diff_sync_confirm_dialog(frame, app, dialog, dialog_area, theme);
+        }
         DialogType::LargeImageConfirm => {
             draw_confirm_dialog(frame, dialog, dialog_area, theme, " Large Image ");
         }
@@ -436,7 +455,8 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
         | DialogType::Mkdir
         | DialogType::Mkfile
         | DialogType::Rename
-        | DialogType::Tar => {
+        | DialogType::Tar
+        | DialogType::DiffExport => {
             draw_simple_input_dialog(frame, dialog, dialog_area, theme);
         }
         DialogType::Progress => {
@@ -468,6 +488,11 @@ pub fn draw_dialog(frame: &mut Frame, app: &App, dialog: &Dialog, area: Rect, th
                 draw_git_log_diff_dialog(frame, dialog, state, dialog_area, theme);
             }
         }
+        DialogType::FileHistory => {
+            if let Some(ref state) = app.file_history_state {
+                draw_file_history_dialog(frame, state, dialog_area, theme);
+            }
+        }
         DialogType::RemoteConnect => {
             draw_remote_connect_dialog(frame, app, dialog_area, theme);
         }
@@ -856,6 +881,7 @@ fn draw_simple_input_dialog(frame: &mut Frame, dialog: &Dialog, area: Rect, them
         DialogType::Tar => " Create Archive ",
         DialogType::RemoteProfileSave => " Save Profile ",
         DialogType::EncryptConfirm => " Encrypt ",
+        DialogType::DiffExport => " Export Diff ",
         _ => " Input ",
     };
 
@@ -984,7 +1010,8 @@ fn draw_simple_input_dialog(frame: &mut Frame, dialog: &Dialog, area: Rect, them
         || dialog.dialog_type == DialogType::Mkfile
         || dialog.dialog_type == DialogType::Rename
         || dialog.dialog_type == DialogType::RemoteProfileSave
-        || dialog.dialog_type == DialogType::EncryptConfirm)
+        || dialog.dialog_type == DialogType::EncryptConfirm
+        || dialog.dialog_type == DialogType::DiffExport)
         && !dialog.message.is_empty()
     {
         let message_y = inner.y;
@@ -1156,6 +1183,93 @@ fn draw_dedup_confirm_dialog(frame: &mut Frame, dialog: &Dialog, area: Rect, the
     );
 }
 
+/// Folder diff sync confirmation: dry-run summary (one operation per line) + Yes/No
+fn draw_diff_sync_confirm_dialog(
+    frame: &mut Frame,
+    app: &App,
+    dialog: &Dialog,
+    area: Rect,
+    theme: &Theme,
+) {
+    let title = app
+        .diff_state
+        .as_ref()
+        .and_then(|s| s.pending_sync.as_ref())
+        .map(|p| format!(" {} (dry run) ", p.mode.display_name()))
+        .unwrap_or_else(|| " Sync ".to_string());
+    let block = Block::default()
+        .title(title)
+        .title_style(
+            Style::default()
+                .fg(theme.confirm_dialog.title)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.confirm_dialog.border))
+        .style(Style::default().bg(theme.confirm_dialog.bg));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Summary: totals line first, then "+ R: path" copies and "- L: path" deletions
+    let max_width = inner.width.saturating_sub(2) as usize;
+    let lines: Vec<Line> = dialog
+        .message
+        .lines()
+        .enumerate()
+        .map(|(i, text)| {
+            let style = if i == 0 {
+                Style::default()
+                    .fg(theme.confirm_dialog.message_text)
+                    .add_modifier(Modifier::BOLD)
+            } else if text.starts_with('-') {
+                Style::default().fg(theme.state.error)
+            } else {
+                Style::default().fg(theme.confirm_dialog.message_text)
+            };
+            Line::from(Span::styled(truncate_with_ellipsis(text, max_width), style))
+        })
+        .collect();
+    let msg_height = inner.height.saturating_sub(4);
+    let msg_area = Rect::new(
+        inner.x + 1,
+        inner.y + 1,
+        inner.width.saturating_sub(2),
+        msg_height,
+    );
+    frame.render_widget(Paragraph::new(lines), msg_area);
+
+    // Buttons
+    let selected_style = Style::default()
+        .fg(theme.confirm_dialog.button_selected_text)
+        .bg(theme.confirm_dialog.button_selected_bg);
+    let normal_style = Style::default().fg(theme.confirm_dialog.button_text);
+
+    let yes_style = if dialog.selected_button == 0 {
+        selected_style
+    } else {
+        normal_style
+    };
+    let no_style = if dialog.selected_button == 1 {
+        selected_style
+    } else {
+        normal_style
+    };
+
+    let buttons = Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(" Yes ", yes_style),
+        Span::styled("    ", Style::default()),
+        Span::styled(" No ", no_style),
+        Span::styled("  ", Style::default()),
+    ]);
+    let button_area = Rect::new(inner.x + 1, inner.y + inner.height - 2, inner.width - 2, 1);
+    frame.render_widget(
+        Paragraph::new(buttons).alignment(ratatui::layout::Alignment::Center),
+        button_area,
+    );
+}
+
 /// Error dialog with OK button only
 fn draw_error_dialog(frame: &mut Frame, dialog: &Dialog, area: Rect, theme: &Theme, title: &str) {
     let block = Block::default()
@@ -1642,6 +1756,7 @@ fn draw_progress_dialog(frame: &mut Frame, app: &App, area: Rect, theme: &Theme)
         FileOperationType::Download => " Downloading ",
         FileOperationType::Encrypt => " Encrypting ",
         FileOperationType::Decrypt => " Decrypting ",
+        FileOperationType::Sync => " Syncing ",
     };
 
     let block = Block::default()
@@ -2255,7 +2370,8 @@ pub fn handle_paste(app: &mut App, text: &str) {
             | DialogType::Rename
             | DialogType::Tar
             | DialogType::BinaryFileHandler
-            | DialogType::EncryptConfirm => {
+            | DialogType::EncryptConfirm
+            | DialogType::DiffExport => {
                 // Delete selection if exists
                 if let Some((sel_start, sel_end)) = dialog.selection.take() {
                     let mut chars: Vec<char> = dialog.input.chars().collect();
@@ -2396,6 +2512,33 @@ pub fn handle_dialog_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers
                 }
                 _ => {}
             },
+            DialogType::DiffSyncConfirm => match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    app.dialog = None;
+                    app.execute_diff_sync();
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    app.dialog = None;
+                    if let Some(ref mut state) = app.diff_state {
+                        state.pending_sync = None;
+                    }
+                }
+                KeyCode::Left | KeyCode::Right | KeyCode::Tab => {
+                    dialog.selected_button = 1 - dialog.selected_button;
+                }
+                KeyCode::Enter => {
+                    if dialog.selected_button == 0 {
+                        app.dialog = None;
+                        app.execute_diff_sync();
+                    } else {
+                        app.dialog = None;
+                        if let Some(ref mut state) = app.diff_state {
+                            state.pending_sync = None;
+                        }
+                    }
+                }
+                _ => {}
+            },
             DialogType::LargeImageConfirm | DialogType::TrueColorWarning => match code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
                     app.dialog = None;
@@ -2472,6 +2615,9 @@ pub fn handle_dialog_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers
             DialogType::GitLogDiff => {
                 return handle_git_log_diff_input(app, code);
             }
+            DialogType::FileHistory => {
+                return handle_file_history_input(app, code);
+            }
             DialogType::RemoteConnect => {
                 return handle_remote_connect_input(app, code);
             }
@@ -2578,6 +2724,22 @@ pub fn handle_dialog_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers
                             }
                         }
 
+                        // For DiffExport, refuse to overwrite an existing file
+                        if dialog_type == DialogType::DiffExport && !input.trim().is_empty() {
+                            let target = PathBuf::from(input.trim());
+                            let target = if target.is_absolute() {
+                                target
+                            } else {
+                                app.active_panel().path.join(target)
+                            };
+                            if target.exists() {
+                                if let Some(ref mut d) = app.dialog {
+                                    d.message = format!("'{}' already exists!", input.trim());
+                                }
+                                return false;
+                            }
+                        }
+
                         // For EncryptConfirm, parse split size
                         if dialog_type == DialogType::EncryptConfirm {
                             let trimmed = input.trim();
@@ -2612,6 +2774,7 @@ pub fn handle_dialog_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers
                                 DialogType::Tar => app.execute_tar(&input),
                                 DialogType::Search => app.execute_search(&input),
                                 DialogType::Goto => app.execute_goto(&input),
+                                DialogType::DiffExport => app.execute_diff_export(&input),
                                 _ => {}
                             }
                         }
@@ -4170,6 +4333,136 @@ fn handle_git_log_diff_input(app: &mut App, code: KeyCode) -> bool {
     false
 }
 
+fn draw_file_history_dialog(
+    frame: &mut Frame,
+    state: &FileHistoryState,
+    area: Rect,
+    theme: &Theme,
+) {
+    let name = state
+        .file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(format!(" History: {} ", name))
+        .title_style(
+            Style::default()
+                .fg(theme.dialog.git_log_diff_title)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.dialog.git_log_diff_border))
+        .style(Style::default().bg(theme.dialog.git_log_diff_bg));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if inner.height < 5 || inner.width < 20 {
+        return;
+    }
+
+    let msg = format!(
+        "{} commit(s)  Enter: view  d: diff with working copy  Esc: close",
+        state.revisions.len()
+    );
+    frame.render_widget(
+        Paragraph::new(msg).style(Style::default().fg(theme.dialog.git_log_diff_message_text)),
+        Rect::new(inner.x + 1, inner.y, inner.width - 2, 1),
+    );
+
+    let list_height = (inner.height - 2) as usize; // header + gap
+    let max_entry_width = (inner.width - 2) as usize;
+    // Revisions from before a rename show the old path
+    let current_path = state.revisions.first().map(|r| r.path.as_str());
+
+    for (i, (idx, revision)) in state
+        .revisions
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(list_height)
+        .enumerate()
+    {
+        let entry = &revision.entry;
+        let renamed = if Some(revision.path.as_str()) != current_path {
+            format!(" [{}]", revision.path)
+        } else {
+            String::new()
+        };
+        let line_text = format!(
+            "{} {} - {} {}{}",
+            entry.hash, entry.message, entry.author, entry.date, renamed
+        );
+        let display_text = if line_text.len() > max_entry_width {
+            safe_prefix(&line_text, max_entry_width).to_string()
+        } else {
+            line_text
+        };
+
+        let is_cursor = idx == state.selected_index;
+        let (style, padded) = if is_cursor {
+            (
+                Style::default()
+                    .fg(theme.dialog.git_log_diff_cursor_text)
+                    .bg(theme.dialog.git_log_diff_cursor_bg),
+                format!("{:<width$}", display_text, width = max_entry_width),
+            )
+        } else {
+            (
+                Style::default().fg(theme.dialog.git_log_diff_entry_text),
+                display_text,
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(padded).style(style),
+            Rect::new(inner.x + 1, inner.y + 2 + i as u16, inner.width - 2, 1),
+        );
+    }
+}
+
+/// Handle input for the file history dialog
+fn handle_file_history_input(app: &mut App, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Enter => {
+            app.open_file_history_revision(false);
+            return false;
+        }
+        KeyCode::Char('d') | KeyCode::Char('D') => {
+            app.open_file_history_revision(true);
+            return false;
+        }
+        KeyCode::Esc => {
+            app.file_history_state = None;
+            app.dialog = None;
+            return false;
+        }
+        _ => {}
+    }
+
+    let state = match app.file_history_state {
+        Some(ref mut s) => s,
+        None => return false,
+    };
+    let last = state.revisions.len().saturating_sub(1);
+    let page = state.visible_height.max(1);
+    state.selected_index = match code {
+        KeyCode::Up => state.selected_index.saturating_sub(1),
+        KeyCode::Down => (state.selected_index + 1).min(last),
+        KeyCode::PageUp => state.selected_index.saturating_sub(page),
+        KeyCode::PageDown => (state.selected_index + page).min(last),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => return false,
+    };
+    if state.selected_index < state.scroll_offset {
+        state.scroll_offset = state.selected_index;
+    } else if state.selected_index >= state.scroll_offset + page {
+        state.scroll_offset = state.selected_index + 1 - page;
+    }
+    false
+}
+
 // ========== Remote Connect Dialog ==========
 
 /// Draw the remote connection form dialog
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
@@ -12,8 +14,10 @@ use ratatui::{
 
 use unicode_width::UnicodeWidthChar;
 
-use super::app::App;
+use super::app::{App, Screen};
+use super::file_editor::{write_atomically, EditorState};
 use super::theme::Theme;
+use crate::services::diff_sync::SyncSide;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Data structures
@@ -49,6 +53,71 @@ pub struct DiffFileViewState {
     pub file_name: String,
     pub max_scroll: usize,                 // max visual row offset
     pub change_visual_offsets: Vec<usize>, // visual row offset for each change_positions entry
+    /// Three-way merge state (only when opened with a base file)
+    pub merge: Option<MergeState>,
+    /// Transient status message, cleared on the next key press
+    pub message: Option<String>,
+    pub options: DiffOptions,
+    /// Either side is binary: nothing is diffed or editable
+    binary: bool,
+    left_text: SideText,
+    right_text: SideText,
+    /// Set after a first Close with unsaved hunk copies; a second Close discards them
+    confirm_discard: bool,
+    /// Set after a first Save refused because a file changed on disk; a second
+    /// Save overwrites it
+    confirm_overwrite: bool,
+    /// Where Close goes back to; set by whoever opens the view
+    pub return_screen: ReturnScreen,
+}
+
+/// Where the file diff view goes back to when closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReturnScreen {
+    Screen(Screen),
+    /// The file history dialog, over the screen it was opened from
+    FileHistory,
+}
+
+/// Lines of one side of the diff, kept so hunks can be copied and saved back.
+#[derive(Debug, Clone, Default)]
+struct SideText {
+    lines: Vec<String>,
+    line_ending: &'static str,
+    trailing_newline: bool,
+    /// False when the file had invalid UTF-8 (lossily decoded, so not writable)
+    valid_utf8: bool,
+    modified: bool,
+    /// The file doesn't exist: the other side was added or deleted
+    missing: bool,
+    /// Modification time and size of the file when loaded or last saved
+    disk_stamp: Option<(Option<SystemTime>, u64)>,
+}
+
+impl SideText {
+    fn parse(data: Option<&[u8]>) -> Self {
+        let missing = data.is_none();
+        let data = data.unwrap_or_default();
+        let text = String::from_utf8_lossy(data);
+        Self {
+            lines: text.lines().map(|l| l.to_string()).collect(),
+            line_ending: if text.contains("\r\n") { "\r\n" } else { "\n" },
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+            valid_utf8: std::str::from_utf8(data).is_ok(),
+            modified: false,
+            missing,
+            disk_stamp: None,
+        }
+    }
+
+    /// File content with the original line ending and final newline convention.
+    fn content(&self) -> String {
+        let mut content = self.lines.join(self.line_ending);
+        if self.trailing_newline && !self.lines.is_empty() {
+            content.push_str(self.line_ending);
+        }
+        content
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -62,74 +131,281 @@ fn is_binary(data: &[u8]) -> bool {
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// LCS diff algorithm
+// Myers diff algorithm
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Compute LCS (Longest Common Subsequence) of two line sequences.
-/// Returns a list of matched pairs (left_index, right_index).
+/// Compute the matched pairs (left_index, right_index) of a shortest edit script
+/// between two sequences, i.e. one of their longest common subsequences.
 ///
-/// For files up to ~10000 lines each, uses standard O(n*m) DP.
-/// For larger files, falls back to a simpler sequential comparison.
-fn compute_lcs(left: &[String], right: &[String]) -> Vec<(usize, usize)> {
-    let n = left.len();
-    let m = right.len();
+/// Uses Myers' O((n+m)·D) algorithm in its linear-space divide-and-conquer form
+/// (middle snake), so memory stays proportional to the input even for large files.
+fn compute_lcs<T: PartialEq>(left: &[T], right: &[T]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    myers_recurse(left, 0, right, 0, &mut result);
+    result
+}
+
+fn myers_recurse<T: PartialEq>(
+    a: &[T],
+    a_off: usize,
+    b: &[T],
+    b_off: usize,
+    out: &mut Vec<(usize, usize)>,
+) {
+    // Common prefix and suffix are always part of the LCS
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    out.extend((0..prefix).map(|i| (a_off + i, b_off + i)));
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let (a_off, b_off) = (a_off + prefix, b_off + prefix);
 
-    // Size limit: if combined lines exceed 20000, use simple sequential matching
-    if n + m > 20000 {
-        return compute_lcs_simple(left, right);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    if !a_mid.is_empty() && !b_mid.is_empty() {
+        if let Some((x0, y0, x1, y1)) = middle_snake(a_mid, b_mid) {
+            myers_recurse(&a_mid[..x0], a_off, &b_mid[..y0], b_off, out);
+            out.extend((0..x1 - x0).map(|i| (a_off + x0 + i, b_off + y0 + i)));
+            myers_recurse(&a_mid[x1..], a_off + x1, &b_mid[y1..], b_off + y1, out);
+        }
     }
 
-    // Standard O(n*m) DP approach
-    // dp[i][j] = length of LCS of left[0..i] and right[0..j]
-    let mut dp = vec![vec![0u32; m + 1]; n + 1];
+    out.extend((0..suffix).map(|i| (a_off + a_mid.len() + i, b_off + b_mid.len() + i)));
+}
 
-    for i in 1..=n {
-        for j in 1..=m {
-            if left[i - 1] == right[j - 1] {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
+/// Find the middle snake of an optimal edit path: returns its start (x0, y0) and
+/// end (x1, y1). The paths before and after it each need at most ceil(D/2) edits.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize, usize, usize)> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // vf[k]: furthest x on forward diagonal k; vb[k]: furthest x' on reverse diagonal k
+    // (-1 = not reached yet)
+    let mut vf = vec![-1isize; (2 * max + 3) as usize];
+    let mut vb = vec![-1isize; (2 * max + 3) as usize];
+    vf[(offset + 1) as usize] = 0;
+    vb[(offset + 1) as usize] = 0;
+    // Whether the opposite search already reached past `x` on the same diagonal
+    let overlaps = |v: &[isize], index: isize, x: isize| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| v.get(i))
+            .is_some_and(|&other| other != -1 && x + other >= n)
+    };
+    // Diagonals trimmed from each end once a search runs off the edit grid
+    let (mut f_start, mut f_end, mut b_start, mut b_end) = (0, 0, 0, 0);
+
+    for d in 0..=max {
+        // Forward search from the top-left corner
+        let mut k = -d + f_start;
+        while k <= d - f_end {
+            let ki = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && vf[ki - 1] < vf[ki + 1]) {
+                vf[ki + 1]
             } else {
-                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
+                vf[ki - 1] + 1
+            };
+            let mut y = x - k;
+            let (x0, y0) = (x, y);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
             }
+            vf[ki] = x;
+            if x > n {
+                f_end += 2;
+            } else if y > m {
+                f_start += 2;
+            } else if odd && overlaps(&vb, delta - k + offset, x) {
+                return Some((x0 as usize, y0 as usize, x as usize, y as usize));
+            }
+            k += 2;
+        }
+
+        // Reverse search from the bottom-right corner (on reversed sequences)
+        let mut k = -d + b_start;
+        while k <= d - b_end {
+            let ki = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && vb[ki - 1] < vb[ki + 1]) {
+                vb[ki + 1]
+            } else {
+                vb[ki - 1] + 1
+            };
+            let mut y = x - k;
+            let (x0, y0) = (x, y);
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            vb[ki] = x;
+            if x > n {
+                b_end += 2;
+            } else if y > m {
+                b_start += 2;
+            } else if !odd && overlaps(&vf, delta - k + offset, x) {
+                return Some((
+                    (n - x) as usize,
+                    (m - y) as usize,
+                    (n - x0) as usize,
+                    (m - y0) as usize,
+                ));
+            }
+            k += 2;
         }
     }
+    // The searches always meet by d = max; treat a miss as "nothing in common"
+    None
+}
 
-    // Backtrack to find actual LCS pairs
-    let mut result = Vec::new();
-    let mut i = n;
-    let mut j = m;
-    while i > 0 && j > 0 {
-        if left[i - 1] == right[j - 1] {
-            result.push((i - 1, j - 1));
-            i -= 1;
-            j -= 1;
-        } else if dp[i - 1][j] >= dp[i][j - 1] {
-            i -= 1;
+/// Comparison options for the file diff.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Treat lines differing only in whitespace as equal
+    pub ignore_whitespace: bool,
+    /// Treat lines differing only in letter case as equal
+    pub ignore_case: bool,
+    /// Highlight changed characters instead of changed words inside modified lines
+    pub char_level: bool,
+}
+
+impl DiffOptions {
+    /// Normalized form of `text` used for equality checks under these options.
+    fn normalize(&self, text: &str) -> String {
+        let text: String = if self.ignore_whitespace {
+            text.chars().filter(|c| !c.is_whitespace()).collect()
+        } else {
+            text.to_string()
+        };
+        if self.ignore_case {
+            text.to_lowercase()
         } else {
-            j -= 1;
+            text
         }
     }
-
-    result.reverse();
-    result
 }
 
-/// Simple sequential matching for large files.
-/// Uses a greedy approach: for each left line, find the nearest unmatched right line.
-fn compute_lcs_simple(left: &[String], right: &[String]) -> Vec<(usize, usize)> {
-    let mut result = Vec::new();
-    let mut right_pos = 0;
+/// Match lines of both sides under `opts`.
+/// Lines are interned to integer ids first so the diff compares cheap keys, and
+/// lines present on only one side are dropped before diffing: they can never
+/// match, and leaving them out keeps mostly-rewritten files fast.
+pub fn compute_line_matches(
+    left: &[String],
+    right: &[String],
+    opts: &DiffOptions,
+) -> Vec<(usize, usize)> {
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut intern = |line: &String| {
+        let key = opts.normalize(line);
+        let next = ids.len();
+        *ids.entry(key).or_insert(next)
+    };
+    let left_ids: Vec<usize> = left.iter().map(&mut intern).collect();
+    let right_ids: Vec<usize> = right.iter().map(&mut intern).collect();
+
+    let mut on_left = vec![false; ids.len()];
+    let mut on_right = vec![false; ids.len()];
+    left_ids.iter().for_each(|&id| on_left[id] = true);
+    right_ids.iter().for_each(|&id| on_right[id] = true);
+    let shared = |ids: &[usize]| -> (Vec<usize>, Vec<usize>) {
+        ids.iter()
+            .enumerate()
+            .filter(|(_, &id)| on_left[id] && on_right[id])
+            .map(|(i, &id)| (i, id))
+            .unzip()
+    };
+    let (left_index, left_shared) = shared(&left_ids);
+    let (right_index, right_shared) = shared(&right_ids);
 
-    for (li, left_line) in left.iter().enumerate() {
-        for ri in right_pos..right.len() {
-            if left_line == &right[ri] {
-                result.push((li, ri));
-                right_pos = ri + 1;
-                break;
+    compute_lcs(&left_shared, &right_shared)
+        .into_iter()
+        .map(|(l, r)| (left_index[l], right_index[r]))
+        .collect()
+}
+
+/// Split expanded line characters into diff tokens: runs of word characters,
+/// runs of whitespace, and single punctuation characters (or single characters
+/// in `char_level` mode). Returns (start, end) index ranges.
+fn tokenize(chars: &[char], char_level: bool) -> Vec<(usize, usize)> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        let cls = class(chars[start]);
+        if !char_level && cls != 2 {
+            while end < chars.len() && class(chars[end]) == cls {
+                end += 1;
             }
         }
+        tokens.push((start, end));
+        start = end;
     }
+    tokens
+}
 
-    result
+/// Per-character change flags for both sides of a modified line.
+/// Tokens are diffed with the same algorithm as lines; unmatched tokens are flagged.
+fn inline_change_masks(
+    left: &[char],
+    right: &[char],
+    opts: &DiffOptions,
+) -> (Vec<bool>, Vec<bool>) {
+    // (token range, comparison key) for every token taking part in the diff
+    let keyed = |chars: &[char]| -> Vec<((usize, usize), String)> {
+        tokenize(chars, opts.char_level)
+            .into_iter()
+            .filter(|&(s, _)| !(opts.ignore_whitespace && chars[s].is_whitespace()))
+            .map(|(s, e)| {
+                let text: String = chars[s..e].iter().collect();
+                let key = if opts.ignore_case {
+                    text.to_lowercase()
+                } else {
+                    text
+                };
+                ((s, e), key)
+            })
+            .collect()
+    };
+    let left_tokens = keyed(left);
+    let right_tokens = keyed(right);
+    let left_keys: Vec<&String> = left_tokens.iter().map(|(_, k)| k).collect();
+    let right_keys: Vec<&String> = right_tokens.iter().map(|(_, k)| k).collect();
+
+    let mut left_matched = vec![false; left_tokens.len()];
+    let mut right_matched = vec![false; right_tokens.len()];
+    for (l, r) in compute_lcs(&left_keys, &right_keys) {
+        left_matched[l] = true;
+        right_matched[r] = true;
+    }
+
+    let to_mask = |len: usize, tokens: &[((usize, usize), String)], matched: &[bool]| {
+        let mut mask = vec![false; len];
+        for (((s, e), _), &is_matched) in tokens.iter().zip(matched) {
+            if !is_matched {
+                mask[*s..*e].iter_mut().for_each(|m| *m = true);
+            }
+        }
+        mask
+    };
+    (
+        to_mask(left.len(), &left_tokens, &left_matched),
+        to_mask(right.len(), &right_tokens, &right_matched),
+    )
 }
 
 /// Build diff lines from two line sequences using LCS matching.
@@ -259,6 +535,422 @@ fn emit_gap(
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Three-way merge
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Classification of a region in a three-way (base/left/right) merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeChunkKind {
+    /// All three sides agree
+    Unchanged,
+    /// Only the left side changed this region
+    LeftChanged,
+    /// Only the right side changed this region
+    RightChanged,
+    /// Both sides made the identical change
+    BothSame,
+    /// Both sides changed the region differently
+    Conflict,
+}
+
+/// Which side's lines a merge chunk contributes to the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeChoice {
+    Left,
+    Right,
+    /// Left lines followed by right lines
+    Both,
+    Base,
+    /// Text typed in the block editor (`MergeChunk::edited`)
+    Edited,
+    /// Not picked yet: written out with git-style conflict markers
+    Unresolved,
+}
+
+impl MergeChoice {
+    pub fn display_name(&self) -> &str {
+        match self {
+            MergeChoice::Left => "Left",
+            MergeChoice::Right => "Right",
+            MergeChoice::Both => "Both",
+            MergeChoice::Base => "Base",
+            MergeChoice::Edited => "Edited",
+            MergeChoice::Unresolved => "Unresolved",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeChunk {
+    pub kind: MergeChunkKind,
+    pub base: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    pub choice: MergeChoice,
+    /// Lines written by the block editor, used when `choice` is `Edited`
+    pub edited: Vec<String>,
+}
+
+impl MergeChunk {
+    fn new(kind: MergeChunkKind, base: &[String], left: &[String], right: &[String]) -> Self {
+        let choice = match kind {
+            MergeChunkKind::Unchanged | MergeChunkKind::LeftChanged | MergeChunkKind::BothSame => {
+                MergeChoice::Left
+            }
+            MergeChunkKind::RightChanged => MergeChoice::Right,
+            MergeChunkKind::Conflict => MergeChoice::Unresolved,
+        };
+        Self {
+            kind,
+            base: base.to_vec(),
+            left: left.to_vec(),
+            right: right.to_vec(),
+            choice,
+            edited: Vec::new(),
+        }
+    }
+
+    /// Lines this chunk contributes to the merged output.
+    pub fn output_lines(&self) -> Vec<String> {
+        match self.choice {
+            MergeChoice::Left => self.left.clone(),
+            MergeChoice::Right => self.right.clone(),
+            MergeChoice::Base => self.base.clone(),
+            MergeChoice::Edited => self.edited.clone(),
+            MergeChoice::Both => {
+                let mut lines = self.left.clone();
+                lines.extend(self.right.iter().cloned());
+                lines
+            }
+            MergeChoice::Unresolved => {
+                let mut lines = Vec::with_capacity(self.left.len() + self.right.len() + 3);
+                lines.push("<<<<<<< left".to_string());
+                lines.extend(self.left.iter().cloned());
+                lines.push("=======".to_string());
+                lines.extend(self.right.iter().cloned());
+                lines.push(">>>>>>> right".to_string());
+                lines
+            }
+        }
+    }
+}
+
+/// In-place text editor for the lines of one merge chunk.
+#[derive(Debug, Clone)]
+pub struct ChunkEditor {
+    /// Index into `MergeState::chunks`
+    pub chunk: usize,
+    pub lines: Vec<String>,
+    pub row: usize,
+    /// Cursor column in characters
+    pub col: usize,
+}
+
+impl ChunkEditor {
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(i, _)| i)
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let i = self.byte_index();
+        self.lines[self.row].insert(i, c);
+        self.col += 1;
+    }
+
+    pub fn newline(&mut self) {
+        let i = self.byte_index();
+        let rest = self.lines[self.row].split_off(i);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let i = self.byte_index();
+            self.lines[self.row].remove(i);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.col < self.line_len() {
+            let i = self.byte_index();
+            self.lines[self.row].remove(i);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    /// Remove the cursor line (handy for dropping conflict markers)
+    pub fn delete_line(&mut self) {
+        if self.lines.len() > 1 {
+            self.lines.remove(self.row);
+            self.row = self.row.min(self.lines.len() - 1);
+        } else {
+            self.lines[0].clear();
+        }
+        self.col = self.col.min(self.line_len());
+    }
+
+    pub fn move_cursor(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up => self.row = self.row.saturating_sub(1),
+            KeyCode::Down => self.row = (self.row + 1).min(self.lines.len() - 1),
+            KeyCode::Left if self.col > 0 => self.col -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len();
+            }
+            KeyCode::Right if self.col < self.line_len() => self.col += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            _ => {}
+        }
+        self.col = self.col.min(self.line_len());
+    }
+}
+
+/// Merge view state attached to a `DiffFileViewState` opened with a base file.
+pub struct MergeState {
+    pub base_path: PathBuf,
+    /// File the merged result is written to
+    pub output_path: PathBuf,
+    pub chunks: Vec<MergeChunk>,
+    /// Indices into `chunks` of every non-`Unchanged` chunk (navigation targets)
+    pub change_indices: Vec<usize>,
+    /// Index into `change_indices`
+    pub current: usize,
+    /// Whether the merged output (instead of the left/right diff) is shown
+    pub show_merged: bool,
+    pub scroll: usize,
+    pub max_scroll: usize,
+    /// Row offset of each `change_indices` entry in the merged view
+    pub change_row_offsets: Vec<usize>,
+    pub modified: bool,
+    /// Set after a first Close on an unsaved merge; a second Close discards it
+    pub confirm_discard: bool,
+    /// Block editor open on one chunk
+    pub edit: Option<ChunkEditor>,
+    /// Line ending and final newline convention of the written file, taken from the left side
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+impl MergeState {
+    pub fn new(base_path: PathBuf, output_path: PathBuf, chunks: Vec<MergeChunk>) -> Self {
+        let change_indices = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.kind != MergeChunkKind::Unchanged)
+            .map(|(i, _)| i)
+            .collect();
+        Self {
+            base_path,
+            output_path,
+            chunks,
+            change_indices,
+            current: 0,
+            show_merged: true,
+            scroll: 0,
+            max_scroll: 0,
+            change_row_offsets: Vec::new(),
+            modified: false,
+            confirm_discard: false,
+            edit: None,
+            line_ending: "\n",
+            trailing_newline: true,
+        }
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|c| c.kind == MergeChunkKind::Conflict)
+            .count()
+    }
+
+    pub fn unresolved_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .filter(|c| c.choice == MergeChoice::Unresolved)
+            .count()
+    }
+
+    /// Set the choice of the currently focused change chunk
+    pub fn choose(&mut self, choice: MergeChoice) -> bool {
+        let Some(&idx) = self.change_indices.get(self.current) else {
+            return false;
+        };
+        let chunk = &mut self.chunks[idx];
+        if chunk.choice != choice {
+            chunk.choice = choice;
+            self.modified = true;
+        }
+        true
+    }
+
+    /// Open the block editor on the focused change chunk, starting from its current output
+    pub fn start_edit(&mut self) -> bool {
+        let Some(&idx) = self.change_indices.get(self.current) else {
+            return false;
+        };
+        let mut lines = self.chunks[idx].output_lines();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        self.edit = Some(ChunkEditor {
+            chunk: idx,
+            lines,
+            row: 0,
+            col: 0,
+        });
+        self.show_merged = true;
+        true
+    }
+
+    /// Store the editor's text as the chunk's output. A single empty line means no lines.
+    pub fn finish_edit(&mut self) {
+        if let Some(edit) = self.edit.take() {
+            let mut lines = edit.lines;
+            if lines.len() == 1 && lines[0].is_empty() {
+                lines.clear();
+            }
+            let chunk = &mut self.chunks[edit.chunk];
+            chunk.edited = lines;
+            chunk.choice = MergeChoice::Edited;
+            self.modified = true;
+        }
+    }
+
+    pub fn merged_lines(&self) -> Vec<String> {
+        self.chunks.iter().flat_map(|c| c.output_lines()).collect()
+    }
+
+    /// Write the merged result to `output_path`, replacing it in one step so an
+    /// interrupted save leaves the previous file (e.g. a conflicted worktree file)
+    pub fn save(&mut self) -> Result<(), String> {
+        let merged = SideText {
+            lines: self.merged_lines(),
+            line_ending: self.line_ending,
+            trailing_newline: self.trailing_newline,
+            ..SideText::default()
+        };
+        write_atomically(&self.output_path, merged.content().as_bytes())?;
+        self.modified = false;
+        Ok(())
+    }
+}
+
+/// Split base/left/right line sequences into merge chunks (diff3).
+///
+/// Stable regions are base lines matched by both the base→left and base→right
+/// LCS; everything between two stable regions becomes one chunk classified by
+/// which sides deviate from base.
+pub fn compute_merge_chunks(base: &[String], left: &[String], right: &[String]) -> Vec<MergeChunk> {
+    let mut left_match: Vec<Option<usize>> = vec![None; base.len()];
+    for (b, l) in compute_lcs(base, left) {
+        left_match[b] = Some(l);
+    }
+    let mut right_match: Vec<Option<usize>> = vec![None; base.len()];
+    for (b, r) in compute_lcs(base, right) {
+        right_match[b] = Some(r);
+    }
+
+    let mut chunks: Vec<MergeChunk> = Vec::new();
+    let (mut b, mut l, mut r) = (0usize, 0usize, 0usize);
+
+    loop {
+        // Consume stable lines
+        let stable_start = b;
+        let (sl, sr) = (l, r);
+        while b < base.len() && left_match[b] == Some(l) && right_match[b] == Some(r) {
+            b += 1;
+            l += 1;
+            r += 1;
+        }
+        if b > stable_start {
+            push_merge_chunk(
+                &mut chunks,
+                MergeChunk::new(
+                    MergeChunkKind::Unchanged,
+                    &base[stable_start..b],
+                    &left[sl..l],
+                    &right[sr..r],
+                ),
+            );
+        }
+
+        // Find the next base line matched on both sides
+        let next = (b..base.len()).find(|&i| left_match[i].is_some() && right_match[i].is_some());
+        let (nb, nl, nr) = match next {
+            Some(i) => (i, left_match[i].unwrap_or(l), right_match[i].unwrap_or(r)),
+            None => (base.len(), left.len(), right.len()),
+        };
+
+        if nb > b || nl > l || nr > r {
+            let base_part = &base[b..nb];
+            let left_part = &left[l..nl];
+            let right_part = &right[r..nr];
+            let left_changed = left_part != base_part;
+            let right_changed = right_part != base_part;
+            let kind = match (left_changed, right_changed) {
+                (false, false) => MergeChunkKind::Unchanged,
+                (true, false) => MergeChunkKind::LeftChanged,
+                (false, true) => MergeChunkKind::RightChanged,
+                (true, true) if left_part == right_part => MergeChunkKind::BothSame,
+                (true, true) => MergeChunkKind::Conflict,
+            };
+            push_merge_chunk(
+                &mut chunks,
+                MergeChunk::new(kind, base_part, left_part, right_part),
+            );
+        }
+
+        b = nb;
+        l = nl;
+        r = nr;
+        if next.is_none() {
+            break;
+        }
+    }
+
+    chunks
+}
+
+/// Append a chunk, coalescing consecutive unchanged chunks.
+fn push_merge_chunk(chunks: &mut Vec<MergeChunk>, chunk: MergeChunk) {
+    if chunk.kind == MergeChunkKind::Unchanged {
+        if let Some(last) = chunks.last_mut() {
+            if last.kind == MergeChunkKind::Unchanged {
+                last.base.extend(chunk.base);
+                last.left.extend(chunk.left);
+                last.right.extend(chunk.right);
+                return;
+            }
+        }
+    }
+    chunks.push(chunk);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // DiffFileViewState implementation
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -271,107 +963,244 @@ impl DiffFileViewState {
         // Check for binary files
         let left_is_binary = left_data.as_ref().map_or(false, |d| is_binary(d));
         let right_is_binary = right_data.as_ref().map_or(false, |d| is_binary(d));
+        let binary = left_is_binary || right_is_binary;
+
+        let mut state = Self {
+            left_path,
+            right_path,
+            diff_lines: Vec::new(),
+            scroll: 0,
+            visible_height: 0,
+            left_total_lines: 0,
+            right_total_lines: 0,
+            change_positions: Vec::new(),
+            current_change: 0,
+            file_name,
+            max_scroll: 0,
+            change_visual_offsets: Vec::new(),
+            merge: None,
+            message: None,
+            options: DiffOptions::default(),
+            binary,
+            left_text: SideText::default(),
+            right_text: SideText::default(),
+            confirm_discard: false,
+            confirm_overwrite: false,
+            return_screen: ReturnScreen::Screen(Screen::FilePanel),
+        };
 
-        if left_is_binary || right_is_binary {
+        if binary {
             // Binary file: show a single informational line
-            let diff_lines = vec![DiffLine {
+            state.diff_lines = vec![DiffLine {
                 left_line_no: None,
                 left_content: Some("Binary file".to_string()),
                 right_line_no: None,
                 right_content: Some("Binary file".to_string()),
                 line_status: DiffLineStatus::Same,
             }];
-            return Self {
-                left_path,
-                right_path,
-                diff_lines,
-                scroll: 0,
-                visible_height: 0,
-                left_total_lines: 0,
-                right_total_lines: 0,
-                change_positions: Vec::new(),
-                current_change: 0,
-                file_name,
-                max_scroll: 0,
-                change_visual_offsets: Vec::new(),
-            };
+            return state;
         }
 
-        // Read as text, handle missing files gracefully
-        let left_text = left_data
-            .map(|d| String::from_utf8_lossy(&d).into_owned())
-            .unwrap_or_default();
-        let right_text = right_data
-            .map(|d| String::from_utf8_lossy(&d).into_owned())
-            .unwrap_or_default();
+        // Read as text, handle missing files gracefully (all LeftOnly or RightOnly)
+        state.left_text = SideText::parse(left_data.as_deref());
+        state.right_text = SideText::parse(right_data.as_deref());
+        state.left_text.disk_stamp = EditorState::read_disk_stamp(&state.left_path);
+        state.right_text.disk_stamp = EditorState::read_disk_stamp(&state.right_path);
+        state.recompute();
+        state
+    }
 
-        let left_lines: Vec<String> = if left_text.is_empty() {
-            Vec::new()
-        } else {
-            left_text.lines().map(|l| l.to_string()).collect()
+    /// Re-run the line diff over the current side texts and options.
+    fn recompute(&mut self) {
+        let left = &self.left_text.lines;
+        let right = &self.right_text.lines;
+        let matches = compute_line_matches(left, right, &self.options);
+        let (diff_lines, change_positions) = build_diff_lines(left, right, &matches);
+        self.left_total_lines = left.len();
+        self.right_total_lines = right.len();
+        self.diff_lines = diff_lines;
+        self.change_positions = change_positions;
+        self.current_change = self
+            .current_change
+            .min(self.change_positions.len().saturating_sub(1));
+    }
+
+    /// Change the comparison options, re-diffing when line equality is affected.
+    pub fn set_options(&mut self, options: DiffOptions) {
+        let rediff = options.ignore_whitespace != self.options.ignore_whitespace
+            || options.ignore_case != self.options.ignore_case;
+        self.options = options;
+        if rediff && !self.binary {
+            self.recompute();
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.left_text.modified || self.right_text.modified
+    }
+
+    /// Replace the current change block on `target` with the other side's lines.
+    pub fn copy_current_hunk(&mut self, target: SyncSide) -> Result<(), String> {
+        if self.binary {
+            return Err("Binary files cannot be edited".to_string());
+        }
+        let Some(&start) = self.change_positions.get(self.current_change) else {
+            return Err("No changes to copy".to_string());
         };
-        let right_lines: Vec<String> = if right_text.is_empty() {
-            Vec::new()
-        } else {
-            right_text.lines().map(|l| l.to_string()).collect()
+        let end = self.diff_lines[start..]
+            .iter()
+            .position(|d| d.line_status == DiffLineStatus::Same)
+            .map_or(self.diff_lines.len(), |p| start + p);
+        let left_count =
+            |lines: &[DiffLine]| lines.iter().filter(|d| d.left_line_no.is_some()).count();
+        let right_count =
+            |lines: &[DiffLine]| lines.iter().filter(|d| d.right_line_no.is_some()).count();
+        let left_start = left_count(&self.diff_lines[..start]);
+        let left_range = left_start..left_start + left_count(&self.diff_lines[start..end]);
+        let right_start = right_count(&self.diff_lines[..start]);
+        let right_range = right_start..right_start + right_count(&self.diff_lines[start..end]);
+
+        let (source, dest, source_range, dest_range, dest_path) = match target {
+            SyncSide::Right => (
+                &self.left_text,
+                &mut self.right_text,
+                left_range,
+                right_range,
+                &self.right_path,
+            ),
+            SyncSide::Left => (
+                &self.right_text,
+                &mut self.left_text,
+                right_range,
+                left_range,
+                &self.left_path,
+            ),
         };
+        if !dest.valid_utf8 {
+            return Err(format!(
+                "{} is not valid UTF-8 and cannot be edited",
+                dest_path.display()
+            ));
+        }
+        let replacement = source.lines[source_range].to_vec();
+        dest.lines.splice(dest_range, replacement);
+        dest.modified = true;
+        self.confirm_discard = false;
+        self.recompute();
+        Ok(())
+    }
 
-        let left_total_lines = left_lines.len();
-        let right_total_lines = right_lines.len();
-
-        // Handle case where one file doesn't exist (all LeftOnly or RightOnly)
-        let (diff_lines, change_positions) = if left_lines.is_empty() && !right_lines.is_empty() {
-            let mut diffs = Vec::new();
-            let mut changes = Vec::new();
-            if !right_lines.is_empty() {
-                changes.push(0);
+    /// Unified patch from the left to the right side, including unsaved hunk copies.
+    /// None for binary files; empty when both sides are identical.
+    pub fn unified_patch(&self) -> Option<String> {
+        if self.binary {
+            return None;
+        }
+        // A missing side that no hunk copy has filled is an added or deleted file
+        let label = |text: &SideText, prefix: &str| {
+            if text.missing && !text.modified {
+                "/dev/null".to_string()
+            } else {
+                format!("{}/{}", prefix, self.file_name)
             }
-            for (idx, line) in right_lines.iter().enumerate() {
-                diffs.push(DiffLine {
-                    left_line_no: None,
-                    left_content: None,
-                    right_line_no: Some(idx + 1),
-                    right_content: Some(line.clone()),
-                    line_status: DiffLineStatus::RightOnly,
-                });
+        };
+        Some(crate::services::diff_export::unified_diff(
+            &label(&self.left_text, "a"),
+            &label(&self.right_text, "b"),
+            &self.left_text.content(),
+            &self.right_text.content(),
+            crate::services::diff_export::CONTEXT_LINES,
+        ))
+    }
+
+    /// Write sides changed by hunk copies back to disk. Returns the saved paths.
+    /// A file changed on disk since it was loaded is only overwritten by a
+    /// second save.
+    pub fn save_edits(&mut self) -> Result<Vec<PathBuf>, String> {
+        let sides = [
+            (&self.left_text, &self.left_path),
+            (&self.right_text, &self.right_path),
+        ];
+        let changed = sides.iter().find(|(text, path)| {
+            text.modified && EditorState::read_disk_stamp(path) != text.disk_stamp
+        });
+        if let Some((_, path)) = changed {
+            if !self.confirm_overwrite {
+                self.confirm_overwrite = true;
+                return Err(format!(
+                    "{} changed on disk since it was loaded, save again to overwrite",
+                    path.display()
+                ));
             }
-            (diffs, changes)
-        } else if !left_lines.is_empty() && right_lines.is_empty() {
-            let mut diffs = Vec::new();
-            let mut changes = Vec::new();
-            if !left_lines.is_empty() {
-                changes.push(0);
+        }
+
+        let mut saved = Vec::new();
+        for (text, path) in [
+            (&mut self.left_text, &self.left_path),
+            (&mut self.right_text, &self.right_path),
+        ] {
+            if text.modified {
+                write_atomically(path, text.content().as_bytes())?;
+                text.modified = false;
+                text.disk_stamp = EditorState::read_disk_stamp(path);
+                saved.push(path.clone());
             }
-            for (idx, line) in left_lines.iter().enumerate() {
-                diffs.push(DiffLine {
-                    left_line_no: Some(idx + 1),
-                    left_content: Some(line.clone()),
-                    right_line_no: None,
-                    right_content: None,
-                    line_status: DiffLineStatus::LeftOnly,
-                });
+        }
+        self.confirm_overwrite = false;
+        Ok(saved)
+    }
+
+    /// Like `new_merge`, but the merged result is written to `output_path`
+    /// (e.g. the worktree file of a git conflict merged from its index stages).
+    pub fn new_merge_into(
+        base_path: PathBuf,
+        left_path: PathBuf,
+        right_path: PathBuf,
+        output_path: PathBuf,
+        file_name: String,
+    ) -> Self {
+        let mut state = Self::new_merge(base_path, left_path, right_path, file_name);
+        if let Some(ref mut merge) = state.merge {
+            merge.output_path = output_path;
+        }
+        state
+    }
+
+    /// Open a three-way merge of `left` and `right` against their common `base`.
+    /// The left/right diff stays available; the merged result is written to `left_path`.
+    pub fn new_merge(
+        base_path: PathBuf,
+        left_path: PathBuf,
+        right_path: PathBuf,
+        file_name: String,
+    ) -> Self {
+        let mut state = Self::new(left_path.clone(), right_path.clone(), file_name);
+
+        let read_text = |path: &PathBuf| -> Option<SideText> {
+            let data = fs::read(path).unwrap_or_default();
+            if is_binary(&data) {
+                return None;
             }
-            (diffs, changes)
-        } else {
-            // Both files have content: compute LCS-based diff
-            let lcs = compute_lcs(&left_lines, &right_lines);
-            build_diff_lines(&left_lines, &right_lines, &lcs)
+            Some(SideText::parse(Some(&data)))
         };
 
-        Self {
-            left_path,
-            right_path,
-            diff_lines,
-            scroll: 0,
-            visible_height: 0,
-            left_total_lines,
-            right_total_lines,
-            change_positions,
-            current_change: 0,
-            file_name,
-            max_scroll: 0,
-            change_visual_offsets: Vec::new(),
+        match (
+            read_text(&base_path),
+            read_text(&left_path),
+            read_text(&right_path),
+        ) {
+            (Some(base), Some(left), Some(right)) => {
+                let chunks = compute_merge_chunks(&base.lines, &left.lines, &right.lines);
+                let mut merge = MergeState::new(base_path, left_path, chunks);
+                merge.line_ending = left.line_ending;
+                merge.trailing_newline = left.trailing_newline;
+                state.merge = Some(merge);
+            }
+            _ => {
+                state.message = Some("Binary files cannot be merged".to_string());
+            }
         }
+        state
     }
 }
 
@@ -410,7 +1239,14 @@ pub fn draw(
     state.visible_height = content_area.height as usize;
 
     // ─── Header ─────────────────────────────────────────────────────────────
-    let header_text = format!("[FILE DIFF] {}", state.file_name);
+    let header_text = match state.merge {
+        Some(ref merge) => format!(
+            "[MERGE] {}  (base: {})",
+            state.file_name,
+            merge.base_path.display()
+        ),
+        None => format!("[FILE DIFF] {}", state.file_name),
+    };
     let header_line = Line::from(Span::styled(
         header_text,
         Style::default()
@@ -421,6 +1257,21 @@ pub fn draw(
         Paragraph::new(header_line).style(Style::default().bg(theme.diff_file_view.bg));
     frame.render_widget(header_paragraph, header_area);
 
+    if let Some(ref mut merge) = state.merge {
+        if merge.show_merged {
+            draw_merge_view(
+                frame,
+                merge,
+                content_area,
+                status_area,
+                state.message.as_deref(),
+                theme,
+            );
+            draw_function_bar(frame, state, function_area, theme, kb);
+            return;
+        }
+    }
+
     // ─── Content: split 50:50 horizontal ────────────────────────────────────
     let content_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -455,11 +1306,8 @@ pub fn draw(
         let visual_rows = if dl.line_status == DiffLineStatus::Modified {
             let lc = dl.left_content.as_deref().unwrap_or("");
             let rc = dl.right_content.as_deref().unwrap_or("");
-            let left_exp = expand_chars(lc);
-            let right_exp = expand_chars(rc);
-            let max_len = left_exp.len().max(right_exp.len());
-            count_inline_wrapped_rows(&left_exp, max_len, wrap_width)
-                .max(count_inline_wrapped_rows(&right_exp, max_len, wrap_width))
+            count_inline_wrapped_rows(&expand_chars(lc), wrap_width)
+                .max(count_inline_wrapped_rows(&expand_chars(rc), wrap_width))
         } else {
             let left_rows = match &dl.left_content {
                 Some(c) if dl.line_status != DiffLineStatus::RightOnly => {
@@ -532,6 +1380,12 @@ pub fn draw(
         None
     };
 
+    let layout = RowLayout {
+        line_no_width,
+        left_width: left_inner_width,
+        right_width: right_area.width as usize,
+        wrap_width,
+    };
     let mut visual_rows_filled = 0usize;
     let mut logical_idx = start_logical;
     let mut first_line = true;
@@ -539,15 +1393,7 @@ pub fn draw(
     while visual_rows_filled < visible_lines && logical_idx < total_logical {
         let diff_line = &state.diff_lines[logical_idx];
         let is_current_change = current_change_pos == Some(logical_idx);
-        let rows = render_diff_line(
-            diff_line,
-            line_no_width,
-            left_inner_width,
-            right_area.width as usize,
-            wrap_width,
-            theme,
-            is_current_change,
-        );
+        let rows = render_diff_line(diff_line, &layout, theme, is_current_change, &state.options);
         let rows_to_skip = if first_line { skip_rows } else { 0 };
         first_line = false;
         for (row_idx, (left_spans, right_spans)) in rows.into_iter().enumerate() {
@@ -606,14 +1452,33 @@ pub fn draw(
     } else {
         0
     };
-    let status_text = format!(
-        " Lines: {}/{} Changes: {} [{}/{}]",
-        state.left_total_lines,
-        state.right_total_lines,
-        changes_count,
-        current_display,
-        changes_count,
-    );
+    let mut flags: Vec<&str> = Vec::new();
+    if state.options.ignore_whitespace {
+        flags.push("ignore space");
+    }
+    if state.options.ignore_case {
+        flags.push("ignore case");
+    }
+    flags.push(if state.options.char_level {
+        "char"
+    } else {
+        "word"
+    });
+    if state.is_modified() {
+        flags.push("modified");
+    }
+    let status_text = match state.message {
+        Some(ref msg) => format!(" {}", msg),
+        None => format!(
+            " Lines: {}/{} Changes: {} [{}/{}]  ({})",
+            state.left_total_lines,
+            state.right_total_lines,
+            changes_count,
+            current_display,
+            changes_count,
+            flags.join(", "),
+        ),
+    };
     let status_line = Line::from(Span::styled(
         status_text,
         Style::default()
@@ -625,6 +1490,16 @@ pub fn draw(
     frame.render_widget(status_paragraph, status_area);
 
     // ─── FunctionBar (keybindings에서 동적으로) ────────────────────────────
+    draw_function_bar(frame, state, function_area, theme, kb);
+}
+
+fn draw_function_bar(
+    frame: &mut Frame,
+    state: &DiffFileViewState,
+    area: Rect,
+    theme: &Theme,
+    kb: &crate::keybindings::Keybindings,
+) {
     use crate::keybindings::DiffFileViewAction;
     let key_style = Style::default()
         .fg(theme.diff_file_view.footer_key)
@@ -632,7 +1507,7 @@ pub fn draw(
     let text_style = Style::default()
         .fg(theme.diff_file_view.footer_text)
         .bg(theme.diff_file_view.bg);
-    let shortcuts: Vec<(String, &str)> = vec![
+    let mut shortcuts: Vec<(String, &str)> = vec![
         (
             kb.diff_file_view_first_key(DiffFileViewAction::MoveUp)
                 .to_string(),
@@ -653,34 +1528,322 @@ pub fn draw(
                 .to_string(),
             "prev ",
         ),
-        (
-            kb.diff_file_view_first_key(DiffFileViewAction::Close)
-                .to_string(),
-            "back",
-        ),
     ];
+    if state.merge.is_some() {
+        shortcuts.extend([
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::ToggleMergeView)
+                    .to_string(),
+                "merge/diff ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::TakeLeft)
+                    .to_string(),
+                "left ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::TakeRight)
+                    .to_string(),
+                "right ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::TakeBoth)
+                    .to_string(),
+                "both ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::TakeBase)
+                    .to_string(),
+                "base ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::EditChunk)
+                    .to_string(),
+                "edit ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::Save)
+                    .to_string(),
+                "save ",
+            ),
+        ]);
+    } else {
+        shortcuts.extend([
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::CopyToRight)
+                    .to_string(),
+                "copy\u{2192} ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::CopyToLeft)
+                    .to_string(),
+                "copy\u{2190} ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::ToggleIgnoreWhitespace)
+                    .to_string(),
+                "space ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::ToggleIgnoreCase)
+                    .to_string(),
+                "case ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::ToggleInlineMode)
+                    .to_string(),
+                "word/char ",
+            ),
+            (
+                kb.diff_file_view_first_key(DiffFileViewAction::ExportPatch)
+                    .to_string(),
+                "patch ",
+            ),
+        ]);
+        if state.is_modified() {
+            shortcuts.push((
+                kb.diff_file_view_first_key(DiffFileViewAction::Save)
+                    .to_string(),
+                "save ",
+            ));
+        }
+    }
+    shortcuts.push((
+        kb.diff_file_view_first_key(DiffFileViewAction::Close)
+            .to_string(),
+        "back",
+    ));
     let mut fn_spans = Vec::new();
     for (key, label) in &shortcuts {
-        fn_spans.push(Span::styled(key.as_str(), key_style));
+        fn_spans.push(Span::styled(key.clone(), key_style));
         fn_spans.push(Span::styled(":", text_style));
         fn_spans.push(Span::styled(*label, text_style));
     }
     let fn_line = Line::from(fn_spans);
     let fn_paragraph = Paragraph::new(fn_line).style(Style::default().bg(theme.diff_file_view.bg));
-    frame.render_widget(fn_paragraph, function_area);
+    frame.render_widget(fn_paragraph, area);
 }
 
-/// Render a single DiffLine into multiple visual rows of (left_spans, right_spans).
-/// Wraps long lines at `wrap_width` (same for both sides) and pads to each panel's own width.
-fn render_diff_line<'a>(
-    diff_line: &DiffLine,
+/// Draw the merged output of a three-way merge as a single pane.
+/// Every changed chunk is preceded by a header row naming its kind and current choice.
+fn draw_merge_view(
+    frame: &mut Frame,
+    merge: &mut MergeState,
+    content_area: Rect,
+    status_area: Rect,
+    message: Option<&str>,
+    theme: &Theme,
+) {
+    let colors = &theme.diff_file_view;
+    let width = content_area.width as usize;
+    let visible = content_area.height as usize;
+
+    let current_chunk = merge.change_indices.get(merge.current).copied();
+
+    // Build every row of the merged view (styled), remembering change chunk offsets
+    let mut rows: Vec<(String, Style)> = Vec::new();
+    let mut change_row_offsets = Vec::with_capacity(merge.change_indices.len());
+    let mut out_line_no = 0usize;
+    let total_changes = merge.change_indices.len();
+    // Row of the block editor's cursor, when one is open
+    let mut cursor_row = None;
+    for (ci, chunk) in merge.chunks.iter().enumerate() {
+        if chunk.kind == MergeChunkKind::Unchanged {
+            for line in &chunk.left {
+                out_line_no += 1;
+                rows.push((
+                    format!(" {:>5}\u{2502}{}", out_line_no, line),
+                    Style::default().fg(colors.same_text).bg(colors.bg),
+                ));
+            }
+            continue;
+        }
+
+        let change_no = change_row_offsets.len() + 1;
+        change_row_offsets.push(rows.len());
+        let kind_label = match chunk.kind {
+            MergeChunkKind::LeftChanged => "changed left",
+            MergeChunkKind::RightChanged => "changed right",
+            MergeChunkKind::BothSame => "changed both (same)",
+            MergeChunkKind::Conflict => "CONFLICT",
+            MergeChunkKind::Unchanged => "",
+        };
+        let marker = if current_chunk == Some(ci) {
+            "\u{25B6}"
+        } else {
+            " "
+        };
+        let header_style = if chunk.kind == MergeChunkKind::Conflict {
+            Style::default()
+                .fg(colors.inline_change_text)
+                .bg(colors.inline_change_bg)
+        } else {
+            Style::default()
+                .fg(colors.modified_text)
+                .bg(colors.modified_bg)
+        };
+        rows.push((
+            format!(
+                "{}\u{2500}\u{2500} [{}/{}] {} \u{2192} {} ",
+                marker,
+                change_no,
+                total_changes,
+                kind_label,
+                chunk.choice.display_name()
+            ),
+            header_style,
+        ));
+
+        if let Some(edit) = merge.edit.as_ref().filter(|e| e.chunk == ci) {
+            cursor_row = Some(rows.len() + edit.row);
+            for line in &edit.lines {
+                out_line_no += 1;
+                rows.push((
+                    format!(" {:>5}\u{2502}{}", out_line_no, line),
+                    Style::default()
+                        .fg(colors.modified_text)
+                        .bg(colors.modified_bg),
+                ));
+            }
+            continue;
+        }
+
+        let body_style = match chunk.choice {
+            MergeChoice::Left => Style::default()
+                .fg(colors.left_only_text)
+                .bg(colors.left_only_bg),
+            MergeChoice::Right => Style::default()
+                .fg(colors.right_only_text)
+                .bg(colors.right_only_bg),
+            MergeChoice::Both | MergeChoice::Base | MergeChoice::Edited => Style::default()
+                .fg(colors.modified_text)
+                .bg(colors.modified_bg),
+            MergeChoice::Unresolved => Style::default()
+                .fg(colors.inline_change_text)
+                .bg(colors.inline_change_bg),
+        };
+        for line in chunk.output_lines() {
+            out_line_no += 1;
+            rows.push((format!(" {:>5}\u{2502}{}", out_line_no, line), body_style));
+        }
+    }
+    merge.change_row_offsets = change_row_offsets;
+    merge.max_scroll = rows.len().saturating_sub(visible);
+    if merge.scroll > merge.max_scroll {
+        merge.scroll = merge.max_scroll;
+    }
+    // Keep the editor cursor on screen
+    if let Some(row) = cursor_row {
+        if row < merge.scroll {
+            merge.scroll = row;
+        } else if visible > 0 && row >= merge.scroll + visible {
+            merge.scroll = row + 1 - visible;
+        }
+    }
+
+    let mut lines: Vec<Line> = Vec::with_capacity(visible);
+    for (text, style) in rows.iter().skip(merge.scroll).take(visible) {
+        let expanded: String = expand_chars(text).into_iter().collect();
+        let fitted = fit_to_width(&expanded, width);
+        lines.push(Line::from(Span::styled(fitted, *style)));
+    }
+    while lines.len() < visible {
+        lines.push(Line::from(Span::styled(
+            " ".repeat(width),
+            Style::default().bg(colors.bg),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), content_area);
+
+    if rows.len() > visible {
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
+        let mut scrollbar_state = ScrollbarState::new(merge.max_scroll).position(merge.scroll);
+        frame.render_stateful_widget(scrollbar, content_area, &mut scrollbar_state);
+    }
+
+    if let (Some(row), Some(edit)) = (cursor_row, merge.edit.as_ref()) {
+        // " nnnnn│" prefix, then the expanded text before the cursor
+        let before: String = edit.lines[edit.row].chars().take(edit.col).collect();
+        let text_width: usize = expand_chars(&before)
+            .iter()
+            .map(|c| c.width().unwrap_or(0))
+            .sum();
+        let x = content_area.x as usize + 7 + text_width;
+        if x < (content_area.x + content_area.width) as usize {
+            frame.set_cursor_position((x as u16, content_area.y + (row - merge.scroll) as u16));
+        }
+    }
+
+    let status_text = match message {
+        Some(msg) => format!(" {}", msg),
+        None if merge.edit.is_some() => {
+            " Editing block | Ctrl+S: apply  Esc: cancel  Ctrl+K: delete line".to_string()
+        }
+        None => format!(
+            " Merge \u{2192} {} | Changes: {} Conflicts: {} Unresolved: {}{}",
+            merge.output_path.display(),
+            merge.change_indices.len(),
+            merge.conflict_count(),
+            merge.unresolved_count(),
+            if merge.modified { " [modified]" } else { "" },
+        ),
+    };
+    let status_line = Line::from(Span::styled(
+        status_text,
+        Style::default()
+            .fg(colors.status_bar_text)
+            .bg(colors.status_bar_bg),
+    ));
+    frame.render_widget(
+        Paragraph::new(status_line).style(Style::default().bg(colors.status_bar_bg)),
+        status_area,
+    );
+}
+
+/// Truncate or space-pad a string to exactly `width` display columns.
+fn fit_to_width(s: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut col = 0usize;
+    for ch in s.chars() {
+        let ch_w = ch.width().unwrap_or(0);
+        if col + ch_w > width {
+            break;
+        }
+        out.push(ch);
+        col += ch_w;
+    }
+    while col < width {
+        out.push(' ');
+        col += 1;
+    }
+    out
+}
+
+/// Column widths shared by every row of the two panels
+#[derive(Clone, Copy)]
+struct RowLayout {
     line_no_width: usize,
     left_width: usize,
     right_width: usize,
+    /// Where long lines wrap, the same on both sides
     wrap_width: usize,
+}
+
+/// Render a single DiffLine into multiple visual rows of (left_spans, right_spans).
+/// Wraps long lines at `wrap_width` (same for both sides) and pads to each panel's own width.
+fn render_diff_line<'a>(
+    diff_line: &DiffLine,
+    layout: &RowLayout,
     theme: &Theme,
     is_current_change: bool,
+    options: &DiffOptions,
 ) -> Vec<(Vec<Span<'a>>, Vec<Span<'a>>)> {
+    let RowLayout {
+        line_no_width,
+        left_width,
+        right_width,
+        wrap_width,
+    } = *layout;
     let colors = &theme.diff_file_view;
 
     // Determine styles based on status
@@ -734,7 +1897,7 @@ fn render_diff_line<'a>(
     let marker = if is_current_change { "\u{25B6}" } else { " " };
     let num_width = line_no_width.saturating_sub(1); // 1 char reserved for marker
 
-    // Inline change style for character-level highlighting within Modified lines
+    // Inline change style for word/character-level highlighting within Modified lines
     let inline_style = Style::default()
         .fg(colors.inline_change_text)
         .bg(colors.inline_change_bg);
@@ -750,12 +1913,13 @@ fn render_diff_line<'a>(
     let right_content_rows: Vec<Vec<Span<'a>>>;
 
     if diff_line.line_status == DiffLineStatus::Modified {
-        let lc = diff_line.left_content.as_deref().unwrap_or("");
-        let rc = diff_line.right_content.as_deref().unwrap_or("");
+        let lc = expand_chars(diff_line.left_content.as_deref().unwrap_or(""));
+        let rc = expand_chars(diff_line.right_content.as_deref().unwrap_or(""));
+        let (left_changed, right_changed) = inline_change_masks(&lc, &rc, options);
         left_content_rows =
-            build_inline_wrapped_lines(lc, rc, wrap_width, left_style, inline_style);
+            build_inline_wrapped_lines(&lc, &left_changed, wrap_width, left_style, inline_style);
         right_content_rows =
-            build_inline_wrapped_lines(rc, lc, wrap_width, right_style, inline_style);
+            build_inline_wrapped_lines(&rc, &right_changed, wrap_width, right_style, inline_style);
     } else {
         // Non-modified lines: wrap_content at wrap_width
         if left_empty {
@@ -979,17 +2143,14 @@ fn wrap_content(content: &str, width: usize) -> Vec<String> {
 }
 
 /// Count wrapped visual rows for inline-diff rendering of Modified lines.
-/// Mirrors the wrapping logic of `build_inline_wrapped_lines` exactly:
-/// processes `max_len` positions, using the actual char from `this_chars`
-/// or a space if beyond its length.
-fn count_inline_wrapped_rows(this_chars: &[char], max_len: usize, width: usize) -> usize {
+/// Mirrors the wrapping logic of `build_inline_wrapped_lines` exactly.
+fn count_inline_wrapped_rows(chars: &[char], width: usize) -> usize {
     if width == 0 {
         return 1;
     }
     let mut lines = 1usize;
     let mut col = 0usize;
-    for i in 0..max_len {
-        let ch = this_chars.get(i).copied().unwrap_or(' ');
+    for &ch in chars {
         let ch_w = ch.width().unwrap_or(0);
         if ch_w > 0 && col + ch_w > width {
             lines += 1;
@@ -1020,13 +2181,15 @@ fn expand_chars(s: &str) -> Vec<char> {
 }
 
 /// Build wrapped visual lines for a Modified line with inline diff highlighting.
+/// `chars` is the tab-expanded line and `changed` flags the characters to highlight
+/// (see `inline_change_masks`).
 /// Returns Vec of span-rows, each row exactly `width` display columns (space-padded).
 /// Wrapping semantics match `wrap_content`: a row is flushed only when the NEXT char
 /// would overflow, not when exactly filling. This keeps row counts consistent with
 /// `count_inline_wrapped_rows`.
 fn build_inline_wrapped_lines<'a>(
-    this_content: &str,
-    other_content: &str,
+    chars: &[char],
+    changed: &[bool],
     width: usize,
     base_style: Style,
     inline_style: Style,
@@ -1034,10 +2197,6 @@ fn build_inline_wrapped_lines<'a>(
     if width == 0 {
         return vec![vec![]];
     }
-    let this_chars = expand_chars(this_content);
-    let other_chars = expand_chars(other_content);
-
-    let max_len = this_chars.len().max(other_chars.len());
 
     let mut all_rows: Vec<Vec<Span<'a>>> = Vec::new();
     let mut row_spans: Vec<Span<'a>> = Vec::new();
@@ -1045,14 +2204,9 @@ fn build_inline_wrapped_lines<'a>(
     let mut buf_is_diff = false;
     let mut col = 0usize;
 
-    for i in 0..max_len {
-        let this_ch = this_chars.get(i).copied();
-        let other_ch = other_chars.get(i).copied();
-        let ch = this_ch.unwrap_or(' ');
+    for (i, &ch) in chars.iter().enumerate() {
         let ch_w = ch.width().unwrap_or(0);
-        // Only highlight as diff when this side has actual content at this position.
-        // Beyond this side's content, use base_style (padding spaces, not inline highlight).
-        let is_diff = this_ch.is_some() && this_ch != other_ch;
+        let is_diff = changed.get(i).copied().unwrap_or(false);
 
         // Check if this char would overflow current row
         if ch_w > 0 && col + ch_w > width {
@@ -1116,66 +2270,288 @@ fn build_inline_wrapped_lines<'a>(
 // Input handling
 // ═══════════════════════════════════════════════════════════════════════════════
 
+fn handle_chunk_edit_input(merge: &mut MergeState, code: KeyCode, modifiers: KeyModifiers) {
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+    let Some(ref mut edit) = merge.edit else {
+        return;
+    };
+    match code {
+        KeyCode::Esc => merge.edit = None,
+        KeyCode::Char('s') if ctrl => merge.finish_edit(),
+        KeyCode::Char('k') if ctrl => edit.delete_line(),
+        KeyCode::Char(c) if !ctrl => edit.insert_char(c),
+        KeyCode::Tab => edit.insert_char('\t'),
+        KeyCode::Enter => edit.newline(),
+        KeyCode::Backspace => edit.backspace(),
+        KeyCode::Delete => edit.delete(),
+        _ => edit.move_cursor(code),
+    }
+}
+
 pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     use crate::keybindings::DiffFileViewAction;
 
+    let action = app.keybindings.diff_file_view_action(code, modifiers);
+
     let state = match app.diff_file_view_state.as_mut() {
         Some(s) => s,
         None => return,
     };
+    state.message = None;
 
-    let visible = state.visible_height;
-    let max_scroll = state.max_scroll;
+    // The block editor takes raw keys
+    if let Some(ref mut merge) = state.merge {
+        if merge.edit.is_some() {
+            handle_chunk_edit_input(merge, code, modifiers);
+            return;
+        }
+    }
 
-    if let Some(action) = app.keybindings.diff_file_view_action(code, modifiers) {
-        match action {
-            DiffFileViewAction::MoveUp => {
-                state.scroll = state.scroll.saturating_sub(1);
+    let Some(action) = action else {
+        return;
+    };
+
+    if action == DiffFileViewAction::Close {
+        if let Some(ref mut merge) = state.merge {
+            if merge.modified && !merge.confirm_discard {
+                merge.confirm_discard = true;
+                state.message =
+                    Some("Merge result not saved. Press again to discard changes".to_string());
+                return;
             }
-            DiffFileViewAction::MoveDown => {
-                if state.scroll < max_scroll {
-                    state.scroll += 1;
+        }
+        if state.is_modified() && !state.confirm_discard {
+            state.confirm_discard = true;
+            state.message = Some("Edits not saved. Press again to discard changes".to_string());
+            return;
+        }
+        let return_screen = state.return_screen;
+        app.diff_file_view_state = None;
+        match return_screen {
+            ReturnScreen::FileHistory => {
+                if !app.return_to_file_history() {
+                    app.current_screen = Screen::FilePanel;
                 }
             }
-            DiffFileViewAction::PageUp => {
-                state.scroll = state.scroll.saturating_sub(visible);
+            ReturnScreen::Screen(screen) => {
+                if screen == Screen::GitScreen {
+                    // A resolved conflict changes the repository status
+                    if let Some(ref mut git) = app.git_screen_state {
+                        git.reload();
+                    }
+                }
+                app.current_screen = screen;
             }
-            DiffFileViewAction::PageDown => {
-                state.scroll = (state.scroll + visible).min(max_scroll);
+        }
+        return;
+    }
+    if let Some(ref mut merge) = state.merge {
+        merge.confirm_discard = false;
+    }
+    state.confirm_discard = false;
+
+    // Merge-specific actions
+    let merge_choice = match action {
+        DiffFileViewAction::TakeLeft => Some(MergeChoice::Left),
+        DiffFileViewAction::TakeRight => Some(MergeChoice::Right),
+        DiffFileViewAction::TakeBoth => Some(MergeChoice::Both),
+        DiffFileViewAction::TakeBase => Some(MergeChoice::Base),
+        _ => None,
+    };
+    if let Some(choice) = merge_choice {
+        match state.merge {
+            Some(ref mut merge) => {
+                merge.show_merged = true;
+                if !merge.choose(choice) {
+                    state.message = Some("No changes to merge".to_string());
+                }
             }
-            DiffFileViewAction::GoHome => {
-                state.scroll = 0;
+            None => state.message = Some("Not a three-way merge".to_string()),
+        }
+        return;
+    }
+    match action {
+        DiffFileViewAction::EditChunk => {
+            match state.merge {
+                Some(ref mut merge) => {
+                    if !merge.start_edit() {
+                        state.message = Some("No changes to merge".to_string());
+                    }
+                }
+                None => state.message = Some("Not a three-way merge".to_string()),
             }
-            DiffFileViewAction::GoEnd => {
-                state.scroll = max_scroll;
+            return;
+        }
+        DiffFileViewAction::ToggleMergeView => {
+            match state.merge {
+                Some(ref mut merge) => merge.show_merged = !merge.show_merged,
+                None => state.message = Some("Not a three-way merge".to_string()),
             }
-            DiffFileViewAction::NextChange => {
-                if !state.change_positions.is_empty() {
-                    if state.current_change + 1 < state.change_positions.len() {
-                        state.current_change += 1;
-                    }
-                    if state.current_change < state.change_visual_offsets.len() {
-                        let target = state.change_visual_offsets[state.current_change];
-                        state.scroll = target.saturating_sub(visible / 4).min(max_scroll);
-                    }
+            return;
+        }
+        DiffFileViewAction::Save => {
+            match state.merge {
+                Some(ref mut merge) => {
+                    let unresolved = merge.unresolved_count();
+                    state.message = Some(match merge.save() {
+                        Ok(()) if unresolved > 0 => format!(
+                            "Saved {} ({} unresolved conflicts written with markers)",
+                            merge.output_path.display(),
+                            unresolved
+                        ),
+                        Ok(()) => format!("Saved {}", merge.output_path.display()),
+                        Err(e) => format!("Save failed: {}", e),
+                    });
+                }
+                None if state.is_modified() => {
+                    state.message = Some(match state.save_edits() {
+                        Ok(paths) => format!(
+                            "Saved {}",
+                            paths
+                                .iter()
+                                .map(|p| p.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        Err(e) => format!("Save failed: {}", e),
+                    });
                 }
+                None => state.message = Some("Nothing to save".to_string()),
             }
-            DiffFileViewAction::PrevChange => {
-                if !state.change_positions.is_empty() {
-                    if state.current_change > 0 {
-                        state.current_change -= 1;
+            return;
+        }
+        DiffFileViewAction::ToggleIgnoreWhitespace
+        | DiffFileViewAction::ToggleIgnoreCase
+        | DiffFileViewAction::ToggleInlineMode => {
+            let mut options = state.options;
+            let (name, enabled) = match action {
+                DiffFileViewAction::ToggleIgnoreWhitespace => {
+                    options.ignore_whitespace = !options.ignore_whitespace;
+                    ("Ignore whitespace", options.ignore_whitespace)
+                }
+                DiffFileViewAction::ToggleIgnoreCase => {
+                    options.ignore_case = !options.ignore_case;
+                    ("Ignore case", options.ignore_case)
+                }
+                _ => {
+                    options.char_level = !options.char_level;
+                    ("Character-level highlighting", options.char_level)
+                }
+            };
+            state.set_options(options);
+            state.message = Some(format!("{}: {}", name, if enabled { "on" } else { "off" }));
+            return;
+        }
+        DiffFileViewAction::ExportPatch => {
+            let name = std::path::Path::new(&state.file_name)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "diff".to_string());
+            app.show_diff_export_dialog(&format!("{}.patch", name), "Unified patch");
+            return;
+        }
+        DiffFileViewAction::CopyToRight | DiffFileViewAction::CopyToLeft => {
+            if state.merge.is_some() {
+                state.message = Some("Use the merge keys in a three-way merge".to_string());
+                return;
+            }
+            let target = if action == DiffFileViewAction::CopyToRight {
+                SyncSide::Right
+            } else {
+                SyncSide::Left
+            };
+            state.message = Some(match state.copy_current_hunk(target) {
+                Ok(()) => format!(
+                    "Copied hunk to {} (unsaved)",
+                    if target == SyncSide::Right {
+                        "right"
+                    } else {
+                        "left"
+                    }
+                ),
+                Err(e) => e,
+            });
+            return;
+        }
+        _ => {}
+    }
+
+    // Navigation in the merged view
+    if let Some(ref mut merge) = state.merge {
+        if merge.show_merged {
+            let visible = state.visible_height;
+            match action {
+                DiffFileViewAction::MoveUp => merge.scroll = merge.scroll.saturating_sub(1),
+                DiffFileViewAction::MoveDown => {
+                    merge.scroll = (merge.scroll + 1).min(merge.max_scroll)
+                }
+                DiffFileViewAction::PageUp => merge.scroll = merge.scroll.saturating_sub(visible),
+                DiffFileViewAction::PageDown => {
+                    merge.scroll = (merge.scroll + visible).min(merge.max_scroll)
+                }
+                DiffFileViewAction::GoHome => merge.scroll = 0,
+                DiffFileViewAction::GoEnd => merge.scroll = merge.max_scroll,
+                DiffFileViewAction::NextChange | DiffFileViewAction::PrevChange
+                    if !merge.change_indices.is_empty() =>
+                {
+                    if action == DiffFileViewAction::NextChange {
+                        if merge.current + 1 < merge.change_indices.len() {
+                            merge.current += 1;
+                        }
+                    } else {
+                        merge.current = merge.current.saturating_sub(1);
                     }
-                    if state.current_change < state.change_visual_offsets.len() {
-                        let target = state.change_visual_offsets[state.current_change];
-                        state.scroll = target.saturating_sub(visible / 4).min(max_scroll);
+                    if let Some(&target) = merge.change_row_offsets.get(merge.current) {
+                        merge.scroll = target.saturating_sub(visible / 4).min(merge.max_scroll);
                     }
                 }
+                _ => {}
             }
-            DiffFileViewAction::Close => {
-                app.current_screen = super::app::Screen::DiffScreen;
-                app.diff_file_view_state = None;
+            return;
+        }
+    }
+
+    let visible = state.visible_height;
+    let max_scroll = state.max_scroll;
+
+    match action {
+        DiffFileViewAction::MoveUp => {
+            state.scroll = state.scroll.saturating_sub(1);
+        }
+        DiffFileViewAction::MoveDown if state.scroll < max_scroll => {
+            state.scroll += 1;
+        }
+        DiffFileViewAction::PageUp => {
+            state.scroll = state.scroll.saturating_sub(visible);
+        }
+        DiffFileViewAction::PageDown => {
+            state.scroll = (state.scroll + visible).min(max_scroll);
+        }
+        DiffFileViewAction::GoHome => {
+            state.scroll = 0;
+        }
+        DiffFileViewAction::GoEnd => {
+            state.scroll = max_scroll;
+        }
+        DiffFileViewAction::NextChange if !state.change_positions.is_empty() => {
+            if state.current_change + 1 < state.change_positions.len() {
+                state.current_change += 1;
+            }
+            if state.current_change < state.change_visual_offsets.len() {
+                let target = state.change_visual_offsets[state.current_change];
+                state.scroll = target.saturating_sub(visible / 4).min(max_scroll);
+            }
+        }
+        DiffFileViewAction::PrevChange if !state.change_positions.is_empty() => {
+            if state.current_change > 0 {
+                state.current_change -= 1;
+            }
+            if state.current_change < state.change_visual_offsets.len() {
+                let target = state.change_visual_offsets[state.current_change];
+                state.scroll = target.saturating_sub(visible / 4).min(max_scroll);
             }
         }
+        _ => {}
     }
 }
 
@@ -1273,4 +2649,338 @@ mod tests {
         let lcs = compute_lcs(&left, &right);
         assert!(lcs.is_empty());
     }
+
+    fn lines(s: &[&str]) -> Vec<String> {
+        s.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_merge_chunks_non_overlapping() {
+        let base = lines(&["a", "b", "c", "d"]);
+        let left = lines(&["a", "B", "c", "d"]);
+        let right = lines(&["a", "b", "c", "D"]);
+        let chunks = compute_merge_chunks(&base, &left, &right);
+        let kinds: Vec<MergeChunkKind> = chunks.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MergeChunkKind::Unchanged,
+                MergeChunkKind::LeftChanged,
+                MergeChunkKind::Unchanged,
+                MergeChunkKind::RightChanged,
+            ]
+        );
+        let merge = MergeState::new(PathBuf::new(), PathBuf::new(), chunks);
+        assert_eq!(merge.merged_lines(), lines(&["a", "B", "c", "D"]));
+        assert_eq!(merge.unresolved_count(), 0);
+    }
+
+    #[test]
+    fn test_merge_chunks_conflict() {
+        let base = lines(&["a", "b", "c"]);
+        let left = lines(&["a", "x", "c"]);
+        let right = lines(&["a", "y", "c"]);
+        let chunks = compute_merge_chunks(&base, &left, &right);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].kind, MergeChunkKind::Conflict);
+
+        let mut merge = MergeState::new(PathBuf::new(), PathBuf::new(), chunks);
+        assert_eq!(merge.conflict_count(), 1);
+        assert_eq!(
+            merge.merged_lines(),
+            lines(&[
+                "a",
+                "<<<<<<< left",
+                "x",
+                "=======",
+                "y",
+                ">>>>>>> right",
+                "c"
+            ])
+        );
+        assert!(merge.choose(MergeChoice::Right));
+        assert_eq!(merge.merged_lines(), lines(&["a", "y", "c"]));
+        assert!(merge.choose(MergeChoice::Both));
+        assert_eq!(merge.merged_lines(), lines(&["a", "x", "y", "c"]));
+    }
+
+    #[test]
+    fn test_merge_chunk_block_editor() {
+        let base = lines(&["a", "b", "c"]);
+        let left = lines(&["a", "x", "c"]);
+        let right = lines(&["a", "y", "c"]);
+        let mut merge = MergeState::new(
+            PathBuf::new(),
+            PathBuf::new(),
+            compute_merge_chunks(&base, &left, &right),
+        );
+        assert!(merge.start_edit());
+        let edit = merge.edit.as_mut().unwrap();
+        assert_eq!(edit.lines[0], "<<<<<<< left");
+        edit.delete_line();
+        edit.move_cursor(KeyCode::End);
+        edit.insert_char('z');
+        edit.newline();
+        edit.insert_char('w');
+        edit.move_cursor(KeyCode::Down);
+        edit.delete_line(); // =======
+        edit.delete_line(); // y
+        edit.delete_line(); // >>>>>>> right
+        edit.backspace();
+        edit.backspace();
+        merge.finish_edit();
+        assert!(merge.edit.is_none());
+        assert_eq!(merge.unresolved_count(), 0);
+        assert_eq!(merge.merged_lines(), lines(&["a", "xz", "c"]));
+    }
+
+    #[test]
+    fn test_merge_chunks_same_change_and_insertions() {
+        let base = lines(&["a", "b"]);
+        let left = lines(&["a", "z", "b", "tail"]);
+        let right = lines(&["a", "z", "b"]);
+        let chunks = compute_merge_chunks(&base, &left, &right);
+        let kinds: Vec<MergeChunkKind> = chunks.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MergeChunkKind::Unchanged,
+                MergeChunkKind::BothSame,
+                MergeChunkKind::Unchanged,
+                MergeChunkKind::LeftChanged,
+            ]
+        );
+        let merge = MergeState::new(PathBuf::new(), PathBuf::new(), chunks);
+        assert_eq!(merge.merged_lines(), lines(&["a", "z", "b", "tail"]));
+    }
+
+    #[test]
+    fn test_merge_save_keeps_line_endings() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.txt");
+        let left = dir.path().join("left.txt");
+        let right = dir.path().join("right.txt");
+        fs::write(&base, "a\r\nb\r\nc").unwrap();
+        fs::write(&left, "a\r\nx\r\nc").unwrap();
+        fs::write(&right, "a\r\ny\r\nc").unwrap();
+
+        let mut state = DiffFileViewState::new_merge(base, left.clone(), right, "f".to_string());
+        let merge = state.merge.as_mut().unwrap();
+        assert!(merge.choose(MergeChoice::Right));
+        merge.save().unwrap();
+        assert_eq!(fs::read_to_string(&left).unwrap(), "a\r\ny\r\nc");
+
+        // An LF file with a final newline keeps it
+        let out = dir.path().join("out.txt");
+        fs::write(&left, "a\nb\n").unwrap();
+        let mut state = DiffFileViewState::new_merge_into(
+            left.clone(),
+            left.clone(),
+            left,
+            out.clone(),
+            "f".to_string(),
+        );
+        state.merge.as_mut().unwrap().save().unwrap();
+        assert_eq!(fs::read_to_string(&out).unwrap(), "a\nb\n");
+    }
+
+    /// Reference O(n*m) LCS length for checking the Myers implementation.
+    fn lcs_len_dp(a: &[u8], b: &[u8]) -> usize {
+        let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                dp[i][j] = if a[i - 1] == b[j - 1] {
+                    dp[i - 1][j - 1] + 1
+                } else {
+                    dp[i - 1][j].max(dp[i][j - 1])
+                };
+            }
+        }
+        dp[a.len()][b.len()]
+    }
+
+    #[test]
+    fn test_compute_lcs_matches_dp_length() {
+        // Small alphabet pseudo-random sequences give many competing alignments
+        let mut seed = 12345u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) % 4) as u8
+        };
+        for round in 0..200 {
+            let a: Vec<u8> = (0..round % 23).map(|_| next()).collect();
+            let b: Vec<u8> = (0..(round * 7) % 19).map(|_| next()).collect();
+            let lcs = compute_lcs(&a, &b);
+            assert_eq!(lcs.len(), lcs_len_dp(&a, &b), "a={:?} b={:?}", a, b);
+            for w in lcs.windows(2) {
+                assert!(w[0].0 < w[1].0 && w[0].1 < w[1].1);
+            }
+            for &(i, j) in &lcs {
+                assert_eq!(a[i], b[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_lcs_large_input() {
+        let left: Vec<String> = (0..60_000).map(|i| format!("line {}", i)).collect();
+        let mut right = left.clone();
+        right[100] = "changed".to_string();
+        right.remove(30_000);
+        right.insert(45_000, "inserted".to_string());
+        let lcs = compute_lcs(&left, &right);
+        assert_eq!(lcs.len(), 59_998);
+    }
+
+    #[test]
+    fn test_line_matches_rewritten_large_file() {
+        let left: Vec<String> = (0..50_000).map(|i| format!("old {}", i)).collect();
+        let mut right: Vec<String> = (0..50_000).map(|i| format!("new {}", i)).collect();
+        right[25_000] = "old 7".to_string();
+        let matches = compute_line_matches(&left, &right, &DiffOptions::default());
+        assert_eq!(matches, vec![(7, 25_000)]);
+    }
+
+    #[test]
+    fn test_line_matches_ignore_options() {
+        let left = lines(&["fn main() {", "    Foo();", "}"]);
+        let right = lines(&["fn main(){", "\tfoo();", "}"]);
+        let plain = compute_line_matches(&left, &right, &DiffOptions::default());
+        assert_eq!(plain, vec![(2, 2)]);
+
+        let ws = DiffOptions {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            compute_line_matches(&left, &right, &ws),
+            vec![(0, 0), (2, 2)]
+        );
+
+        let both = DiffOptions {
+            ignore_whitespace: true,
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            compute_line_matches(&left, &right, &both),
+            vec![(0, 0), (1, 1), (2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_inline_change_masks_word_and_char() {
+        let left: Vec<char> = "let value = old_name + 1;".chars().collect();
+        let right: Vec<char> = "let value = new_name + 1;".chars().collect();
+
+        let (lm, rm) = inline_change_masks(&left, &right, &DiffOptions::default());
+        let marked = |chars: &[char], mask: &[bool]| -> String {
+            chars
+                .iter()
+                .zip(mask)
+                .filter(|(_, &m)| m)
+                .map(|(c, _)| *c)
+                .collect()
+        };
+        // Word mode highlights the whole identifier
+        assert_eq!(marked(&left, &lm), "old_name");
+        assert_eq!(marked(&right, &rm), "new_name");
+
+        let char_level = DiffOptions {
+            char_level: true,
+            ..Default::default()
+        };
+        let (lm, rm) = inline_change_masks(&left, &right, &char_level);
+        assert_eq!(marked(&left, &lm), "old");
+        assert_eq!(marked(&right, &rm), "new");
+
+        // Whitespace-only differences are not highlighted when ignored
+        let left: Vec<char> = "a  b".chars().collect();
+        let right: Vec<char> = "a b".chars().collect();
+        let ws = DiffOptions {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let (lm, rm) = inline_change_masks(&left, &right, &ws);
+        assert!(!lm.contains(&true) && !rm.contains(&true));
+    }
+
+    #[test]
+    fn test_copy_hunk_and_save() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let left_path = dir.path().join("left.txt");
+        let right_path = dir.path().join("right.txt");
+        fs::write(&left_path, "a\r\nb\r\nc\r\nd\r\n").expect("write left");
+        fs::write(&right_path, "a\nx\ny\nc\n").expect("write right");
+
+        let mut state =
+            DiffFileViewState::new(left_path.clone(), right_path.clone(), "f".to_string());
+        assert_eq!(state.change_positions.len(), 2);
+
+        // First hunk: b -> x, y
+        state.copy_current_hunk(SyncSide::Left).expect("copy left");
+        assert_eq!(state.change_positions.len(), 1);
+        assert!(state.is_modified());
+        // Remaining hunk: trailing "d" only on the left
+        state
+            .copy_current_hunk(SyncSide::Right)
+            .expect("copy right");
+        assert!(state.change_positions.is_empty());
+        assert!(state.copy_current_hunk(SyncSide::Right).is_err());
+
+        let saved = state.save_edits().expect("save");
+        assert_eq!(saved, vec![left_path.clone(), right_path.clone()]);
+        assert!(!state.is_modified());
+        // Each side keeps its own line ending convention
+        assert_eq!(
+            fs::read_to_string(&left_path).expect("read left"),
+            "a\r\nx\r\ny\r\nc\r\nd\r\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&right_path).expect("read right"),
+            "a\nx\ny\nc\nd\n"
+        );
+    }
+
+    #[test]
+    fn test_save_edits_asks_before_overwriting_external_change() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let left_path = dir.path().join("left.txt");
+        let right_path = dir.path().join("right.txt");
+        fs::write(&left_path, "a\nb\n").expect("write left");
+        fs::write(&right_path, "a\nc\n").expect("write right");
+
+        let mut state = DiffFileViewState::new(left_path.clone(), right_path, "f.txt".into());
+        state.copy_current_hunk(SyncSide::Left).expect("copy");
+        fs::write(&left_path, "changed elsewhere, longer\n").expect("write left");
+
+        // The first save refuses, the second overwrites
+        assert!(state.save_edits().is_err());
+        assert_eq!(
+            fs::read_to_string(&left_path).expect("read left"),
+            "changed elsewhere, longer\n"
+        );
+        assert_eq!(state.save_edits().expect("save"), vec![left_path.clone()]);
+        assert_eq!(fs::read_to_string(&left_path).expect("read left"), "a\nc\n");
+        assert!(!dir.path().join("left.tmp").exists());
+    }
+
+    #[test]
+    fn test_unified_patch_of_added_and_deleted_file() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        let present = dir.path().join("present.txt");
+        let missing = dir.path().join("missing.txt");
+        fs::write(&present, "a\nb\n").expect("write");
+
+        let added = DiffFileViewState::new(missing.clone(), present.clone(), "f.txt".into());
+        assert_eq!(
+            added.unified_patch().as_deref(),
+            Some("--- /dev/null\n+++ b/f.txt\n@@ -0,0 +1,2 @@\n+a\n+b\n")
+        );
+        let deleted = DiffFileViewState::new(present, missing, "f.txt".into());
+        assert_eq!(
+            deleted.unified_patch().as_deref(),
+            Some("--- a/f.txt\n+++ /dev/null\n@@ -1,2 +0,0 @@\n-a\n-b\n")
+        );
+    }
 }
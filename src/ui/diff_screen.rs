@@ -1,10 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use chrono::{DateTime, Local};
@@ -19,7 +19,12 @@ use ratatui::{
 use unicode_width::UnicodeWidthStr;
 
 use super::app::{App, Screen, SortBy, SortOrder};
+use super::diff_file_view::ReturnScreen;
 use super::theme::Theme;
+use crate::services::diff_compare::{self, DigestCache, IgnoreRules};
+use crate::services::diff_export::PatchTarget;
+use crate::services::diff_sync::{SyncMode, SyncOp, SyncPlan, SyncRoot, SyncSide};
+use crate::services::remote::{RemoteProfile, SftpSession};
 use crate::utils::format::{format_size, safe_suffix};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -58,12 +63,50 @@ pub struct DiffEntry {
     pub children_not_loaded: bool,
 }
 
+/// Three-way classification of an entry against the common base directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStatus {
+    Unchanged,
+    ChangedLeft,
+    ChangedRight,
+    /// Changed on both sides without conflicting (identical edits, or for
+    /// directories, changes to different children)
+    ChangedBoth,
+    Conflict,
+}
+
+impl MergeStatus {
+    /// Single-character marker shown in front of the left-side name
+    pub fn marker(&self) -> char {
+        match self {
+            MergeStatus::Unchanged => ' ',
+            MergeStatus::ChangedLeft => '<',
+            MergeStatus::ChangedRight => '>',
+            MergeStatus::ChangedBoth => '=',
+            MergeStatus::Conflict => '!',
+        }
+    }
+
+    /// Combine two statuses (used to aggregate children into a directory)
+    fn combine(self, other: MergeStatus) -> MergeStatus {
+        use MergeStatus::*;
+        match (self, other) {
+            (Conflict, _) | (_, Conflict) => Conflict,
+            (Unchanged, x) | (x, Unchanged) => x,
+            (a, b) if a == b => a,
+            _ => ChangedBoth,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DiffFilter {
     All,
     DifferentOnly,
     LeftOnly,
     RightOnly,
+    /// Three-way only: entries changed differently on both sides
+    Conflicts,
 }
 
 impl DiffFilter {
@@ -72,7 +115,8 @@ impl DiffFilter {
             DiffFilter::All => DiffFilter::DifferentOnly,
             DiffFilter::DifferentOnly => DiffFilter::LeftOnly,
             DiffFilter::LeftOnly => DiffFilter::RightOnly,
-            DiffFilter::RightOnly => DiffFilter::All,
+            DiffFilter::RightOnly => DiffFilter::Conflicts,
+            DiffFilter::Conflicts => DiffFilter::All,
         }
     }
 
@@ -82,6 +126,7 @@ impl DiffFilter {
             DiffFilter::DifferentOnly => "Different Only",
             DiffFilter::LeftOnly => "Left Only",
             DiffFilter::RightOnly => "Right Only",
+            DiffFilter::Conflicts => "Conflicts",
         }
     }
 }
@@ -91,6 +136,10 @@ pub enum CompareMethod {
     Content,
     ModifiedTime,
     ContentAndTime,
+    /// Sizes only, without reading file contents
+    Size,
+    /// SHA-256 digests, cached across runs by path, size and modification time
+    Hash,
 }
 
 impl Default for CompareMethod {
@@ -105,6 +154,8 @@ impl CompareMethod {
             CompareMethod::Content => "Content",
             CompareMethod::ModifiedTime => "Modified Time",
             CompareMethod::ContentAndTime => "Content + Time",
+            CompareMethod::Size => "Size",
+            CompareMethod::Hash => "Hash (SHA-256)",
         }
     }
 }
@@ -115,19 +166,97 @@ pub fn parse_compare_method(s: &str) -> CompareMethod {
         "content" => CompareMethod::Content,
         "time" | "modified" | "modifiedtime" | "modified_time" => CompareMethod::ModifiedTime,
         "contentandtime" | "content_and_time" | "contenttime" => CompareMethod::ContentAndTime,
+        "size" | "size_only" => CompareMethod::Size,
+        "hash" | "sha256" | "checksum" => CompareMethod::Hash,
         _ => CompareMethod::default(),
     }
 }
 
+/// Per-run comparison settings: the method, the ignore rules and the digest cache
+pub struct CompareContext {
+    pub method: CompareMethod,
+    pub ignore: IgnoreRules,
+    digests: Mutex<DigestCache>,
+}
+
+impl CompareContext {
+    pub fn new(method: CompareMethod, ignore: IgnoreRules, digests: DigestCache) -> Self {
+        Self {
+            method,
+            ignore,
+            digests: Mutex::new(digests),
+        }
+    }
+
+    /// Read the ignore files for `roots` and, in hash mode, the persisted digest cache
+    fn load(method: CompareMethod, roots: &[&Path]) -> Self {
+        let digests = match (method, DigestCache::default_path()) {
+            (CompareMethod::Hash, Some(path)) => DigestCache::load(path),
+            _ => DigestCache::in_memory(),
+        };
+        Self::new(method, IgnoreRules::load(roots), digests)
+    }
+
+    /// Compare two files with the run's method, using cached digests in hash mode
+    fn files_match(&self, left: &DiffFileInfo, right: &DiffFileInfo) -> bool {
+        if self.method != CompareMethod::Hash || (left.is_symlink && right.is_symlink) {
+            return compare_files(left, right, self.method);
+        }
+        if left.size != right.size {
+            return false;
+        }
+        let mut digests = match self.digests.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match (
+            digests.digest(&left.full_path),
+            digests.digest(&right.full_path),
+        ) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn save_digests(&self) {
+        if let Ok(mut digests) = self.digests.lock() {
+            let _ = digests.save();
+        }
+    }
+
+    /// Drop names matched by the ignore rules from a listing of `dir`
+    fn retain_not_ignored(&self, names: &mut Vec<String>, dir: &Path, relative_path: &str) {
+        if self.ignore.is_empty() {
+            return;
+        }
+        let check_dirs = self.ignore.has_dir_only_rules();
+        names.retain(|name| {
+            let is_dir = check_dirs && is_dir_via_info(&dir.join(name));
+            !self
+                .ignore
+                .is_ignored(&child_relative_path(relative_path, name), is_dir)
+        });
+    }
+}
+
+fn child_relative_path(relative_path: &str, name: &str) -> String {
+    if relative_path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", relative_path, name)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Async diff types
 // ═══════════════════════════════════════════════════════════════════════════════
 
-struct DiffCompareResult(Vec<DiffEntry>);
+struct DiffCompareResult(Vec<DiffEntry>, HashMap<String, MergeStatus>);
 
 enum DiffProgressMsg {
     Counting(usize),
     Comparing(String, usize, usize),
+    Failed(String),
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -137,6 +266,16 @@ enum DiffProgressMsg {
 pub struct DiffState {
     pub left_root: PathBuf,
     pub right_root: PathBuf,
+    /// Common ancestor directory for three-way comparison
+    pub base_root: Option<PathBuf>,
+    /// Three-way status per relative_path (empty for two-way comparison)
+    pub merge_status: HashMap<String, MergeStatus>,
+    /// Side served by an SFTP panel and its profile (that root is a remote path)
+    pub remote: Option<(SyncSide, RemoteProfile)>,
+    /// Sync plan waiting for confirmation in the dry-run dialog
+    pub pending_sync: Option<SyncPlan>,
+    /// One-shot message shown in the status bar
+    pub message: Option<String>,
     pub all_entries: Vec<DiffEntry>,
     pub filtered_indices: Vec<usize>,
     pub selected_index: usize,
@@ -145,6 +284,8 @@ pub struct DiffState {
     pub sort_by: SortBy,
     pub sort_order: SortOrder,
     pub compare_method: CompareMethod,
+    /// Ignore rules and digest cache of the latest comparison
+    context: Arc<CompareContext>,
     pub selected_files: HashSet<String>,
     pub visible_height: usize,
     /// Set of relative_path values for collapsed directories
@@ -170,6 +311,11 @@ impl DiffState {
         Self {
             left_root: left,
             right_root: right,
+            base_root: None,
+            merge_status: HashMap::new(),
+            remote: None,
+            pending_sync: None,
+            message: None,
             all_entries: Vec::new(),
             filtered_indices: Vec::new(),
             selected_index: 0,
@@ -178,6 +324,11 @@ impl DiffState {
             sort_by,
             sort_order,
             compare_method,
+            context: Arc::new(CompareContext::new(
+                compare_method,
+                IgnoreRules::default(),
+                DigestCache::in_memory(),
+            )),
             selected_files: HashSet::new(),
             visible_height: 0,
             collapsed_dirs: HashSet::new(),
@@ -191,6 +342,47 @@ impl DiffState {
         }
     }
 
+    /// Create a three-way comparison of `left` and `right` against their common `base`
+    pub fn new_three_way(
+        base: PathBuf,
+        left: PathBuf,
+        right: PathBuf,
+        compare_method: CompareMethod,
+        sort_by: SortBy,
+        sort_order: SortOrder,
+    ) -> Self {
+        let mut state = Self::new(left, right, compare_method, sort_by, sort_order);
+        state.base_root = Some(base);
+        state
+    }
+
+    /// Create a comparison where `side` is a directory on the SFTP server of `profile`
+    pub fn new_remote(
+        left: PathBuf,
+        right: PathBuf,
+        side: SyncSide,
+        profile: RemoteProfile,
+        compare_method: CompareMethod,
+        sort_by: SortBy,
+        sort_order: SortOrder,
+    ) -> Self {
+        let mut state = Self::new(left, right, compare_method, sort_by, sort_order);
+        state.remote = Some((side, profile));
+        state
+    }
+
+    pub fn is_three_way(&self) -> bool {
+        self.base_root.is_some()
+    }
+
+    /// Three-way status of an entry (Unchanged for two-way comparison)
+    pub fn merge_status_of(&self, entry: &DiffEntry) -> MergeStatus {
+        self.merge_status
+            .get(&entry.relative_path)
+            .copied()
+            .unwrap_or(MergeStatus::Unchanged)
+    }
+
     /// Start async comparison in a background thread
     pub fn start_comparison(&mut self) {
         // Cancel any previous comparison
@@ -198,6 +390,7 @@ impl DiffState {
 
         self.is_comparing = true;
         self.all_entries.clear();
+        self.merge_status.clear();
         self.filtered_indices.clear();
         self.collapsed_dirs.clear();
         self.selected_index = 0;
@@ -206,6 +399,7 @@ impl DiffState {
         self.progress_count = 0;
         self.progress_total = 0;
         self.cancel_flag = Arc::new(AtomicBool::new(false));
+        self.context = Arc::new(self.load_context());
 
         let (result_tx, result_rx) = mpsc::channel();
         let (progress_tx, progress_rx) = mpsc::channel();
@@ -214,18 +408,44 @@ impl DiffState {
 
         let left_root = self.left_root.clone();
         let right_root = self.right_root.clone();
-        let compare_method = self.compare_method;
+        let base_root = self.base_root.clone();
+        let ctx = self.context.clone();
         let sort_by = self.sort_by;
         let sort_order = self.sort_order;
         let cancel_flag = self.cancel_flag.clone();
+        let remote = self.remote.clone();
 
         thread::spawn(move || {
+            // Remote comparison: a single listing pass over SFTP
+            if let Some((side, profile)) = remote {
+                let walk = RemoteWalk {
+                    left_root: &left_root,
+                    right_root: &right_root,
+                    remote_side: side,
+                    ctx: &ctx,
+                    cancel_flag: &cancel_flag,
+                    progress_tx: &progress_tx,
+                };
+                match build_remote_tree(&walk, &profile, sort_by, sort_order) {
+                    Ok(entries) => {
+                        if !cancel_flag.load(Ordering::Relaxed) {
+                            let _ = result_tx.send(DiffCompareResult(entries, HashMap::new()));
+                        }
+                    }
+                    Err(e) => {
+                        let _ = progress_tx.send(DiffProgressMsg::Failed(e));
+                    }
+                }
+                return;
+            }
+
             // Phase 1: Count total items (with live progress)
             let counting_counter = Arc::new(std::sync::atomic::AtomicUsize::new(0));
             let total = count_entries_recursive(
                 &left_root,
                 &right_root,
                 "",
+                &ctx,
                 &cancel_flag,
                 &progress_tx,
                 &counting_counter,
@@ -242,7 +462,7 @@ impl DiffState {
                 &right_root,
                 "",
                 0,
-                compare_method,
+                &ctx,
                 sort_by,
                 sort_order,
                 &mut entries,
@@ -252,8 +472,19 @@ impl DiffState {
                 &counter,
             );
 
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+
+            // Phase 3 (three-way only): classify entries against the base
+            let merge_status = match base_root {
+                Some(ref base_root) => classify_merge(&entries, base_root, &ctx),
+                None => HashMap::new(),
+            };
+            ctx.save_digests();
+
             if !cancel_flag.load(Ordering::Relaxed) {
-                let _ = result_tx.send(DiffCompareResult(entries));
+                let _ = result_tx.send(DiffCompareResult(entries, merge_status));
             }
         });
     }
@@ -266,6 +497,7 @@ impl DiffState {
         }
 
         // Drain progress messages
+        let mut failure = None;
         if let Some(ref progress_rx) = self.progress_receiver {
             loop {
                 match progress_rx.try_recv() {
@@ -277,16 +509,26 @@ impl DiffState {
                         self.progress_count = count;
                         self.progress_total = total;
                     }
+                    Ok(DiffProgressMsg::Failed(e)) => {
+                        failure = Some(e);
+                        break;
+                    }
                     Err(_) => break,
                 }
             }
         }
+        if let Some(e) = failure {
+            self.message = Some(format!("Comparison failed: {}", e));
+            self.cancel();
+            return false;
+        }
 
         // Check for completion
         if let Some(ref receiver) = self.receiver {
             match receiver.try_recv() {
-                Ok(DiffCompareResult(entries)) => {
+                Ok(DiffCompareResult(entries, merge_status)) => {
                     self.all_entries = entries;
+                    self.merge_status = merge_status;
                     // Collapse all directories by default
                     self.collapsed_dirs.clear();
                     for entry in &self.all_entries {
@@ -313,6 +555,13 @@ impl DiffState {
 
     /// Returns true if there are any differences (Modified, LeftOnly, RightOnly, DirModified)
     pub fn has_differences(&self) -> bool {
+        if self
+            .merge_status
+            .values()
+            .any(|s| *s != MergeStatus::Unchanged)
+        {
+            return true;
+        }
         self.all_entries.iter().any(|e| {
             matches!(
                 e.status,
@@ -332,21 +581,40 @@ impl DiffState {
         self.progress_receiver = None;
     }
 
+    /// Comparison context for the next run; ignore files are only read from local roots
+    fn load_context(&self) -> CompareContext {
+        let remote_side = self.remote.as_ref().map(|(side, _)| *side);
+        let mut roots: Vec<&Path> = Vec::new();
+        if remote_side != Some(SyncSide::Left) {
+            roots.push(&self.left_root);
+        }
+        if remote_side != Some(SyncSide::Right) {
+            roots.push(&self.right_root);
+        }
+        CompareContext::load(self.compare_method, &roots)
+    }
+
     /// Build the flat diff list by recursively comparing both directory trees (synchronous)
     pub fn build_diff_list(&mut self) {
         self.all_entries.clear();
         let left_root = self.left_root.clone();
         let right_root = self.right_root.clone();
+        self.context = Arc::new(self.load_context());
         build_recursive(
             &left_root,
             &right_root,
             "",
             0,
-            self.compare_method,
+            &self.context,
             self.sort_by,
             self.sort_order,
             &mut self.all_entries,
         );
+        self.merge_status = match self.base_root {
+            Some(ref base_root) => classify_merge(&self.all_entries, base_root, &self.context),
+            None => HashMap::new(),
+        };
+        self.context.save_digests();
         // Collapse all directories by default
         self.collapsed_dirs.clear();
         for entry in &self.all_entries {
@@ -364,17 +632,21 @@ impl DiffState {
         let mut matching_indices: HashSet<usize> = HashSet::new();
 
         for (i, entry) in self.all_entries.iter().enumerate() {
+            let merge_status = self.merge_status_of(entry);
             let matches = match self.filter {
                 DiffFilter::All => true,
-                DiffFilter::DifferentOnly => matches!(
-                    entry.status,
-                    DiffStatus::Modified
-                        | DiffStatus::LeftOnly
-                        | DiffStatus::RightOnly
-                        | DiffStatus::DirModified
-                ),
+                DiffFilter::DifferentOnly => {
+                    matches!(
+                        entry.status,
+                        DiffStatus::Modified
+                            | DiffStatus::LeftOnly
+                            | DiffStatus::RightOnly
+                            | DiffStatus::DirModified
+                    ) || merge_status != MergeStatus::Unchanged
+                }
                 DiffFilter::LeftOnly => entry.status == DiffStatus::LeftOnly,
                 DiffFilter::RightOnly => entry.status == DiffStatus::RightOnly,
+                DiffFilter::Conflicts => merge_status == MergeStatus::Conflict,
             };
 
             if matches {
@@ -683,7 +955,9 @@ impl DiffState {
 
         // Load one level of children
         let dir_path = root.join(&relative_path);
-        let names = read_dir_names(&dir_path);
+        let mut names = read_dir_names(&dir_path);
+        self.context
+            .retain_not_ignored(&mut names, &dir_path, &relative_path);
 
         let mut sorted_names = names;
         sort_names_one_side(&mut sorted_names, &dir_path);
@@ -713,6 +987,15 @@ impl DiffState {
             });
         }
 
+        // Classify lazily loaded children against the base
+        if let Some(ref base_root) = self.base_root {
+            for child in &children {
+                let status = classify_entry(child, base_root, &self.context);
+                self.merge_status
+                    .insert(child.relative_path.clone(), status);
+            }
+        }
+
         // Mark as loaded
         self.all_entries[all_entry_idx].children_not_loaded = false;
 
@@ -833,6 +1116,168 @@ impl DiffState {
         self.all_entries = sorted;
         self.apply_filter();
     }
+
+    /// Root of `side` as a sync endpoint
+    pub fn sync_root(&self, side: SyncSide) -> SyncRoot {
+        let root = match side {
+            SyncSide::Left => &self.left_root,
+            SyncSide::Right => &self.right_root,
+        };
+        match self.remote {
+            Some((remote_side, ref profile)) if remote_side == side => SyncRoot::Remote {
+                profile: profile.clone(),
+                path: root.to_string_lossy().to_string(),
+            },
+            _ => SyncRoot::Local(root.clone()),
+        }
+    }
+
+    /// Build the operations for `mode`. Mirror modes cover the whole tree; the
+    /// other modes cover the selected entries (or the current one) and their
+    /// descendants. Nothing is touched until the plan is executed.
+    pub fn plan_sync(&self, mode: SyncMode) -> SyncPlan {
+        let mut plan = SyncPlan::new(
+            mode,
+            self.sync_root(SyncSide::Left),
+            self.sync_root(SyncSide::Right),
+        );
+
+        let roots: Vec<String> = if mode.is_mirror() {
+            Vec::new()
+        } else if !self.selected_files.is_empty() {
+            self.selected_files.iter().cloned().collect()
+        } else {
+            match self.current_entry() {
+                Some(entry) => vec![entry.relative_path.clone()],
+                None => return plan,
+            }
+        };
+        let in_scope = |path: &str| {
+            mode.is_mirror()
+                || roots
+                    .iter()
+                    .any(|r| path == r || path.starts_with(&format!("{}/", r)))
+        };
+
+        // Paths whose whole subtree is already handled by a planned operation
+        let mut covered: Vec<String> = Vec::new();
+        for entry in &self.all_entries {
+            let path = entry.relative_path.as_str();
+            if !in_scope(path) || covered.iter().any(|c| path.starts_with(&format!("{}/", c))) {
+                continue;
+            }
+            match entry.status {
+                DiffStatus::Same | DiffStatus::DirSame | DiffStatus::DirModified => {}
+                DiffStatus::Modified => {
+                    let Some(from) = mode.source() else {
+                        continue;
+                    };
+                    let (source, target) = match from {
+                        SyncSide::Left => (&entry.left, &entry.right),
+                        SyncSide::Right => (&entry.right, &entry.left),
+                    };
+                    let (Some(source), Some(target)) = (source, target) else {
+                        continue;
+                    };
+                    if source.is_directory != target.is_directory {
+                        plan.ops.push(SyncOp::Delete {
+                            side: from.other(),
+                            relative_path: path.to_string(),
+                            is_dir: target.is_directory,
+                        });
+                    }
+                    plan.ops.push(SyncOp::Copy {
+                        from,
+                        relative_path: path.to_string(),
+                        is_dir: source.is_directory,
+                        size: source.size,
+                    });
+                    covered.push(path.to_string());
+                }
+                DiffStatus::LeftOnly | DiffStatus::RightOnly => {
+                    let (side, info) = if entry.status == DiffStatus::LeftOnly {
+                        (SyncSide::Left, &entry.left)
+                    } else {
+                        (SyncSide::Right, &entry.right)
+                    };
+                    let Some(info) = info else {
+                        continue;
+                    };
+                    if mode.source() == Some(side) {
+                        plan.ops.push(SyncOp::Copy {
+                            from: side,
+                            relative_path: path.to_string(),
+                            is_dir: info.is_directory,
+                            size: info.size,
+                        });
+                    } else if mode == SyncMode::DeleteExtra || mode.is_mirror() {
+                        plan.ops.push(SyncOp::Delete {
+                            side,
+                            relative_path: path.to_string(),
+                            is_dir: info.is_directory,
+                        });
+                    } else {
+                        continue;
+                    }
+                    covered.push(path.to_string());
+                }
+            }
+        }
+        plan
+    }
+
+    /// Files to include in a patch export: the selected entries and their
+    /// descendants, or the whole tree when nothing is selected.
+    pub fn patch_targets(&self) -> Vec<PatchTarget> {
+        let roots: Vec<&String> = self.selected_files.iter().collect();
+        let in_scope = |path: &str| {
+            roots.is_empty()
+                || roots
+                    .iter()
+                    .any(|r| path == r.as_str() || path.starts_with(&format!("{}/", r)))
+        };
+        let target =
+            |path: &str, left: Option<&DiffFileInfo>, right: Option<&DiffFileInfo>| PatchTarget {
+                relative_path: path.to_string(),
+                left: left.map(|i| i.full_path.clone()),
+                right: right.map(|i| i.full_path.clone()),
+            };
+
+        let mut targets = Vec::new();
+        // One-side directories are walked by the exporter, so skip their loaded children
+        let mut covered: Vec<String> = Vec::new();
+        for entry in &self.all_entries {
+            let path = entry.relative_path.as_str();
+            if !in_scope(path) || covered.iter().any(|c| path.starts_with(&format!("{}/", c))) {
+                continue;
+            }
+            match entry.status {
+                DiffStatus::Same | DiffStatus::DirSame | DiffStatus::DirModified => {}
+                DiffStatus::Modified => {
+                    let (Some(left), Some(right)) = (&entry.left, &entry.right) else {
+                        continue;
+                    };
+                    if left.is_directory == right.is_directory {
+                        targets.push(target(path, Some(left), Some(right)));
+                    } else {
+                        // File replaced by a directory (or vice versa): removal plus addition
+                        targets.push(target(path, Some(left), None));
+                        targets.push(target(path, None, Some(right)));
+                        covered.push(path.to_string());
+                    }
+                }
+                DiffStatus::LeftOnly => {
+                    targets.push(target(path, entry.left.as_ref(), None));
+                    covered.push(path.to_string());
+                }
+                DiffStatus::RightOnly => {
+                    targets.push(target(path, None, entry.right.as_ref()));
+                    covered.push(path.to_string());
+                }
+            }
+        }
+        targets
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -844,7 +1289,7 @@ fn build_recursive(
     right_root: &Path,
     relative_path: &str,
     depth: usize,
-    compare_method: CompareMethod,
+    ctx: &CompareContext,
     sort_by: SortBy,
     sort_order: SortOrder,
     entries: &mut Vec<DiffEntry>,
@@ -861,8 +1306,10 @@ fn build_recursive(
     };
 
     // Read entries from both sides
-    let left_names = read_dir_names(&left_dir);
-    let right_names = read_dir_names(&right_dir);
+    let mut left_names = read_dir_names(&left_dir);
+    let mut right_names = read_dir_names(&right_dir);
+    ctx.retain_not_ignored(&mut left_names, &left_dir, relative_path);
+    ctx.retain_not_ignored(&mut right_names, &right_dir, relative_path);
 
     // Merge into union of names
     let mut all_names: Vec<String> = {
@@ -933,7 +1380,7 @@ fn build_recursive(
                     right_root,
                     &child_relative,
                     depth + 1,
-                    compare_method,
+                    ctx,
                     sort_by,
                     sort_order,
                     entries,
@@ -958,7 +1405,7 @@ fn build_recursive(
             } else if !left_is_dir && !right_is_dir {
                 // Both are files - compare
                 let same = match (left_info.as_ref(), right_info.as_ref()) {
-                    (Some(l), Some(r)) => compare_files(l, r, compare_method),
+                    (Some(l), Some(r)) => ctx.files_match(l, r),
                     _ => false, // If either info is None (stat failed), treat as different
                 };
                 entries.push(DiffEntry {
@@ -1027,6 +1474,7 @@ fn count_entries_recursive(
     left_root: &Path,
     right_root: &Path,
     relative_path: &str,
+    ctx: &CompareContext,
     cancel_flag: &AtomicBool,
     progress_tx: &Sender<DiffProgressMsg>,
     running_count: &Arc<std::sync::atomic::AtomicUsize>,
@@ -1046,8 +1494,10 @@ fn count_entries_recursive(
         right_root.join(relative_path)
     };
 
-    let left_names = read_dir_names(&left_dir);
-    let right_names = read_dir_names(&right_dir);
+    let mut left_names = read_dir_names(&left_dir);
+    let mut right_names = read_dir_names(&right_dir);
+    ctx.retain_not_ignored(&mut left_names, &left_dir, relative_path);
+    ctx.retain_not_ignored(&mut right_names, &right_dir, relative_path);
 
     let mut all_names: HashSet<String> = HashSet::new();
     for name in &left_names {
@@ -1087,6 +1537,7 @@ fn count_entries_recursive(
                     left_root,
                     right_root,
                     &child_relative,
+                    ctx,
                     cancel_flag,
                     progress_tx,
                     running_count,
@@ -1105,7 +1556,7 @@ fn build_recursive_threaded(
     right_root: &Path,
     relative_path: &str,
     depth: usize,
-    compare_method: CompareMethod,
+    ctx: &CompareContext,
     sort_by: SortBy,
     sort_order: SortOrder,
     entries: &mut Vec<DiffEntry>,
@@ -1129,8 +1580,10 @@ fn build_recursive_threaded(
         right_root.join(relative_path)
     };
 
-    let left_names = read_dir_names(&left_dir);
-    let right_names = read_dir_names(&right_dir);
+    let mut left_names = read_dir_names(&left_dir);
+    let mut right_names = read_dir_names(&right_dir);
+    ctx.retain_not_ignored(&mut left_names, &left_dir, relative_path);
+    ctx.retain_not_ignored(&mut right_names, &right_dir, relative_path);
 
     let mut all_names: Vec<String> = {
         let mut set: HashSet<String> = HashSet::new();
@@ -1206,7 +1659,7 @@ fn build_recursive_threaded(
                     right_root,
                     &child_relative,
                     depth + 1,
-                    compare_method,
+                    ctx,
                     sort_by,
                     sort_order,
                     entries,
@@ -1233,7 +1686,7 @@ fn build_recursive_threaded(
                 };
             } else if !left_is_dir && !right_is_dir {
                 let same = match (left_info.as_ref(), right_info.as_ref()) {
-                    (Some(l), Some(r)) => compare_files(l, r, compare_method),
+                    (Some(l), Some(r)) => ctx.files_match(l, r),
                     _ => false,
                 };
                 entries.push(DiffEntry {
@@ -1286,6 +1739,210 @@ fn build_recursive_threaded(
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Remote diff tree builder (one side on an SFTP server)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What a remote comparison run walks and where it reports progress
+struct RemoteWalk<'a> {
+    left_root: &'a Path,
+    right_root: &'a Path,
+    /// Side that is a directory on the SFTP server
+    remote_side: SyncSide,
+    ctx: &'a CompareContext,
+    cancel_flag: &'a Arc<AtomicBool>,
+    progress_tx: &'a Sender<DiffProgressMsg>,
+}
+
+/// Build the diff tree when `walk.remote_side` is a directory on an SFTP
+/// server. Remote file contents are not downloaded, so files are compared by
+/// size, plus modification time when the compare method includes it.
+fn build_remote_tree(
+    walk: &RemoteWalk,
+    profile: &RemoteProfile,
+    sort_by: SortBy,
+    sort_order: SortOrder,
+) -> Result<Vec<DiffEntry>, String> {
+    let session = SftpSession::connect(profile).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    let mut seen = 0;
+    build_remote_recursive(walk, &session, "", 0, &mut entries, &mut seen)?;
+    Ok(resort_level(
+        &entries,
+        0,
+        0,
+        entries.len(),
+        sort_by,
+        sort_order,
+    ))
+}
+
+fn build_remote_recursive(
+    walk: &RemoteWalk,
+    session: &SftpSession,
+    relative_path: &str,
+    depth: usize,
+    entries: &mut Vec<DiffEntry>,
+    seen: &mut usize,
+) -> Result<(), String> {
+    let ctx = walk.ctx;
+    if walk.cancel_flag.load(Ordering::Relaxed) {
+        return Err("Cancelled".to_string());
+    }
+    let left_dir = walk.left_root.join(relative_path);
+    let right_dir = walk.right_root.join(relative_path);
+    let (mut left_infos, mut right_infos) = match walk.remote_side {
+        SyncSide::Left => (
+            list_remote_infos(session, &left_dir)?,
+            list_local_infos(&right_dir),
+        ),
+        SyncSide::Right => (
+            list_local_infos(&left_dir),
+            list_remote_infos(session, &right_dir)?,
+        ),
+    };
+    if !ctx.ignore.is_empty() {
+        for infos in [&mut left_infos, &mut right_infos] {
+            infos.retain(|name, info| {
+                !ctx.ignore
+                    .is_ignored(&child_relative_path(relative_path, name), info.is_directory)
+            });
+        }
+    }
+
+    let mut all_names: Vec<String> = left_infos
+        .keys()
+        .chain(right_infos.keys())
+        .cloned()
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect();
+    all_names.sort();
+
+    for name in &all_names {
+        *seen += 1;
+        let _ = walk.progress_tx.send(DiffProgressMsg::Counting(*seen));
+
+        let child_relative = if relative_path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative_path, name)
+        };
+        let left_info = left_infos.get(name).cloned();
+        let right_info = right_infos.get(name).cloned();
+
+        match (left_info, right_info) {
+            (Some(l), Some(r)) if l.is_directory && r.is_directory => {
+                let dir_index = entries.len();
+                entries.push(DiffEntry {
+                    relative_path: child_relative.clone(),
+                    left: Some(l),
+                    right: Some(r),
+                    status: DiffStatus::DirSame,
+                    is_directory: true,
+                    depth,
+                    children_not_loaded: false,
+                });
+                build_remote_recursive(walk, session, &child_relative, depth + 1, entries, seen)?;
+                let has_diff = entries[dir_index + 1..].iter().any(|e| {
+                    matches!(
+                        e.status,
+                        DiffStatus::Modified
+                            | DiffStatus::LeftOnly
+                            | DiffStatus::RightOnly
+                            | DiffStatus::DirModified
+                    )
+                });
+                if has_diff {
+                    entries[dir_index].status = DiffStatus::DirModified;
+                }
+            }
+            (Some(l), Some(r)) => {
+                let same = !l.is_directory
+                    && !r.is_directory
+                    && l.size == r.size
+                    && (!matches!(
+                        ctx.method,
+                        CompareMethod::ModifiedTime | CompareMethod::ContentAndTime
+                    ) || l.modified.timestamp() == r.modified.timestamp());
+                let is_directory = l.is_directory || r.is_directory;
+                entries.push(DiffEntry {
+                    relative_path: child_relative,
+                    left: Some(l),
+                    right: Some(r),
+                    status: if same {
+                        DiffStatus::Same
+                    } else {
+                        DiffStatus::Modified
+                    },
+                    is_directory,
+                    depth,
+                    children_not_loaded: false,
+                });
+            }
+            (Some(l), None) => {
+                // Only local one-side directories can be expanded lazily
+                let is_directory = l.is_directory;
+                entries.push(DiffEntry {
+                    relative_path: child_relative,
+                    left: Some(l),
+                    right: None,
+                    status: DiffStatus::LeftOnly,
+                    is_directory,
+                    depth,
+                    children_not_loaded: is_directory && walk.remote_side != SyncSide::Left,
+                });
+            }
+            (None, Some(r)) => {
+                let is_directory = r.is_directory;
+                entries.push(DiffEntry {
+                    relative_path: child_relative,
+                    left: None,
+                    right: Some(r),
+                    status: DiffStatus::RightOnly,
+                    is_directory,
+                    depth,
+                    children_not_loaded: is_directory && walk.remote_side != SyncSide::Right,
+                });
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+/// List a local directory as name → DiffFileInfo
+fn list_local_infos(dir: &Path) -> HashMap<String, DiffFileInfo> {
+    read_dir_names(dir)
+        .into_iter()
+        .filter_map(|name| make_file_info(&dir.join(&name), &name).map(|info| (name, info)))
+        .collect()
+}
+
+/// List a remote directory as name → DiffFileInfo (full_path holds the remote path)
+fn list_remote_infos(
+    session: &SftpSession,
+    dir: &Path,
+) -> Result<HashMap<String, DiffFileInfo>, String> {
+    let entries = session
+        .list_dir(&dir.to_string_lossy())
+        .map_err(|e| e.to_string())?;
+    Ok(entries
+        .into_iter()
+        .map(|e| {
+            let info = DiffFileInfo {
+                name: e.name.clone(),
+                size: if e.is_directory { 0 } else { e.size },
+                modified: e.modified,
+                is_directory: e.is_directory,
+                is_symlink: e.is_symlink,
+                full_path: dir.join(&e.name),
+            };
+            (e.name, info)
+        })
+        .collect())
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Helper functions
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1576,6 +2233,82 @@ fn compare_entries_for_sort(
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Three-way classification
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Classify every entry against the base tree. Files and one-side-only entries are
+/// compared directly; directories present on both sides aggregate their descendants.
+fn classify_merge(
+    entries: &[DiffEntry],
+    base_root: &Path,
+    ctx: &CompareContext,
+) -> HashMap<String, MergeStatus> {
+    let mut result: HashMap<String, MergeStatus> = HashMap::new();
+    let mut dir_status: HashMap<String, MergeStatus> = HashMap::new();
+
+    for entry in entries {
+        let both_dirs = entry.left.as_ref().is_some_and(|i| i.is_directory)
+            && entry.right.as_ref().is_some_and(|i| i.is_directory);
+        if both_dirs {
+            continue;
+        }
+        let status = classify_entry(entry, base_root, ctx);
+        result.insert(entry.relative_path.clone(), status);
+        if status == MergeStatus::Unchanged {
+            continue;
+        }
+        // Propagate to every ancestor directory
+        let mut current = entry.relative_path.as_str();
+        while let Some(pos) = current.rfind('/') {
+            current = &current[..pos];
+            let agg = dir_status
+                .entry(current.to_string())
+                .or_insert(MergeStatus::Unchanged);
+            *agg = agg.combine(status);
+        }
+    }
+
+    for entry in entries {
+        if !result.contains_key(&entry.relative_path) {
+            let status = dir_status
+                .get(&entry.relative_path)
+                .copied()
+                .unwrap_or(MergeStatus::Unchanged);
+            result.insert(entry.relative_path.clone(), status);
+        }
+    }
+    result
+}
+
+/// Classify a single entry by comparing each side with the same path under `base_root`.
+/// Always by content: copies of a tree rarely keep the base's modification times,
+/// so a time or size comparison would report untouched files as changed.
+fn classify_entry(entry: &DiffEntry, base_root: &Path, ctx: &CompareContext) -> MergeStatus {
+    let base_info = make_file_info(&base_root.join(&entry.relative_path), "");
+    let same = |a: Option<&DiffFileInfo>, b: Option<&DiffFileInfo>| -> bool {
+        match (a, b) {
+            (None, None) => true,
+            (Some(a), Some(b)) if a.is_directory || b.is_directory => {
+                a.is_directory == b.is_directory
+            }
+            // Hash mode compares content too, through the digest cache
+            (Some(a), Some(b)) if ctx.method == CompareMethod::Hash => ctx.files_match(a, b),
+            (Some(a), Some(b)) => compare_files(a, b, CompareMethod::Content),
+            _ => false,
+        }
+    };
+    let left_changed = !same(base_info.as_ref(), entry.left.as_ref());
+    let right_changed = !same(base_info.as_ref(), entry.right.as_ref());
+    match (left_changed, right_changed) {
+        (false, false) => MergeStatus::Unchanged,
+        (true, false) => MergeStatus::ChangedLeft,
+        (false, true) => MergeStatus::ChangedRight,
+        (true, true) if same(entry.left.as_ref(), entry.right.as_ref()) => MergeStatus::ChangedBoth,
+        (true, true) => MergeStatus::Conflict,
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // File comparison
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1602,6 +2335,20 @@ pub fn compare_files(left: &DiffFileInfo, right: &DiffFileInfo, method: CompareM
                 && left.size == right.size
                 && byte_compare(&left.full_path, &right.full_path)
         }
+        CompareMethod::Size => left.size == right.size,
+        CompareMethod::Hash => {
+            // Uncached: the diff screen goes through CompareContext's digest cache
+            if left.size != right.size {
+                return false;
+            }
+            match (
+                diff_compare::file_digest(&left.full_path),
+                diff_compare::file_digest(&right.full_path),
+            ) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            }
+        }
     }
 }
 
@@ -1936,7 +2683,14 @@ fn draw_header(frame: &mut Frame, state: &DiffState, area: Rect, theme: &Theme)
     let max_path_width = (area.width as usize).saturating_sub(12); // "[DIFF] " + " ⟷ "
     let half_width = max_path_width / 2;
 
-    let left_str = state.left_root.display().to_string();
+    let root_str = |side: SyncSide, root: &Path| match state.remote {
+        Some((remote_side, ref profile)) if remote_side == side => {
+            crate::services::remote::format_remote_display(profile, &root.to_string_lossy())
+        }
+        _ => root.display().to_string(),
+    };
+
+    let left_str = root_str(SyncSide::Left, &state.left_root);
     let left_display = if left_str.width() > half_width {
         let suffix =
             crate::utils::format::display_width_suffix(&left_str, half_width.saturating_sub(3));
@@ -1945,7 +2699,7 @@ fn draw_header(frame: &mut Frame, state: &DiffState, area: Rect, theme: &Theme)
         left_str
     };
 
-    let right_str = state.right_root.display().to_string();
+    let right_str = root_str(SyncSide::Right, &state.right_root);
     let right_display = if right_str.width() > half_width {
         let suffix =
             crate::utils::format::display_width_suffix(&right_str, half_width.saturating_sub(3));
@@ -1954,13 +2708,29 @@ fn draw_header(frame: &mut Frame, state: &DiffState, area: Rect, theme: &Theme)
         right_str
     };
 
-    let header_line = Line::from(vec![
-        Span::styled(
-            "[DIFF] ",
-            Style::default()
-                .fg(theme.diff.header_label)
-                .add_modifier(Modifier::BOLD),
-        ),
+    let label_style = Style::default()
+        .fg(theme.diff.header_label)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    if let Some(ref base_root) = state.base_root {
+        let base_str = base_root.display().to_string();
+        let base_display = if base_str.width() > half_width {
+            let suffix =
+                crate::utils::format::display_width_suffix(&base_str, half_width.saturating_sub(3));
+            format!("...{}", suffix)
+        } else {
+            base_str
+        };
+        spans.push(Span::styled("[DIFF3] base: ", label_style));
+        spans.push(Span::styled(
+            base_display,
+            Style::default().fg(theme.diff.header_text),
+        ));
+        spans.push(Span::styled(" | ", label_style));
+    } else {
+        spans.push(Span::styled("[DIFF] ", label_style));
+    }
+    spans.extend(vec![
         Span::styled(left_display, Style::default().fg(theme.diff.header_text)),
         Span::styled(
             " \u{27F7} ",
@@ -1971,7 +2741,7 @@ fn draw_header(frame: &mut Frame, state: &DiffState, area: Rect, theme: &Theme)
         Span::styled(right_display, Style::default().fg(theme.diff.header_text)),
     ]);
 
-    frame.render_widget(Paragraph::new(header_line), area);
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
 fn draw_column_headers(frame: &mut Frame, area: Rect, theme: &Theme) {
@@ -2109,10 +2879,18 @@ fn draw_content_side(
 
             let date_str = format!("{}", file_info.modified.format("%m-%d %H:%M"));
 
+            // Three-way: the leading column on the left side carries the merge marker
+            let lead = if is_left && state.is_three_way() {
+                state.merge_status_of(entry).marker()
+            } else {
+                ' '
+            };
+
             let line = Line::from(vec![
                 Span::styled(
                     format!(
-                        " {:<name_w$}",
+                        "{}{:<name_w$}",
+                        lead,
                         name_str,
                         name_w = name_col.saturating_sub(1)
                     ),
@@ -2262,14 +3040,50 @@ fn draw_status_bar(frame: &mut Frame, state: &DiffState, area: Rect, theme: &The
         String::new()
     };
 
+    let merge_str = if state.is_three_way() {
+        let count = |status: MergeStatus| {
+            state
+                .all_entries
+                .iter()
+                .filter(|e| !e.is_directory && state.merge_status_of(e) == status)
+                .count()
+        };
+        format!(
+            " | <:{} >:{} =:{} Conflicts: {}",
+            count(MergeStatus::ChangedLeft),
+            count(MergeStatus::ChangedRight),
+            count(MergeStatus::ChangedBoth),
+            count(MergeStatus::Conflict),
+        )
+    } else {
+        String::new()
+    };
+
+    if let Some(ref message) = state.message {
+        let line = Line::from(Span::styled(
+            format!(
+                " {:<width$}",
+                message,
+                width = (area.width as usize).saturating_sub(1)
+            ),
+            Style::default()
+                .fg(theme.diff.status_bar_text)
+                .bg(theme.diff.status_bar_bg)
+                .add_modifier(Modifier::BOLD),
+        ));
+        frame.render_widget(Paragraph::new(line), area);
+        return;
+    }
+
     let status_text = format!(
-        " Filter: {} | Compare: {} | Total: {} Different: {} Left: {} Right: {}{}",
+        " Filter: {} | Compare: {} | Total: {} Different: {} Left: {} Right: {}{}{}",
         state.filter.display_name(),
         state.compare_method.display_name(),
         total,
         diff_count,
         left_count,
         right_count,
+        merge_str,
         sel_str,
     );
 
@@ -2351,6 +3165,37 @@ fn draw_function_bar(
                 .to_string(),
             ":type ",
         ),
+        (
+            format!(
+                "{}{}",
+                kb.diff_screen_first_key(DiffScreenAction::SyncToLeft),
+                kb.diff_screen_first_key(DiffScreenAction::SyncToRight)
+            ),
+            ":copy ",
+        ),
+        (
+            kb.diff_screen_first_key(DiffScreenAction::DeleteExtra)
+                .to_string(),
+            ":del extra ",
+        ),
+        (
+            format!(
+                "{}{}",
+                kb.diff_screen_first_key(DiffScreenAction::MirrorToLeft),
+                kb.diff_screen_first_key(DiffScreenAction::MirrorToRight)
+            ),
+            ":mirror ",
+        ),
+        (
+            kb.diff_screen_first_key(DiffScreenAction::ExportPatch)
+                .to_string(),
+            ":patch ",
+        ),
+        (
+            kb.diff_screen_first_key(DiffScreenAction::ExportReport)
+                .to_string(),
+            ":report ",
+        ),
         (
             kb.diff_screen_first_key(DiffScreenAction::Close)
                 .to_string(),
@@ -2408,6 +3253,7 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             Some(s) => s,
             None => return,
         };
+        state.message = None;
 
         match action {
             DiffScreenAction::MoveUp => {
@@ -2441,6 +3287,9 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             }
             DiffScreenAction::CycleFilter => {
                 state.filter = state.filter.next();
+                if state.filter == DiffFilter::Conflicts && !state.is_three_way() {
+                    state.filter = state.filter.next();
+                }
                 state.apply_filter();
             }
             DiffScreenAction::SortByName => {
@@ -2475,10 +3324,56 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 app.diff_state = None;
                 return;
             }
+            DiffScreenAction::SyncToRight => request_sync(app, SyncMode::CopyToRight),
+            DiffScreenAction::SyncToLeft => request_sync(app, SyncMode::CopyToLeft),
+            DiffScreenAction::DeleteExtra => request_sync(app, SyncMode::DeleteExtra),
+            DiffScreenAction::MirrorToRight => request_sync(app, SyncMode::MirrorToRight),
+            DiffScreenAction::MirrorToLeft => request_sync(app, SyncMode::MirrorToLeft),
+            DiffScreenAction::ExportPatch => {
+                let scope = if state.selected_files.is_empty() {
+                    "whole tree".to_string()
+                } else {
+                    format!("{} selected item(s)", state.selected_files.len())
+                };
+                app.show_diff_export_dialog(
+                    "diff.patch",
+                    &format!("Unified patch of {} (.html for a report)", scope),
+                );
+            }
+            DiffScreenAction::ExportReport => {
+                app.show_diff_export_dialog("diff-report.html", "HTML comparison report");
+            }
         }
     };
 }
 
+/// Maximum number of operations listed in the sync dry-run dialog
+const SYNC_SUMMARY_ITEMS: usize = 12;
+
+/// Plan a sync and ask for confirmation with its dry-run summary
+fn request_sync(app: &mut App, mode: SyncMode) {
+    let Some(state) = app.diff_state.as_mut() else {
+        return;
+    };
+    let plan = state.plan_sync(mode);
+    if plan.is_empty() {
+        state.message = Some(format!("{}: nothing to sync", mode.display_name()));
+        return;
+    }
+    let message = plan.summary_lines(SYNC_SUMMARY_ITEMS).join("\n");
+    state.pending_sync = Some(plan);
+    app.dialog = Some(super::app::Dialog {
+        dialog_type: super::app::DialogType::DiffSyncConfirm,
+        input: String::new(),
+        cursor_pos: 0,
+        message,
+        completion: None,
+        selected_button: 1, // Default: No
+        selection: None,
+        use_md5: false,
+    });
+}
+
 /// Toggle sort field/order for the diff state
 fn toggle_diff_sort(state: &mut DiffState, sort_by: SortBy) {
     if state.sort_by == sort_by {
@@ -2515,6 +3410,13 @@ fn handle_enter(app: &mut App) {
         return;
     }
 
+    if app.diff_state.as_ref().is_some_and(|s| s.remote.is_some()) {
+        if let Some(ref mut state) = app.diff_state {
+            state.message = Some("File content diff is not available for remote panels".into());
+        }
+        return;
+    }
+
     // Need both sides for file diff view
     let left_path = entry
         .left
@@ -2530,6 +3432,29 @@ fn handle_enter(app: &mut App) {
     // Get file name for display
     let file_name = entry.relative_path.clone();
 
+    // Three-way comparison opens the merge view against the base file
+    // (missing sides resolve to their would-be path so the merge can be saved there)
+    let roots = app.diff_state.as_ref().and_then(|s| {
+        s.base_root
+            .clone()
+            .map(|base| (base, s.left_root.clone(), s.right_root.clone()))
+    });
+    if let Some((base_root, left_root, right_root)) = roots {
+        app.enter_merge_file_view(
+            base_root.join(&entry.relative_path),
+            left_root.join(&entry.relative_path),
+            right_root.join(&entry.relative_path),
+            file_name,
+            ReturnScreen::Screen(Screen::DiffScreen),
+        );
+        return;
+    }
+
     // Enter file content diff view
-    app.enter_diff_file_view(left_path, right_path, file_name);
+    app.enter_diff_file_view(
+        left_path,
+        right_path,
+        file_name,
+        ReturnScreen::Screen(Screen::DiffScreen),
+    );
 }
@@ -10,7 +10,7 @@ use unicode_width::UnicodeWidthStr;
 use super::{
     advanced_search, ai_screen,
     app::{App, Screen},
-    dedup_screen, dialogs, diff_file_view, diff_screen, file_editor, file_info, file_viewer,
+    dedup_screen, dialogs, diff_file_view, diff_screen, editor_workspace, file_info, file_viewer,
     git_screen, help, image_viewer, panel, process_manager, search_result, system_info,
     theme::Theme,
 };
@@ -65,7 +65,14 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 // AI 모드: 에디터와 AI 화면을 나란히 표시
                 draw_editor_with_ai(frame, app, area, &theme);
             } else if let Some(ref mut state) = app.editor_state {
-                file_editor::draw(frame, state, area, &theme, &app.keybindings);
+                editor_workspace::draw(
+                    frame,
+                    state,
+                    &mut app.editor_buffers,
+                    area,
+                    &theme,
+                    &app.keybindings,
+                );
             }
         }
         Screen::FileInfo => file_info::draw(frame, app, area, &theme),
@@ -144,6 +151,13 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 state.visible_height = inner_h.saturating_sub(3) as usize; // header + gap + buttons
             }
         }
+        if dialog.dialog_type == crate::ui::app::DialogType::FileHistory {
+            if let Some(ref mut state) = app.file_history_state {
+                let dialog_h = area.height.saturating_sub(6).clamp(15, 30);
+                let inner_h = dialog_h.saturating_sub(2); // borders
+                state.visible_height = inner_h.saturating_sub(2) as usize; // header + gap
+            }
+        }
     }
 
     // Draw remote spinner overlay on top of everything
@@ -350,6 +364,7 @@ fn draw_function_bar(frame: &mut Frame, app: &App, area: Rect, theme: &Theme) {
         (PanelAction::ToggleBookmark, "bmk "),
         (PanelAction::GitScreen, "git "),
         (PanelAction::GitLogDiff, "glog "),
+        (PanelAction::FileHistory, "hist "),
         (PanelAction::StartDiff, "diff "),
         (PanelAction::AddPanel, "+pan "),
         (PanelAction::ClosePanel, "-pan "),
@@ -430,12 +445,26 @@ fn draw_editor_with_ai(frame: &mut Frame, app: &mut App, area: Rect, theme: &The
                 ai_screen::draw_with_focus(frame, state, panel_chunks[0], theme, false);
             }
             if let Some(ref mut state) = app.editor_state {
-                file_editor::draw(frame, state, panel_chunks[1], theme, &app.keybindings);
+                editor_workspace::draw(
+                    frame,
+                    state,
+                    &mut app.editor_buffers,
+                    panel_chunks[1],
+                    theme,
+                    &app.keybindings,
+                );
             }
         } else {
             // 에디터 왼쪽, AI 오른쪽
             if let Some(ref mut state) = app.editor_state {
-                file_editor::draw(frame, state, panel_chunks[0], theme, &app.keybindings);
+                editor_workspace::draw(
+                    frame,
+                    state,
+                    &mut app.editor_buffers,
+                    panel_chunks[0],
+                    theme,
+                    &app.keybindings,
+                );
             }
             if let Some(ref mut state) = app.ai_state {
                 ai_screen::draw_with_focus(frame, state, panel_chunks[1], theme, false);
@@ -0,0 +1,650 @@
+//! Open buffers and splits of the file editor.
+//!
+//! The focused buffer stays in `App::editor_state`, where the rest of the app
+//! looks for the editor, and [`EditorBuffers`] keeps the other buffers in list
+//! order. List positions don't change while switching, so the unfocused split
+//! pane refers to its buffer by position and keeps its own [`EditorView`],
+//! which lets both panes show the same file.
+//!
+//! Language servers are shared by the buffers of a language and run until
+//! the editor is closed.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::file_editor::{self, EditorState, EditorView};
+use super::theme::{SyntaxColors, Theme};
+use crate::config::LspServerSettings;
+use crate::keybindings::Keybindings;
+use crate::services::lsp::{self, LspEvent, LspManager, LspRequest};
+
+/// How the editor area is shared by two panes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLayout {
+    /// One pane above the other
+    Stacked,
+    SideBySide,
+}
+
+/// The pane without focus
+#[derive(Debug)]
+pub struct EditorSplit {
+    pub layout: SplitLayout,
+    /// List position of the buffer shown in the pane
+    pub pos: usize,
+    pub view: EditorView,
+    /// The pane is above or left of the focused one
+    pub first: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct EditorBuffers {
+    /// Buffers other than the focused one, in list order
+    others: Vec<EditorState>,
+    /// List position of the focused buffer
+    active: usize,
+    pub split: Option<EditorSplit>,
+    /// Selected row of the buffer list, while it is open
+    pub picker: Option<usize>,
+    /// Path being typed into the open-file prompt
+    pub open_input: Option<String>,
+    lsp: LspManager,
+}
+
+impl EditorBuffers {
+    /// Number of open buffers, the focused one included
+    pub fn len(&self) -> usize {
+        self.others.len() + 1
+    }
+
+    /// List position of the focused buffer
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// All buffers in list order
+    pub fn iter<'a>(&'a self, focused: &'a EditorState) -> impl Iterator<Item = &'a EditorState> {
+        self.others[..self.active]
+            .iter()
+            .chain(std::iter::once(focused))
+            .chain(self.others[self.active..].iter())
+    }
+
+    /// Index in `others` of a list position that isn't the focused one
+    fn other_index(&self, pos: usize) -> usize {
+        if pos < self.active {
+            pos
+        } else {
+            pos - 1
+        }
+    }
+
+    fn buffer<'a>(&'a mut self, focused: &'a mut EditorState, pos: usize) -> &'a mut EditorState {
+        if pos == self.active {
+            focused
+        } else {
+            let idx = self.other_index(pos);
+            &mut self.others[idx]
+        }
+    }
+
+    /// Open `editor` as a new buffer at the end of the list and focus it, or
+    /// focus the buffer that already has its file
+    pub fn open(&mut self, focused: &mut EditorState, editor: EditorState) {
+        let path = &editor.file_path;
+        let existing = if path.as_os_str().is_empty() {
+            None
+        } else {
+            self.iter(focused).position(|b| &b.file_path == path)
+        };
+        if let Some(pos) = existing {
+            self.switch_to(focused, pos);
+            return;
+        }
+        let previous = std::mem::replace(focused, editor);
+        self.others.insert(self.active, previous);
+        self.active = self.others.len();
+    }
+
+    /// Focus the buffer at list position `pos`
+    pub fn switch_to(&mut self, focused: &mut EditorState, pos: usize) {
+        if pos == self.active || pos >= self.len() {
+            return;
+        }
+        let idx = self.other_index(pos);
+        std::mem::swap(focused, &mut self.others[idx]);
+        // The previously focused buffer goes back to its own position
+        let previous = self.others.remove(idx);
+        let back = if self.active < pos {
+            self.active
+        } else {
+            self.active - 1
+        };
+        self.others.insert(back, previous);
+        self.active = pos;
+    }
+
+    /// Focus the next or previous buffer, wrapping around
+    pub fn cycle(&mut self, focused: &mut EditorState, forward: bool) {
+        let len = self.len();
+        if len < 2 {
+            focused.set_message("No other buffers", 30);
+            return;
+        }
+        let pos = if forward {
+            (self.active + 1) % len
+        } else {
+            (self.active + len - 1) % len
+        };
+        self.switch_to(focused, pos);
+        focused.set_message(
+            format!("Buffer {}/{}: {}", pos + 1, len, buffer_name(focused)),
+            30,
+        );
+    }
+
+    /// Close the focused buffer and focus the next one. Returns false, closing
+    /// nothing, when it is the only buffer.
+    pub fn close_focused(&mut self, focused: &mut EditorState) -> bool {
+        if self.others.is_empty() {
+            return false;
+        }
+        focused.remove_swap();
+        if let Some(client) = self.lsp.running(focused.language.lsp_id()) {
+            client.did_close(&lsp::path_to_uri(&focused.file_path));
+        }
+        let closed = self.active;
+        let idx = self.active.min(self.others.len() - 1);
+        *focused = self.others.remove(idx);
+        self.active = idx;
+
+        if self.split.as_ref().is_some_and(|s| s.pos == closed) {
+            self.split = None;
+        }
+        if let Some(split) = self.split.as_mut().filter(|s| s.pos > closed) {
+            split.pos -= 1;
+        }
+        true
+    }
+
+    /// Write swap files and check for changes on disk, in every buffer
+    pub fn autosave(&mut self, focused: &mut EditorState) {
+        focused.autosave();
+        for buffer in &mut self.others {
+            buffer.autosave();
+        }
+    }
+
+    /// Delete the swap files of every buffer, when their changes are discarded
+    pub fn remove_swaps(&self, focused: &EditorState) {
+        for buffer in self.iter(focused) {
+            buffer.remove_swap();
+        }
+    }
+
+    /// Keep the language servers in step with every buffer and hand their
+    /// answers to the buffers they are about
+    pub fn lsp_tick(
+        &mut self,
+        focused: &mut EditorState,
+        servers: &HashMap<String, LspServerSettings>,
+        syntax: SyntaxColors,
+    ) {
+        sync_lsp(&mut self.lsp, focused, servers);
+        for buffer in &mut self.others {
+            sync_lsp(&mut self.lsp, buffer, servers);
+        }
+
+        for event in self.lsp.poll() {
+            let uri = match event {
+                LspEvent::Diagnostics { ref uri, .. }
+                | LspEvent::Hover { ref uri, .. }
+                | LspEvent::Definition { ref uri, .. }
+                | LspEvent::Completion { ref uri, .. }
+                | LspEvent::Formatting { ref uri, .. } => uri.clone(),
+                LspEvent::Message(_) | LspEvent::Exited => {
+                    focused.apply_lsp_event(event);
+                    continue;
+                }
+            };
+            let is_focused = lsp::path_to_uri(&focused.file_path) == uri;
+            match event {
+                LspEvent::Diagnostics { .. } if !is_focused => {
+                    if let Some(buffer) = self
+                        .others
+                        .iter_mut()
+                        .find(|b| lsp::path_to_uri(&b.file_path) == uri)
+                    {
+                        buffer.apply_lsp_event(event);
+                    }
+                }
+                // Answers to a buffer no longer focused are dropped
+                _ if !is_focused => {}
+                LspEvent::Definition {
+                    location: Some(location),
+                    ..
+                } if location.path != focused.file_path => {
+                    let mut editor = EditorState::new();
+                    editor.set_syntax_colors(syntax);
+                    match editor.load_file(&location.path) {
+                        Ok(_) => {
+                            self.open(focused, editor);
+                            focused.goto_position(location.position);
+                        }
+                        Err(e) => focused.set_message(format!("Cannot open file: {}", e), 50),
+                    }
+                }
+                _ => focused.apply_lsp_event(event),
+            }
+        }
+    }
+
+    /// Ask the focused buffer's language server about the cursor position, or
+    /// for formatting the buffer
+    pub fn lsp_request(
+        &mut self,
+        focused: &mut EditorState,
+        kind: LspRequest,
+        servers: &HashMap<String, LspServerSettings>,
+    ) {
+        let language = focused.language;
+        if !servers.contains_key(language.lsp_id()) {
+            focused.set_message(
+                format!(
+                    "No language server for {} (lsp_servers.{} in settings)",
+                    language.name(),
+                    language.lsp_id()
+                ),
+                60,
+            );
+            return;
+        }
+        if focused.file_path.as_os_str().is_empty() {
+            focused.set_message("Save the file first to use its language server", 50);
+            return;
+        }
+        sync_lsp(&mut self.lsp, focused, servers);
+        let Some(client) = self.lsp.running(language.lsp_id()) else {
+            focused.set_message(
+                format!("Language server for {} is not running", language.name()),
+                50,
+            );
+            return;
+        };
+        let uri = lsp::path_to_uri(&focused.file_path);
+        match kind {
+            LspRequest::Formatting => {
+                client.request_formatting(&uri, focused.tab_size, !focused.use_tabs)
+            }
+            _ => client.request_at(kind, &uri, focused.lsp_position()),
+        }
+    }
+
+    /// Drop every buffer but the focused one, which the caller discards
+    pub fn close_all(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Names of the buffers with unsaved changes
+    pub fn unsaved(&self, focused: &EditorState) -> Vec<String> {
+        self.iter(focused)
+            .filter(|b| b.modified)
+            .map(buffer_name)
+            .collect()
+    }
+
+    /// Split the editor with the focused buffer in both panes, or switch the
+    /// layout of an existing split
+    pub fn split(&mut self, focused: &EditorState, layout: SplitLayout) {
+        match self.split.as_mut() {
+            Some(split) => split.layout = layout,
+            None => {
+                self.split = Some(EditorSplit {
+                    layout,
+                    pos: self.active,
+                    view: focused.view(),
+                    first: false,
+                })
+            }
+        }
+    }
+
+    /// Move the focus to the other pane
+    pub fn focus_other(&mut self, focused: &mut EditorState) {
+        let Some(split) = self.split.take() else {
+            return;
+        };
+        let previous = EditorSplit {
+            layout: split.layout,
+            pos: self.active,
+            view: focused.view(),
+            first: !split.first,
+        };
+        self.switch_to(focused, split.pos);
+        focused.set_view(split.view);
+        self.split = Some(previous);
+    }
+
+    /// Keys of the buffer list and the open-file prompt. Returns true when one
+    /// of them is open and took the key.
+    pub fn handle_overlay_key(
+        &mut self,
+        focused: &mut EditorState,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        syntax: SyntaxColors,
+    ) -> bool {
+        if let Some(input) = self.open_input.as_mut() {
+            match code {
+                KeyCode::Esc => self.open_input = None,
+                KeyCode::Enter => {
+                    let input = self.open_input.take().unwrap_or_default();
+                    self.open_path(focused, &input, syntax);
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => input.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        if let Some(selected) = self.picker {
+            match code {
+                KeyCode::Esc => self.picker = None,
+                KeyCode::Up => self.picker = Some(selected.saturating_sub(1)),
+                KeyCode::Down => self.picker = Some((selected + 1).min(self.len() - 1)),
+                KeyCode::Enter => {
+                    self.picker = None;
+                    self.switch_to(focused, selected);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Load `input`, relative to the focused file's directory, into a new buffer
+    fn open_path(&mut self, focused: &mut EditorState, input: &str, syntax: SyntaxColors) {
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let path = if let Some(rest) = input.strip_prefix("~/") {
+            dirs::home_dir().unwrap_or_default().join(rest)
+        } else if Path::new(input).is_absolute() {
+            PathBuf::from(input)
+        } else {
+            focused
+                .file_path
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(Path::to_path_buf)
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_default()
+                .join(input)
+        };
+
+        let mut editor = EditorState::new();
+        editor.set_syntax_colors(syntax);
+        match editor.load_file(&path) {
+            Ok(_) => self.open(focused, editor),
+            Err(e) => focused.set_message(format!("Cannot open file: {}", e), 50),
+        }
+    }
+}
+
+/// Start the buffer's language server if needed and send it the buffer's text
+fn sync_lsp(
+    manager: &mut LspManager,
+    buffer: &mut EditorState,
+    servers: &HashMap<String, LspServerSettings>,
+) {
+    if buffer.file_path.as_os_str().is_empty() {
+        return;
+    }
+    match manager.client(buffer.language.lsp_id(), servers, &buffer.file_path) {
+        Ok(Some(client)) => buffer.sync_lsp(client),
+        Ok(None) => {}
+        Err(e) => buffer.set_message(e, 80),
+    }
+}
+
+fn buffer_name(buffer: &EditorState) -> String {
+    buffer
+        .file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "New File".to_string())
+}
+
+/// Draw the buffer tabs, the panes and the buffer list or open prompt
+pub fn draw(
+    frame: &mut Frame,
+    focused: &mut EditorState,
+    buffers: &mut EditorBuffers,
+    area: Rect,
+    theme: &Theme,
+    kb: &Keybindings,
+) {
+    let mut area = area;
+    if buffers.len() > 1 && area.height > 4 {
+        draw_tabs(frame, focused, buffers, Rect { height: 1, ..area }, theme);
+        area.y += 1;
+        area.height -= 1;
+    }
+
+    if let Some(mut split) = buffers.split.take() {
+        let direction = match split.layout {
+            SplitLayout::Stacked => Direction::Vertical,
+            SplitLayout::SideBySide => Direction::Horizontal,
+        };
+        let chunks = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        let (other_area, focused_area) = if split.first {
+            (chunks[0], chunks[1])
+        } else {
+            (chunks[1], chunks[0])
+        };
+
+        // The other pane first: with the same buffer in both panes, the
+        // focused one must be drawn last to leave its sizes in the state
+        let other = buffers.buffer(focused, split.pos);
+        other.swap_view(&mut split.view);
+        file_editor::draw(frame, other, other_area, theme, kb);
+        other.swap_view(&mut split.view);
+        buffers.split = Some(split);
+
+        file_editor::draw(frame, focused, focused_area, theme, kb);
+    } else {
+        file_editor::draw(frame, focused, area, theme, kb);
+    }
+
+    if let Some(selected) = buffers.picker {
+        draw_picker(frame, focused, buffers, selected, area, theme);
+    }
+    if let Some(ref input) = buffers.open_input {
+        if area.height > 2 && area.width > 2 {
+            let prompt = Line::from(vec![
+                Span::styled("Open: ", theme.header_style()),
+                Span::styled(input.clone(), Style::default().fg(theme.editor.text)),
+                Span::styled(" ", theme.selected_style()),
+            ]);
+            frame.render_widget(
+                Paragraph::new(prompt).style(theme.status_bar_style()),
+                Rect::new(area.x + 1, area.y + area.height - 2, area.width - 2, 1),
+            );
+        }
+    }
+}
+
+fn draw_tabs(
+    frame: &mut Frame,
+    focused: &EditorState,
+    buffers: &EditorBuffers,
+    area: Rect,
+    theme: &Theme,
+) {
+    let split_pos = buffers.split.as_ref().map(|s| s.pos);
+    let mut spans = Vec::new();
+    for (pos, buffer) in buffers.iter(focused).enumerate() {
+        let style = if pos == buffers.active {
+            theme.selected_style()
+        } else if Some(pos) == split_pos {
+            theme.header_style()
+        } else {
+            theme.dim_style()
+        };
+        let mark = if buffer.modified { "✻" } else { "" };
+        spans.push(Span::styled(
+            format!(" {}:{}{} ", pos + 1, mark, buffer_name(buffer)),
+            style,
+        ));
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).style(theme.status_bar_style()),
+        area,
+    );
+}
+
+fn draw_picker(
+    frame: &mut Frame,
+    focused: &EditorState,
+    buffers: &EditorBuffers,
+    selected: usize,
+    area: Rect,
+    theme: &Theme,
+) {
+    let width = area.width.saturating_sub(4).min(72);
+    let height = (buffers.len() as u16 + 2).min(area.height);
+    if width < 10 || height < 3 {
+        return;
+    }
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Buffers ")
+        .border_style(Style::default().fg(theme.editor.border));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let rows = inner.height as usize;
+    let offset = (selected + 1).saturating_sub(rows);
+    for (i, (pos, buffer)) in buffers
+        .iter(focused)
+        .enumerate()
+        .skip(offset)
+        .take(rows)
+        .enumerate()
+    {
+        let style = if pos == selected {
+            theme.selected_style()
+        } else {
+            theme.normal_style()
+        };
+        let path = if buffer.file_path.as_os_str().is_empty() {
+            buffer_name(buffer)
+        } else {
+            buffer.file_path.display().to_string()
+        };
+        let mark = if buffer.modified { "✻" } else { " " };
+        frame.render_widget(
+            Paragraph::new(format!("{} {}", mark, path)).style(style),
+            Rect::new(inner.x, inner.y + i as u16, inner.width, 1),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(name: &str) -> EditorState {
+        let mut editor = EditorState::new();
+        editor.file_path = PathBuf::from(format!("/tmp/{}", name));
+        editor.lines = vec![name.to_string(); 3];
+        editor
+    }
+
+    fn names(buffers: &EditorBuffers, focused: &EditorState) -> Vec<String> {
+        buffers.iter(focused).map(buffer_name).collect()
+    }
+
+    #[test]
+    fn test_buffers_keep_their_order_while_switching() {
+        let mut buffers = EditorBuffers::default();
+        let mut focused = buffer("a");
+        buffers.open(&mut focused, buffer("b"));
+        buffers.open(&mut focused, buffer("c"));
+        assert_eq!(names(&buffers, &focused), ["a", "b", "c"]);
+        assert_eq!((buffers.active(), buffer_name(&focused).as_str()), (2, "c"));
+
+        buffers.switch_to(&mut focused, 0);
+        assert_eq!(names(&buffers, &focused), ["a", "b", "c"]);
+        assert_eq!(buffer_name(&focused), "a");
+        buffers.cycle(&mut focused, false);
+        assert_eq!(buffer_name(&focused), "c");
+
+        // Opening a file that is already open focuses its buffer
+        buffers.open(&mut focused, buffer("b"));
+        assert_eq!(buffers.len(), 3);
+        assert_eq!(buffers.active(), 1);
+
+        focused.modified = true;
+        assert_eq!(buffers.unsaved(&focused), ["b"]);
+        assert!(buffers.close_focused(&mut focused));
+        assert_eq!(names(&buffers, &focused), ["a", "c"]);
+        assert_eq!(buffer_name(&focused), "c");
+        assert!(buffers.close_focused(&mut focused));
+        assert!(!buffers.close_focused(&mut focused));
+        assert_eq!(buffer_name(&focused), "a");
+    }
+
+    #[test]
+    fn test_split_panes_keep_separate_views() {
+        let mut buffers = EditorBuffers::default();
+        let mut focused = buffer("a");
+        focused.cursor_line = 2;
+        buffers.split(&focused, SplitLayout::SideBySide);
+        focused.cursor_line = 0;
+
+        // Both panes on the same buffer: focusing swaps the views
+        buffers.focus_other(&mut focused);
+        assert_eq!(focused.cursor_line, 2);
+        let split = buffers.split.as_ref().unwrap();
+        assert_eq!(
+            (split.pos, split.view.cursor_line, split.first),
+            (0, 0, true)
+        );
+
+        // Another buffer in the focused pane leaves the other pane on "a"
+        buffers.open(&mut focused, buffer("b"));
+        buffers.focus_other(&mut focused);
+        assert_eq!(buffer_name(&focused), "a");
+        assert_eq!(focused.cursor_line, 0);
+        assert_eq!(buffers.split.as_ref().unwrap().pos, 1);
+
+        // Closing the buffer shown in the other pane removes the split
+        buffers.switch_to(&mut focused, 1);
+        buffers.close_focused(&mut focused);
+        assert!(buffers.split.is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -9,15 +9,23 @@ use ratatui::{
 use regex::Regex;
 use std::collections::VecDeque;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 use unicode_width::UnicodeWidthChar;
 
 use super::{
     app::{App, Screen},
+    diff_file_view::ReturnScreen,
+    grammar,
     syntax::{Language, SyntaxHighlighter},
     theme::Theme,
 };
 use crate::keybindings::EditorAction;
+use crate::services::lsp::{
+    self, CompletionItem, Diagnostic, LspClient, LspEvent, LspRequest, Position, Severity, TextEdit,
+};
+use crate::services::swap::{self, SwapFile};
+use crate::utils::encoding::TextEncoding;
 
 /// Undo/Redo 액션 유형
 #[derive(Debug, Clone)]
@@ -57,6 +65,10 @@ pub enum EditAction {
         line1: usize,
         line2: usize,
     },
+    SetFinalNewline {
+        old: bool,
+        new: bool,
+    },
     Batch {
         actions: Vec<EditAction>,
     },
@@ -119,6 +131,164 @@ impl Selection {
     }
 }
 
+/// Extra cursor of multi-cursor editing. `anchor` is the other end of a
+/// selection, which stays on the cursor's line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub line: usize,
+    pub col: usize,
+    pub anchor: Option<usize>,
+}
+
+impl Cursor {
+    /// Selection between the anchor and the cursor
+    pub fn selection(&self) -> Option<Selection> {
+        let anchor = self.anchor.filter(|&a| a != self.col)?;
+        Some(Selection {
+            start_line: self.line,
+            start_col: anchor.min(self.col),
+            end_line: self.line,
+            end_col: anchor.max(self.col),
+        })
+    }
+}
+
+/// Rectangular selection, in visual columns so it stays straight across tabs
+/// and wide characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSelection {
+    pub anchor_line: usize,
+    pub anchor_col: usize,
+    pub head_line: usize,
+    pub head_col: usize,
+}
+
+/// Cursor, selection and scroll of one view onto a buffer, so a split can show
+/// the same file twice
+#[derive(Debug, Clone, Default)]
+pub struct EditorView {
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub scroll: usize,
+    pub horizontal_scroll: usize,
+    pub selection: Option<Selection>,
+    pub cursors: Vec<Cursor>,
+    pub block: Option<BlockSelection>,
+}
+
+/// Line terminator a file is saved with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    /// Classic Mac OS
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            LineEnding::Lf => LineEnding::CrLf,
+            LineEnding::CrLf => LineEnding::Cr,
+            LineEnding::Cr => LineEnding::Lf,
+        }
+    }
+
+    /// The most common terminator in `text`; LF when there are none
+    pub fn detect(text: &str) -> Self {
+        let bytes = text.as_bytes();
+        let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    crlf += 1;
+                    i += 1;
+                }
+                b'\r' => cr += 1,
+                b'\n' => lf += 1,
+                _ => {}
+            }
+            i += 1;
+        }
+        if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Terminator of each line of `text`, in order
+    pub fn each(text: &str) -> Vec<Self> {
+        let bytes = text.as_bytes();
+        let mut endings = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    endings.push(LineEnding::CrLf);
+                    i += 1;
+                }
+                b'\r' => endings.push(LineEnding::Cr),
+                b'\n' => endings.push(LineEnding::Lf),
+                _ => {}
+            }
+            i += 1;
+        }
+        endings
+    }
+
+    /// Split `text` into lines, and tell whether it ended with a terminator.
+    /// Any of the three terminators ends a line, so mixed files load cleanly.
+    pub fn split(text: &str) -> (Vec<String>, bool) {
+        let mut lines = Vec::new();
+        let mut rest = text;
+        let mut final_newline = false;
+        while !rest.is_empty() {
+            match rest.find(['\n', '\r']) {
+                Some(i) => {
+                    lines.push(rest[..i].to_string());
+                    let len = if rest[i..].starts_with("\r\n") { 2 } else { 1 };
+                    rest = &rest[i + len..];
+                    final_newline = rest.is_empty();
+                }
+                None => {
+                    lines.push(rest.to_string());
+                    rest = "";
+                }
+            }
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        (lines, final_newline)
+    }
+}
+
+/// `endings` when they are not all the same kind
+fn mixed_endings(endings: Vec<LineEnding>) -> Option<Vec<LineEnding>> {
+    let first = *endings.first()?;
+    endings.iter().any(|&e| e != first).then_some(endings)
+}
+
 /// 찾기/바꾸기 모드
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FindReplaceMode {
@@ -138,6 +308,18 @@ pub struct FindReplaceOptions {
 /// Default maximum memory for undo/redo stacks (50MB)
 const DEFAULT_MAX_UNDO_MEMORY: usize = 50 * 1024 * 1024;
 
+/// Popup showing a language server's answer
+#[derive(Debug, Clone)]
+pub enum LspPopup {
+    /// Hover text, after the diagnostics of the cursor line
+    Hover(Vec<String>),
+    /// Completions, narrowed down by the word typed before the cursor
+    Completion {
+        items: Vec<CompletionItem>,
+        selected: usize,
+    },
+}
+
 /// 편집기 상태
 #[derive(Debug)]
 pub struct EditorState {
@@ -149,11 +331,25 @@ pub struct EditorState {
     pub horizontal_scroll: usize,
     pub modified: bool,
     pub original_lines: Vec<String>,
+    /// Encoding the file is saved in
+    pub encoding: TextEncoding,
+    /// Line ending of new lines, and of every line once converted
+    pub line_ending: LineEnding,
+    /// Ending of each of `original_lines` when the file mixes them. Lines
+    /// that are still unchanged keep theirs on save; converting drops this.
+    pub mixed_endings: Option<Vec<LineEnding>>,
+    /// Whether the last line is followed by a line ending
+    pub final_newline: bool,
+    /// Encoding, line ending, final newline and mixed endings of the file on
+    /// disk, to tell a conversion is unsaved
+    saved_format: (TextEncoding, LineEnding, bool, bool),
 
     // Undo/Redo
     pub undo_stack: VecDeque<EditAction>,
     pub redo_stack: VecDeque<EditAction>,
     pub max_undo_size: usize,
+    /// Actions of an edit made of several steps, pushed as one batch at its end
+    undo_group: Option<Vec<EditAction>>,
 
     // Memory tracking for undo/redo
     undo_memory_usage: usize,
@@ -197,8 +393,12 @@ pub struct EditorState {
     pub matching_bracket: Option<(usize, usize)>,
 
     // 다중 커서 (Ctrl+D)
-    pub cursors: Vec<(usize, usize)>, // (line, col) 추가 커서들
+    pub cursors: Vec<Cursor>,                // 추가 커서들
     pub last_word_selection: Option<String>, // 마지막 선택된 단어 (Ctrl+D용)
+    /// Block selection being extended (Alt+Shift+arrows, Alt+drag)
+    pub block: Option<BlockSelection>,
+    /// Screen area of the text, for mapping mouse positions
+    text_area: Rect,
 
     // Esc 두 번 누르기 상태
     pub pending_exit: bool,
@@ -216,6 +416,33 @@ pub struct EditorState {
 
     // 원격 파일 편집 원본 정보
     pub remote_origin: Option<RemoteEditOrigin>,
+
+    /// Where swap files are written (None: never)
+    pub swap_dir: Option<PathBuf>,
+    /// Edits not in the swap file yet
+    swap_pending: bool,
+    swap_written: Instant,
+    /// Swap left by an earlier session, until recovered or discarded
+    pub recovery: Option<SwapFile>,
+    /// Pid of another running instance that owns the file's swap, which this
+    /// buffer then neither writes nor removes
+    pub swap_owner: Option<u32>,
+    /// Modification time and size of the file when loaded or last saved
+    disk_stamp: Option<(Option<SystemTime>, u64)>,
+    /// The file was found changed on disk and the user told so
+    disk_change_reported: bool,
+    /// A save stopped because of a change on disk; the next one overwrites it
+    overwrite_armed: bool,
+
+    /// Diagnostics the language server published for the file
+    pub diagnostics: Vec<Diagnostic>,
+    pub lsp_popup: Option<LspPopup>,
+    /// Bumped on every change of the text
+    edit_version: u64,
+    /// `edit_version` last sent to the language server
+    lsp_synced: u64,
+    /// Saved since the language server was last told
+    lsp_saved: bool,
 }
 
 impl EditorState {
@@ -234,6 +461,7 @@ impl EditorState {
                 ..
             } => old_content.len() + new_content.len() + 32,
             EditAction::SwapLines { .. } => 24,
+            EditAction::SetFinalNewline { .. } => 8,
             EditAction::Batch { actions } => {
                 actions
                     .iter()
@@ -254,9 +482,15 @@ impl EditorState {
             horizontal_scroll: 0,
             modified: false,
             original_lines: vec![String::new()],
+            encoding: TextEncoding::Utf8,
+            line_ending: LineEnding::Lf,
+            mixed_endings: None,
+            final_newline: true,
+            saved_format: (TextEncoding::Utf8, LineEnding::Lf, true, false),
             undo_stack: VecDeque::new(),
             redo_stack: VecDeque::new(),
             max_undo_size: 1000,
+            undo_group: None,
             undo_memory_usage: 0,
             redo_memory_usage: 0,
             max_undo_memory: DEFAULT_MAX_UNDO_MEMORY,
@@ -285,6 +519,8 @@ impl EditorState {
             matching_bracket: None,
             cursors: Vec::new(),
             last_word_selection: None,
+            block: None,
+            text_area: Rect::default(),
             pending_exit: false,
             word_wrap: false,
             visible_height: 20, // 기본값, 렌더링 시 업데이트됨
@@ -292,6 +528,19 @@ impl EditorState {
             message: None,
             message_timer: 0,
             remote_origin: None,
+            swap_dir: swap::default_dir(),
+            swap_pending: false,
+            swap_written: Instant::now(),
+            recovery: None,
+            swap_owner: None,
+            disk_stamp: None,
+            disk_change_reported: false,
+            overwrite_armed: false,
+            diagnostics: Vec::new(),
+            lsp_popup: None,
+            edit_version: 0,
+            lsp_synced: 0,
+            lsp_saved: false,
         }
     }
 
@@ -442,79 +691,535 @@ impl EditorState {
         self.find_mode = FindReplaceMode::None;
         self.find_error = None;
 
-        // 파일 읽기
-        match fs::read_to_string(path) {
-            Ok(content) => {
-                self.lines = content.lines().map(String::from).collect();
-                if self.lines.is_empty() {
-                    self.lines.push(String::new());
-                }
+        // 파일 읽기: detect the encoding and line endings so they can be
+        // saved the same way
+        let mut invalid = false;
+        match fs::read(path) {
+            Ok(bytes) => {
+                self.encoding = TextEncoding::detect(&bytes);
+                let (content, had_errors) = self.encoding.decode(&bytes);
+                invalid = had_errors;
+                self.line_ending = LineEnding::detect(&content);
+                (self.lines, self.final_newline) = LineEnding::split(&content);
+                self.mixed_endings = mixed_endings(LineEnding::each(&content));
             }
-            Err(_) => {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 // 새 파일
+                self.encoding = TextEncoding::Utf8;
+                self.line_ending = LineEnding::Lf;
+                self.mixed_endings = None;
+                self.final_newline = true;
                 self.lines = vec![String::new()];
             }
+            Err(e) => return Err(e.to_string()),
+        }
+        self.saved_format = self.format();
+        if invalid {
+            self.set_message(
+                format!("Invalid {} sequences were replaced", self.encoding.name()),
+                50,
+            );
         }
 
         // 원본 상태 저장
         self.original_lines = self.lines.clone();
 
         // 언어 감지
-        self.language = Language::from_extension(path);
+        self.language = Language::detect(path, &self.lines);
+        // Detection read the user's grammar files; say once if some are broken
+        if let Some(errors) = grammar::take_load_errors() {
+            self.set_message(errors, 100);
+        }
         self.highlighter = Some(SyntaxHighlighter::new(self.language, self.syntax_colors));
 
+        self.disk_stamp = Self::read_disk_stamp(path);
+        self.disk_change_reported = false;
+        self.overwrite_armed = false;
+        self.swap_pending = false;
+        self.edit_version += 1;
+        self.diagnostics.clear();
+        self.lsp_popup = None;
+
+        // Unsaved changes left by a session that didn't end normally
+        self.recovery = None;
+        self.swap_owner = None;
+        if let Some(ref dir) = self.swap_dir {
+            match swap::read(dir, path) {
+                // Not left behind: another instance is editing the file now
+                Some(found) if found.owner_alive() => {
+                    self.set_message(format!("File is being edited by pid {}", found.pid), 100);
+                    self.swap_owner = Some(found.pid);
+                }
+                Some(found) if found.lines == self.lines => swap::remove(dir, path),
+                Some(found) => self.recovery = Some(found),
+                None => {}
+            }
+        }
+
         Ok(())
     }
 
     /// 파일 저장
-    /// Security: Preserves original file permissions and uses atomic write
     pub fn save_file(&mut self) -> Result<(), String> {
-        // Resolve symlink to actual file path to avoid replacing symlink with regular file
-        let is_symlink = fs::symlink_metadata(&self.file_path)
-            .map(|m| m.is_symlink())
-            .unwrap_or(false);
-
-        let actual_path = if is_symlink {
-            fs::canonicalize(&self.file_path)
-                .map_err(|e| format!("Failed to resolve symlink: {}", e))?
-        } else {
-            self.file_path.clone()
+        // Don't silently overwrite someone else's edits: the first save stops
+        if self.changed_on_disk() && !self.overwrite_armed {
+            self.overwrite_armed = true;
+            self.disk_change_reported = true;
+            return Err("file changed on disk since it was opened, save again to overwrite".into());
+        }
+
+        // Saved in the encoding and line endings it was loaded (or converted) in
+        let endings = self.line_endings_to_save();
+        let mut content = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            content.push_str(line);
+            if i + 1 < self.lines.len() || self.final_newline {
+                content.push_str(endings[i].as_str());
+            }
+        }
+        let bytes = self.encoding.encode(&content)?;
+        write_atomically(&self.file_path, &bytes)?;
+
+        self.modified = false;
+        self.original_lines = self.lines.clone();
+        if self.mixed_endings.is_some() {
+            self.mixed_endings = mixed_endings(endings);
+        }
+        self.saved_format = self.format();
+        self.disk_stamp = Self::read_disk_stamp(&self.file_path);
+        self.disk_change_reported = false;
+        self.overwrite_armed = false;
+        self.swap_pending = false;
+        self.lsp_saved = true;
+        self.remove_swap();
+        Ok(())
+    }
+
+    /// Ending of each line on save: in a file of mixed endings the lines that
+    /// match an original line keep its ending, others get `line_ending`
+    fn line_endings_to_save(&self) -> Vec<LineEnding> {
+        let mut endings = vec![self.line_ending; self.lines.len()];
+        if let Some(ref original) = self.mixed_endings {
+            let matches = super::diff_file_view::compute_line_matches(
+                &self.original_lines,
+                &self.lines,
+                &Default::default(),
+            );
+            for (old, new) in matches {
+                if let Some(&ending) = original.get(old) {
+                    endings[new] = ending;
+                }
+            }
+        }
+        endings
+    }
+
+    pub(crate) fn read_disk_stamp(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+        fs::metadata(path)
+            .ok()
+            .map(|m| (m.modified().ok(), m.len()))
+    }
+
+    /// The file's modification time or size differs from when it was loaded
+    /// or saved
+    pub fn changed_on_disk(&self) -> bool {
+        !self.file_path.as_os_str().is_empty()
+            && Self::read_disk_stamp(&self.file_path) != self.disk_stamp
+    }
+
+    /// Periodic work: keep the swap file in step with unsaved changes and
+    /// report a change of the file on disk once
+    pub fn autosave(&mut self) {
+        const SWAP_INTERVAL: Duration = Duration::from_secs(4);
+
+        if self.file_path.as_os_str().is_empty() || self.recovery.is_some() {
+            return;
+        }
+        if !self.disk_change_reported && self.changed_on_disk() {
+            self.disk_change_reported = true;
+            self.set_message(
+                "File changed on disk; saving will ask before overwriting it",
+                80,
+            );
+        }
+        if !self.swap_pending || self.swap_written.elapsed() < SWAP_INTERVAL {
+            return;
+        }
+        self.swap_pending = false;
+        self.swap_written = Instant::now();
+
+        let Some(ref dir) = self.swap_dir else {
+            return;
         };
+        if self.swap_owner.is_some() {
+            return;
+        }
+        if !self.modified {
+            swap::remove(dir, &self.file_path);
+        } else if let Err(e) = swap::write(dir, &self.file_path, &self.lines) {
+            self.set_message(format!("Cannot write swap file: {}", e), 50);
+        }
+    }
 
-        // Save original permissions before writing
-        #[cfg(unix)]
-        let original_perms = fs::metadata(&actual_path).map(|m| m.permissions()).ok();
+    /// Delete the buffer's swap file, once its changes are saved or discarded.
+    /// A swap still waiting to be recovered, or owned by another running
+    /// instance, is kept.
+    pub fn remove_swap(&self) {
+        if self.recovery.is_some() || self.swap_owner.is_some() {
+            return;
+        }
+        if let Some(ref dir) = self.swap_dir {
+            if !self.file_path.as_os_str().is_empty() {
+                swap::remove(dir, &self.file_path);
+            }
+        }
+    }
 
-        let content = self.lines.join("\n");
+    /// Replace the text with the lines of the swap left by an earlier session
+    pub fn recover_swap(&mut self) {
+        let Some(found) = self.recovery.take() else {
+            return;
+        };
+        self.lines = found.lines;
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.edit_version += 1;
+        self.cursor_line = self.cursor_line.min(self.lines.len() - 1);
+        self.cursor_col = 0;
+        self.selection = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.undo_memory_usage = 0;
+        self.redo_memory_usage = 0;
+        // The swap already holds these lines and stays until they are saved
+        self.update_modified();
+        self.update_scroll();
+        self.set_message("Recovered unsaved changes; save to keep them", 60);
+    }
 
-        // Use atomic write: write to temp file, then rename
-        let temp_path = actual_path.with_extension("tmp");
+    /// Forget the swap left by an earlier session
+    pub fn discard_swap(&mut self) {
+        if self.recovery.take().is_some() {
+            self.remove_swap();
+            self.set_message("Swap file discarded", 30);
+        }
+    }
 
-        // Write to temporary file
-        fs::write(&temp_path, &content)
-            .map_err(|e| format!("Failed to write temporary file: {}", e))?;
+    /// Write the swapped lines next to the swap for comparing them with the
+    /// file; returns the copy's path
+    pub fn write_recovery_copy(&self) -> Result<PathBuf, String> {
+        let (Some(dir), Some(found)) = (&self.swap_dir, &self.recovery) else {
+            return Err("No swap file".into());
+        };
+        let path = swap::recovered_path(dir, &self.file_path);
+        let mut content = found.lines.join("\n");
+        content.push('\n');
+        fs::write(&path, content).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
 
-        // Restore original permissions on temp file before rename
-        #[cfg(unix)]
-        if let Some(perms) = original_perms {
-            let _ = fs::set_permissions(&temp_path, perms);
+    /// Text as the language server sees it: lines joined with LF
+    fn document_text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        if self.final_newline {
+            text.push('\n');
         }
+        text
+    }
 
-        // Atomic rename (on same filesystem)
-        fs::rename(&temp_path, &actual_path).map_err(|e| {
-            // Clean up temp file on failure
-            let _ = fs::remove_file(&temp_path);
-            format!("Failed to save file: {}", e)
-        })?;
+    /// Cursor position in the language server's UTF-16 columns
+    pub fn lsp_position(&self) -> Position {
+        Position {
+            line: self.cursor_line,
+            character: lsp::utf16_col(&self.lines[self.cursor_line], self.cursor_col),
+        }
+    }
 
-        self.modified = false;
-        self.original_lines = self.lines.clone();
-        Ok(())
+    /// Open the document on the server, or send its text if it changed since
+    pub fn sync_lsp(&mut self, client: &mut LspClient) {
+        let uri = lsp::path_to_uri(&self.file_path);
+        if !client.is_open(&uri) {
+            client.did_open(&uri, self.language.lsp_id(), &self.document_text());
+            self.lsp_synced = self.edit_version;
+        } else if self.lsp_synced != self.edit_version {
+            client.did_change(&uri, &self.document_text());
+            self.lsp_synced = self.edit_version;
+        }
+        if self.lsp_saved {
+            self.lsp_saved = false;
+            client.did_save(&uri);
+        }
+    }
+
+    /// Show or apply a response about this buffer. A definition in another
+    /// file is opened by the caller.
+    pub fn apply_lsp_event(&mut self, event: LspEvent) {
+        match event {
+            LspEvent::Diagnostics { diagnostics, .. } => self.diagnostics = diagnostics,
+            LspEvent::Hover { text, .. } => {
+                let mut lines: Vec<String> = self
+                    .diagnostics
+                    .iter()
+                    .filter(|d| (d.start.line..=d.end.line).contains(&self.cursor_line))
+                    .map(|d| format!("{}: {}", d.severity.name(), d.message))
+                    .collect();
+                if !text.is_empty() {
+                    if !lines.is_empty() {
+                        lines.push(String::new());
+                    }
+                    lines.extend(text.lines().map(str::to_string));
+                }
+                if lines.is_empty() {
+                    self.set_message("No hover information", 30);
+                } else {
+                    self.lsp_popup = Some(LspPopup::Hover(lines));
+                }
+            }
+            LspEvent::Definition { location, .. } => match location {
+                Some(location) if location.path == self.file_path => {
+                    self.goto_position(location.position)
+                }
+                Some(_) => {}
+                None => self.set_message("No definition found", 30),
+            },
+            LspEvent::Completion { items, .. } => {
+                if items.is_empty() {
+                    self.set_message("No completions", 30);
+                } else {
+                    self.lsp_popup = Some(LspPopup::Completion { items, selected: 0 });
+                }
+            }
+            LspEvent::Formatting { edits, .. } => self.apply_formatting(&edits),
+            LspEvent::Message(text) => self.set_message(text, 60),
+            LspEvent::Exited => {}
+        }
+    }
+
+    /// Move the cursor to a position given in UTF-16 columns
+    pub fn goto_position(&mut self, position: Position) {
+        self.cursor_line = position.line.min(self.lines.len() - 1);
+        self.cursor_col = lsp::char_col(&self.lines[self.cursor_line], position.character);
+        self.selection = None;
+        self.cursors.clear();
+        self.block = None;
+        self.update_scroll();
+    }
+
+    /// Most severe diagnostic starting on `line`
+    pub fn line_severity(&self, line: usize) -> Option<Severity> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.start.line == line)
+            .map(|d| d.severity)
+            .min()
+    }
+
+    /// Go to the next diagnostic after the cursor line, wrapping around, and
+    /// show its message
+    pub fn next_diagnostic(&mut self) {
+        let next = self
+            .diagnostics
+            .iter()
+            .filter(|d| d.start.line > self.cursor_line)
+            .min_by_key(|d| d.start)
+            .or_else(|| self.diagnostics.iter().min_by_key(|d| d.start))
+            .cloned();
+        let Some(diagnostic) = next else {
+            self.set_message("No diagnostics", 30);
+            return;
+        };
+        self.goto_position(diagnostic.start);
+        let first_line = diagnostic.message.lines().next().unwrap_or_default();
+        self.set_message(
+            format!("{}: {}", diagnostic.severity.name(), first_line),
+            100,
+        );
+    }
+
+    /// Start of the word ending at the cursor
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.lines[self.cursor_line].chars().collect();
+        let mut col = self.cursor_col.min(chars.len());
+        while col > 0 && Self::is_word_char(chars[col - 1]) {
+            col -= 1;
+        }
+        col
+    }
+
+    /// Completions matching the word typed before the cursor
+    pub fn completion_candidates<'a>(
+        &self,
+        items: &'a [CompletionItem],
+    ) -> Vec<&'a CompletionItem> {
+        let prefix: String = self.lines[self.cursor_line]
+            .chars()
+            .skip(self.word_start())
+            .take(self.cursor_col - self.word_start())
+            .collect::<String>()
+            .to_lowercase();
+        items
+            .iter()
+            .filter(|item| item.filter_text.to_lowercase().starts_with(&prefix))
+            .collect()
+    }
+
+    /// Replace the word typed before the cursor with a completion, as one
+    /// undo step
+    pub fn accept_completion(&mut self, item: &CompletionItem) {
+        self.cursors.clear();
+        self.block = None;
+        self.selection = None;
+        let line = &self.lines[self.cursor_line];
+        let (start, text) = match item.edit {
+            Some(TextEdit {
+                start,
+                ref new_text,
+                ..
+            }) if start.line == self.cursor_line => (
+                lsp::char_col(line, start.character).min(self.cursor_col),
+                new_text.as_str(),
+            ),
+            _ => (self.word_start(), item.insert_text.as_str()),
+        };
+        let text = text.to_string();
+
+        self.undo_as_one_step(|s| {
+            if start < s.cursor_col {
+                let old_content = s.lines[s.cursor_line].clone();
+                let new_content: String = old_content
+                    .chars()
+                    .take(start)
+                    .chain(old_content.chars().skip(s.cursor_col))
+                    .collect();
+                s.lines[s.cursor_line] = new_content.clone();
+                s.push_undo(EditAction::Replace {
+                    line: s.cursor_line,
+                    old_content,
+                    new_content,
+                });
+                s.cursor_col = start;
+            }
+            s.insert_str(&text);
+        });
+    }
+
+    /// Apply a formatting response to the whole text as one undo step
+    fn apply_formatting(&mut self, edits: &[TextEdit]) {
+        let text = self.document_text();
+        let formatted = lsp::apply_text_edits(&text, edits);
+        if formatted == text {
+            self.set_message("Already formatted", 30);
+            return;
+        }
+        let (lines, final_newline) = LineEnding::split(&formatted);
+        self.replace_lines(lines, final_newline);
+        self.set_message("Formatted", 30);
+    }
+
+    /// Replace the text with `new_lines` ending in a newline or not, touching
+    /// only the lines that differ
+    fn replace_lines(&mut self, new_lines: Vec<String>, final_newline: bool) {
+        let common = self.lines.len().min(new_lines.len());
+        let mut actions = Vec::new();
+        if self.final_newline != final_newline {
+            actions.push(EditAction::SetFinalNewline {
+                old: self.final_newline,
+                new: final_newline,
+            });
+        }
+        for (i, new_content) in new_lines.iter().enumerate().take(common) {
+            if self.lines[i] != *new_content {
+                actions.push(EditAction::Replace {
+                    line: i,
+                    old_content: self.lines[i].clone(),
+                    new_content: new_content.clone(),
+                });
+            }
+        }
+        for i in (common..self.lines.len()).rev() {
+            actions.push(EditAction::DeleteLine {
+                line: i,
+                content: self.lines[i].clone(),
+            });
+        }
+        for (i, content) in new_lines.iter().enumerate().skip(common) {
+            actions.push(EditAction::InsertLine {
+                line: i,
+                content: content.clone(),
+            });
+        }
+        if actions.is_empty() {
+            self.update_modified();
+            return;
+        }
+
+        let action = EditAction::Batch { actions };
+        self.apply_action(&action, false);
+        self.push_undo(action);
+        self.update_modified();
+        self.cursors.clear();
+        self.block = None;
+        self.selection = None;
+        self.cursor_line = self.cursor_line.min(self.lines.len() - 1);
+        self.cursor_col = self
+            .cursor_col
+            .min(self.lines[self.cursor_line].chars().count());
+        self.update_scroll();
+    }
+
+    fn format(&self) -> (TextEncoding, LineEnding, bool, bool) {
+        (
+            self.encoding,
+            self.line_ending,
+            self.final_newline,
+            self.mixed_endings.is_some(),
+        )
     }
 
     /// 현재 상태와 원본을 비교하여 modified 플래그 업데이트
     pub fn update_modified(&mut self) {
-        self.modified = self.lines != self.original_lines;
+        self.modified = self.lines != self.original_lines || self.format() != self.saved_format;
+    }
+
+    /// Switch every line ending to the next kind on save. Mixed endings are
+    /// first made all the most common one.
+    pub fn convert_line_ending(&mut self) {
+        if self.mixed_endings.take().is_none() {
+            self.line_ending = self.line_ending.next();
+        }
+        self.update_modified();
+        self.set_message(
+            format!(
+                "Line endings: {} (applied on save)",
+                self.line_ending.name()
+            ),
+            50,
+        );
+    }
+
+    /// Add or remove the line ending after the last line
+    pub fn toggle_final_newline(&mut self) {
+        self.final_newline = !self.final_newline;
+        self.edit_version += 1;
+        self.update_modified();
+        let msg = if self.final_newline {
+            "Final newline will be added on save"
+        } else {
+            "Final newline will be removed on save"
+        };
+        self.set_message(msg, 50);
+    }
+
+    /// Switch the encoding the file is saved in to the next one
+    pub fn convert_encoding(&mut self) {
+        self.encoding = self.encoding.next();
+        self.update_modified();
+        let msg = match self.encoding.encode(&self.lines.join("\n")) {
+            Ok(_) => format!("Encoding: {} (applied on save)", self.encoding.name()),
+            Err(e) => format!("Encoding: {}, but {}", self.encoding.name(), e),
+        };
+        self.set_message(msg, 50);
     }
 
     /// Undo 액션 추가 (with memory limit enforcement)
@@ -523,6 +1228,14 @@ impl EditorState {
         self.redo_stack.clear();
         self.redo_memory_usage = 0;
 
+        if let Some(ref mut group) = self.undo_group {
+            group.push(action);
+            self.modified = true;
+            self.swap_pending = true;
+            self.edit_version += 1;
+            return;
+        }
+
         let action_size = Self::estimate_action_size(&action);
 
         // Enforce memory limit by removing oldest actions
@@ -548,10 +1261,14 @@ impl EditorState {
         self.undo_memory_usage += action_size;
         self.undo_stack.push_back(action);
         self.modified = true;
+        self.swap_pending = true;
+        self.edit_version += 1;
     }
 
     /// Undo 실행
     pub fn undo(&mut self) {
+        self.cursors.clear();
+        self.block = None;
         if let Some(action) = self.undo_stack.pop_back() {
             let action_size = Self::estimate_action_size(&action);
             self.undo_memory_usage = self.undo_memory_usage.saturating_sub(action_size);
@@ -562,11 +1279,15 @@ impl EditorState {
             self.redo_memory_usage += action_size;
             self.redo_stack.push_back(action);
             self.update_modified();
+            self.swap_pending = true;
+            self.edit_version += 1;
         }
     }
 
     /// Redo 실행
     pub fn redo(&mut self) {
+        self.cursors.clear();
+        self.block = None;
         if let Some(action) = self.redo_stack.pop_back() {
             let action_size = Self::estimate_action_size(&action);
             self.redo_memory_usage = self.redo_memory_usage.saturating_sub(action_size);
@@ -576,6 +1297,8 @@ impl EditorState {
             self.undo_memory_usage += action_size;
             self.undo_stack.push_back(action);
             self.update_modified();
+            self.swap_pending = true;
+            self.edit_version += 1;
         }
     }
 
@@ -621,6 +1344,10 @@ impl EditorState {
                 line1: *line1,
                 line2: *line2,
             },
+            EditAction::SetFinalNewline { old, new } => EditAction::SetFinalNewline {
+                old: *new,
+                new: *old,
+            },
             EditAction::Batch { actions } => EditAction::Batch {
                 actions: actions
                     .iter()
@@ -696,6 +1423,9 @@ impl EditorState {
                     self.lines.swap(*line1, *line2);
                 }
             }
+            EditAction::SetFinalNewline { new, .. } => {
+                self.final_newline = *new;
+            }
             EditAction::Batch { actions } => {
                 for a in actions {
                     self.apply_action(a, false);
@@ -704,8 +1434,12 @@ impl EditorState {
         }
     }
 
-    /// 문자 삽입
+    /// 문자 삽입 (at every cursor)
     pub fn insert_char(&mut self, c: char) {
+        self.for_each_cursor(|s| s.insert_char_at_cursor(c));
+    }
+
+    fn insert_char_at_cursor(&mut self, c: char) {
         self.delete_selection();
 
         let action = EditAction::Insert {
@@ -724,8 +1458,12 @@ impl EditorState {
         self.update_scroll();
     }
 
-    /// 문자열 삽입 (단일 Undo 액션으로 처리)
+    /// 문자열 삽입 (단일 Undo 액션으로 처리, at every cursor)
     pub fn insert_str(&mut self, s: &str) {
+        self.for_each_cursor(|state| state.insert_str_at_cursor(s));
+    }
+
+    fn insert_str_at_cursor(&mut self, s: &str) {
         if s.is_empty() {
             return;
         }
@@ -818,8 +1556,12 @@ impl EditorState {
         self.insert_str(&indent);
     }
 
-    /// 새 줄 삽입
+    /// 새 줄 삽입 (at every cursor)
     pub fn insert_newline(&mut self) {
+        self.for_each_cursor(Self::insert_newline_at_cursor);
+    }
+
+    fn insert_newline_at_cursor(&mut self) {
         self.delete_selection();
 
         let line = &self.lines[self.cursor_line];
@@ -850,8 +1592,12 @@ impl EditorState {
         self.update_scroll();
     }
 
-    /// 뒤로 삭제 (Backspace)
+    /// 뒤로 삭제 (Backspace, at every cursor)
     pub fn delete_backward(&mut self) {
+        self.for_each_cursor(Self::delete_backward_at_cursor);
+    }
+
+    fn delete_backward_at_cursor(&mut self) {
         if self.selection.is_some() {
             self.delete_selection();
             return;
@@ -888,8 +1634,12 @@ impl EditorState {
         self.update_scroll();
     }
 
-    /// 앞으로 삭제 (Delete)
+    /// 앞으로 삭제 (Delete, at every cursor)
     pub fn delete_forward(&mut self) {
+        self.for_each_cursor(Self::delete_forward_at_cursor);
+    }
+
+    fn delete_forward_at_cursor(&mut self) {
         if self.selection.is_some() {
             self.delete_selection();
             return;
@@ -1181,6 +1931,10 @@ impl EditorState {
             sel.end_col = (self.cursor_col + 1).min(line_len);
         }
 
+        if !self.cursors.is_empty() {
+            self.move_secondary_cursors(line_delta, col_delta, extend_selection);
+        }
+
         self.update_scroll();
         self.find_matching_bracket();
     }
@@ -1219,6 +1973,10 @@ impl EditorState {
             let line_len = self.lines[self.cursor_line].chars().count();
             sel.end_col = (self.cursor_col + 1).min(line_len);
         }
+
+        if !self.cursors.is_empty() {
+            self.move_secondary_cursors_to_edge(false, extend_selection);
+        }
     }
 
     /// 줄 끝으로
@@ -1236,6 +1994,10 @@ impl EditorState {
         if let Some(ref mut sel) = self.selection {
             sel.end_col = self.cursor_col;
         }
+
+        if !self.cursors.is_empty() {
+            self.move_secondary_cursors_to_edge(true, extend_selection);
+        }
     }
 
     /// Word wrap 모드에서 논리적 줄이 차지하는 시각적 행 수 계산
@@ -1702,8 +2464,12 @@ impl EditorState {
         self.update_scroll();
     }
 
-    /// 단어 삭제 (뒤, Ctrl+Backspace)
+    /// 단어 삭제 (뒤, Ctrl+Backspace, at every cursor)
     pub fn delete_word_backward(&mut self) {
+        self.for_each_cursor(Self::delete_word_backward_at_cursor);
+    }
+
+    fn delete_word_backward_at_cursor(&mut self) {
         if self.selection.is_some() {
             self.delete_selection();
             return;
@@ -1749,8 +2515,12 @@ impl EditorState {
         self.update_scroll();
     }
 
-    /// 단어 삭제 (앞, Ctrl+Delete)
+    /// 단어 삭제 (앞, Ctrl+Delete, at every cursor)
     pub fn delete_word_forward(&mut self) {
+        self.for_each_cursor(Self::delete_word_forward_at_cursor);
+    }
+
+    fn delete_word_forward_at_cursor(&mut self) {
         if self.selection.is_some() {
             self.delete_selection();
             return;
@@ -1891,8 +2661,12 @@ impl EditorState {
                 if is_word_start && is_word_end {
                     // 현재 선택 위치를 다중 커서에 추가
                     if let Some(sel) = &self.selection {
-                        let (_, _, el, ec) = sel.normalized();
-                        self.cursors.push((el, ec));
+                        let (_, sc, el, ec) = sel.normalized();
+                        self.cursors.push(Cursor {
+                            line: el,
+                            col: ec,
+                            anchor: Some(sc),
+                        });
                     }
 
                     // 새 위치로 선택 이동
@@ -1935,8 +2709,12 @@ impl EditorState {
 
                 if is_word_start && is_word_end {
                     if let Some(sel) = &self.selection {
-                        let (_, _, el, ec) = sel.normalized();
-                        self.cursors.push((el, ec));
+                        let (_, sc, el, ec) = sel.normalized();
+                        self.cursors.push(Cursor {
+                            line: el,
+                            col: ec,
+                            anchor: Some(sc),
+                        });
                     }
 
                     self.cursor_line = line_idx;
@@ -1954,16 +2732,387 @@ impl EditorState {
         }
     }
 
-    /// 현재 줄 선택 (Ctrl+L)
-    pub fn select_line(&mut self) {
-        let line_len = self.lines[self.cursor_line].chars().count();
-        self.selection = Some(Selection {
-            start_line: self.cursor_line,
-            start_col: 0,
-            end_line: self.cursor_line,
-            end_col: line_len,
-        });
-        self.cursor_col = line_len;
+    /// Current view of the buffer
+    pub fn view(&self) -> EditorView {
+        EditorView {
+            cursor_line: self.cursor_line,
+            cursor_col: self.cursor_col,
+            scroll: self.scroll,
+            horizontal_scroll: self.horizontal_scroll,
+            selection: self.selection,
+            cursors: self.cursors.clone(),
+            block: self.block,
+        }
+    }
+
+    /// Show `view`, clamped to the current text which may have been edited
+    /// through another view since
+    pub fn set_view(&mut self, view: EditorView) {
+        let last_line = self.lines.len().saturating_sub(1);
+        let line_len = |lines: &[String], line: usize| lines[line].chars().count();
+        self.cursor_line = view.cursor_line.min(last_line);
+        self.cursor_col = view.cursor_col.min(line_len(&self.lines, self.cursor_line));
+        self.scroll = view.scroll.min(last_line);
+        self.horizontal_scroll = view.horizontal_scroll;
+        self.selection = view
+            .selection
+            .filter(|s| s.start_line <= last_line && s.end_line <= last_line);
+        self.cursors = view
+            .cursors
+            .into_iter()
+            .filter(|c| c.line <= last_line)
+            .map(|c| {
+                let len = line_len(&self.lines, c.line);
+                Cursor {
+                    line: c.line,
+                    col: c.col.min(len),
+                    anchor: c.anchor.map(|a| a.min(len)),
+                }
+            })
+            .collect();
+        self.block = view
+            .block
+            .filter(|b| b.anchor_line <= last_line && b.head_line <= last_line);
+    }
+
+    /// Exchange the current view with `view`
+    pub fn swap_view(&mut self, view: &mut EditorView) {
+        let current = self.view();
+        self.set_view(std::mem::take(view));
+        *view = current;
+    }
+
+    /// Run an edit at the primary cursor and at every extra cursor, recorded as
+    /// a single undo step
+    fn for_each_cursor(&mut self, mut op: impl FnMut(&mut Self)) {
+        if self.cursors.is_empty() {
+            op(self);
+            return;
+        }
+
+        // (0 = primary, line, col, selection)
+        let mut carets = vec![(0, self.cursor_line, self.cursor_col, self.selection.take())];
+        for (i, c) in self.cursors.drain(..).enumerate() {
+            carets.push((i + 1, c.line, c.col, c.selection()));
+        }
+        carets.sort_by_key(|&(_, line, col, sel)| match sel {
+            Some(s) => {
+                let (sl, sc, _, _) = s.normalized();
+                (sl, sc)
+            }
+            None => (line, col),
+        });
+
+        // Edit from the bottom up so positions not yet visited stay valid. Each
+        // result is kept relative to the end of the file and of its line, which
+        // edits at earlier carets don't move.
+        let mut results = Vec::with_capacity(carets.len());
+        self.undo_as_one_step(|s| {
+            for (idx, line, col, sel) in carets.into_iter().rev() {
+                s.cursor_line = line.min(s.lines.len() - 1);
+                s.cursor_col = col.min(s.lines[s.cursor_line].chars().count());
+                s.selection = sel;
+                op(s);
+                s.selection = None;
+                let line_len = s.lines[s.cursor_line].chars().count();
+                results.push((
+                    idx,
+                    s.lines.len() - 1 - s.cursor_line,
+                    line_len.saturating_sub(s.cursor_col),
+                ));
+            }
+        });
+
+        for (idx, from_end_line, from_end_col) in results {
+            let line = (self.lines.len() - 1).saturating_sub(from_end_line);
+            let col = self.lines[line]
+                .chars()
+                .count()
+                .saturating_sub(from_end_col);
+            if idx == 0 {
+                self.cursor_line = line;
+                self.cursor_col = col;
+            } else {
+                self.cursors.push(Cursor {
+                    line,
+                    col,
+                    anchor: None,
+                });
+            }
+        }
+        self.merge_cursors();
+        self.update_scroll();
+    }
+
+    /// Run `op`, recording the undo actions it pushes as a single undo step
+    fn undo_as_one_step(&mut self, op: impl FnOnce(&mut Self)) {
+        let outer = self.undo_group.replace(Vec::new());
+        op(self);
+        let mut actions = std::mem::replace(&mut self.undo_group, outer).unwrap_or_default();
+        match actions.len() {
+            0 => {}
+            1 => self.push_undo(actions.remove(0)),
+            _ => self.push_undo(EditAction::Batch { actions }),
+        }
+    }
+
+    /// Sort extra cursors and drop the ones that landed on another cursor
+    fn merge_cursors(&mut self) {
+        let primary = (self.cursor_line, self.cursor_col);
+        self.cursors.sort_by_key(|c| (c.line, c.col));
+        self.cursors.dedup_by_key(|c| (c.line, c.col));
+        self.cursors.retain(|c| (c.line, c.col) != primary);
+    }
+
+    /// Lines holding the primary or an extra cursor, in order
+    fn cursor_lines(&self) -> Vec<usize> {
+        let mut lines: Vec<usize> = std::iter::once(self.cursor_line)
+            .chain(self.cursors.iter().map(|c| c.line))
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// Rewrite every line holding a cursor as one undo step, keeping the
+    /// cursors on their text
+    fn rewrite_cursor_lines(&mut self, rewrite: impl Fn(&str) -> String) {
+        let mut actions = Vec::new();
+        for line_idx in self.cursor_lines() {
+            let old_content = self.lines[line_idx].clone();
+            let new_content = rewrite(&old_content);
+            let new_len = new_content.chars().count();
+            let delta = new_len as isize - old_content.chars().count() as isize;
+            let shift = |col: usize| (col as isize + delta).clamp(0, new_len as isize) as usize;
+
+            if self.cursor_line == line_idx {
+                self.cursor_col = shift(self.cursor_col);
+            }
+            for c in self.cursors.iter_mut().filter(|c| c.line == line_idx) {
+                c.col = shift(c.col);
+                c.anchor = c.anchor.map(shift);
+            }
+
+            self.lines[line_idx] = new_content.clone();
+            actions.push(EditAction::Replace {
+                line: line_idx,
+                old_content,
+                new_content,
+            });
+        }
+        self.selection = None;
+        self.push_undo(EditAction::Batch { actions });
+    }
+
+    /// Move extra cursors along with the primary one
+    fn move_secondary_cursors(&mut self, line_delta: i32, col_delta: i32, extend: bool) {
+        let last_line = self.lines.len().saturating_sub(1) as i32;
+        for c in self.cursors.iter_mut() {
+            c.anchor = if extend {
+                Some(c.anchor.unwrap_or(c.col))
+            } else {
+                None
+            };
+
+            let new_line = (c.line as i32 + line_delta).clamp(0, last_line) as usize;
+            if new_line != c.line {
+                // Selections of extra cursors stay on one line
+                c.line = new_line;
+                c.col = c.col.min(self.lines[c.line].chars().count());
+                c.anchor = None;
+            }
+
+            if col_delta != 0 {
+                let line_len = self.lines[c.line].chars().count();
+                let new_col = (c.col as i32 + col_delta).max(0) as usize;
+                if new_col > line_len && col_delta > 0 && c.line + 1 < self.lines.len() {
+                    c.line += 1;
+                    c.col = 0;
+                    c.anchor = None;
+                } else if c.col == 0 && col_delta < 0 && c.line > 0 {
+                    c.line -= 1;
+                    c.col = self.lines[c.line].chars().count();
+                    c.anchor = None;
+                } else {
+                    c.col = new_col.min(line_len);
+                }
+            }
+        }
+        self.merge_cursors();
+    }
+
+    /// Move extra cursors to the start (first non-blank) or end of their line
+    fn move_secondary_cursors_to_edge(&mut self, end: bool, extend: bool) {
+        for c in self.cursors.iter_mut() {
+            c.anchor = if extend {
+                Some(c.anchor.unwrap_or(c.col))
+            } else {
+                None
+            };
+            let line = &self.lines[c.line];
+            c.col = if end {
+                line.chars().count()
+            } else if extend {
+                0
+            } else {
+                let first_non_ws = line.chars().position(|ch| !ch.is_whitespace()).unwrap_or(0);
+                if c.col == 0 {
+                    first_non_ws
+                } else if c.col == first_non_ws {
+                    0
+                } else {
+                    first_non_ws
+                }
+            };
+        }
+        self.merge_cursors();
+    }
+
+    /// Add a cursor on the line above the topmost or below the bottommost
+    /// cursor, at the primary cursor's column (Ctrl+Alt+Up/Down)
+    pub fn add_cursor_vertical(&mut self, down: bool) {
+        self.block = None;
+        let lines = self.cursor_lines();
+        let target = if down {
+            lines.last().map(|l| l + 1)
+        } else {
+            lines.first().and_then(|l| l.checked_sub(1))
+        };
+        let Some(target) = target.filter(|&l| l < self.lines.len()) else {
+            return;
+        };
+
+        if self.selection.is_some_and(|s| s.start_line != s.end_line) {
+            self.selection = None;
+        }
+        let col = self.visual_to_char(&self.lines[target], self.cursor_visual_col());
+        self.cursors.push(Cursor {
+            line: target,
+            col,
+            anchor: None,
+        });
+        self.merge_cursors();
+        self.set_message(format!("{} cursors", self.cursors.len() + 1), 30);
+    }
+
+    /// Select every whole-word occurrence of the selected word, or of the
+    /// word at the cursor (Ctrl+Shift+L)
+    pub fn select_all_occurrences(&mut self) {
+        self.block = None;
+        if self.selection.is_none() || self.last_word_selection.is_none() {
+            self.select_word_at_cursor();
+        }
+        let word = match &self.last_word_selection {
+            Some(w) if !w.is_empty() => w.clone(),
+            _ => return,
+        };
+        let current = self.selection.map(|s| s.normalized());
+        let word_len = word.chars().count();
+
+        self.cursors.clear();
+        for (line_idx, line) in self.lines.iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            for (byte_pos, _) in line.match_indices(&word) {
+                let start = line[..byte_pos].chars().count();
+                let end = start + word_len;
+                let is_word_start = start == 0 || !Self::is_word_char(chars[start - 1]);
+                let is_word_end = end >= chars.len() || !Self::is_word_char(chars[end]);
+                if !is_word_start
+                    || !is_word_end
+                    || current == Some((line_idx, start, line_idx, end))
+                {
+                    continue;
+                }
+                self.cursors.push(Cursor {
+                    line: line_idx,
+                    col: end,
+                    anchor: Some(start),
+                });
+            }
+        }
+        self.merge_cursors();
+        self.set_message(format!("{} occurrences", self.cursors.len() + 1), 30);
+    }
+
+    /// Grow the block selection by lines and visual columns, starting one at
+    /// the cursor (Alt+Shift+arrows)
+    pub fn extend_block(&mut self, line_delta: i32, col_delta: i32) {
+        let mut block = self.block.unwrap_or_else(|| {
+            let col = self.cursor_visual_col();
+            BlockSelection {
+                anchor_line: self.cursor_line,
+                anchor_col: col,
+                head_line: self.cursor_line,
+                head_col: col,
+            }
+        });
+        let last_line = self.lines.len().saturating_sub(1) as i64;
+        block.head_line = (block.head_line as i64 + line_delta as i64).clamp(0, last_line) as usize;
+        block.head_col = (block.head_col as i64 + col_delta as i64).max(0) as usize;
+        self.set_block(block);
+    }
+
+    /// Put a cursor on every line of a block selection, selecting the block's
+    /// columns. Lines ending left of a block with width are skipped.
+    pub fn set_block(&mut self, block: BlockSelection) {
+        let top = block.anchor_line.min(block.head_line);
+        let bottom = block.anchor_line.max(block.head_line);
+        let left = block.anchor_col.min(block.head_col);
+        let right = block.anchor_col.max(block.head_col);
+        let head_right = block.head_col >= block.anchor_col;
+
+        self.block = Some(block);
+        self.cursors.clear();
+        self.selection = None;
+        self.cursor_line = block.head_line;
+        self.cursor_col = self.visual_to_char(&self.lines[block.head_line], block.head_col);
+
+        for line_idx in top..=bottom {
+            let line = &self.lines[line_idx];
+            let width = self.char_to_visual(line, line.chars().count());
+            if left < right && width <= left {
+                continue;
+            }
+            let start = self.visual_to_char(line, left);
+            let end = self.visual_to_char(line, right);
+            let (col, anchor) = if head_right {
+                (end, start)
+            } else {
+                (start, end)
+            };
+
+            if line_idx == block.head_line {
+                self.cursor_col = col;
+                if start != end {
+                    self.selection = Some(Selection {
+                        start_line: line_idx,
+                        start_col: start,
+                        end_line: line_idx,
+                        end_col: end,
+                    });
+                }
+            } else {
+                self.cursors.push(Cursor {
+                    line: line_idx,
+                    col,
+                    anchor: (anchor != col).then_some(anchor),
+                });
+            }
+        }
+        self.merge_cursors();
+        self.update_scroll();
+    }
+
+    /// 현재 줄 선택 (Ctrl+L)
+    pub fn select_line(&mut self) {
+        let line_len = self.lines[self.cursor_line].chars().count();
+        self.selection = Some(Selection {
+            start_line: self.cursor_line,
+            start_col: 0,
+            end_line: self.cursor_line,
+            end_col: line_len,
+        });
+        self.cursor_col = line_len;
     }
 
     /// 아래에 빈 줄 삽입 (Ctrl+Enter)
@@ -2085,6 +3234,11 @@ impl EditorState {
             " ".repeat(self.tab_size)
         };
 
+        if !self.cursors.is_empty() {
+            self.rewrite_cursor_lines(|line| format!("{}{}", indent_str, line));
+            return;
+        }
+
         if let Some(sel) = &self.selection {
             let (start_line, _, end_line, _) = sel.normalized();
             let mut actions = Vec::new();
@@ -2132,6 +3286,11 @@ impl EditorState {
             }
         };
 
+        if !self.cursors.is_empty() {
+            self.rewrite_cursor_lines(|line| remove_indent(line, tab_size).0);
+            return;
+        }
+
         if let Some(sel) = &self.selection {
             let (start_line, _, end_line, _) = sel.normalized();
             let mut actions = Vec::new();
@@ -2188,6 +3347,18 @@ impl EditorState {
             Language::Php => Some("//"),
 
             Language::Markdown | Language::Json | Language::Plain => Some("//"),
+
+            Language::Dockerfile
+            | Language::Makefile
+            | Language::Nix
+            | Language::Terraform
+            | Language::Lua
+            | Language::Zig
+            | Language::Elixir
+            | Language::User(_) => self
+                .language
+                .grammar()
+                .and_then(|g| g.line_comment.as_deref()),
         }
     }
 
@@ -2199,6 +3370,23 @@ impl EditorState {
         };
         let comment_with_space = format!("{} ", comment);
 
+        if !self.cursors.is_empty() {
+            let all_commented = self
+                .cursor_lines()
+                .iter()
+                .all(|&i| self.lines[i].starts_with(comment));
+            self.rewrite_cursor_lines(|line| {
+                if !all_commented {
+                    format!("{}{}", comment_with_space, line)
+                } else if let Some(rest) = line.strip_prefix(&comment_with_space) {
+                    rest.to_string()
+                } else {
+                    line[comment.len()..].to_string()
+                }
+            });
+            return;
+        }
+
         if let Some(sel) = &self.selection {
             let (start_line, _, mut end_line, end_col) = sel.normalized();
             // 블록 커서: end_col = cursor + 1이므로 end_col <= 1이면 cursor가 라인 시작(col 0)
@@ -2316,7 +3504,20 @@ pub fn draw(
         },
         Span::styled(format!("{} ", file_name), theme.header_style()),
         remote_span,
-        Span::styled(format!("[{}] ", state.language.name()), theme.dim_style()),
+        Span::styled(
+            format!(
+                "[{}] {} {}{} ",
+                state.language.name(),
+                state.encoding.name(),
+                if state.mixed_endings.is_some() {
+                    "Mixed"
+                } else {
+                    state.line_ending.name()
+                },
+                if state.final_newline { "" } else { " noeol" }
+            ),
+            theme.dim_style(),
+        ),
         Span::styled(
             format!(
                 "Ln {}, Col {} ",
@@ -2325,6 +3526,14 @@ pub fn draw(
             ),
             theme.dim_style(),
         ),
+        if !state.cursors.is_empty() {
+            Span::styled(
+                format!("{} cursors ", state.cursors.len() + 1),
+                theme.header_style(),
+            )
+        } else {
+            Span::raw("")
+        },
         if !state.undo_stack.is_empty() {
             Span::styled(
                 format!("Undo:{} ", state.undo_stack.len()),
@@ -2333,6 +3542,8 @@ pub fn draw(
         } else {
             Span::raw("")
         },
+        diagnostic_count_span(state, Severity::Error, "E", theme.error_style()),
+        diagnostic_count_span(state, Severity::Warning, "W", theme.warning_style()),
     ]);
     frame.render_widget(
         Paragraph::new(header).style(theme.status_bar_style()),
@@ -2354,6 +3565,12 @@ pub fn draw(
 
     // visible_width 업데이트
     state.visible_width = inner.width.saturating_sub(line_num_col_width as u16 + 1) as usize;
+    state.text_area = Rect::new(
+        inner.x + line_num_col_width as u16,
+        inner.y + 1,
+        state.visible_width as u16,
+        content_height as u16,
+    );
 
     // 선택 영역 정규화
     let selection = state.selection.as_ref().map(|s| s.normalized());
@@ -2421,17 +3638,13 @@ pub fn draw(
                     Style::default().fg(theme.editor.line_number)
                 };
 
-                let line_num_span = if is_first {
-                    Span::styled(
-                        format!("{:>width$} ", line_idx + 1, width = line_num_width),
-                        line_num_style,
-                    )
-                } else {
-                    Span::styled(
-                        format!("{:>width$} ", "", width = line_num_width),
-                        line_num_style,
-                    )
-                };
+                let gutter = gutter_spans(
+                    state,
+                    is_first.then_some(line_idx),
+                    line_num_width,
+                    line_num_style,
+                    theme,
+                );
 
                 let content_spans = render_editor_line(
                     &expanded_line,
@@ -2449,7 +3662,7 @@ pub fn draw(
                     Some(&orig_styles),
                 );
 
-                let mut spans = vec![line_num_span];
+                let mut spans = gutter.to_vec();
                 spans.extend(content_spans);
 
                 frame.render_widget(
@@ -2484,10 +3697,7 @@ pub fn draw(
                 Style::default().fg(theme.editor.line_number)
             };
 
-            let line_num_span = Span::styled(
-                format!("{:>width$} ", line_num + 1, width = line_num_width),
-                line_num_style,
-            );
+            let gutter = gutter_spans(state, Some(line_num), line_num_width, line_num_style, theme);
 
             // 라인 렌더링
             let in_find_mode = state.find_mode != FindReplaceMode::None;
@@ -2507,7 +3717,7 @@ pub fn draw(
                 None,
             );
 
-            let mut spans = vec![line_num_span];
+            let mut spans = gutter.to_vec();
             spans.extend(content_spans);
 
             frame.render_widget(
@@ -2538,6 +3748,8 @@ pub fn draw(
         frame.render_stateful_widget(scrollbar, scrollbar_area, &mut scrollbar_state);
     }
 
+    draw_lsp_popup(frame, state, theme);
+
     // Footer
     let footer_y = inner.y + inner.height - 1;
 
@@ -2746,6 +3958,30 @@ pub fn draw(
         }
     }
 
+    // Swap left by an earlier session: the footer asks what to do with it
+    if let Some(ref found) = state.recovery {
+        let footer = Rect::new(inner.x, footer_y, inner.width, 1);
+        let prompt = Line::from(vec![
+            Span::styled(
+                format!("Unsaved changes from {} found ", found.saved_at),
+                theme.header_style(),
+            ),
+            Span::styled("r", theme.header_style()),
+            Span::styled(":recover ", theme.dim_style()),
+            Span::styled("d", theme.header_style()),
+            Span::styled(":diff ", theme.dim_style()),
+            Span::styled("x", theme.header_style()),
+            Span::styled(":discard ", theme.dim_style()),
+            Span::styled("Esc", theme.header_style()),
+            Span::styled(":close", theme.dim_style()),
+        ]);
+        frame.render_widget(Clear, footer);
+        frame.render_widget(
+            Paragraph::new(prompt).style(theme.status_bar_style()),
+            footer,
+        );
+    }
+
     // 메시지 표시 (화면 상단에 오버레이)
     if let Some(ref msg) = state.message {
         let msg_width = (msg.len() + 4).min(inner.width as usize) as u16;
@@ -2817,6 +4053,16 @@ fn render_editor_line(
         None
     };
 
+    // 이 줄의 추가 커서: 선택이 없으면 caret의 visual column, 있으면 선택 범위
+    let mut extra_carets: Vec<usize> = Vec::new();
+    let mut extra_selections: Vec<(usize, usize)> = Vec::new();
+    for cursor in state.cursors.iter().filter(|c| c.line == line_num) {
+        match cursor.selection() {
+            Some(sel) => extra_selections.push((sel.start_col, sel.end_col)),
+            None => extra_carets.push(state.char_to_visual(original_line, cursor.col)),
+        }
+    }
+
     // 문법 강조: pre_computed_styles가 있으면 토큰화 건너뜀
     let orig_styles: Vec<ratatui::style::Style> = if let Some(pcs) = pre_computed_styles {
         pcs.clone()
@@ -2888,6 +4134,14 @@ fn render_editor_line(
                         .fg(theme.editor.selection_text);
                 }
             }
+            if extra_selections
+                .iter()
+                .any(|&(start, end)| orig_idx >= start && orig_idx < end)
+            {
+                style = style
+                    .bg(theme.editor.selection_bg)
+                    .fg(theme.editor.selection_text);
+            }
 
             // 검색 매치 하이라이트 (원본 인덱스 기준)
             for (idx, (ml, ms, me)) in state.match_positions.iter().enumerate() {
@@ -2917,6 +4171,9 @@ fn render_editor_line(
                     style = theme.selected_style();
                 }
             }
+            if extra_carets.contains(&char_visual_start) {
+                style = theme.selected_style();
+            }
 
             // 전각 문자가 왼쪽 경계에 걸리는 경우: 공백으로 대체
             if char_visual_start < view_start && char_width == 2 {
@@ -2947,10 +4204,21 @@ fn render_editor_line(
             spans.push(Span::styled(" ", cursor_style));
         }
     }
+    // 줄 끝의 추가 커서 (주 커서와는 merge되어 겹치지 않음)
+    let line_end_visual = state.char_to_visual(original_line, orig_chars.len());
+    if !orig_chars.is_empty()
+        && extra_carets.contains(&line_end_visual)
+        && line_end_visual >= view_start
+        && line_end_visual < view_end
+    {
+        spans.push(Span::styled(" ", theme.selected_style()));
+    }
 
     if spans.is_empty() {
         // 빈 줄에 커서 표시 (수평 스크롤이 0일 때만)
-        if is_cursor_line && state.selection.is_none() && horizontal_scroll == 0 {
+        if (is_cursor_line && state.selection.is_none() || !extra_carets.is_empty())
+            && horizontal_scroll == 0
+        {
             let cursor_style = if in_find_mode {
                 Style::default()
                     .fg(theme.editor.text)
@@ -2980,12 +4248,271 @@ pub fn handle_paste(app: &mut App, text: &str) {
     }
 }
 
+/// Handle mouse events for file editor: click places the cursor, Alt+drag
+/// makes a block selection
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent) {
+    let state = match &mut app.editor_state {
+        Some(s) => s,
+        None => return,
+    };
+    // Wrapped rows don't map to one line and column
+    if state.word_wrap || state.find_mode != FindReplaceMode::None || state.goto_mode {
+        return;
+    }
+
+    let area = state.text_area;
+    let inside = mouse.column >= area.x
+        && mouse.column < area.x + area.width
+        && mouse.row >= area.y
+        && mouse.row < area.y + area.height;
+    let line = (state.scroll + mouse.row.saturating_sub(area.y) as usize)
+        .min(state.lines.len().saturating_sub(1));
+    let col = state.horizontal_scroll + mouse.column.saturating_sub(area.x) as usize;
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) if inside => {
+            state.pending_exit = false;
+            state.selection = None;
+            state.cursors.clear();
+            state.block = None;
+            if mouse.modifiers.contains(KeyModifiers::ALT) {
+                state.set_block(BlockSelection {
+                    anchor_line: line,
+                    anchor_col: col,
+                    head_line: line,
+                    head_col: col,
+                });
+            } else {
+                state.cursor_line = line;
+                state.cursor_col = state.visual_to_char(&state.lines[line], col);
+                state.update_scroll();
+                state.find_matching_bracket();
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let Some(mut block) = state.block {
+                block.head_line = line;
+                block.head_col = col;
+                state.set_block(block);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Line number column of `line` (blank for a wrapped continuation), and a
+/// marker of its most severe diagnostic in place of the following space
+fn gutter_spans(
+    state: &EditorState,
+    line: Option<usize>,
+    width: usize,
+    style: Style,
+    theme: &Theme,
+) -> [Span<'static>; 2] {
+    let number = match line {
+        Some(line) => format!("{:>width$}", line + 1, width = width),
+        None => " ".repeat(width),
+    };
+    let marker = match line.and_then(|l| state.line_severity(l)) {
+        Some(Severity::Error) => Span::styled("●", theme.error_style()),
+        Some(Severity::Warning) => Span::styled("●", theme.warning_style()),
+        Some(_) => Span::styled("●", theme.info_style()),
+        None => Span::raw(" "),
+    };
+    [Span::styled(number, style), marker]
+}
+
+/// `E3 `-style count of the diagnostics of one severity, empty when none
+fn diagnostic_count_span(
+    state: &EditorState,
+    severity: Severity,
+    label: &str,
+    style: Style,
+) -> Span<'static> {
+    let count = state
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == severity)
+        .count();
+    if count == 0 {
+        Span::raw("")
+    } else {
+        Span::styled(format!("{}{} ", label, count), style)
+    }
+}
+
+/// Hover text or completion list next to the cursor
+fn draw_lsp_popup(frame: &mut Frame, state: &EditorState, theme: &Theme) {
+    let Some(ref popup) = state.lsp_popup else {
+        return;
+    };
+    let area = state.text_area;
+    if area.width < 10 || area.height < 3 {
+        return;
+    }
+
+    let (lines, max_rows): (Vec<Line>, usize) = match popup {
+        LspPopup::Hover(text) => (
+            text.iter()
+                .map(|l| Line::styled(l.clone(), theme.normal_style()))
+                .collect(),
+            12,
+        ),
+        LspPopup::Completion { items, selected } => {
+            const ROWS: usize = 8;
+            let candidates = state.completion_candidates(items);
+            if candidates.is_empty() {
+                return;
+            }
+            let selected = (*selected).min(candidates.len() - 1);
+            let first = selected.saturating_sub(ROWS - 1);
+            let lines = candidates
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(ROWS)
+                .map(|(i, item)| {
+                    let mut spans = vec![Span::raw(item.label.clone())];
+                    if let Some(ref detail) = item.detail {
+                        spans.push(Span::styled(format!("  {}", detail), theme.dim_style()));
+                    }
+                    if i == selected {
+                        Line::from(spans).style(theme.selected_style())
+                    } else {
+                        Line::from(spans).style(theme.normal_style())
+                    }
+                })
+                .collect();
+            (lines, ROWS)
+        }
+    };
+
+    // Screen row and column of the cursor within the text area
+    let cursor_visual = state.cursor_visual_col();
+    let (row, col) = if state.word_wrap {
+        let segments = |line: &str| {
+            let (expanded, _) = state.expand_tabs_with_mapping(line);
+            EditorState::compute_wrap_segments(&expanded, state.visible_width)
+        };
+        let above: usize = (state.scroll..state.cursor_line)
+            .map(|l| segments(&state.lines[l]).len())
+            .sum();
+        let starts = segments(&state.lines[state.cursor_line]);
+        let seg = starts
+            .iter()
+            .rposition(|&s| s <= cursor_visual)
+            .unwrap_or(0);
+        (above + seg, cursor_visual - starts[seg])
+    } else {
+        (
+            state.cursor_line.saturating_sub(state.scroll),
+            cursor_visual.saturating_sub(state.horizontal_scroll),
+        )
+    };
+    let row = (row as u16).min(area.height - 1);
+    let col = (col as u16).min(area.width - 1);
+
+    let content_width = lines.iter().map(|l| l.width()).max().unwrap_or(0);
+    let width = (content_width as u16 + 2).min(area.width);
+    let height = (lines.len().min(max_rows) as u16 + 2).min(area.height);
+    let below = area.y + row + 1;
+    let y = if below + height <= area.y + area.height {
+        below
+    } else {
+        (area.y + row).saturating_sub(height).max(area.y)
+    };
+    let x = (area.x + col).min(area.x + area.width - width);
+    let rect = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, rect);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.editor.border)),
+        ),
+        rect,
+    );
+}
+
 pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     let state = match &mut app.editor_state {
         Some(s) => s,
         None => return,
     };
 
+    // 버퍼 목록 / 파일 열기 입력
+    if app
+        .editor_buffers
+        .handle_overlay_key(state, code, modifiers, app.theme.syntax)
+    {
+        return;
+    }
+
+    // Swap left by an earlier session: recover, compare or discard it first
+    if state.recovery.is_some() && code != KeyCode::Esc {
+        match code {
+            KeyCode::Char('r') => state.recover_swap(),
+            KeyCode::Char('x') => state.discard_swap(),
+            KeyCode::Char('d') => match state.write_recovery_copy() {
+                Ok(copy) => {
+                    let path = state.file_path.clone();
+                    let name = format!(
+                        "{} (recovered)",
+                        path.file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    );
+                    app.enter_diff_file_view(
+                        path,
+                        copy,
+                        name,
+                        ReturnScreen::Screen(Screen::FileEditor),
+                    );
+                }
+                Err(e) => state.set_message(format!("Cannot compare: {}", e), 50),
+            },
+            _ => {}
+        }
+        return;
+    }
+
+    // Language server popup: the completion list takes the navigation keys,
+    // Esc closes either popup and other keys go on to the editor
+    if let Some(popup) = state.lsp_popup.take() {
+        if let LspPopup::Completion { items, selected } = popup {
+            let count = state.completion_candidates(&items).len();
+            let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+            match code {
+                KeyCode::Up | KeyCode::Down => {
+                    let selected = if code == KeyCode::Up {
+                        selected.saturating_sub(1)
+                    } else {
+                        (selected + 1).min(count.saturating_sub(1))
+                    };
+                    state.lsp_popup = Some(LspPopup::Completion { items, selected });
+                    return;
+                }
+                KeyCode::Enter | KeyCode::Tab if count > 0 => {
+                    let item = state.completion_candidates(&items)[selected.min(count - 1)].clone();
+                    state.accept_completion(&item);
+                    return;
+                }
+                KeyCode::Esc => return,
+                // Typing the word narrows the list down
+                KeyCode::Char(c) if plain && EditorState::is_word_char(c) => {
+                    state.lsp_popup = Some(LspPopup::Completion { items, selected: 0 });
+                }
+                KeyCode::Backspace if plain => {
+                    state.lsp_popup = Some(LspPopup::Completion { items, selected: 0 });
+                }
+                _ => {}
+            }
+        } else if code == KeyCode::Esc {
+            return;
+        }
+    }
+
     // Goto 모드 (텍스트 입력 모드이므로 pending_exit 리셋)
     if state.goto_mode {
         state.pending_exit = false;
@@ -3163,9 +4690,37 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     // EditorAction 조회 (Ctrl/Alt 조합 및 Esc)
     if let Some(action) = app.keybindings.editor_action(code, modifiers) {
         // Exit 이외의 action은 pending_exit 리셋
-        if action != EditorAction::Exit {
+        if !matches!(action, EditorAction::Exit | EditorAction::CloseBuffer) {
             state.pending_exit = false;
         }
+        // Actions that only know the primary cursor drop the extra ones
+        if matches!(
+            action,
+            EditorAction::Cut
+                | EditorAction::SelectAll
+                | EditorAction::DeleteLine
+                | EditorAction::DuplicateLine
+                | EditorAction::SelectLine
+                | EditorAction::InsertLineBelow
+                | EditorAction::InsertLineAbove
+                | EditorAction::MoveWordLeft
+                | EditorAction::MoveWordRight
+                | EditorAction::GoToFileStart
+                | EditorAction::GoToFileEnd
+                | EditorAction::MoveLineUp
+                | EditorAction::MoveLineDown
+        ) {
+            state.cursors.clear();
+        }
+        if !matches!(
+            action,
+            EditorAction::BlockSelectUp
+                | EditorAction::BlockSelectDown
+                | EditorAction::BlockSelectLeft
+                | EditorAction::BlockSelectRight
+        ) {
+            state.block = None;
+        }
 
         match action {
             EditorAction::Save => {
@@ -3276,6 +4831,15 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             EditorAction::Cut => {
                 state.cut_line_or_selection();
             }
+            EditorAction::ConvertEncoding => {
+                state.convert_encoding();
+            }
+            EditorAction::ConvertLineEnding => {
+                state.convert_line_ending();
+            }
+            EditorAction::ToggleFinalNewline => {
+                state.toggle_final_newline();
+            }
             EditorAction::Undo => {
                 state.undo();
             }
@@ -3306,6 +4870,27 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             EditorAction::SelectNextOccurrence => {
                 state.select_next_occurrence();
             }
+            EditorAction::SelectAllOccurrences => {
+                state.select_all_occurrences();
+            }
+            EditorAction::AddCursorAbove => {
+                state.add_cursor_vertical(false);
+            }
+            EditorAction::AddCursorBelow => {
+                state.add_cursor_vertical(true);
+            }
+            EditorAction::BlockSelectUp => {
+                state.extend_block(-1, 0);
+            }
+            EditorAction::BlockSelectDown => {
+                state.extend_block(1, 0);
+            }
+            EditorAction::BlockSelectLeft => {
+                state.extend_block(0, -1);
+            }
+            EditorAction::BlockSelectRight => {
+                state.extend_block(0, 1);
+            }
             EditorAction::SelectLine => {
                 state.select_line();
             }
@@ -3390,16 +4975,100 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             EditorAction::MoveLineDown => {
                 state.move_line_down();
             }
-            EditorAction::Exit => {
-                if state.selection.is_some() {
+            EditorAction::FileHistory => {
+                let path = state.file_path.clone();
+                if state.remote_origin.is_some() {
+                    state.set_message("Git history is not available for remote files", 30);
+                    return;
+                }
+                app.show_file_history(path);
+                // Errors go to the editor's message line
+                if app.dialog.is_none() {
+                    if let (Some(msg), Some(editor)) = (app.message.take(), &mut app.editor_state) {
+                        editor.set_message(msg, 30);
+                    }
+                }
+            }
+            EditorAction::OpenFile => {
+                state.pending_exit = false;
+                app.editor_buffers.open_input = Some(String::new());
+            }
+            EditorAction::NextBuffer => {
+                app.editor_buffers.cycle(state, true);
+            }
+            EditorAction::PrevBuffer => {
+                app.editor_buffers.cycle(state, false);
+            }
+            EditorAction::BufferList => {
+                app.editor_buffers.picker = Some(app.editor_buffers.active());
+            }
+            EditorAction::CloseBuffer if app.editor_buffers.len() > 1 => {
+                if state.modified && !state.pending_exit {
+                    state.pending_exit = true;
+                    let close_key = app.keybindings.editor_first_key(EditorAction::CloseBuffer);
+                    state.set_message(
+                        format!("Unsaved changes! Press {} again to discard", close_key),
+                        60,
+                    );
+                } else {
+                    app.editor_buffers.close_focused(state);
+                }
+            }
+            EditorAction::SplitHorizontal => {
+                app.editor_buffers
+                    .split(state, crate::ui::editor_workspace::SplitLayout::Stacked);
+            }
+            EditorAction::SplitVertical => {
+                app.editor_buffers
+                    .split(state, crate::ui::editor_workspace::SplitLayout::SideBySide);
+            }
+            EditorAction::FocusOtherSplit => {
+                app.editor_buffers.focus_other(state);
+            }
+            EditorAction::CloseSplit => {
+                app.editor_buffers.split = None;
+            }
+            EditorAction::LspHover => {
+                app.editor_buffers
+                    .lsp_request(state, LspRequest::Hover, &app.settings.lsp_servers);
+            }
+            EditorAction::LspDefinition => {
+                app.editor_buffers.lsp_request(
+                    state,
+                    LspRequest::Definition,
+                    &app.settings.lsp_servers,
+                );
+            }
+            EditorAction::LspCompletion => {
+                app.editor_buffers.lsp_request(
+                    state,
+                    LspRequest::Completion,
+                    &app.settings.lsp_servers,
+                );
+            }
+            EditorAction::LspFormat => {
+                app.editor_buffers.lsp_request(
+                    state,
+                    LspRequest::Formatting,
+                    &app.settings.lsp_servers,
+                );
+            }
+            EditorAction::NextDiagnostic => {
+                state.next_diagnostic();
+            }
+            EditorAction::Exit | EditorAction::CloseBuffer => {
+                let unsaved = app.editor_buffers.unsaved(state);
+                if state.selection.is_some() || !state.cursors.is_empty() {
                     // 선택 해제 및 다중 커서 초기화
                     state.selection = None;
                     state.cursors.clear();
                     state.last_word_selection = None;
-                } else if state.modified {
-                    // 변경사항이 있을 때
+                } else if !unsaved.is_empty() {
+                    // 변경사항이 있을 때 (모든 버퍼)
                     if state.pending_exit {
                         // 두 번째 Esc: 변경 무시하고 종료
+                        app.editor_buffers.remove_swaps(state);
+                        app.editor_buffers.close_all();
                         if let Some(Screen::FileViewer) = app.previous_screen {
                             if let Some(ref mut viewer) = app.viewer_state {
                                 viewer.scroll = state.scroll;
@@ -3412,18 +5081,26 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                     } else {
                         // 첫 번째 Esc: 경고 메시지
                         state.pending_exit = true;
-                        let exit_key = app.keybindings.editor_first_key(EditorAction::Exit);
+                        let exit_key = app.keybindings.editor_first_key(action);
                         let save_key = app.keybindings.editor_first_key(EditorAction::Save);
-                        state.set_message(
+                        let msg = if unsaved.len() == 1 && state.modified {
                             format!(
                                 "Unsaved changes! Press {} again to discard, {} to save",
                                 exit_key, save_key
-                            ),
-                            60,
-                        );
+                            )
+                        } else {
+                            format!(
+                                "Unsaved changes in {}! Press {} again to discard all",
+                                unsaved.join(", "),
+                                exit_key
+                            )
+                        };
+                        state.set_message(msg, 60);
                     }
                 } else {
                     // 변경사항 없으면 바로 종료
+                    app.editor_buffers.remove_swaps(state);
+                    app.editor_buffers.close_all();
                     if let Some(Screen::FileViewer) = app.previous_screen {
                         if let Some(ref mut viewer) = app.viewer_state {
                             let scroll = state.scroll;
@@ -3444,6 +5121,7 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
 
     // 일반 모드 (화살표, Home/End, Enter, Tab, Backspace, Delete, 문자 입력)
     state.pending_exit = false;
+    state.block = None;
     let extend_selection = modifiers.contains(KeyModifiers::SHIFT);
 
     match code {
@@ -3512,3 +5190,490 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
         _ => {}
     }
 }
+
+/// Replace the file at `path` with `bytes`
+/// Security: Preserves original file permissions and uses atomic write
+pub(crate) fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    // Resolve symlink to actual file path to avoid replacing symlink with regular file
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.is_symlink())
+        .unwrap_or(false);
+
+    let actual_path = if is_symlink {
+        fs::canonicalize(path).map_err(|e| format!("Failed to resolve symlink: {}", e))?
+    } else {
+        path.to_path_buf()
+    };
+
+    // Save original permissions before writing
+    #[cfg(unix)]
+    let original_perms = fs::metadata(&actual_path).map(|m| m.permissions()).ok();
+
+    // Use atomic write: write to temp file, then rename
+    let temp_path = actual_path.with_extension("tmp");
+
+    // Write to temporary file
+    fs::write(&temp_path, bytes).map_err(|e| format!("Failed to write temporary file: {}", e))?;
+
+    // Restore original permissions on temp file before rename
+    #[cfg(unix)]
+    if let Some(perms) = original_perms {
+        let _ = fs::set_permissions(&temp_path, perms);
+    }
+
+    // Atomic rename (on same filesystem)
+    fs::rename(&temp_path, &actual_path).map_err(|e| {
+        // Clean up temp file on failure
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to save file: {}", e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saves_in_original_or_converted_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.txt");
+        let original = TextEncoding::EucKr.encode("안녕\n세계").unwrap();
+        fs::write(&path, &original).unwrap();
+
+        let mut editor = EditorState::new();
+        editor.load_file(&path).unwrap();
+        assert_eq!(editor.encoding, TextEncoding::EucKr);
+        assert_eq!(editor.lines, ["안녕", "세계"]);
+        editor.save_file().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), original);
+
+        // Converting is an unsaved change until written
+        while editor.encoding != TextEncoding::Utf16Be {
+            editor.convert_encoding();
+        }
+        assert!(editor.modified);
+        editor.save_file().unwrap();
+        assert!(!editor.modified);
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            TextEncoding::Utf16Be.encode("안녕\n세계").unwrap()
+        );
+
+        // Text that cannot be represented is not written
+        editor.encoding = TextEncoding::Latin1;
+        assert!(editor.save_file().is_err());
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            TextEncoding::Utf16Be.encode("안녕\n세계").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_line_endings_and_final_newline_are_preserved() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\rb\r"), LineEnding::Cr);
+        assert_eq!(LineEnding::detect("single line"), LineEnding::Lf);
+        assert_eq!(
+            LineEnding::split("a\rb\r\nc\n\n"),
+            (
+                vec!["a".into(), "b".into(), "c".into(), String::new()],
+                true
+            )
+        );
+        assert_eq!(LineEnding::split(""), (vec![String::new()], false));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = EditorState::new();
+        for content in ["one\r\ntwo\r\n", "one\rtwo", "one\ntwo\n\n", "\n"] {
+            let path = dir.path().join("file.txt");
+            fs::write(&path, content).unwrap();
+            editor.load_file(&path).unwrap();
+            editor.save_file().unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        }
+
+        let path = dir.path().join("dos.txt");
+        fs::write(&path, "one\r\ntwo").unwrap();
+        editor.load_file(&path).unwrap();
+        assert_eq!(editor.line_ending, LineEnding::CrLf);
+        assert!(!editor.final_newline);
+        editor.convert_line_ending();
+        editor.convert_line_ending();
+        editor.convert_line_ending();
+        // Back where it started
+        assert!(!editor.modified);
+        editor.convert_line_ending();
+        editor.toggle_final_newline();
+        assert!(editor.modified);
+        editor.save_file().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\rtwo\r");
+    }
+
+    #[test]
+    fn test_mixed_line_endings_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.txt");
+        fs::write(&path, "a\nb\r\nc\n").unwrap();
+        let mut editor = EditorState::new();
+        editor.load_file(&path).unwrap();
+        assert!(editor.mixed_endings.is_some());
+        assert_eq!(editor.line_ending, LineEnding::Lf);
+        assert!(!editor.modified);
+        editor.save_file().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\r\nc\n");
+
+        // Unchanged lines keep their ending, a new line gets the common one
+        editor.cursor_line = 2;
+        editor.cursor_col = 1;
+        editor.insert_newline();
+        editor.insert_char('d');
+        editor.save_file().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\r\nc\nd\n");
+        assert!(editor.mixed_endings.is_some());
+
+        // Normalizing is a conversion to save
+        editor.convert_line_ending();
+        assert!(editor.mixed_endings.is_none());
+        assert!(editor.modified);
+        editor.save_file().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn test_swap_recovery_and_external_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let swap_dir = dir.path().join("swap");
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "one\ntwo\n").unwrap();
+
+        // A session that died with unsaved lines left its swap behind
+        swap::write(&swap_dir, &path, &["one".into(), "changed".into()]).unwrap();
+        let mut editor = EditorState::new();
+        editor.swap_dir = Some(swap_dir.clone());
+        editor.load_file(&path).unwrap();
+        assert!(editor.recovery.is_some());
+        // Kept while the prompt is open
+        editor.remove_swap();
+        assert!(swap::read(&swap_dir, &path).is_some());
+        let copy = editor.write_recovery_copy().unwrap();
+        assert_eq!(fs::read_to_string(copy).unwrap(), "one\nchanged\n");
+        editor.recover_swap();
+        assert_eq!(editor.lines, vec!["one", "changed"]);
+        assert!(editor.modified);
+        editor.save_file().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\nchanged\n");
+        assert!(swap::read(&swap_dir, &path).is_none());
+
+        // Discarding forgets the swap without touching the text
+        swap::write(&swap_dir, &path, &["other".into()]).unwrap();
+        editor.load_file(&path).unwrap();
+        editor.discard_swap();
+        assert!(editor.recovery.is_none());
+        assert_eq!(editor.lines, vec!["one", "changed"]);
+        assert!(swap::read(&swap_dir, &path).is_none());
+
+        // A swap holding the file's own text is stale and removed
+        swap::write(&swap_dir, &path, &["one".into(), "changed".into()]).unwrap();
+        editor.load_file(&path).unwrap();
+        assert!(editor.recovery.is_none());
+        assert!(swap::read(&swap_dir, &path).is_none());
+
+        // Someone else's write is not overwritten without a second save
+        fs::write(&path, "theirs, and longer\n").unwrap();
+        assert!(editor.changed_on_disk());
+        editor.insert_char('x');
+        assert!(editor.save_file().is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theirs, and longer\n");
+        editor.save_file().unwrap();
+        assert!(!editor.changed_on_disk());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "xone\nchanged\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_swap_of_running_instance_is_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let swap_dir = dir.path().join("swap");
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "one\n").unwrap();
+
+        // Another instance that is still running has unsaved lines
+        let mut other = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let swap_path = swap::swap_path(&swap_dir, &path);
+        swap::write(&swap_dir, &path, &["theirs".into()]).unwrap();
+        let mut found = swap::read(&swap_dir, &path).unwrap();
+        found.pid = other.id();
+        fs::write(&swap_path, serde_json::to_vec(&found).unwrap()).unwrap();
+
+        let mut editor = EditorState::new();
+        editor.swap_dir = Some(swap_dir.clone());
+        editor.load_file(&path).unwrap();
+        assert!(editor.recovery.is_none());
+        assert_eq!(editor.swap_owner, Some(other.id()));
+        assert_eq!(
+            editor.message.as_deref(),
+            Some(format!("File is being edited by pid {}", other.id()).as_str())
+        );
+
+        // Neither overwritten by our edits nor removed when saving
+        editor.insert_char('x');
+        editor.swap_written = Instant::now() - Duration::from_secs(60);
+        editor.autosave();
+        editor.save_file().unwrap();
+        assert_eq!(swap::read(&swap_dir, &path).unwrap().lines, vec!["theirs"]);
+
+        other.kill().unwrap();
+        other.wait().unwrap();
+    }
+
+    #[test]
+    fn test_lsp_completion_formatting_and_diagnostics() {
+        let at = |line, character| Position { line, character };
+        let item = |label: &str| CompletionItem {
+            label: label.into(),
+            detail: None,
+            filter_text: label.into(),
+            insert_text: label.into(),
+            edit: None,
+        };
+
+        let mut editor = editor_with(&["let value = 1;", "x = va"]);
+        editor.cursor_line = 1;
+        editor.cursor_col = 6;
+        editor.apply_lsp_event(LspEvent::Completion {
+            uri: String::new(),
+            items: vec![item("value"), item("Valid"), item("other")],
+        });
+        let Some(LspPopup::Completion { ref items, .. }) = editor.lsp_popup else {
+            panic!("no completion popup");
+        };
+        let candidates: Vec<String> = editor
+            .completion_candidates(items)
+            .iter()
+            .map(|i| i.label.clone())
+            .collect();
+        assert_eq!(candidates, vec!["value", "Valid"]);
+        editor.accept_completion(&item("value"));
+        assert_eq!(editor.lines[1], "x = value");
+        assert_eq!(editor.cursor_col, 9);
+        editor.undo();
+        assert_eq!(editor.lines[1], "x = va");
+
+        // Formatting rewrites the text as one undo step
+        editor.apply_lsp_event(LspEvent::Formatting {
+            uri: String::new(),
+            edits: vec![
+                TextEdit {
+                    start: at(1, 6),
+                    end: at(1, 6),
+                    new_text: "lue;\n\nfn main() {}".into(),
+                },
+                TextEdit {
+                    start: at(0, 0),
+                    end: at(0, 0),
+                    new_text: "// formatted\n".into(),
+                },
+            ],
+        });
+        assert_eq!(
+            editor.lines,
+            vec![
+                "// formatted",
+                "let value = 1;",
+                "x = value;",
+                "",
+                "fn main() {}"
+            ]
+        );
+        assert!(editor.modified);
+        editor.undo();
+        assert_eq!(editor.lines, vec!["let value = 1;", "x = va"]);
+        assert!(!editor.modified);
+
+        // ...including dropping the final newline
+        assert!(editor.final_newline);
+        editor.apply_lsp_event(LspEvent::Formatting {
+            uri: String::new(),
+            edits: vec![TextEdit {
+                start: at(1, 6),
+                end: at(2, 0),
+                new_text: String::new(),
+            }],
+        });
+        assert!(!editor.final_newline);
+        assert!(editor.modified);
+        editor.undo();
+        assert!(editor.final_newline);
+        assert!(!editor.modified);
+        editor.redo();
+        assert!(!editor.final_newline);
+        editor.undo();
+
+        // Diagnostics mark their lines and are visited in order
+        let diagnostic = |line, severity| Diagnostic {
+            start: at(line, 4),
+            end: at(line, 5),
+            severity,
+            message: "msg".into(),
+        };
+        editor.apply_lsp_event(LspEvent::Diagnostics {
+            uri: String::new(),
+            diagnostics: vec![
+                diagnostic(1, Severity::Warning),
+                diagnostic(0, Severity::Warning),
+                diagnostic(0, Severity::Error),
+            ],
+        });
+        assert_eq!(editor.line_severity(0), Some(Severity::Error));
+        assert_eq!(editor.line_severity(1), Some(Severity::Warning));
+        editor.next_diagnostic();
+        assert_eq!((editor.cursor_line, editor.cursor_col), (0, 4));
+        editor.next_diagnostic();
+        assert_eq!((editor.cursor_line, editor.cursor_col), (1, 4));
+        assert_eq!(editor.message.as_deref(), Some("warning: msg"));
+    }
+
+    fn editor_with(lines: &[&str]) -> EditorState {
+        let mut editor = EditorState::new();
+        editor.lines = lines.iter().map(|l| l.to_string()).collect();
+        editor.original_lines = editor.lines.clone();
+        editor
+    }
+
+    #[test]
+    fn test_multi_cursor_edits_undo_as_one_step() {
+        let mut editor = editor_with(&["abc", "de", "fghi"]);
+        editor.cursor_col = 1;
+        editor.add_cursor_vertical(true);
+        editor.add_cursor_vertical(true);
+        assert_eq!(
+            editor.cursors,
+            [
+                Cursor {
+                    line: 1,
+                    col: 1,
+                    anchor: None
+                },
+                Cursor {
+                    line: 2,
+                    col: 1,
+                    anchor: None
+                }
+            ]
+        );
+
+        editor.insert_char('X');
+        assert_eq!(editor.lines, ["aXbc", "dXe", "fXghi"]);
+        editor.insert_newline();
+        assert_eq!(editor.lines, ["aX", "bc", "dX", "e", "fX", "ghi"]);
+        assert_eq!((editor.cursor_line, editor.cursor_col), (1, 0));
+        assert_eq!(
+            editor
+                .cursors
+                .iter()
+                .map(|c| (c.line, c.col))
+                .collect::<Vec<_>>(),
+            [(3, 0), (5, 0)]
+        );
+        editor.delete_backward();
+        editor.delete_backward();
+        assert_eq!(editor.lines, ["abc", "de", "fghi"]);
+
+        // One step per keystroke, however many cursors
+        editor.undo();
+        assert_eq!(editor.lines, ["aXbc", "dXe", "fXghi"]);
+        assert!(editor.cursors.is_empty());
+        editor.undo();
+        assert_eq!(editor.lines, ["aX", "bc", "dX", "e", "fX", "ghi"]);
+        editor.undo();
+        assert_eq!(editor.lines, ["aXbc", "dXe", "fXghi"]);
+        editor.undo();
+        assert_eq!(editor.lines, ["abc", "de", "fghi"]);
+        assert!(!editor.modified);
+    }
+
+    #[test]
+    fn test_multi_cursor_edit_with_full_undo_stack() {
+        let mut editor = editor_with(&["abc", "de", "fghi"]);
+        for _ in 0..editor.max_undo_size {
+            editor.insert_char('a');
+            editor.delete_backward();
+        }
+        assert_eq!(editor.undo_stack.len(), editor.max_undo_size);
+
+        editor.cursor_col = 1;
+        editor.add_cursor_vertical(true);
+        editor.add_cursor_vertical(true);
+        editor.insert_char('X');
+        assert_eq!(editor.lines, ["aXbc", "dXe", "fXghi"]);
+        assert_eq!(editor.undo_stack.len(), editor.max_undo_size);
+        assert!(matches!(
+            editor.undo_stack.back(),
+            Some(EditAction::Batch { actions }) if actions.len() == 3
+        ));
+        editor.undo();
+        assert_eq!(editor.lines, ["abc", "de", "fghi"]);
+    }
+
+    #[test]
+    fn test_select_all_occurrences_replaces_each() {
+        let mut editor = editor_with(&["let foo = foo2;", "foo(foo);"]);
+        editor.cursor_col = 5;
+        editor.select_all_occurrences();
+        assert_eq!(editor.cursors.len(), 2);
+        editor.insert_str("bar");
+        assert_eq!(editor.lines, ["let bar = foo2;", "bar(bar);"]);
+        editor.undo();
+        assert_eq!(editor.lines, ["let foo = foo2;", "foo(foo);"]);
+    }
+
+    #[test]
+    fn test_block_selection_edits_columns() {
+        let mut editor = editor_with(&["a\tbc", "wxyz", "", "0123"]);
+        editor.tab_size = 4;
+        editor.cursor_col = 0;
+        editor.extend_block(3, 0);
+        editor.extend_block(0, 1);
+        editor.extend_block(0, 4);
+        // Columns 0..5: a tab counts as visual columns 1..4; the empty line is skipped
+        assert_eq!(editor.cursors.len(), 2);
+        editor.delete_backward();
+        assert_eq!(editor.lines, ["c", "", "", ""]);
+        editor.undo();
+        assert_eq!(editor.lines, ["a\tbc", "wxyz", "", "0123"]);
+
+        // A zero-width block inserts on every line
+        editor.block = None;
+        editor.cursor_line = 0;
+        editor.cursor_col = 0;
+        editor.extend_block(1, 0);
+        editor.insert_char('>');
+        assert_eq!(editor.lines, [">a\tbc", ">wxyz", "", "0123"]);
+    }
+
+    #[test]
+    fn test_indent_and_comment_at_every_cursor() {
+        let mut editor = editor_with(&["one", "two", "three"]);
+        editor.cursor_col = 2;
+        editor.cursors.push(Cursor {
+            line: 2,
+            col: 1,
+            anchor: None,
+        });
+        editor.toggle_comment();
+        assert_eq!(editor.lines, ["// one", "two", "// three"]);
+        assert_eq!(editor.cursor_col, 5);
+        assert_eq!(editor.cursors[0].col, 4);
+        editor.indent();
+        assert_eq!(editor.lines, ["    // one", "two", "    // three"]);
+        editor.outdent();
+        editor.toggle_comment();
+        assert_eq!(editor.lines, ["one", "two", "three"]);
+        assert_eq!((editor.cursor_col, editor.cursors[0].col), (2, 1));
+        editor.undo();
+        assert_eq!(editor.lines, ["// one", "two", "// three"]);
+    }
+}
@@ -8,14 +8,33 @@ use ratatui::{
 };
 use regex::Regex;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use unicode_width::UnicodeWidthChar;
 
 use super::{
     app::{App, Screen},
+    git_screen::{BlameLine, GitRevision},
+    hex_editor::HexEditor,
     syntax::{Language, SyntaxHighlighter},
     theme::Theme,
 };
+use crate::services::large_file::{FilterJob, LargeFile, SearchJob, MAX_SEARCH_MATCHES};
+use crate::utils::encoding::TextEncoding;
+use crate::utils::format::{pad_to_display_width, truncate_to_display_width};
+
+/// Width of the blame column: hash, author and age
+const BLAME_WIDTH: usize = 24;
+
+/// Lines (or hex rows) held in memory at once for a paged file
+const PAGE_LINES: usize = 1000;
+
+/// Bytes per row of the hex view
+const HEX_ROW_BYTES: usize = 16;
+
+/// How often a followed file is checked for appends, truncation and rotation
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
 
 /// 뷰어 모드
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,6 +51,88 @@ pub struct SearchOptions {
     pub whole_word: bool,
 }
 
+/// Where the goto prompt jumps to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GotoTarget {
+    /// 1-based line number
+    Line(usize),
+    /// Share of the file, by bytes
+    Percent(f64),
+    /// Byte offset
+    Offset(u64),
+}
+
+impl GotoTarget {
+    /// Parse `123` (line), `45%` (percentage), `@4096`/`@512M` or `0x1f00` (byte offset)
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(percent) = input.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse().ok()?;
+            return (0.0..=100.0)
+                .contains(&percent)
+                .then_some(GotoTarget::Percent(percent));
+        }
+        if let Some(hex) = input
+            .strip_prefix("0x")
+            .or_else(|| input.strip_prefix("0X"))
+        {
+            return u64::from_str_radix(hex, 16).ok().map(GotoTarget::Offset);
+        }
+        if let Some(offset) = input.strip_prefix('@') {
+            let (digits, unit) = match offset.char_indices().last() {
+                Some((i, c)) if c.is_ascii_alphabetic() => (&offset[..i], c),
+                _ => (offset, 'b'),
+            };
+            let unit: u64 = match unit.to_ascii_lowercase() {
+                'b' => 1,
+                'k' => 1024,
+                'm' => 1024 * 1024,
+                'g' => 1024 * 1024 * 1024,
+                _ => return None,
+            };
+            let value: u64 = digits.parse().ok()?;
+            return value.checked_mul(unit).map(GotoTarget::Offset);
+        }
+        input.parse().ok().map(GotoTarget::Line)
+    }
+}
+
+/// Backend of a file too large to read into memory; `ViewerState::lines`
+/// then holds one page of it
+#[derive(Debug)]
+struct PagedView {
+    file: LargeFile,
+    /// Line (or hex row) number of `lines[0]`
+    first_line: usize,
+    /// Byte offset of each line in `lines`
+    offsets: Vec<u64>,
+    search: Option<SearchJob>,
+    /// Byte offset (and lines of context above it) to jump to once the
+    /// line index has reached it
+    pending_jump: Option<(u64, usize)>,
+}
+
+/// Follow mode (tail -f): what the file looked like at the last check
+#[derive(Debug)]
+struct FollowState {
+    /// Bytes loaded so far; more are appended when the file grows
+    size: u64,
+    /// Inode of the file, which changes when a log is rotated
+    identity: u64,
+    last_check: Instant,
+}
+
+/// Live filter: only the lines matching `regex` are shown
+#[derive(Debug)]
+struct LineFilter {
+    pattern: String,
+    regex: Regex,
+    /// Matching lines with their text, in file order
+    lines: Vec<(usize, String)>,
+    /// Background scan of a paged file
+    job: Option<FilterJob>,
+}
+
 /// 뷰어 상태
 #[derive(Debug)]
 pub struct ViewerState {
@@ -65,6 +166,10 @@ pub struct ViewerState {
     pub goto_mode: bool,
     pub goto_input: String,
 
+    // Live filter prompt
+    pub filter_mode: bool,
+    pub filter_input: String,
+
     // 문법 강조
     pub language: Language,
     pub highlighter: Option<SyntaxHighlighter>,
@@ -72,6 +177,8 @@ pub struct ViewerState {
 
     // 인코딩
     pub encoding: String,
+    /// Encoding the text was decoded with
+    pub text_encoding: TextEncoding,
     pub is_binary: bool,
 
     // 파일 정보
@@ -80,6 +187,22 @@ pub struct ViewerState {
 
     // 화면 크기 (렌더링 시 업데이트)
     pub visible_height: usize,
+
+    // Git
+    /// Set when viewing a past revision from the file history
+    pub revision: Option<GitRevision>,
+    /// Blame per line while blame mode is on
+    pub blame: Option<Vec<BlameLine>>,
+    /// One-off message shown in the footer until the next key
+    pub message: Option<String>,
+
+    /// Set for files above `MAX_FILE_SIZE`, which are read in pages
+    paged: Option<PagedView>,
+    /// Set while following appends to the file
+    follow: Option<FollowState>,
+    filter: Option<LineFilter>,
+    /// Set while editing the bytes in hex mode; holds the file's bytes
+    pub hex_edit: Option<HexEditor>,
 }
 
 impl ViewerState {
@@ -106,14 +229,24 @@ impl ViewerState {
             bookmarks: HashSet::new(),
             goto_mode: false,
             goto_input: String::new(),
+            filter_mode: false,
+            filter_input: String::new(),
             language: Language::Plain,
             highlighter: None,
             syntax_colors: crate::ui::theme::Theme::default().syntax,
             encoding: "UTF-8".to_string(),
+            text_encoding: TextEncoding::Utf8,
             is_binary: false,
             file_size: 0,
             total_lines: 0,
             visible_height: 20, // 기본값, 렌더링 시 업데이트됨
+            revision: None,
+            blame: None,
+            message: None,
+            paged: None,
+            follow: None,
+            filter: None,
+            hex_edit: None,
         }
     }
 
@@ -126,7 +259,7 @@ impl ViewerState {
         }
     }
 
-    /// Maximum file size for viewing (100MB)
+    /// Larger files are paged from disk instead of read into memory (100MB)
     const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
 
     /// 파일 로드
@@ -138,53 +271,41 @@ impl ViewerState {
         self.search_term.clear();
         self.match_lines.clear();
         self.match_positions.clear();
+        self.revision = None;
+        self.blame = None;
+        self.paged = None;
+        self.follow = None;
+        self.filter = None;
 
-        // Check file size before loading to prevent memory exhaustion
+        // Large files are paged instead of read into memory
         let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
         if metadata.len() > Self::MAX_FILE_SIZE {
-            return Err(format!(
-                "File too large ({:.1} MB). Maximum size is {} MB.",
-                metadata.len() as f64 / 1024.0 / 1024.0,
-                Self::MAX_FILE_SIZE / 1024 / 1024
-            ));
+            return self.load_paged(path);
         }
 
         // 파일 읽기
         let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
         self.file_size = bytes.len() as u64;
 
-        // 바이너리 파일 감지
-        self.is_binary = self.detect_binary(&bytes);
+        // 바이너리 파일 감지 (UTF-16 text is full of NUL bytes)
+        self.is_binary = !TextEncoding::has_utf16_bom(&bytes) && self.detect_binary(&bytes);
 
         if self.is_binary {
             self.mode = ViewerMode::Hex;
-            self.lines = self.format_hex_view(&bytes);
+            self.lines = Self::format_hex_view(&bytes, 0);
             self.encoding = "Binary".to_string();
             self.raw_bytes = bytes;
         } else {
             self.mode = ViewerMode::Text;
-            // UTF-8로 시도
-            match String::from_utf8(bytes) {
-                Ok(content) => {
-                    self.encoding = "UTF-8".to_string();
-                    self.raw_bytes = content.as_bytes().to_vec();
-                    self.lines = content.lines().map(String::from).collect();
-                }
-                Err(e) => {
-                    // Latin-1 (ISO-8859-1)로 시도
-                    self.encoding = "ISO-8859-1".to_string();
-                    let bytes = e.into_bytes();
-                    let content: String = bytes.iter().map(|&b| b as char).collect();
-                    self.raw_bytes = bytes;
-                    self.lines = content.lines().map(String::from).collect();
-                }
-            }
+            self.set_text_encoding(TextEncoding::detect(&bytes));
+            self.lines = self.decode_lines(&bytes);
+            self.raw_bytes = bytes;
         }
 
         self.total_lines = self.lines.len();
 
         // 언어 감지 및 하이라이터 초기화
-        self.language = Language::from_extension(path);
+        self.language = Language::detect(path, &self.lines);
         if !self.is_binary {
             self.highlighter = Some(SyntaxHighlighter::new(self.language, self.syntax_colors));
         }
@@ -192,6 +313,498 @@ impl ViewerState {
         Ok(())
     }
 
+    fn set_text_encoding(&mut self, encoding: TextEncoding) {
+        self.text_encoding = encoding;
+        self.encoding = encoding.name().to_string();
+    }
+
+    fn decode_lines(&self, bytes: &[u8]) -> Vec<String> {
+        let (content, _) = self.text_encoding.decode(bytes);
+        content.lines().map(String::from).collect()
+    }
+
+    /// Decode the file again with the next encoding, for when detection
+    /// guessed wrong
+    pub fn cycle_encoding(&mut self) {
+        if self.is_binary || self.paged.is_some() {
+            self.message = Some("Encoding can only be changed for text files".to_string());
+            return;
+        }
+        self.set_text_encoding(self.text_encoding.next());
+        if self.mode == ViewerMode::Text {
+            self.lines = self.decode_lines(&self.raw_bytes);
+            self.total_lines = self.lines.len();
+        }
+        self.match_lines.clear();
+        self.match_positions.clear();
+        if let Some(pattern) = self.filter_pattern().map(String::from) {
+            self.apply_filter(&pattern);
+        }
+        self.scroll = self.scroll.min(self.row_count().saturating_sub(1));
+        self.message = Some(format!("Viewing as {}", self.encoding));
+    }
+
+    /// Open a large file in pages; lines are indexed in the background
+    fn load_paged(&mut self, path: &std::path::Path) -> Result<(), String> {
+        let mut file = LargeFile::open(path).map_err(|e| e.to_string())?;
+        let head = file.read_bytes(0, 8192).map_err(|e| e.to_string())?;
+        self.file_size = file.size();
+        self.is_binary = self.detect_binary(&head);
+        if self.is_binary {
+            self.mode = ViewerMode::Hex;
+            self.encoding = "Binary".to_string();
+        } else {
+            self.mode = ViewerMode::Text;
+            self.encoding = "UTF-8".to_string();
+        }
+        // Highlighting and wrapping need every line before the visible ones
+        self.language = Language::Plain;
+        self.highlighter = None;
+        self.word_wrap = false;
+        self.lines.clear();
+        self.raw_bytes.clear();
+        self.total_lines = 0;
+        self.paged = Some(PagedView {
+            file,
+            first_line: 0,
+            offsets: Vec::new(),
+            search: None,
+            pending_jump: None,
+        });
+        Ok(())
+    }
+
+    /// True when the file is read in pages
+    pub fn is_paged(&self) -> bool {
+        self.paged.is_some()
+    }
+
+    /// Number of lines (hex rows in hex mode); for a paged file still being
+    /// indexed, the lines found so far
+    pub fn line_count(&self) -> usize {
+        match self.paged {
+            Some(ref paged) => match self.mode {
+                ViewerMode::Text => paged.file.line_count(),
+                ViewerMode::Hex => paged.file.size().div_ceil(HEX_ROW_BYTES as u64) as usize,
+            },
+            None => self.lines.len(),
+        }
+    }
+
+    /// Number of rows on screen: the matching lines while filtered
+    pub fn row_count(&self) -> usize {
+        match self.filter {
+            Some(ref filter) => filter.lines.len(),
+            None => self.line_count(),
+        }
+    }
+
+    /// Line number of the first visible row
+    pub fn top_line(&self) -> usize {
+        match self.filter {
+            Some(ref filter) => filter
+                .lines
+                .get(self.scroll)
+                .or(filter.lines.last())
+                .map_or(0, |(line, _)| *line),
+            None => self.scroll,
+        }
+    }
+
+    /// Scroll so `line` (or, while filtered, the first match at or after it)
+    /// is `context` rows below the top
+    fn scroll_to_line(&mut self, line: usize, context: usize) {
+        let row = match self.filter {
+            Some(ref filter) => filter.lines.partition_point(|(n, _)| *n < line),
+            None => line,
+        };
+        self.scroll = row.saturating_sub(context);
+    }
+
+    fn scroll_to_bottom(&mut self) {
+        self.scroll = self.row_count().saturating_sub(self.visible_height);
+    }
+
+    /// Line number of `lines[0]`
+    fn page_first(&self) -> usize {
+        self.paged.as_ref().map_or(0, |p| p.first_line)
+    }
+
+    /// Load the page around the scroll position when it is not in `lines`
+    fn sync_page(&mut self) {
+        let line_count = self.line_count();
+        let wanted_end = (self.scroll + self.visible_height).min(line_count);
+        let paged = match self.paged {
+            Some(ref mut p) => p,
+            None => return,
+        };
+        let loaded_end = paged.first_line + self.lines.len();
+        if self.scroll >= paged.first_line && wanted_end <= loaded_end && !self.lines.is_empty() {
+            return;
+        }
+        let first = self.scroll.saturating_sub(PAGE_LINES / 4);
+        let page = match self.mode {
+            ViewerMode::Text => paged.file.read_lines(first, PAGE_LINES),
+            ViewerMode::Hex => {
+                let offset = (first * HEX_ROW_BYTES) as u64;
+                paged
+                    .file
+                    .read_bytes(offset, PAGE_LINES * HEX_ROW_BYTES)
+                    .map(|bytes| {
+                        Self::format_hex_view(&bytes, offset)
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, line)| (offset + (i * HEX_ROW_BYTES) as u64, line))
+                            .collect()
+                    })
+            }
+        };
+        match page {
+            Ok(page) => {
+                paged.first_line = first;
+                (paged.offsets, self.lines) = page.into_iter().unzip();
+            }
+            Err(e) => self.message = Some(format!("Read failed: {}", e)),
+        }
+    }
+
+    /// Byte offset of the first visible line
+    fn scroll_offset(&self) -> u64 {
+        match self.mode {
+            ViewerMode::Hex => (self.scroll * HEX_ROW_BYTES) as u64,
+            ViewerMode::Text => match self.paged {
+                Some(ref paged) => {
+                    let idx = self.scroll.saturating_sub(paged.first_line);
+                    paged
+                        .offsets
+                        .get(idx)
+                        .copied()
+                        .unwrap_or_else(|| paged.file.indexed_bytes())
+                }
+                None => {
+                    let mut offset = 0;
+                    for line in self.lines.iter().take(self.scroll) {
+                        offset += line.len() as u64 + 1;
+                    }
+                    offset.min(self.raw_bytes.len() as u64)
+                }
+            },
+        }
+    }
+
+    /// Take background results: appends to a followed file, index and
+    /// search progress of a paged file and filter matches.
+    /// Returns true when something changed on screen.
+    pub fn poll_background(&mut self) -> bool {
+        let at_bottom = self.scroll + self.visible_height >= self.row_count();
+        let mut changed = self.poll_follow();
+        changed |= self.poll_paged();
+        changed |= self.poll_filter();
+        // Keep up with the end of a followed file unless scrolled away from it
+        if changed && at_bottom && self.follow.is_some() {
+            self.scroll_to_bottom();
+        }
+        changed
+    }
+
+    fn poll_paged(&mut self) -> bool {
+        let paged = match self.paged {
+            Some(ref mut p) => p,
+            None => return false,
+        };
+        let mut changed = paged.file.poll();
+        if changed {
+            if let Some(e) = paged.file.index_error() {
+                self.message = Some(format!("Indexing failed: {}", e));
+            }
+        }
+        if let Some((offset, context)) = paged.pending_jump {
+            if !paged.file.is_indexing() || offset < paged.file.indexed_bytes() {
+                paged.pending_jump = None;
+                self.scroll_to_offset(offset, context);
+                changed = true;
+            }
+        }
+        let paged = match self.paged {
+            Some(ref mut p) => p,
+            None => return changed,
+        };
+        if let Some(ref mut job) = paged.search {
+            let was_running = job.is_running();
+            let found = job.poll();
+            let first_results = self.match_positions.is_empty() && !found.is_empty();
+            for &(line, start, end) in &found {
+                if self.match_lines.last() != Some(&line) {
+                    self.match_lines.push(line);
+                }
+                self.match_positions.push((line, start, end));
+            }
+            if was_running && !job.is_running() {
+                if let Some(e) = job.error() {
+                    self.message = Some(format!("Search failed: {}", e));
+                }
+                changed = true;
+            }
+            if first_results {
+                self.current_match = 0;
+                self.scroll_to_current_match();
+            }
+            changed |= !found.is_empty();
+        }
+        changed
+    }
+
+    fn poll_filter(&mut self) -> bool {
+        let filter = match self.filter {
+            Some(ref mut f) => f,
+            None => return false,
+        };
+        let job = match filter.job {
+            Some(ref mut j) => j,
+            None => return false,
+        };
+        let found = job.poll();
+        let changed = !found.is_empty() || !job.is_running();
+        filter.lines.extend(found);
+        if !job.is_running() {
+            if let Some(e) = job.error() {
+                self.message = Some(format!("Filter failed: {}", e));
+            } else if job.truncated() {
+                self.message = Some(format!("Filter stopped at {} lines", MAX_SEARCH_MATCHES));
+            }
+            filter.job = None;
+        }
+        changed
+    }
+
+    /// True while something runs in the background: a paged file being
+    /// indexed, searched or filtered, or follow mode
+    pub fn is_busy(&self) -> bool {
+        self.follow.is_some()
+            || self
+                .filter
+                .as_ref()
+                .is_some_and(|f| f.job.as_ref().is_some_and(|j| j.is_running()))
+            || self.paged.as_ref().is_some_and(|p| {
+                p.file.is_indexing()
+                    || p.pending_jump.is_some()
+                    || p.search.as_ref().is_some_and(|s| s.is_running())
+            })
+    }
+
+    /// Follow mode on/off: watch the file for appends (tail -f)
+    pub fn toggle_follow(&mut self) {
+        if self.follow.take().is_some() {
+            return;
+        }
+        if self.revision.is_some() {
+            self.message = Some("Cannot follow a past revision".to_string());
+            return;
+        }
+        self.start_follow();
+        if self.follow.is_some() {
+            self.scroll_to_bottom();
+        }
+    }
+
+    fn start_follow(&mut self) {
+        match std::fs::metadata(&self.file_path) {
+            Ok(meta) => {
+                self.follow = Some(FollowState {
+                    size: self.file_size,
+                    identity: file_identity(&meta),
+                    last_check: Instant::now(),
+                })
+            }
+            Err(e) => self.message = Some(format!("Cannot follow: {}", e)),
+        }
+    }
+
+    /// Check a followed file: load what was appended, reload it when it
+    /// was truncated or replaced. Returns true when the content changed.
+    fn poll_follow(&mut self) -> bool {
+        let follow = match self.follow {
+            Some(ref mut f) if f.last_check.elapsed() >= FOLLOW_INTERVAL => f,
+            _ => return false,
+        };
+        follow.last_check = Instant::now();
+        // A rotated log may be missing until it is recreated
+        let meta = match std::fs::metadata(&self.file_path) {
+            Ok(m) => m,
+            Err(_) => return false,
+        };
+        let (old_size, size) = (follow.size, meta.len());
+        if file_identity(&meta) != follow.identity {
+            self.reopen_following(Some("File was replaced, reopened"));
+            return true;
+        }
+        if size < old_size {
+            self.reopen_following(Some("File was truncated, reloaded"));
+            return true;
+        }
+        if size == old_size {
+            return false;
+        }
+        if let Some(ref mut paged) = self.paged {
+            // Appends are picked up once the running scans are done
+            let filtering = self
+                .filter
+                .as_ref()
+                .is_some_and(|f| f.job.as_ref().is_some_and(|j| j.is_running()));
+            if filtering {
+                return false;
+            }
+            let (first_line, start) = paged.file.complete_lines();
+            if !paged.file.extend(size) {
+                return false;
+            }
+            // The last line on screen may have grown
+            self.lines.clear();
+            if let Some(ref mut filter) = self.filter {
+                filter.lines.retain(|(line, _)| *line < first_line);
+                filter.job = Some(FilterJob::start(
+                    &self.file_path,
+                    start..size,
+                    first_line,
+                    filter.regex.clone(),
+                ));
+            }
+        } else if size > Self::MAX_FILE_SIZE {
+            self.reopen_following(None);
+            return true;
+        } else {
+            match read_range(&self.file_path, old_size, size) {
+                Ok(bytes) => self.append_bytes(&bytes),
+                Err(e) => {
+                    self.message = Some(format!("Read failed: {}", e));
+                    return false;
+                }
+            }
+        }
+        self.file_size = size;
+        if let Some(ref mut follow) = self.follow {
+            follow.size = size;
+        }
+        true
+    }
+
+    /// Load the followed file again from the start, keeping follow mode and
+    /// the filter
+    fn reopen_following(&mut self, message: Option<&str>) {
+        let path = self.file_path.clone();
+        let filter = self.filter.as_ref().map(|f| f.pattern.clone());
+        if let Err(e) = self.load_file(&path) {
+            self.message = Some(e);
+            return;
+        }
+        self.start_follow();
+        if let Some(pattern) = filter {
+            self.apply_filter(&pattern);
+        }
+        self.scroll_to_bottom();
+        self.message = message.map(String::from);
+    }
+
+    /// Add bytes appended to a file held in memory
+    fn append_bytes(&mut self, bytes: &[u8]) {
+        let old_len = self.raw_bytes.len();
+        self.raw_bytes.extend_from_slice(bytes);
+        if self.mode == ViewerMode::Hex {
+            let first_row = old_len / HEX_ROW_BYTES;
+            let base = first_row * HEX_ROW_BYTES;
+            self.lines.truncate(first_row);
+            self.lines
+                .extend(Self::format_hex_view(&self.raw_bytes[base..], base as u64));
+            return;
+        }
+        // Split again from the start of the last line, which may have been
+        // unfinished. UTF-16 has no newline byte to split at and is decoded whole.
+        let (line_start, first_changed) = match self.text_encoding {
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => (0, 0),
+            _ => {
+                let line_start = self.raw_bytes[..old_len]
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |i| i + 1);
+                if line_start < old_len {
+                    (line_start, self.lines.len().saturating_sub(1))
+                } else {
+                    (line_start, self.lines.len())
+                }
+            }
+        };
+        let tail = self.decode_lines(&self.raw_bytes[line_start..]);
+        self.lines.truncate(first_changed);
+        self.lines.extend(tail);
+        self.total_lines = self.lines.len();
+        if let Some(ref mut filter) = self.filter {
+            filter.lines.retain(|(line, _)| *line < first_changed);
+            for (i, line) in self.lines.iter().enumerate().skip(first_changed) {
+                if filter.regex.is_match(line) {
+                    filter.lines.push((i, line.clone()));
+                }
+            }
+        }
+    }
+
+    /// Show only the lines matching the regex `pattern`; empty clears the filter
+    pub fn apply_filter(&mut self, pattern: &str) {
+        let top = self.top_line();
+        if pattern.is_empty() {
+            self.filter = None;
+            self.scroll = top;
+            return;
+        }
+        if self.mode == ViewerMode::Hex {
+            self.message = Some("Switch to text mode to filter".to_string());
+            return;
+        }
+        let regex_pattern = if self.search_options.case_sensitive {
+            pattern.to_string()
+        } else {
+            format!("(?i){}", pattern)
+        };
+        let regex = match Regex::new(&regex_pattern) {
+            Ok(re) => re,
+            Err(_) => {
+                self.message = Some(format!("Invalid filter regex: {}", pattern));
+                return;
+            }
+        };
+        let mut filter = LineFilter {
+            pattern: pattern.to_string(),
+            regex,
+            lines: Vec::new(),
+            job: None,
+        };
+        match self.paged {
+            Some(ref paged) => {
+                filter.job = Some(FilterJob::start(
+                    paged.file.path(),
+                    0..paged.file.size(),
+                    0,
+                    filter.regex.clone(),
+                ))
+            }
+            None => {
+                filter.lines = self
+                    .lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| filter.regex.is_match(line))
+                    .map(|(i, line)| (i, line.clone()))
+                    .collect()
+            }
+        }
+        self.filter = Some(filter);
+        self.scroll_to_line(top, 0);
+    }
+
+    /// Pattern of the live filter, if any
+    pub fn filter_pattern(&self) -> Option<&str> {
+        self.filter.as_ref().map(|f| f.pattern.as_str())
+    }
+
     /// 바이너리 파일 감지
     fn detect_binary(&self, bytes: &[u8]) -> bool {
         // 처음 8KB를 검사
@@ -206,13 +819,13 @@ impl ViewerState {
         null_count > 0 || non_text as f32 / check_size as f32 > 0.3
     }
 
-    /// 헥스 뷰 포맷
-    fn format_hex_view(&self, bytes: &[u8]) -> Vec<String> {
+    /// 헥스 뷰 포맷 (`base`: offset of the first byte)
+    fn format_hex_view(bytes: &[u8], base: u64) -> Vec<String> {
         let mut lines = Vec::new();
-        let bytes_per_line = 16;
+        let bytes_per_line = HEX_ROW_BYTES;
 
         for (i, chunk) in bytes.chunks(bytes_per_line).enumerate() {
-            let offset = i * bytes_per_line;
+            let offset = base + (i * bytes_per_line) as u64;
             let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
             let hex_str = if hex.len() <= 8 {
                 format!("{:<24}", hex.join(" "))
@@ -287,6 +900,20 @@ impl ViewerState {
             }
         }
 
+        // Paged files are searched in the background; see poll_background
+        if let Some(ref mut paged) = self.paged {
+            if self.mode == ViewerMode::Hex {
+                self.message = Some("Switch to text mode to search".to_string());
+                return;
+            }
+            self.current_match = 0;
+            paged.search = self
+                .cached_regex
+                .clone()
+                .map(|re| SearchJob::start(paged.file.path(), paged.file.size(), re));
+            return;
+        }
+
         // Use cached regex for search
         if let Some(ref re) = self.cached_regex {
             for (line_idx, line) in self.lines.iter().enumerate() {
@@ -314,7 +941,7 @@ impl ViewerState {
     pub fn scroll_to_current_match(&mut self) {
         if !self.match_positions.is_empty() && self.current_match < self.match_positions.len() {
             let (line, _, _) = self.match_positions[self.current_match];
-            self.scroll = line.saturating_sub(5);
+            self.scroll_to_line(line, 5);
         }
     }
 
@@ -354,18 +981,18 @@ impl ViewerState {
         }
 
         // 현재 화면에 보이는 첫 번째 줄 기준
-        let current_line = self.scroll + 5; // 화면 중앙 근처
+        let current_line = self.top_line() + 5; // 화면 중앙 근처
         let mut sorted: Vec<_> = self.bookmarks.iter().copied().collect();
         sorted.sort();
 
         for &bm in &sorted {
             if bm > current_line {
-                self.scroll = bm.saturating_sub(5);
+                self.scroll_to_line(bm, 5);
                 return;
             }
         }
         // 처음 북마크로 순환
-        self.scroll = sorted[0].saturating_sub(5);
+        self.scroll_to_line(sorted[0], 5);
     }
 
     /// 이전 북마크로 이동
@@ -375,46 +1002,173 @@ impl ViewerState {
         }
 
         // 현재 화면에 보이는 첫 번째 줄 기준
-        let current_line = self.scroll + 5; // 화면 중앙 근처
+        let current_line = self.top_line() + 5; // 화면 중앙 근처
         let mut sorted: Vec<_> = self.bookmarks.iter().copied().collect();
         sorted.sort();
         sorted.reverse();
 
         for &bm in &sorted {
             if bm < current_line {
-                self.scroll = bm.saturating_sub(5);
+                self.scroll_to_line(bm, 5);
                 return;
             }
         }
         // 마지막 북마크로 순환
-        self.scroll = sorted[0].saturating_sub(5);
+        self.scroll_to_line(sorted[0], 5);
     }
 
-    /// 줄 번호로 이동
+    /// 줄 번호로 이동: a line number, a percentage or a byte offset (see [`GotoTarget`])
     pub fn goto_line(&mut self, line_str: &str) {
-        if let Ok(line_num) = line_str.parse::<usize>() {
-            if line_num > 0 && line_num <= self.lines.len() {
-                self.scroll = (line_num - 1).saturating_sub(5);
+        match GotoTarget::parse(line_str) {
+            Some(GotoTarget::Line(line_num)) => {
+                if line_num > 0 && line_num <= self.line_count() {
+                    self.scroll_to_line(line_num - 1, 5);
+                } else if line_num > 0 && self.paged.as_ref().is_some_and(|p| p.file.is_indexing())
+                {
+                    self.message = Some(format!(
+                        "Line {} not indexed yet ({} lines so far)",
+                        line_num,
+                        self.line_count()
+                    ));
+                }
+            }
+            Some(GotoTarget::Percent(percent)) => {
+                let offset = (self.file_size as f64 * percent / 100.0) as u64;
+                self.goto_offset(offset);
+            }
+            Some(GotoTarget::Offset(offset)) => self.goto_offset(offset),
+            None => self.message = Some(format!("Invalid position: {}", line_str)),
+        }
+    }
+
+    /// Scroll to the line containing byte `offset`. In a paged file still
+    /// being indexed, the jump happens once the index gets there.
+    pub fn goto_offset(&mut self, offset: u64) {
+        self.scroll_to_offset(offset, 5);
+    }
+
+    /// Scroll so the line containing `offset` is `context` lines below the top
+    fn scroll_to_offset(&mut self, offset: u64, context: usize) {
+        let offset = offset.min(self.file_size.saturating_sub(1));
+        if self.mode == ViewerMode::Hex {
+            self.scroll = (offset as usize / HEX_ROW_BYTES).saturating_sub(context);
+            return;
+        }
+        let line = match self.paged {
+            Some(ref mut paged) => match paged.file.line_at_offset(offset) {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    paged.pending_jump = Some((offset, context));
+                    self.message = Some("Indexing, will jump when the position is reached".into());
+                    return;
+                }
+                Err(e) => {
+                    self.message = Some(format!("Read failed: {}", e));
+                    return;
+                }
+            },
+            None => {
+                let end = (offset as usize).min(self.raw_bytes.len());
+                self.raw_bytes[..end]
+                    .iter()
+                    .filter(|&&b| b == b'\n')
+                    .count()
+            }
+        };
+        self.scroll_to_line(line, context);
+    }
+
+    /// Blame mode on/off: annotate each line with the commit that last changed it
+    pub fn toggle_blame(&mut self) {
+        if self.blame.take().is_some() {
+            return;
+        }
+        if self.is_binary {
+            self.message = Some("Blame is not available for binary files".to_string());
+            return;
+        }
+        let result = match self.revision {
+            Some(ref rev) => super::git_screen::get_blame(&rev.repo, Some(&rev.hash), &rev.path),
+            None => match (self.file_path.parent(), self.file_path.file_name()) {
+                (Some(dir), Some(name)) => {
+                    super::git_screen::get_blame(dir, None, &name.to_string_lossy())
+                }
+                _ => Err("No file".to_string()),
+            },
+        };
+        match result {
+            Ok(blame) => self.blame = Some(blame),
+            Err(e) => {
+                self.message = Some(e.lines().next().unwrap_or("git blame failed").to_string())
             }
         }
     }
 
+    /// Edit the bytes of the file in the hex view
+    pub fn start_hex_edit(&mut self) {
+        if self.revision.is_some() {
+            self.message = Some("Cannot edit a past revision".to_string());
+            return;
+        }
+        if self.paged.is_some() {
+            self.message = Some("File is too large to edit".to_string());
+            return;
+        }
+        if self.follow.is_some() {
+            self.message = Some("Stop following to edit".to_string());
+            return;
+        }
+        let mut editor =
+            HexEditor::new(self.file_path.clone(), std::mem::take(&mut self.raw_bytes));
+        // Start at the first row on screen
+        editor.move_to(self.scroll * HEX_ROW_BYTES);
+        editor.scroll = self.scroll;
+        self.hex_edit = Some(editor);
+    }
+
+    /// Leave the hex editor, dropping unsaved changes
+    pub fn close_hex_edit(&mut self) {
+        let editor = match self.hex_edit.take() {
+            Some(e) => e,
+            None => return,
+        };
+        self.scroll = editor.scroll;
+        self.raw_bytes = editor.revert();
+        self.file_size = self.raw_bytes.len() as u64;
+        self.lines = Self::format_hex_view(&self.raw_bytes, 0);
+        self.total_lines = self.lines.len();
+        self.scroll = self.scroll.min(self.lines.len().saturating_sub(1));
+        self.match_lines.clear();
+        self.match_positions.clear();
+    }
+
     /// 모드 토글 (텍스트/헥스)
     pub fn toggle_mode(&mut self) {
         if self.is_binary {
             return; // 바이너리 파일은 항상 헥스 모드
         }
 
+        if self.paged.is_some() {
+            // Keep the position: switch views at the byte offset on screen
+            self.sync_page();
+            let offset = self.scroll_offset();
+            self.mode = match self.mode {
+                ViewerMode::Text => ViewerMode::Hex,
+                ViewerMode::Hex => ViewerMode::Text,
+            };
+            self.lines.clear();
+            self.scroll_to_offset(offset, 0);
+            return;
+        }
+
         match self.mode {
             ViewerMode::Text => {
                 self.mode = ViewerMode::Hex;
-                self.lines = self.format_hex_view(&self.raw_bytes);
+                self.lines = Self::format_hex_view(&self.raw_bytes, 0);
             }
             ViewerMode::Hex => {
                 self.mode = ViewerMode::Text;
-                if let Ok(content) = String::from_utf8(self.raw_bytes.clone()) {
-                    self.lines = content.lines().map(String::from).collect();
-                }
+                self.lines = self.decode_lines(&self.raw_bytes);
             }
         }
         self.scroll = 0;
@@ -442,35 +1196,81 @@ pub fn draw(
     // 화면 크기 업데이트 (스크롤 계산에 사용)
     let visible_lines = (inner.height - 2) as usize;
     state.visible_height = visible_lines;
+    state.sync_page();
+    let page_first = state.page_first();
 
     // Header
-    let total_lines = state.lines.len();
+    let total_lines = state.row_count();
     let end_line = (state.scroll + visible_lines).min(total_lines);
-    let percentage = if total_lines > 0 {
+    let percentage = if state.is_paged() {
+        // Lines of a paged file may not all be counted yet
+        (state.scroll_offset() * 100)
+            .checked_div(state.file_size)
+            .unwrap_or(100) as u32
+    } else if total_lines > 0 {
         ((end_line as f32 / total_lines as f32) * 100.0) as u32
     } else {
         100
     };
+    let indexing = match state.paged {
+        Some(ref paged) if paged.file.is_indexing() => Some(
+            (paged.file.indexed_bytes() * 100)
+                .checked_div(paged.file.size())
+                .unwrap_or(0),
+        ),
+        _ => None,
+    };
 
     let mode_str = match state.mode {
         ViewerMode::Text => state.language.name(),
+        ViewerMode::Hex if state.hex_edit.is_some() => "Hex edit",
         ViewerMode::Hex => "Hex",
     };
 
+    let revision_label = match state.revision {
+        Some(ref rev) => format!("{} @ {} ", rev.path, rev.hash),
+        None => String::new(),
+    };
+
     let header = Line::from(vec![
         Span::styled(" File Viewer ", theme.header_style()),
+        Span::styled(revision_label, theme.header_style()),
         Span::styled(
             format!(
-                "[{}] {} | {}-{}/{} ({}%) ",
+                "[{}] {} | {}-{}/{}{} ({}%) ",
                 mode_str,
                 state.encoding,
                 state.scroll + 1,
                 end_line,
                 total_lines,
+                if state.filter.is_none() && indexing.is_some() {
+                    "+"
+                } else {
+                    ""
+                },
                 percentage
             ),
             theme.dim_style(),
         ),
+        match indexing {
+            Some(pct) => Span::styled(format!("indexing {}% ", pct), theme.dim_style()),
+            None => Span::raw(""),
+        },
+        match state.filter {
+            Some(ref filter) => Span::styled(
+                match filter.job {
+                    Some(ref job) => format!("[filter /{}/ {}%] ", filter.pattern, job.percent()),
+                    None => format!("[filter /{}/] ", filter.pattern),
+                },
+                theme.header_style(),
+            ),
+            None => Span::raw(""),
+        },
+        if state.follow.is_some() {
+            Span::styled("[follow] ", theme.header_style())
+        } else {
+            Span::raw("")
+        },
         if !state.bookmarks.is_empty() {
             Span::styled(
                 format!(" [{}]", state.bookmarks.len()),
@@ -487,12 +1287,35 @@ pub fn draw(
         Rect::new(inner.x, inner.y, inner.width, 1),
     );
 
+    if let Some(ref mut editor) = state.hex_edit {
+        let footer_y = inner.y + inner.height - 1;
+        super::hex_editor::draw(
+            frame,
+            editor,
+            Rect::new(inner.x, inner.y + 1, inner.width, inner.height - 2),
+            Rect::new(inner.x, footer_y, inner.width, 1),
+            theme,
+        );
+        return;
+    }
+
     // Content
     let content_height = (inner.height - 2) as usize;
-    let content_width = (inner.width - 5) as usize; // 줄 번호 공간 제외
+    // Blame column (text mode only)
+    let blame = match state.blame {
+        Some(ref b) if state.mode == ViewerMode::Text => Some(b.as_slice()),
+        _ => None,
+    };
+    let blame_width = if blame.is_some() { BLAME_WIDTH } else { 0 };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let content_width = (inner.width as usize).saturating_sub(5 + blame_width); // 줄 번호 공간 제외
 
     // 하이라이터 리셋
-    let mut highlighter = state.highlighter.clone();
+    // Filtered rows are not contiguous, so they are not highlighted
+    let mut highlighter = state.highlighter.clone().filter(|_| state.filter.is_none());
     if let Some(ref mut hl) = highlighter {
         hl.reset();
         // 스크롤 전까지 상태 업데이트
@@ -502,7 +1325,7 @@ pub fn draw(
     }
 
     // Word wrap 모드일 경우 표시할 줄들을 미리 계산
-    if state.word_wrap {
+    if state.word_wrap && state.filter.is_none() {
         // wrapped 줄 목록 생성: (원본 줄 번호, 원본 줄 참조, 줄 내용, 첫 줄 여부)
         let mut wrapped_lines: Vec<(usize, String, bool)> = Vec::new();
 
@@ -599,7 +1422,15 @@ pub fn draw(
                 vec![Span::styled(display_text.clone(), line_bg_style)]
             };
 
-            let mut spans = vec![line_num_span];
+            let mut spans = Vec::new();
+            if let Some(blame) = blame {
+                spans.push(if *is_first {
+                    blame_span(blame, *orig_line_num, i == 0, now, theme)
+                } else {
+                    Span::raw(" ".repeat(BLAME_WIDTH))
+                });
+            }
+            spans.push(line_num_span);
             spans.extend(content_spans);
 
             frame.render_widget(
@@ -608,17 +1439,27 @@ pub fn draw(
             );
         }
     } else {
-        // 일반 모드 (word wrap 없음)
-        for (i, original_line) in state
-            .lines
-            .iter()
-            .skip(state.scroll)
-            .take(content_height)
-            .enumerate()
-        {
+        // 일반 모드 (word wrap 없음): line number and text of each row
+        let rows: Vec<(usize, &String)> = match state.filter {
+            Some(ref filter) => filter
+                .lines
+                .iter()
+                .skip(state.scroll)
+                .take(content_height)
+                .map(|(line_num, line)| (*line_num, line))
+                .collect(),
+            None => state
+                .lines
+                .iter()
+                .skip(state.scroll.saturating_sub(page_first))
+                .take(content_height)
+                .enumerate()
+                .map(|(i, line)| (state.scroll + i, line))
+                .collect(),
+        };
+        for (i, (line_num, original_line)) in rows.into_iter().enumerate() {
             // TAB을 4칸 스페이스로 변환 (잔상 방지)
             let line = original_line.replace('\t', "    ");
-            let line_num = state.scroll + i;
             let is_bookmarked = state.bookmarks.contains(&line_num);
 
             // 줄 번호
@@ -638,6 +1479,24 @@ pub fn draw(
             // 콘텐츠 렌더링
             let content_spans = if state.mode == ViewerMode::Hex {
                 render_hex_line(&line, theme)
+            } else if let Some(ref filter) = state.filter {
+                // Highlight what the live filter matched
+                let positions: Vec<(usize, usize, usize)> = filter
+                    .regex
+                    .find_iter(&line)
+                    .map(|m| {
+                        let start = line[..m.start()].chars().count();
+                        (line_num, start, start + m.as_str().chars().count())
+                    })
+                    .collect();
+                highlight_search_in_line(
+                    &line,
+                    &positions,
+                    line_num,
+                    usize::MAX,
+                    line_bg_style,
+                    theme,
+                )
             } else if let Some(ref mut hl) = highlighter {
                 // 문법 강조와 검색 하이라이트를 함께 처리
                 render_line_with_syntax_and_search(
@@ -709,7 +1568,11 @@ pub fn draw(
                 content_spans
             };
 
-            let mut spans = vec![line_num_span];
+            let mut spans = Vec::new();
+            if let Some(blame) = blame {
+                spans.push(blame_span(blame, line_num, i == 0, now, theme));
+            }
+            spans.push(line_num_span);
             spans.extend(final_spans);
 
             frame.render_widget(
@@ -744,7 +1607,7 @@ pub fn draw(
 
     if state.goto_mode {
         let goto_line = Line::from(vec![
-            Span::styled("Go to line: ", theme.header_style()),
+            Span::styled("Go to (line, N%, @offset, 0xhex): ", theme.header_style()),
             Span::styled(&state.goto_input, theme.normal_style()),
             Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
         ]);
@@ -752,6 +1615,16 @@ pub fn draw(
             Paragraph::new(goto_line).style(theme.status_bar_style()),
             Rect::new(inner.x, footer_y, inner.width, 1),
         );
+    } else if state.filter_mode {
+        let filter_line = Line::from(vec![
+            Span::styled("Filter (regex, empty clears): ", theme.header_style()),
+            Span::styled(&state.filter_input, theme.normal_style()),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ]);
+        frame.render_widget(
+            Paragraph::new(filter_line).style(theme.status_bar_style()),
+            Rect::new(inner.x, footer_y, inner.width, 1),
+        );
     } else if state.search_mode {
         let search_opts = format!(
             "[{}{}{}]",
@@ -794,22 +1667,45 @@ pub fn draw(
         };
 
         // 매치 정보
-        let (match_info, match_info_style) = if !state.match_positions.is_empty() {
-            let count = state.match_positions.len();
-            (
-                format!(
-                    " {}/{} ({} matches) ",
-                    state.current_match + 1,
-                    count,
-                    count
-                ),
-                theme.dim_style(),
-            )
-        } else if !state.search_term.is_empty() {
-            (" No matches ".to_string(), theme.dim_style())
-        } else {
-            (String::new(), theme.dim_style())
-        };
+        let search_job = state.paged.as_ref().and_then(|p| p.search.as_ref());
+        let (match_info, match_info_style) =
+            if let Some(job) = search_job.filter(|j| j.is_running()) {
+                let count = state.match_positions.len();
+                (
+                    format!(
+                        " {}/{}+ (searching {}%) ",
+                        (state.current_match + 1).min(count),
+                        count,
+                        job.percent()
+                    ),
+                    theme.dim_style(),
+                )
+            } else if search_job.is_some_and(|j| j.truncated()) {
+                (
+                    format!(
+                        " {}/{} (stopped at {} matches) ",
+                        state.current_match + 1,
+                        state.match_positions.len(),
+                        MAX_SEARCH_MATCHES
+                    ),
+                    theme.dim_style(),
+                )
+            } else if !state.match_positions.is_empty() {
+                let count = state.match_positions.len();
+                (
+                    format!(
+                        " {}/{} ({} matches) ",
+                        state.current_match + 1,
+                        count,
+                        count
+                    ),
+                    theme.dim_style(),
+                )
+            } else if !state.search_term.is_empty() {
+                (" No matches ".to_string(), theme.dim_style())
+            } else {
+                (String::new(), theme.dim_style())
+            };
 
         let mut spans = vec![
             Span::styled("Find: ", theme.header_style()),
@@ -837,6 +1733,12 @@ pub fn draw(
             Paragraph::new(Line::from(spans)).style(theme.status_bar_style()),
             Rect::new(inner.x, footer_y, inner.width, 1),
         );
+    } else if let Some(ref msg) = state.message {
+        frame.render_widget(
+            Paragraph::new(Span::styled(format!(" {}", msg), theme.header_style()))
+                .style(theme.status_bar_style()),
+            Rect::new(inner.x, footer_y, inner.width, 1),
+        );
     } else {
         let wrap_indicator = if state.word_wrap { "Wrap " } else { "" };
 
@@ -849,6 +1751,18 @@ pub fn draw(
                 Style::default().fg(theme.viewer.wrap_indicator),
             ));
         }
+        if state.blame.is_some() {
+            footer_spans.push(Span::styled(
+                "Blame ",
+                Style::default().fg(theme.viewer.wrap_indicator),
+            ));
+        }
+        if state.follow.is_some() {
+            footer_spans.push(Span::styled(
+                "Follow ",
+                Style::default().fg(theme.viewer.wrap_indicator),
+            ));
+        }
 
         // 단축키 표시: keybindings에서 동적으로
         use crate::keybindings::ViewerAction;
@@ -878,6 +1792,22 @@ pub fn draw(
                 vkb.viewer_first_key(ViewerAction::ToggleHex).to_string(),
                 "hex ",
             ),
+            (
+                vkb.viewer_first_key(ViewerAction::ToggleBlame).to_string(),
+                "blame ",
+            ),
+            (
+                vkb.viewer_first_key(ViewerAction::FileHistory).to_string(),
+                "hist ",
+            ),
+            (
+                vkb.viewer_first_key(ViewerAction::Follow).to_string(),
+                "follow ",
+            ),
+            (
+                vkb.viewer_first_key(ViewerAction::Filter).to_string(),
+                "filter ",
+            ),
             (
                 vkb.viewer_first_key(ViewerAction::ToggleBookmark)
                     .to_string(),
@@ -899,6 +1829,65 @@ pub fn draw(
     }
 }
 
+/// Bytes `start..end` of the file at `path`
+fn read_range(path: &Path, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(end.saturating_sub(start))
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Identity of the file behind a path, to notice it was replaced (log rotation)
+fn file_identity(meta: &std::fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        meta.ino()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        0
+    }
+}
+
+/// Blame column of one line. Left blank while the line above comes from the
+/// same commit, except on the first row on screen.
+fn blame_span(
+    blame: &[BlameLine],
+    line: usize,
+    first_row: bool,
+    now: i64,
+    theme: &Theme,
+) -> Span<'static> {
+    let entry = match blame.get(line) {
+        Some(b) => b,
+        None => return Span::raw(" ".repeat(BLAME_WIDTH)),
+    };
+    let same_as_above = line > 0 && blame.get(line - 1).is_some_and(|b| b.hash == entry.hash);
+    if same_as_above && !first_row {
+        return Span::raw(" ".repeat(BLAME_WIDTH));
+    }
+    if entry.hash.is_empty() {
+        return Span::styled(
+            pad_to_display_width("(not committed)", BLAME_WIDTH),
+            Style::default().fg(theme.viewer.wrap_indicator),
+        );
+    }
+    let author = pad_to_display_width(&truncate_to_display_width(&entry.author, 10), 10);
+    Span::styled(
+        format!(
+            "{:<7} {} {:>4} ",
+            entry.hash,
+            author,
+            super::git_screen::format_age(entry.time, now)
+        ),
+        Style::default().fg(theme.viewer.line_number),
+    )
+}
+
 /// 헥스 라인 렌더링
 fn render_hex_line(line: &str, theme: &Theme) -> Vec<Span<'static>> {
     // 헥스 뷰: offset | hex bytes | ascii
@@ -1336,6 +2325,17 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
         None => return,
     };
 
+    // Hex editor takes every key until closed
+    if let Some(ref mut editor) = state.hex_edit {
+        let height = state
+            .visible_height
+            .saturating_sub(super::hex_editor::INSPECTOR_ROWS);
+        if super::hex_editor::handle_key(editor, code, modifiers, height) {
+            state.close_hex_edit();
+        }
+        return;
+    }
+
     // Goto 모드
     if state.goto_mode {
         match code {
@@ -1351,7 +2351,7 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             KeyCode::Backspace => {
                 state.goto_input.pop();
             }
-            KeyCode::Char(c) if c.is_ascii_digit() => {
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() || matches!(c, '%' | '@' | '.') => {
                 state.goto_input.push(c);
             }
             _ => {}
@@ -1359,6 +2359,27 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
         return;
     }
 
+    // Filter prompt
+    if state.filter_mode {
+        match code {
+            KeyCode::Esc => {
+                state.filter_mode = false;
+            }
+            KeyCode::Enter => {
+                state.filter_mode = false;
+                state.apply_filter(&state.filter_input.clone());
+            }
+            KeyCode::Backspace => {
+                state.filter_input.pop();
+            }
+            KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                state.filter_input.push(c);
+            }
+            _ => {}
+        }
+        return;
+    }
+
     // 검색 모드
     if state.search_mode {
         match code {
@@ -1455,26 +2476,41 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     }
 
     let visible_lines = state.visible_height;
+    state.message = None;
 
     use crate::keybindings::ViewerAction;
     if let Some(action) = app.keybindings.viewer_action(code, modifiers) {
         match action {
             ViewerAction::Quit => {
-                app.current_screen = Screen::FilePanel;
+                // Revisions opened from a file's history go back to it
+                if state.revision.is_none() || !app.return_to_file_history() {
+                    app.current_screen = Screen::FilePanel;
+                }
             }
             ViewerAction::Edit => {
+                if state.mode == ViewerMode::Hex {
+                    state.start_hex_edit();
+                    return;
+                }
+                if state.revision.is_some() {
+                    state.message = Some("Cannot edit a past revision".to_string());
+                    return;
+                }
+                if state.is_paged() {
+                    state.message = Some("File is too large to edit".to_string());
+                    return;
+                }
                 if let Some(ref viewer_state) = app.viewer_state {
                     if !viewer_state.is_binary {
                         let path = viewer_state.file_path.clone();
-                        let viewer_scroll = viewer_state.scroll;
+                        let viewer_scroll = viewer_state.top_line();
                         let mut editor = super::file_editor::EditorState::new();
                         if editor.load_file(&path).is_ok() {
                             editor.scroll = viewer_scroll;
                             editor.cursor_line = viewer_scroll;
                             editor.cursor_col = 0;
-                            app.editor_state = Some(editor);
+                            app.open_editor(editor);
                             app.previous_screen = Some(Screen::FileViewer);
-                            app.current_screen = Screen::FileEditor;
                         }
                     }
                 }
@@ -1483,7 +2519,7 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 state.scroll = state.scroll.saturating_sub(1);
             }
             ViewerAction::ScrollDown => {
-                if state.scroll + visible_lines < state.lines.len() {
+                if state.scroll + visible_lines < state.row_count() {
                     state.scroll += 1;
                 }
             }
@@ -1497,14 +2533,21 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 state.scroll = state.scroll.saturating_sub(visible_lines);
             }
             ViewerAction::PageDown => {
-                let max = state.lines.len().saturating_sub(visible_lines);
+                let max = state.row_count().saturating_sub(visible_lines);
                 state.scroll = (state.scroll + visible_lines).min(max);
             }
             ViewerAction::GoTop => {
                 state.scroll = 0;
             }
             ViewerAction::GoBottom => {
-                state.scroll = state.lines.len().saturating_sub(visible_lines);
+                if state.is_paged() && state.mode == ViewerMode::Text && state.filter.is_none() {
+                    // The end may not be indexed yet
+                    state.goto_offset(u64::MAX);
+                    if state.message.is_some() {
+                        return;
+                    }
+                }
+                state.scroll = state.row_count().saturating_sub(visible_lines);
             }
             ViewerAction::Find => {
                 state.search_mode = true;
@@ -1512,7 +2555,7 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 state.search_cursor_pos = 0;
             }
             ViewerAction::ToggleBookmark => {
-                let current_line = state.scroll;
+                let current_line = state.top_line();
                 state.toggle_bookmark(current_line);
             }
             ViewerAction::NextBookmark => {
@@ -1522,15 +2565,269 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
                 state.goto_prev_bookmark();
             }
             ViewerAction::ToggleWrap => {
+                if state.is_paged() {
+                    state.message = Some("Wrap is not available for large files".to_string());
+                    return;
+                }
                 state.word_wrap = !state.word_wrap;
             }
             ViewerAction::ToggleHex => {
+                if state.filter.is_some() {
+                    state.message = Some("Clear the filter to switch to hex".to_string());
+                    return;
+                }
                 state.toggle_mode();
             }
+            ViewerAction::Follow => {
+                state.toggle_follow();
+            }
+            ViewerAction::CycleEncoding => {
+                state.cycle_encoding();
+            }
+            ViewerAction::Filter => {
+                state.filter_mode = true;
+                state.filter_input = state.filter_pattern().unwrap_or_default().to_string();
+            }
             ViewerAction::GotoLine => {
                 state.goto_mode = true;
                 state.goto_input.clear();
             }
+            ViewerAction::ToggleBlame => {
+                if state.is_paged() {
+                    state.message = Some("Blame is not available for large files".to_string());
+                    return;
+                }
+                state.toggle_blame();
+            }
+            ViewerAction::FileHistory => {
+                let path = state.file_path.clone();
+                // A revision goes back to the history it was opened from
+                if state.revision.is_some() && app.return_to_file_history() {
+                    return;
+                }
+                app.show_file_history(path);
+                // Errors go to the viewer's footer
+                if app.dialog.is_none() {
+                    if let (Some(msg), Some(viewer)) = (app.message.take(), &mut app.viewer_state) {
+                        viewer.message = Some(msg);
+                    }
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_goto_target_parse() {
+        assert_eq!(GotoTarget::parse("120"), Some(GotoTarget::Line(120)));
+        assert_eq!(GotoTarget::parse("50%"), Some(GotoTarget::Percent(50.0)));
+        assert_eq!(GotoTarget::parse("12.5%"), Some(GotoTarget::Percent(12.5)));
+        assert_eq!(
+            GotoTarget::parse("0x1F00"),
+            Some(GotoTarget::Offset(0x1f00))
+        );
+        assert_eq!(GotoTarget::parse("@4096"), Some(GotoTarget::Offset(4096)));
+        assert_eq!(
+            GotoTarget::parse("@2M"),
+            Some(GotoTarget::Offset(2 * 1024 * 1024))
+        );
+        assert_eq!(GotoTarget::parse("150%"), None);
+        assert_eq!(GotoTarget::parse("@2x"), None);
+        assert_eq!(GotoTarget::parse("abc"), None);
+    }
+
+    #[test]
+    fn test_paged_view_jumps_and_searches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let content: String = (0..3000).map(|i| format!("{:04} entry\n", i)).collect();
+        std::fs::write(&path, &content).unwrap();
+
+        let mut viewer = ViewerState::new();
+        viewer.file_path = path.clone();
+        viewer.load_paged(&path).unwrap();
+        viewer.visible_height = 20;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while viewer.is_busy() {
+            viewer.poll_background();
+            assert!(Instant::now() < deadline, "indexing did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(viewer.is_paged());
+        assert_eq!(viewer.line_count(), 3000);
+
+        // Every line is 11 bytes: 50% is line 1500, shown with 5 lines of context
+        viewer.goto_line("50%");
+        assert_eq!(viewer.scroll, 1495);
+        viewer.sync_page();
+        let idx = viewer.scroll - viewer.page_first();
+        assert_eq!(viewer.lines[idx], "1495 entry");
+        viewer.goto_line("@11");
+        assert_eq!(viewer.scroll, 0);
+        viewer.goto_line("2500");
+        assert_eq!(viewer.scroll, 2494);
+
+        // Hex mode keeps the position
+        viewer.toggle_mode();
+        assert_eq!(viewer.mode, ViewerMode::Hex);
+        assert_eq!(viewer.scroll, 2494 * 11 / HEX_ROW_BYTES);
+        // Back in text mode: the line holding the row's first byte
+        viewer.toggle_mode();
+        assert_eq!(viewer.scroll, 2493);
+
+        viewer.search_term = "^2999".to_string();
+        viewer.search_options.use_regex = true;
+        viewer.perform_search();
+        while viewer.is_busy() {
+            viewer.poll_background();
+            assert!(Instant::now() < deadline, "search did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(viewer.match_positions, [(2999, 0, 4)]);
+        assert_eq!(viewer.scroll, 2994);
+    }
+
+    /// Run the next follow check now instead of after FOLLOW_INTERVAL
+    fn poll_follow_now(viewer: &mut ViewerState) -> bool {
+        if let Some(ref mut follow) = viewer.follow {
+            follow.last_check = Instant::now()
+                .checked_sub(FOLLOW_INTERVAL)
+                .unwrap_or(follow.last_check);
+        }
+        viewer.poll_background()
+    }
+
+    #[test]
+    fn test_follow_appends_truncation_and_filter() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("service.log");
+        std::fs::write(&path, "INFO start\nWARN disk\nINFO par").unwrap();
+
+        let mut viewer = ViewerState::new();
+        viewer.load_file(&path).unwrap();
+        viewer.visible_height = 2;
+        viewer.toggle_follow();
+        assert!(viewer.follow.is_some());
+        assert_eq!(viewer.scroll, 1);
+        viewer.apply_filter("warn|error");
+        assert_eq!(viewer.row_count(), 1);
+
+        // Nothing changed yet
+        assert!(!poll_follow_now(&mut viewer));
+
+        // An unfinished line is completed, and new lines arrive
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"tial\nERROR boom\nINFO ok\n").unwrap();
+        assert!(poll_follow_now(&mut viewer));
+        assert_eq!(
+            viewer.lines,
+            [
+                "INFO start",
+                "WARN disk",
+                "INFO partial",
+                "ERROR boom",
+                "INFO ok"
+            ]
+        );
+        let filtered: Vec<usize> = viewer
+            .filter
+            .as_ref()
+            .unwrap()
+            .lines
+            .iter()
+            .map(|l| l.0)
+            .collect();
+        assert_eq!(filtered, [1, 3]);
+        // Still at the bottom of the filtered rows
+        assert_eq!(viewer.scroll, 0);
+        assert_eq!(viewer.top_line(), 1);
+
+        // Truncation reloads the file and keeps follow mode and the filter
+        std::fs::write(&path, "ERROR again\n").unwrap();
+        assert!(poll_follow_now(&mut viewer));
+        assert_eq!(viewer.lines, ["ERROR again"]);
+        assert!(viewer.follow.is_some());
+        assert_eq!(viewer.filter_pattern(), Some("warn|error"));
+        assert_eq!(viewer.row_count(), 1);
+        assert_eq!(
+            viewer.message.as_deref(),
+            Some("File was truncated, reloaded")
+        );
+
+        // Rotation: a new file replaces the old one
+        std::fs::rename(&path, dir.path().join("service.log.1")).unwrap();
+        assert!(!poll_follow_now(&mut viewer));
+        std::fs::write(&path, "INFO fresh\nERROR fresh\n").unwrap();
+        assert!(poll_follow_now(&mut viewer));
+        assert_eq!(viewer.lines, ["INFO fresh", "ERROR fresh"]);
+        assert_eq!(
+            viewer.message.as_deref(),
+            Some("File was replaced, reopened")
+        );
+
+        viewer.apply_filter("");
+        assert_eq!(viewer.row_count(), 2);
+    }
+
+    #[test]
+    fn test_detected_encoding_and_cycling() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("utf16.txt");
+        let bytes = TextEncoding::Utf16Le.encode("첫 줄\nsecond").unwrap();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut viewer = ViewerState::new();
+        viewer.load_file(&path).unwrap();
+        // NUL bytes of UTF-16 do not make the file binary
+        assert!(!viewer.is_binary);
+        assert_eq!(viewer.encoding, "UTF-16LE");
+        assert_eq!(viewer.lines, ["첫 줄", "second"]);
+
+        let path = dir.path().join("korean.txt");
+        std::fs::write(&path, TextEncoding::EucKr.encode("안녕하세요").unwrap()).unwrap();
+        viewer.load_file(&path).unwrap();
+        assert_eq!(viewer.encoding, "EUC-KR");
+        assert_eq!(viewer.lines, ["안녕하세요"]);
+        viewer.cycle_encoding();
+        assert_eq!(viewer.text_encoding, TextEncoding::ShiftJis);
+        assert_ne!(viewer.lines, ["안녕하세요"]);
+    }
+
+    #[test]
+    fn test_hex_edit_saves_or_reverts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blob.bin");
+        std::fs::write(&path, [0u8, 1, 2, 3]).unwrap();
+
+        let mut viewer = ViewerState::new();
+        viewer.load_file(&path).unwrap();
+        assert_eq!(viewer.mode, ViewerMode::Hex);
+        viewer.start_hex_edit();
+        let editor = viewer.hex_edit.as_mut().unwrap();
+        editor.type_char('f');
+        editor.type_char('f');
+        // Closing without saving gives the original bytes back
+        viewer.close_hex_edit();
+        assert_eq!(viewer.raw_bytes, [0, 1, 2, 3]);
+
+        viewer.start_hex_edit();
+        let editor = viewer.hex_edit.as_mut().unwrap();
+        editor.delete();
+        editor.save().unwrap();
+        viewer.close_hex_edit();
+        assert_eq!(viewer.raw_bytes, [1, 2, 3]);
+        assert_eq!(viewer.file_size, 3);
+        assert_eq!(std::fs::read(&path).unwrap(), [1, 2, 3]);
+        assert!(viewer.lines[0].starts_with("00000000  01 02 03"));
+    }
+}
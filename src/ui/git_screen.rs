@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
 
 use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
@@ -12,8 +17,13 @@ use ratatui::{
 use unicode_width::UnicodeWidthStr;
 
 use super::app::{App, Screen};
+use super::diff_file_view::ReturnScreen;
 use super::theme::Theme;
-use crate::utils::format::{pad_to_display_width, truncate_to_display_width};
+use crate::config::CommitDraftSettings;
+use crate::services::claude::{self, CancelToken, StreamMessage};
+use crate::services::provider::ProviderKind;
+use crate::services::provider_exec;
+use crate::utils::format::{pad_to_display_width, safe_prefix, truncate_to_display_width};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // 데이터 구조
@@ -24,6 +34,47 @@ pub enum GitTab {
     Commit,
     Log,
     Branch,
+    Stash,
+    Tags,
+    Remotes,
+    Trees,
+}
+
+impl GitTab {
+    /// Tabs in display order (number keys 1..=7)
+    const ALL: [GitTab; 7] = [
+        GitTab::Commit,
+        GitTab::Log,
+        GitTab::Branch,
+        GitTab::Stash,
+        GitTab::Tags,
+        GitTab::Remotes,
+        GitTab::Trees,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            GitTab::Commit => "1:Commit",
+            GitTab::Log => "2:Log",
+            GitTab::Branch => "3:Branch",
+            GitTab::Stash => "4:Stash",
+            GitTab::Tags => "5:Tags",
+            GitTab::Remotes => "6:Remotes",
+            GitTab::Trees => "7:Trees",
+        }
+    }
+
+    fn index(self) -> usize {
+        GitTab::ALL.iter().position(|t| *t == self).unwrap_or(0)
+    }
+
+    fn next(self) -> GitTab {
+        GitTab::ALL[(self.index() + 1) % GitTab::ALL.len()]
+    }
+
+    fn prev(self) -> GitTab {
+        GitTab::ALL[(self.index() + GitTab::ALL.len() - 1) % GitTab::ALL.len()]
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -41,6 +92,74 @@ pub struct GitLogEntry {
     pub author: String,
     pub date: String,
     pub refs: String,
+    /// Commit graph cells for this row (empty when the graph is off)
+    pub graph: String,
+}
+
+/// Who last changed one line of a file
+#[derive(Debug, Clone)]
+pub struct BlameLine {
+    /// Short hash, empty for lines not committed yet
+    pub hash: String,
+    pub author: String,
+    /// Author time (unix seconds)
+    pub time: i64,
+}
+
+/// A file as it was at a commit
+#[derive(Debug, Clone)]
+pub struct GitRevision {
+    /// Repository root
+    pub repo: PathBuf,
+    pub hash: String,
+    /// Path at that commit, relative to the root
+    pub path: String,
+}
+
+/// One commit in the history of a single file
+#[derive(Debug, Clone)]
+pub struct FileRevision {
+    pub entry: GitLogEntry,
+    /// Path of the file at this commit (changes across renames)
+    pub path: String,
+}
+
+/// Commits loaded per page of the log tab
+const LOG_PAGE_SIZE: usize = 200;
+
+/// Lanes of the commit graph, carried from row to row (and page to page)
+#[derive(Debug, Clone, Default)]
+pub struct LogGraph {
+    /// Commit each lane is waiting for (full hashes)
+    lanes: Vec<Option<String>>,
+}
+
+/// Log filters, typed as `author:NAME path:PATH since:DATE until:DATE`
+/// plus free text matched against commit messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    pub author: Option<String>,
+    pub path: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub message: Option<String>,
+}
+
+/// A file changed by a commit
+#[derive(Debug, Clone)]
+pub struct CommitFile {
+    pub status: char,
+    pub path: String,
+    /// Source path of a rename or copy
+    pub old_path: Option<String>,
+}
+
+/// Files of one commit listed next to the log, to open in the file diff view
+pub struct CommitFiles {
+    pub hash: String,
+    pub files: Vec<CommitFile>,
+    pub selected: usize,
+    pub scroll: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -50,16 +169,322 @@ pub struct GitBranchEntry {
     pub is_remote: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct GitStashEntry {
+    pub index: usize,
+    pub message: String,
+    pub date: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitTagEntry {
+    pub name: String,
+    pub hash: String,
+    pub subject: String,
+    pub annotated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitRemoteEntry {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeKind {
+    Worktree,
+    Submodule,
+}
+
+/// A worktree or submodule of the repository
+#[derive(Debug, Clone)]
+pub struct GitTreeEntry {
+    pub kind: TreeKind,
+    /// Absolute path of the checkout
+    pub path: PathBuf,
+    /// Shown name: the worktree path, or the submodule path relative to the root
+    pub name: String,
+    /// Checked-out branch, or "(hash)" when detached
+    pub branch: String,
+    /// Number of changed files; None when there is no checkout
+    pub changes: Option<usize>,
+    /// The worktree this screen was opened in
+    pub is_current: bool,
+    /// "locked", "prunable", "not initialized", ... (empty if none)
+    pub note: String,
+}
+
+/// Upstream of the current branch with ahead/behind commit counts
+#[derive(Debug, Clone)]
+pub struct GitUpstream {
+    pub name: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// An unmerged path left by a merge, rebase, cherry-pick or stash apply
+#[derive(Debug, Clone)]
+pub struct GitConflict {
+    pub path: String,
+    /// Porcelain status pair such as "UU" or "DU"
+    pub code: String,
+    /// Conflict marker blocks still present in the worktree file
+    pub markers: usize,
+}
+
+impl GitConflict {
+    fn describe(&self) -> &'static str {
+        match self.code.as_str() {
+            "UU" => "both modified",
+            "AA" => "both added",
+            "DD" => "both deleted",
+            "AU" => "added by us",
+            "UA" => "added by them",
+            "DU" => "deleted by us",
+            "UD" => "deleted by them",
+            _ => "unmerged",
+        }
+    }
+}
+
+/// Index stages of a conflicted file written out for the three-way merge view
+pub struct ConflictStages {
+    pub base: PathBuf,
+    pub ours: PathBuf,
+    pub theirs: PathBuf,
+    /// Worktree file the merged result is saved to
+    pub output: PathBuf,
+    pub file: String,
+}
+
+/// A cherry-pick, revert, rebase or merge that stopped and waits for --continue/--abort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoOperation {
+    Rebase { step: usize, total: usize },
+    CherryPick,
+    Revert,
+    Merge,
+}
+
+impl RepoOperation {
+    fn label(self) -> String {
+        match self {
+            RepoOperation::Rebase { step, total } if total > 0 => {
+                format!("REBASE {}/{}", step, total)
+            }
+            RepoOperation::Rebase { .. } => "REBASE".to_string(),
+            RepoOperation::CherryPick => "CHERRY-PICK".to_string(),
+            RepoOperation::Revert => "REVERT".to_string(),
+            RepoOperation::Merge => "MERGE".to_string(),
+        }
+    }
+
+    /// git subcommand that continues, skips or aborts this operation
+    fn command(self) -> &'static str {
+        match self {
+            RepoOperation::Rebase { .. } => "rebase",
+            RepoOperation::CherryPick => "cherry-pick",
+            RepoOperation::Revert => "revert",
+            RepoOperation::Merge => "merge",
+        }
+    }
+
+    fn can_skip(self) -> bool {
+        self != RepoOperation::Merge
+    }
+}
+
+/// Action of one line in an interactive rebase todo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    fn label(self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RebaseItem {
+    pub action: RebaseAction,
+    pub hash: String,
+    pub message: String,
+    /// Replacement message for a reword
+    pub new_message: Option<String>,
+}
+
+/// Interactive rebase editor over the commits after `base`, oldest first (todo order)
+pub struct RebaseEditor {
+    /// Parent of the oldest commit; None rebases from the root commit
+    pub base: Option<String>,
+    pub items: Vec<RebaseItem>,
+    pub selected: usize,
+    pub scroll: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum InputMode {
     BranchCreate,
     CommitAmend,
+    StashCreate,
+    TagCreate,
+    TagMessage(String),  // tag name
+    RebaseReword(usize), // index into the rebase editor
+    LogFilter,
+    WorktreeAdd,
+    WorktreeBranch(String), // worktree path
+}
+
+enum GitJobMsg {
+    /// A complete output line
+    Line(String),
+    /// A progress line ending in '\r' that the next output overwrites
+    Progress(String),
+    Done(Result<(), String>),
+}
+
+/// Maximum number of output lines kept for a job
+const JOB_OUTPUT_LIMIT: usize = 500;
+
+/// A long-running git command (fetch/pull/push) whose output streams into the screen
+pub struct GitJob {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub running: bool,
+    pub succeeded: bool,
+    last_is_progress: bool,
+    receiver: Receiver<GitJobMsg>,
+}
+
+/// How commits made from the git screen are signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitSigning {
+    /// Whatever `commit.gpgsign` / `gpg.format` say
+    Config,
+    Gpg,
+    Ssh,
+    Off,
+}
+
+impl CommitSigning {
+    fn next(self) -> Self {
+        match self {
+            CommitSigning::Config => CommitSigning::Gpg,
+            CommitSigning::Gpg => CommitSigning::Ssh,
+            CommitSigning::Ssh => CommitSigning::Off,
+            CommitSigning::Off => CommitSigning::Config,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CommitSigning::Config => "git config",
+            CommitSigning::Gpg => "GPG",
+            CommitSigning::Ssh => "SSH",
+            CommitSigning::Off => "off",
+        }
+    }
+}
+
+/// Signing and hook options applied to commit and amend
+#[derive(Debug, Clone, Copy)]
+pub struct CommitOptions {
+    pub signing: CommitSigning,
+    /// Pass --no-verify (pre-commit and commit-msg hooks do not run)
+    pub skip_hooks: bool,
+}
+
+impl Default for CommitOptions {
+    fn default() -> Self {
+        Self {
+            signing: CommitSigning::Config,
+            skip_hooks: false,
+        }
+    }
+}
+
+/// Full output of a commit rejected by a hook
+pub struct HookOutput {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+/// Staged diff sent for a commit message draft is cut off after this many bytes
+const DRAFT_DIFF_LIMIT: usize = 40_000;
+
+/// A commit message being written by an AI provider, streamed into the message box
+pub struct CommitDraft {
+    pub provider: ProviderKind,
+    receiver: Receiver<StreamMessage>,
+    cancel: Arc<CancelToken>,
+    /// Message to restore if the draft fails or is cancelled
+    previous: String,
 }
 
 #[derive(Debug, Clone)]
 pub enum ConfirmAction {
     BranchDelete(String),
     RestoreToCommit(String), // hash
+    DiscardChanges(String),  // file path (selection taken from the hunk view)
+    StashDrop(usize),
+    TagDelete(String),
+    MarkResolved(String),    // file path that still has conflict markers
+    CherryPick(Vec<String>), // hashes, oldest first
+    Revert(Vec<String>),     // hashes, newest first
+    RunRebase,
+    AbortOperation(RepoOperation),
+    WorktreeRemove(PathBuf, usize), // path, number of changed files to discard
+    CommitDraft,
+    SkipHooks,
+}
+
+/// Which diff the hunk view shows: worktree vs index, or index vs HEAD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkSide {
+    Unstaged,
+    Staged,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub new_start: usize,
+    /// Text after the second "@@" (usually the enclosing function)
+    pub context: String,
+    /// Raw hunk lines including their ' ', '+', '-' or '\\' prefix
+    pub lines: Vec<String>,
+}
+
+/// `git diff` output for a single file, split into its header and hunks
+#[derive(Debug, Clone, Default)]
+pub struct FileDiff {
+    pub header: Vec<String>,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Interactive hunk/line staging view for one file
+pub struct HunkView {
+    pub file: String,
+    pub side: HunkSide,
+    pub diff: FileDiff,
+    /// Row under the cursor (hunk headers and lines are rows)
+    pub cursor: usize,
+    /// Start of a line selection, if one is active
+    pub anchor: Option<usize>,
+    pub scroll: usize,
 }
 
 pub struct GitScreenState {
@@ -77,6 +502,12 @@ pub struct GitScreenState {
     pub commit_input_active: bool,
     pub commit_selected: usize,
     pub commit_scroll: usize,
+    pub draft: Option<CommitDraft>,
+    /// The message came from an AI draft; committing it asks for confirmation
+    pub message_drafted: bool,
+    pub draft_settings: CommitDraftSettings,
+    pub commit_options: CommitOptions,
+    pub hook_output: Option<HookOutput>,
 
     // Log tab
     pub log_entries: Vec<GitLogEntry>,
@@ -84,12 +515,56 @@ pub struct GitScreenState {
     pub log_scroll: usize,
     pub log_detail: Option<String>,
     pub log_detail_scroll: usize,
+    /// Branch whose history is shown instead of HEAD's
+    pub log_ref: Option<String>,
+    /// Hashes marked for cherry-pick/revert
+    pub log_marked: Vec<String>,
+    pub log_filter: LogFilter,
+    pub log_graph: LogGraph,
+    /// No more commits to page in
+    pub log_exhausted: bool,
+    pub log_files: Option<CommitFiles>,
+    pub rebase_editor: Option<RebaseEditor>,
+
+    /// Operation stopped in the middle (conflicts or a failed step)
+    pub operation: Option<RepoOperation>,
+
+    // Conflict list (commit tab)
+    pub conflicts: Vec<GitConflict>,
+    pub conflict_view: bool,
+    pub conflict_selected: usize,
+    pub conflict_scroll: usize,
+
+    // Hunk staging view (commit tab)
+    pub hunk_view: Option<HunkView>,
 
     // Branch tab
     pub branches: Vec<GitBranchEntry>,
     pub branch_selected: usize,
     pub branch_scroll: usize,
 
+    // Stash tab
+    pub stashes: Vec<GitStashEntry>,
+    pub stash_selected: usize,
+    pub stash_scroll: usize,
+
+    // Tags tab
+    pub tags: Vec<GitTagEntry>,
+    pub tag_selected: usize,
+    pub tag_scroll: usize,
+
+    // Remotes tab
+    pub remotes: Vec<GitRemoteEntry>,
+    pub remote_selected: usize,
+    pub remote_scroll: usize,
+    pub upstream: Option<GitUpstream>,
+    pub job: Option<GitJob>,
+
+    // Trees tab (loaded when the tab is opened)
+    pub trees: Vec<GitTreeEntry>,
+    pub tree_selected: usize,
+    pub tree_scroll: usize,
+
     // Dialog
     pub input_mode: Option<InputMode>,
     pub input_buffer: String,
@@ -101,13 +576,18 @@ pub struct GitScreenState {
 
 impl GitScreenState {
     pub fn new(repo_path: PathBuf) -> Self {
-        stage_all(&repo_path);
         let branch_name = get_current_branch(&repo_path);
         let status_files = get_status(&repo_path);
-        let log_entries = get_log(&repo_path, 200);
         let branches = get_branches(&repo_path);
-
-        Self {
+        let stashes = get_stashes(&repo_path);
+        let tags = get_tags(&repo_path);
+        let remotes = get_remotes(&repo_path);
+        let upstream = get_upstream(&repo_path);
+        let operation = get_operation(&repo_path);
+        let conflicts = get_conflicts(&repo_path, &status_files);
+        let conflict_view = !conflicts.is_empty();
+
+        let mut state = Self {
             repo_path,
             current_tab: GitTab::Commit,
             branch_name,
@@ -118,26 +598,69 @@ impl GitScreenState {
             commit_input_active: false,
             commit_selected: 0,
             commit_scroll: 0,
-            log_entries,
+            draft: None,
+            message_drafted: false,
+            draft_settings: CommitDraftSettings::default(),
+            commit_options: CommitOptions::default(),
+            hook_output: None,
+            log_entries: Vec::new(),
             log_selected: 0,
             log_scroll: 0,
             log_detail: None,
             log_detail_scroll: 0,
+            log_ref: None,
+            log_marked: Vec::new(),
+            log_filter: LogFilter::default(),
+            log_graph: LogGraph::default(),
+            log_exhausted: false,
+            log_files: None,
+            rebase_editor: None,
+            operation,
+            conflicts,
+            conflict_view,
+            conflict_selected: 0,
+            conflict_scroll: 0,
+            hunk_view: None,
             branches,
             branch_selected: 0,
             branch_scroll: 0,
+            stashes,
+            stash_selected: 0,
+            stash_scroll: 0,
+            tags,
+            tag_selected: 0,
+            tag_scroll: 0,
+            remotes,
+            remote_selected: 0,
+            remote_scroll: 0,
+            upstream,
+            job: None,
+            trees: Vec::new(),
+            tree_selected: 0,
+            tree_scroll: 0,
             input_mode: None,
             input_buffer: String::new(),
             confirm_action: None,
             confirm_selected_button: 1, // Default: No
             message: None,
             message_timer: 0,
-        }
+        };
+        state.load_log(LOG_PAGE_SIZE);
+        state
     }
 
     fn refresh_status(&mut self) {
         self.branch_name = get_current_branch(&self.repo_path);
         self.status_files = get_status(&self.repo_path);
+        self.operation = get_operation(&self.repo_path);
+        self.conflicts = get_conflicts(&self.repo_path, &self.status_files);
+        self.conflict_selected = self
+            .conflict_selected
+            .min(self.conflicts.len().saturating_sub(1));
+        // Keep the list up while an operation waits for "continue"
+        if self.conflicts.is_empty() && self.operation.is_none() {
+            self.conflict_view = false;
+        }
         let len = self.status_files.len();
         if self.status_selected >= len {
             self.status_selected = len.saturating_sub(1);
@@ -147,117 +670,1534 @@ impl GitScreenState {
         }
     }
 
+    /// Re-read everything, e.g. after returning from the file diff or merge view
+    pub fn reload(&mut self) {
+        self.refresh_all();
+    }
+
+    /// Load the first `count` commits of the log, restarting the graph
+    fn load_log(&mut self, count: usize) {
+        self.log_graph = LogGraph::default();
+        let graph = if self.log_filter.allows_graph() {
+            Some(&mut self.log_graph)
+        } else {
+            None
+        };
+        self.log_entries = get_log_page(
+            &self.repo_path,
+            self.log_ref.as_deref(),
+            &self.log_filter,
+            0,
+            count,
+            graph,
+        );
+        self.log_exhausted = self.log_entries.len() < count;
+    }
+
+    /// Append the next page of commits once the selection nears the end
+    fn load_more_log(&mut self) {
+        if self.log_exhausted || self.log_selected + 20 < self.log_entries.len() {
+            return;
+        }
+        let graph = if self.log_filter.allows_graph() {
+            Some(&mut self.log_graph)
+        } else {
+            None
+        };
+        let more = get_log_page(
+            &self.repo_path,
+            self.log_ref.as_deref(),
+            &self.log_filter,
+            self.log_entries.len(),
+            LOG_PAGE_SIZE,
+            graph,
+        );
+        self.log_exhausted = more.len() < LOG_PAGE_SIZE;
+        self.log_entries.extend(more);
+    }
+
+    fn set_log_filter(&mut self, filter: LogFilter) {
+        self.log_filter = filter;
+        self.log_marked.clear();
+        self.log_selected = 0;
+        self.log_scroll = 0;
+        self.log_detail = None;
+        self.log_files = None;
+        self.load_log(LOG_PAGE_SIZE);
+    }
+
     fn refresh_all(&mut self) {
         self.refresh_status();
-        self.log_entries = get_log(&self.repo_path, 200);
+        // Keep the pages loaded so far
+        self.load_log(self.log_entries.len().max(LOG_PAGE_SIZE));
+        let entries = &self.log_entries;
+        self.log_marked
+            .retain(|hash| entries.iter().any(|e| &e.hash == hash));
         self.branches = get_branches(&self.repo_path);
+        self.stashes = get_stashes(&self.repo_path);
+        self.tags = get_tags(&self.repo_path);
+        self.remotes = get_remotes(&self.repo_path);
+        self.upstream = get_upstream(&self.repo_path);
         if self.log_selected >= self.log_entries.len() {
             self.log_selected = self.log_entries.len().saturating_sub(1);
         }
         if self.branch_selected >= self.branches.len() {
             self.branch_selected = self.branches.len().saturating_sub(1);
         }
+        self.stash_selected = self
+            .stash_selected
+            .min(self.stashes.len().saturating_sub(1));
+        self.tag_selected = self.tag_selected.min(self.tags.len().saturating_sub(1));
+        self.remote_selected = self
+            .remote_selected
+            .min(self.remotes.len().saturating_sub(1));
+        if self.current_tab == GitTab::Trees {
+            self.refresh_trees();
+        }
     }
 
-    fn show_msg(&mut self, msg: &str) {
-        self.message = Some(msg.to_string());
-        self.message_timer = 4;
+    /// Reload worktrees and submodules. Only done for the trees tab since it
+    /// runs `git status` in every checkout.
+    fn refresh_trees(&mut self) {
+        self.trees = get_trees(&self.repo_path);
+        self.tree_selected = self.tree_selected.min(self.trees.len().saturating_sub(1));
     }
-}
-
-// ═══════════════════════════════════════════════════════════════════════════════
-// Git CLI 함수들
-// ═══════════════════════════════════════════════════════════════════════════════
-
-/// Create a git Command with safe.directory configured for the given path
-fn git_cmd(path: &Path) -> Command {
-    let mut cmd = Command::new("git");
-    cmd.arg("-c")
-        .arg(format!("safe.directory={}", path.to_string_lossy()));
-    cmd.arg("-C").arg(path);
-    cmd
-}
 
-/// Create a git Command with user identity fallback for commit operations
-fn git_commit_cmd(path: &Path) -> Command {
-    let mut cmd = git_cmd(path);
-    // Check if user.name is configured
-    let has_name = git_cmd(path)
-        .args(["config", "user.name"])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-    let has_email = git_cmd(path)
-        .args(["config", "user.email"])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-    if !has_name {
-        let user = std::env::var("USER").unwrap_or_else(|_| "User".to_string());
-        cmd.arg("-c").arg(format!("user.name={}", user));
+    fn set_tab(&mut self, tab: GitTab) {
+        self.current_tab = tab;
+        match tab {
+            GitTab::Commit => self.refresh_status(),
+            GitTab::Trees => self.refresh_trees(),
+            _ => {}
+        }
     }
-    if !has_email {
-        let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
-        cmd.arg("-c").arg(format!("user.email={}@localhost", user));
+
+    /// Start a fetch/pull/push in the background unless another one is running
+    fn start_job(&mut self, args: Vec<String>) {
+        if self.job.as_ref().is_some_and(|j| j.running) {
+            self.show_msg("Another git command is still running");
+            return;
+        }
+        self.job = Some(GitJob::spawn(&self.repo_path, args));
     }
-    cmd
-}
 
-pub fn is_git_repo(path: &Path) -> bool {
-    git_cmd(path)
-        .args(["rev-parse", "--git-dir"])
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-}
+    /// Ask the configured AI provider for a message for the staged changes.
+    /// The reply streams into the message box; nothing is committed until the
+    /// user confirms.
+    fn start_draft(&mut self) {
+        if self.draft.is_some() {
+            self.show_msg("A draft is already being written");
+            return;
+        }
+        let diff = get_staged_diff(&self.repo_path);
+        if diff.trim().is_empty() {
+            self.show_msg("Stage changes to draft a commit message");
+            return;
+        }
+        let provider = self.draft_settings.provider_kind();
+        let prompt = draft_prompt(&diff);
+        let style = self.draft_settings.style.clone();
+        let work_dir = self.repo_path.to_string_lossy().to_string();
+        let cancel = Arc::new(CancelToken::new());
+        let token = cancel.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = provider_exec::execute_streaming(
+                provider,
+                &prompt,
+                &style,
+                &work_dir,
+                tx.clone(),
+                Some(token),
+            );
+            if let Err(message) = result {
+                let _ = tx.send(StreamMessage::Error {
+                    message,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    exit_code: None,
+                });
+            }
+        });
+        self.draft = Some(CommitDraft {
+            provider,
+            receiver: rx,
+            cancel,
+            previous: std::mem::take(&mut self.commit_message),
+        });
+        self.message_drafted = false;
+        self.commit_input_active = true;
+    }
 
-/// Public wrapper for get_log()
-pub fn get_log_public(path: &Path, count: usize) -> Vec<GitLogEntry> {
-    get_log(path, count)
-}
+    /// Report a failed commit or amend. When hooks ran, their full output is
+    /// shown in a scrollable pane instead of the first line only.
+    fn commit_failed(&mut self, error: &str, what: &str) {
+        let hooks = if self.commit_options.skip_hooks {
+            Vec::new()
+        } else {
+            active_commit_hooks(&self.repo_path)
+        };
+        if hooks.is_empty() {
+            let short_err = error.lines().next().unwrap_or("Commit failed").to_string();
+            self.show_msg(&short_err);
+            return;
+        }
+        let mut lines: Vec<String> = error.lines().map(|l| l.to_string()).collect();
+        if lines.is_empty() {
+            lines.push("(no output)".to_string());
+        }
+        self.hook_output = Some(HookOutput {
+            title: format!("{} failed \u{2014} hooks: {}", what, hooks.join(", ")),
+            lines,
+            scroll: 0,
+        });
+    }
 
-/// Get the repo root directory using git rev-parse --show-toplevel
-pub fn get_repo_root(path: &Path) -> Option<PathBuf> {
-    git_cmd(path)
-        .args(["rev-parse", "--show-toplevel"])
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim().to_string()))
-}
+    pub fn is_drafting(&self) -> bool {
+        self.draft.is_some()
+    }
 
-/// Public wrapper for git_cmd() - for external checkout operations
-pub fn git_cmd_public(path: &Path) -> Command {
-    git_cmd(path)
-}
+    /// Stop a running draft and put the previous message back
+    fn cancel_draft(&mut self) {
+        if let Some(draft) = self.draft.take() {
+            draft
+                .cancel
+                .cancelled
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+            if let Ok(guard) = draft.cancel.child_pid.lock() {
+                if let Some(pid) = *guard {
+                    claude::kill_pid_tree(pid);
+                }
+            }
+            self.commit_message = draft.previous;
+            self.show_msg("Draft cancelled");
+        }
+    }
 
-fn get_current_branch(path: &Path) -> String {
-    git_cmd(path)
-        .args(["branch", "--show-current"])
-        .output()
+    /// Append streamed draft text and finish the draft when the provider is done
+    fn poll_draft(&mut self) {
+        let draft = match self.draft {
+            Some(ref mut d) => d,
+            None => return,
+        };
+        let result = loop {
+            match draft.receiver.try_recv() {
+                Ok(StreamMessage::Text { content }) => {
+                    if !self.commit_message.is_empty() && !self.commit_message.ends_with('\n') {
+                        self.commit_message.push('\n');
+                    }
+                    self.commit_message.push_str(&content);
+                }
+                Ok(StreamMessage::Done { result, .. }) => break Ok(result),
+                Ok(StreamMessage::Error { message, .. }) => break Err(message),
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    break Err(format!(
+                        "{} stopped unexpectedly",
+                        draft.provider.display_name()
+                    ))
+                }
+            }
+        };
+        let draft = match self.draft.take() {
+            Some(d) => d,
+            None => return,
+        };
+        // The final result is authoritative; fall back to what was streamed
+        let text = match result {
+            Ok(ref text) if !text.trim().is_empty() => clean_draft(text),
+            _ => clean_draft(&self.commit_message),
+        };
+        match result {
+            Ok(_) if !text.is_empty() => {
+                self.commit_message = text;
+                self.message_drafted = true;
+                self.commit_input_active = true;
+                self.show_msg(&format!(
+                    "Drafted by {} \u{2014} edit it, then Enter to commit",
+                    draft.provider.display_name()
+                ));
+            }
+            Ok(_) => {
+                self.commit_message = draft.previous;
+                self.show_msg(&format!(
+                    "{} returned an empty message",
+                    draft.provider.display_name()
+                ));
+            }
+            Err(e) => {
+                self.commit_message = draft.previous;
+                let short_err = e.lines().next().unwrap_or("Draft failed").to_string();
+                self.show_msg(&format!("Draft failed: {}", short_err));
+            }
+        }
+    }
+
+    /// Drain output of the background job. Returns true when it finished this tick.
+    pub fn poll(&mut self) -> bool {
+        self.poll_draft();
+        let finished = match self.job {
+            Some(ref mut job) => job.poll(),
+            None => false,
+        };
+        if finished {
+            let summary = self.job.as_ref().map(|job| {
+                if job.succeeded {
+                    format!("{}: done", job.title)
+                } else {
+                    // The last line is the exit status; prefer git's own error message
+                    let detail = job
+                        .lines
+                        .iter()
+                        .rev()
+                        .skip(1)
+                        .find(|l| !l.trim().is_empty())
+                        .or(job.lines.last());
+                    format!("{}: {}", job.title, detail.map_or("failed", |l| l.as_str()))
+                }
+            });
+            self.refresh_all();
+            if let Some(summary) = summary {
+                self.show_msg(&summary);
+            }
+            // A pull that stopped on conflicts
+            if !self.conflicts.is_empty() {
+                self.open_conflict_view();
+            }
+        }
+        finished
+    }
+
+    pub fn is_job_running(&self) -> bool {
+        self.job.as_ref().is_some_and(|j| j.running)
+    }
+
+    fn show_msg(&mut self, msg: &str) {
+        self.message = Some(msg.to_string());
+        self.message_timer = 4;
+    }
+
+    fn conflict_count(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    fn open_conflict_view(&mut self) {
+        self.current_tab = GitTab::Commit;
+        self.conflict_view = true;
+        self.conflict_selected = 0;
+        self.hunk_view = None;
+        self.log_detail = None;
+        self.commit_input_active = false;
+    }
+
+    /// Show the log of `branch` (None: the current branch)
+    fn set_log_ref(&mut self, branch: Option<String>) {
+        self.log_ref = branch;
+        self.log_marked.clear();
+        self.log_selected = 0;
+        self.log_scroll = 0;
+        self.log_files = None;
+        self.load_log(LOG_PAGE_SIZE);
+    }
+
+    /// Commits to cherry-pick or revert: the marked ones, or the selected one
+    fn target_commits(&self, oldest_first: bool) -> Vec<String> {
+        let mut hashes: Vec<String> = if self.log_marked.is_empty() {
+            self.log_entries
+                .get(self.log_selected)
+                .map(|e| vec![e.hash.clone()])
+                .unwrap_or_default()
+        } else {
+            // Log order is newest first
+            self.log_entries
+                .iter()
+                .filter(|e| self.log_marked.contains(&e.hash))
+                .map(|e| e.hash.clone())
+                .collect()
+        };
+        if oldest_first {
+            hashes.reverse();
+        }
+        hashes
+    }
+
+    /// Open the rebase editor for the selected commit and everything above it
+    fn open_rebase_editor(&mut self) -> Result<(), String> {
+        if self.log_ref.is_some() {
+            return Err("Interactive rebase works on the current branch's log".to_string());
+        }
+        if self.operation.is_some() {
+            return Err("Finish the operation in progress first".to_string());
+        }
+        let hash = match self.log_entries.get(self.log_selected) {
+            Some(entry) => entry.hash.clone(),
+            None => return Err("No commit selected".to_string()),
+        };
+        let base = commit_parent(&self.repo_path, &hash);
+        let items = get_rebase_items(&self.repo_path, base.as_deref())?;
+        if items.is_empty() {
+            return Err("No commits to rebase".to_string());
+        }
+        self.rebase_editor = Some(RebaseEditor {
+            base,
+            items,
+            selected: 0,
+            scroll: 0,
+        });
+        Ok(())
+    }
+
+    /// Refresh after a cherry-pick/revert/rebase step and report how it ended
+    fn finish_sequence_step(&mut self, result: Result<String, String>, done: &str) {
+        self.refresh_all();
+        match (result, self.operation) {
+            (_, Some(op)) => {
+                let conflicts = self.conflict_count();
+                if conflicts > 0 {
+                    self.open_conflict_view();
+                    self.show_msg(&format!(
+                        "{} stopped: {} conflicted file(s), resolve and stage them, then C to continue",
+                        op.label(),
+                        conflicts
+                    ));
+                } else {
+                    self.show_msg(&format!("{} stopped, C: continue, A: abort", op.label()));
+                }
+            }
+            (Ok(_), None) => self.show_msg(done),
+            (Err(e), None) => {
+                let short_err = e.lines().next().unwrap_or("Failed").to_string();
+                self.show_msg(&short_err);
+            }
+        }
+        if !matches!(self.operation, Some(RepoOperation::Rebase { .. })) {
+            remove_rebase_work_dir(&self.repo_path);
+        }
+    }
+
+    /// Open the hunk view for a file, preferring its unstaged changes
+    fn open_hunk_view(&mut self, entry: &GitFileEntry) -> Result<(), String> {
+        let side = if entry.worktree_status != ' ' {
+            HunkSide::Unstaged
+        } else {
+            HunkSide::Staged
+        };
+        let diff = load_file_diff(&self.repo_path, &entry.path, side)?;
+        if diff.hunks.is_empty() {
+            return Err("No text hunks to show".to_string());
+        }
+        self.hunk_view = Some(HunkView {
+            file: entry.path.clone(),
+            side,
+            diff,
+            cursor: 0,
+            anchor: None,
+            scroll: 0,
+        });
+        Ok(())
+    }
+
+    /// Re-read the hunk view's diff after the index or worktree changed
+    fn reload_hunk_view(&mut self, side: HunkSide) {
+        let repo_path = self.repo_path.clone();
+        if let Some(ref mut view) = self.hunk_view {
+            match load_file_diff(&repo_path, &view.file, side) {
+                Ok(diff) => {
+                    if side != view.side {
+                        view.cursor = 0;
+                        view.scroll = 0;
+                    }
+                    view.side = side;
+                    view.diff = diff;
+                    view.anchor = None;
+                    view.cursor = view.cursor.min(view.row_count().saturating_sub(1));
+                }
+                Err(e) => self.show_msg(&e),
+            }
+        }
+        self.refresh_status();
+    }
+}
+
+impl FileDiff {
+    /// Parse the output of `git diff -- <file>`. Lines keep any trailing '\r'.
+    pub fn parse(text: &str) -> Self {
+        let mut diff = FileDiff::default();
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        if lines.last() == Some(&"") {
+            lines.pop();
+        }
+        for line in lines {
+            if let Some(hunk) = parse_hunk_header(line) {
+                diff.hunks.push(hunk);
+            } else if let Some(hunk) = diff.hunks.last_mut() {
+                hunk.lines.push(line.to_string());
+            } else {
+                diff.header.push(line.to_string());
+            }
+        }
+        diff
+    }
+
+    /// True when the diff creates or deletes the file (only whole hunks apply cleanly)
+    fn is_new_or_deleted(&self) -> bool {
+        self.header
+            .iter()
+            .any(|l| l.starts_with("new file mode") || l.starts_with("deleted file mode"))
+    }
+
+    /// Build a patch containing hunk `index`, limited to the change lines in
+    /// `selected` (indices into the hunk's lines; None keeps every change).
+    /// `reverse` builds the patch for `git apply --reverse`, where unselected
+    /// additions stay as context instead of unselected removals.
+    pub fn hunk_patch(
+        &self,
+        index: usize,
+        selected: Option<&[usize]>,
+        reverse: bool,
+    ) -> Option<String> {
+        let hunk = self.hunks.get(index)?;
+        let mut body = Vec::new();
+        let (mut old_count, mut new_count) = (0, 0);
+        let mut has_change = false;
+        let mut kept_previous = true;
+
+        for (i, line) in hunk.lines.iter().enumerate() {
+            let is_selected = selected.is_none_or(|s| s.contains(&i));
+            let rest = line.get(1..).unwrap_or("");
+            kept_previous = match line.chars().next() {
+                Some('+') if is_selected => {
+                    body.push(line.clone());
+                    new_count += 1;
+                    has_change = true;
+                    true
+                }
+                Some('-') if is_selected => {
+                    body.push(line.clone());
+                    old_count += 1;
+                    has_change = true;
+                    true
+                }
+                // Unselected change lines either become context or disappear
+                Some('+') if reverse => {
+                    body.push(format!(" {}", rest));
+                    old_count += 1;
+                    new_count += 1;
+                    true
+                }
+                Some('-') if !reverse => {
+                    body.push(format!(" {}", rest));
+                    old_count += 1;
+                    new_count += 1;
+                    true
+                }
+                Some('+') | Some('-') => false,
+                Some('\\') => {
+                    if kept_previous {
+                        body.push(line.clone());
+                    }
+                    kept_previous
+                }
+                _ => {
+                    body.push(format!(" {}", rest));
+                    old_count += 1;
+                    new_count += 1;
+                    true
+                }
+            };
+        }
+        if !has_change {
+            return None;
+        }
+
+        let mut patch = String::new();
+        for line in &self.header {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+        patch.push_str(&format!(
+            "@@ -{},{} +{},{} @@{}\n",
+            hunk.old_start, old_count, hunk.new_start, new_count, hunk.context
+        ));
+        for line in body {
+            patch.push_str(&line);
+            patch.push('\n');
+        }
+        Some(patch)
+    }
+}
+
+/// Parse "@@ -a,b +c,d @@ context" into an empty hunk
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let end = rest.find(" @@")?;
+    let mut ranges = rest[..end].split(" +");
+    let start = |range: &str| range.split(',').next()?.parse::<usize>().ok();
+    let old_start = start(ranges.next()?)?;
+    let new_start = start(ranges.next()?)?;
+    Some(DiffHunk {
+        old_start,
+        new_start,
+        context: rest[end + 3..].to_string(),
+        lines: Vec::new(),
+    })
+}
+
+impl HunkView {
+    pub fn row_count(&self) -> usize {
+        self.diff.hunks.iter().map(|h| h.lines.len() + 1).sum()
+    }
+
+    /// First row (the "@@" header) of hunk `index`
+    fn hunk_row(&self, index: usize) -> usize {
+        self.diff.hunks[..index.min(self.diff.hunks.len())]
+            .iter()
+            .map(|h| h.lines.len() + 1)
+            .sum()
+    }
+
+    /// Hunk and line index of a row (None for the line of a hunk header)
+    fn locate(&self, row: usize) -> Option<(usize, Option<usize>)> {
+        let mut start = 0;
+        for (i, hunk) in self.diff.hunks.iter().enumerate() {
+            let end = start + hunk.lines.len() + 1;
+            if row < end {
+                return Some((i, (row > start).then(|| row - start - 1)));
+            }
+            start = end;
+        }
+        None
+    }
+
+    /// Hunk under the cursor and the selected change lines in it
+    /// (None when no line selection is active, meaning the whole hunk)
+    fn selection(&self) -> Option<(usize, Option<Vec<usize>>)> {
+        let (hunk, _) = self.locate(self.cursor)?;
+        let anchor = match self.anchor {
+            Some(anchor) => anchor,
+            None => return Some((hunk, None)),
+        };
+        let (lo, hi) = (anchor.min(self.cursor), anchor.max(self.cursor));
+        let first_row = self.hunk_row(hunk) + 1;
+        let lines = self.diff.hunks[hunk]
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(i, l)| {
+                (lo..=hi).contains(&(first_row + i)) && (l.starts_with('+') || l.starts_with('-'))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        Some((hunk, Some(lines)))
+    }
+
+    fn is_selected_row(&self, row: usize) -> bool {
+        match self.anchor {
+            Some(anchor) => {
+                let same_hunk =
+                    self.locate(row).map(|(h, _)| h) == self.locate(self.cursor).map(|(h, _)| h);
+                same_hunk && (anchor.min(self.cursor)..=anchor.max(self.cursor)).contains(&row)
+            }
+            None => false,
+        }
+    }
+}
+
+impl LogGraph {
+    /// Graph cells for one commit, two columns per lane. Rows must come in
+    /// topological order (children before parents).
+    fn row(&mut self, hash: &str, parents: &[String]) -> String {
+        let col = match self.lanes.iter().position(|l| l.as_deref() == Some(hash)) {
+            Some(c) => c,
+            None => self.free_lane(),
+        };
+        let before = self.lanes.clone();
+
+        // Other lanes waiting for this commit end here (branches forked from it)
+        let merging_in: Vec<usize> = (0..before.len())
+            .filter(|&i| i != col && before[i].as_deref() == Some(hash))
+            .collect();
+        self.lanes[col] = parents.first().cloned();
+        // Further parents of a merge join an existing lane or open a new one
+        let mut branching_out = Vec::new();
+        for parent in parents.iter().skip(1) {
+            match self
+                .lanes
+                .iter()
+                .position(|l| l.as_deref() == Some(parent.as_str()))
+            {
+                Some(i) => branching_out.push((i, false)),
+                None => {
+                    let i = self.free_lane();
+                    self.lanes[i] = Some(parent.clone());
+                    branching_out.push((i, true));
+                }
+            }
+        }
+        for &i in &merging_in {
+            self.lanes[i] = None;
+        }
+
+        let width = self.lanes.len().max(before.len());
+        let mut cells = vec![' '; width * 2];
+        for (i, lane) in before.iter().enumerate() {
+            if lane.is_some() {
+                cells[i * 2] = '\u{2502}';
+            }
+        }
+        cells[col * 2] = '\u{25CF}';
+        for &i in &merging_in {
+            let end = if i > col { '\u{256F}' } else { '\u{2570}' };
+            connect_lanes(&mut cells, col, i, end);
+        }
+        for &(i, new) in &branching_out {
+            let end = match (i > col, new) {
+                (true, true) => '\u{256E}',
+                (false, true) => '\u{256D}',
+                (true, false) => '\u{2524}',
+                (false, false) => '\u{251C}',
+            };
+            connect_lanes(&mut cells, col, i, end);
+        }
+
+        while self.lanes.last().is_some_and(|l| l.is_none()) {
+            self.lanes.pop();
+        }
+        cells.into_iter().collect::<String>().trim_end().to_string()
+    }
+
+    fn free_lane(&mut self) -> usize {
+        match self.lanes.iter().position(|l| l.is_none()) {
+            Some(i) => i,
+            None => {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            }
+        }
+    }
+}
+
+/// Draw a horizontal line from lane `from` to lane `to`, crossing other lanes
+fn connect_lanes(cells: &mut [char], from: usize, to: usize, end: char) {
+    let (lo, hi) = if to > from { (from, to) } else { (to, from) };
+    for cell in &mut cells[lo * 2 + 1..hi * 2] {
+        *cell = if *cell == '\u{2502}' {
+            '\u{253C}'
+        } else {
+            '\u{2500}'
+        };
+    }
+    cells[to * 2] = end;
+}
+
+impl LogFilter {
+    fn parse(input: &str) -> Self {
+        let mut filter = LogFilter::default();
+        let mut words = Vec::new();
+        for token in split_filter_tokens(input) {
+            let (key, value) = match token.split_once(':') {
+                Some((k, v)) if !v.is_empty() => (k, v.to_string()),
+                _ => {
+                    words.push(token);
+                    continue;
+                }
+            };
+            match key {
+                "author" => filter.author = Some(value),
+                "path" => filter.path = Some(value),
+                "since" | "after" => filter.since = Some(value),
+                "until" | "before" => filter.until = Some(value),
+                _ => words.push(token),
+            }
+        }
+        if !words.is_empty() {
+            filter.message = Some(words.join(" "));
+        }
+        filter
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == LogFilter::default()
+    }
+
+    /// Path limiting keeps the graph connected through parent rewriting;
+    /// the other filters drop commits and would leave it broken
+    fn allows_graph(&self) -> bool {
+        self.author.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.message.is_none()
+    }
+
+    /// `git log` options for everything but the path
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(ref author) = self.author {
+            args.push(format!("--author={}", author));
+        }
+        if let Some(ref since) = self.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(ref until) = self.until {
+            args.push(format!("--until={}", until));
+        }
+        if let Some(ref message) = self.message {
+            args.push(format!("--grep={}", message));
+            args.push("--regexp-ignore-case".to_string());
+        }
+        args
+    }
+
+    /// The filter in the syntax `parse` reads
+    fn to_input(&self) -> String {
+        let quote = |v: &str| {
+            if v.contains(' ') {
+                format!("\"{}\"", v)
+            } else {
+                v.to_string()
+            }
+        };
+        let mut parts = Vec::new();
+        for (key, value) in [
+            ("author", &self.author),
+            ("path", &self.path),
+            ("since", &self.since),
+            ("until", &self.until),
+        ] {
+            if let Some(v) = value {
+                parts.push(format!("{}:{}", key, quote(v)));
+            }
+        }
+        if let Some(ref message) = self.message {
+            parts.push(message.clone());
+        }
+        parts.join(" ")
+    }
+}
+
+/// Split on whitespace, keeping double-quoted runs (`since:"2 weeks ago"`) together
+fn split_filter_tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+impl RebaseEditor {
+    /// Swap the selected commit with its neighbour
+    fn move_selected(&mut self, down: bool) {
+        let target = if down {
+            self.selected + 1
+        } else {
+            match self.selected.checked_sub(1) {
+                Some(t) => t,
+                None => return,
+            }
+        };
+        if target < self.items.len() {
+            self.items.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+
+    fn set_action(&mut self, action: RebaseAction) {
+        if let Some(item) = self.items.get_mut(self.selected) {
+            item.action = action;
+            if action != RebaseAction::Reword {
+                item.new_message = None;
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self
+            .items
+            .iter()
+            .find(|item| item.action != RebaseAction::Drop)
+        {
+            Some(first) if matches!(first.action, RebaseAction::Squash | RebaseAction::Fixup) => {
+                Err("The first kept commit has nothing to squash into".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Build the todo file. A reword becomes a pick followed by an `exec` that
+    /// amends the message from a file in `work_dir`, so git never opens an editor;
+    /// the amend skips the commit hooks only when `skip_hooks` is set.
+    /// Returns the todo text and the message files to write.
+    fn todo(&self, work_dir: &Path, skip_hooks: bool) -> (String, Vec<(PathBuf, String)>) {
+        let mut todo = String::new();
+        let mut messages = Vec::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let keyword = match item.action {
+                RebaseAction::Reword => RebaseAction::Pick.label(),
+                action => action.label(),
+            };
+            todo.push_str(&format!("{} {} {}\n", keyword, item.hash, item.message));
+            if let (RebaseAction::Reword, Some(message)) = (item.action, &item.new_message) {
+                let file = work_dir.join(format!("message-{}", i));
+                todo.push_str(&format!(
+                    "exec git commit --amend --only{} --quiet -F {}\n",
+                    if skip_hooks { " --no-verify" } else { "" },
+                    shell_quote(&file.to_string_lossy())
+                ));
+                messages.push((file, message.clone()));
+            }
+        }
+        (todo, messages)
+    }
+}
+
+impl GitJob {
+    /// Run `git <args>` in a background thread, streaming stdout and stderr
+    fn spawn(path: &Path, args: Vec<String>) -> Self {
+        let title = std::iter::once("git")
+            .chain(
+                args.iter()
+                    .map(|a| a.as_str())
+                    .filter(|a| *a != "--progress"),
+            )
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (tx, rx) = mpsc::channel();
+        let mut cmd = git_cmd(path);
+        cmd.args(&args)
+            // Never block on a credential prompt the TUI cannot answer
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        thread::spawn(move || {
+            let mut child = match cmd.spawn() {
+                Ok(c) => c,
+                Err(e) => {
+                    let _ = tx.send(GitJobMsg::Done(Err(e.to_string())));
+                    return;
+                }
+            };
+            let stdout_thread = child.stdout.take().map(|out| {
+                let tx = tx.clone();
+                thread::spawn(move || stream_output(out, &tx))
+            });
+            if let Some(err) = child.stderr.take() {
+                stream_output(err, &tx);
+            }
+            if let Some(handle) = stdout_thread {
+                let _ = handle.join();
+            }
+            let result = match child.wait() {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(match status.code() {
+                    Some(code) => format!("exit code {}", code),
+                    None => "terminated".to_string(),
+                }),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(GitJobMsg::Done(result));
+        });
+
+        GitJob {
+            title,
+            lines: Vec::new(),
+            running: true,
+            succeeded: false,
+            last_is_progress: false,
+            receiver: rx,
+        }
+    }
+
+    /// Apply pending output. Returns true when the command finished this tick.
+    fn poll(&mut self) -> bool {
+        if !self.running {
+            return false;
+        }
+        while let Ok(msg) = self.receiver.try_recv() {
+            match msg {
+                GitJobMsg::Line(line) => self.push_line(line, false),
+                GitJobMsg::Progress(line) => self.push_line(line, true),
+                GitJobMsg::Done(result) => {
+                    self.succeeded = result.is_ok();
+                    if let Err(e) = result {
+                        self.push_line(e, false);
+                    }
+                    self.running = false;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn push_line(&mut self, line: String, progress: bool) {
+        // A progress line is overwritten by whatever comes next
+        if self.last_is_progress {
+            self.lines.pop();
+        }
+        self.lines.push(line);
+        self.last_is_progress = progress;
+        if self.lines.len() > JOB_OUTPUT_LIMIT {
+            let excess = self.lines.len() - JOB_OUTPUT_LIMIT;
+            self.lines.drain(..excess);
+        }
+    }
+}
+
+/// Forward output as lines, treating a lone '\r' as a progress update
+fn stream_output(mut reader: impl Read, tx: &Sender<GitJobMsg>) {
+    let mut splitter = LineSplitter::default();
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                for msg in splitter.feed(&buf[..n]) {
+                    let _ = tx.send(msg);
+                }
+            }
+        }
+    }
+    if let Some(msg) = splitter.finish() {
+        let _ = tx.send(msg);
+    }
+}
+
+#[derive(Default)]
+struct LineSplitter {
+    buf: Vec<u8>,
+    pending_cr: bool,
+}
+
+impl LineSplitter {
+    fn take_line(&mut self) -> String {
+        let line = String::from_utf8_lossy(&self.buf).to_string();
+        self.buf.clear();
+        line
+    }
+
+    fn feed(&mut self, bytes: &[u8]) -> Vec<GitJobMsg> {
+        let mut out = Vec::new();
+        for &b in bytes {
+            if self.pending_cr {
+                self.pending_cr = false;
+                if b == b'\n' {
+                    out.push(GitJobMsg::Line(self.take_line()));
+                    continue;
+                }
+                out.push(GitJobMsg::Progress(self.take_line()));
+            }
+            match b {
+                b'\r' => self.pending_cr = true,
+                b'\n' => out.push(GitJobMsg::Line(self.take_line())),
+                _ => self.buf.push(b),
+            }
+        }
+        out
+    }
+
+    fn finish(&mut self) -> Option<GitJobMsg> {
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(GitJobMsg::Line(self.take_line()))
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Git CLI 함수들
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Create a git Command with safe.directory configured for the given path
+fn git_cmd(path: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-c")
+        .arg(format!("safe.directory={}", path.to_string_lossy()));
+    cmd.arg("-C").arg(path);
+    cmd
+}
+
+/// Create a git Command with user identity fallback for commit operations
+fn git_commit_cmd(path: &Path) -> Command {
+    let mut cmd = git_cmd(path);
+    // Check if user.name is configured
+    let has_name = git_cmd(path)
+        .args(["config", "user.name"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    let has_email = git_cmd(path)
+        .args(["config", "user.email"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if !has_name {
+        let user = std::env::var("USER").unwrap_or_else(|_| "User".to_string());
+        cmd.arg("-c").arg(format!("user.name={}", user));
+    }
+    if !has_email {
+        let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+        cmd.arg("-c").arg(format!("user.email={}@localhost", user));
+    }
+    cmd
+}
+
+pub fn is_git_repo(path: &Path) -> bool {
+    git_cmd(path)
+        .args(["rev-parse", "--git-dir"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Public wrapper for get_log()
+pub fn get_log_public(path: &Path, count: usize) -> Vec<GitLogEntry> {
+    get_log(path, count)
+}
+
+/// Get the repo root directory using git rev-parse --show-toplevel
+pub fn get_repo_root(path: &Path) -> Option<PathBuf> {
+    git_cmd(path)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim().to_string()))
+}
+
+/// Blame of `path` (relative to `dir`) at `rev`, or of the working copy
+/// when None. One entry per line.
+pub fn get_blame(dir: &Path, rev: Option<&str>, path: &str) -> Result<Vec<BlameLine>, String> {
+    let mut cmd = git_cmd(dir);
+    cmd.args(["blame", "--porcelain"]);
+    if let Some(rev) = rev {
+        if rev.starts_with('-') {
+            return Err("Invalid revision".to_string());
+        }
+        cmd.arg(rev);
+    }
+    let output = cmd
+        .args(["--", path])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(parse_blame_porcelain(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git blame --porcelain`. Commit details are only given the first
+/// time a commit appears, so they are remembered by hash.
+fn parse_blame_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut commits: HashMap<String, (String, i64)> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            let (author, time) = commits.get(&current).cloned().unwrap_or_default();
+            let hash = if current.bytes().all(|b| b == b'0') {
+                String::new()
+            } else {
+                current.chars().take(7).collect()
+            };
+            lines.push(BlameLine { hash, author, time });
+        } else if let Some(author) = line.strip_prefix("author ") {
+            commits.entry(current.clone()).or_default().0 = author.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            commits.entry(current.clone()).or_default().1 = time.parse().unwrap_or(0);
+        } else {
+            let first = line.split(' ').next().unwrap_or("");
+            if first.len() == 40 && first.bytes().all(|b| b.is_ascii_hexdigit()) {
+                current = first.to_string();
+            }
+        }
+    }
+    lines
+}
+
+/// Compact age of a unix time, e.g. "5m", "3d", "2y"
+pub fn format_age(time: i64, now: i64) -> String {
+    let secs = (now - time).max(0);
+    match secs {
+        s if s < 60 => "now".to_string(),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s if s < 86400 * 14 => format!("{}d", s / 86400),
+        s if s < 86400 * 60 => format!("{}w", s / (86400 * 7)),
+        s if s < 86400 * 365 => format!("{}mo", s / (86400 * 30)),
+        s => format!("{}y", s / (86400 * 365)),
+    }
+}
+
+/// History of one file across renames (`git log --follow`), newest first.
+/// `name` is relative to `dir`.
+pub fn get_file_history(dir: &Path, name: &str) -> Result<Vec<FileRevision>, String> {
+    // Each commit starts with 0x1e; --name-only adds the file's path at that commit
+    let output = git_cmd(dir)
+        .args([
+            "log",
+            "--follow",
+            "--name-only",
+            "--format=%x1e%h%x1f%s%x1f%an%x1f%ar%x1f%D",
+            "--",
+            name,
+        ])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut revisions: Vec<FileRevision> = Vec::new();
+    for record in stdout.split('\u{1e}').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let header = lines.next().unwrap_or("");
+        let parts: Vec<&str> = header.splitn(5, '\u{1f}').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        // Merges list no file; they keep the path of the newer commit
+        let path = match lines.find(|l| !l.is_empty()) {
+            Some(p) => p.to_string(),
+            None => match revisions.last() {
+                Some(r) => r.path.clone(),
+                None => continue,
+            },
+        };
+        revisions.push(FileRevision {
+            entry: GitLogEntry {
+                hash: parts[0].to_string(),
+                message: parts[1].to_string(),
+                author: parts[2].to_string(),
+                date: parts[3].to_string(),
+                refs: parts.get(4).unwrap_or(&"").to_string(),
+                graph: String::new(),
+            },
+            path,
+        });
+    }
+    Ok(revisions)
+}
+
+/// Write the file of `revision` to a scratch file to view or diff it
+pub fn extract_revision(revision: &GitRevision) -> Result<PathBuf, String> {
+    if !revision.hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Invalid commit hash".to_string());
+    }
+    let output = git_cmd(&revision.repo)
+        .args(["show", &format!("{}:{}", revision.hash, revision.path)])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let dir = git_dir(&revision.repo)
+        .ok_or("Not a git repository")?
+        .join("remotecc-history");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    // Keep the file name so the viewer detects the language
+    let name = Path::new(&revision.path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+    let target = dir.join(format!("{}_{}", revision.hash, name));
+    std::fs::write(&target, output.stdout).map_err(|e| e.to_string())?;
+    Ok(target)
+}
+
+/// Public wrapper for git_cmd() - for external checkout operations
+pub fn git_cmd_public(path: &Path) -> Command {
+    git_cmd(path)
+}
+
+fn get_current_branch(path: &Path) -> String {
+    git_cmd(path)
+        .args(["branch", "--show-current"])
+        .output()
+        .ok()
+        .and_then(|o| {
+            if o.status.success() {
+                let s = String::from_utf8_lossy(&o.stdout).trim().to_string();
+                if s.is_empty() {
+                    // detached HEAD
+                    git_cmd(path)
+                        .args(["rev-parse", "--short", "HEAD"])
+                        .output()
+                        .ok()
+                        .map(|o2| format!("({})", String::from_utf8_lossy(&o2.stdout).trim()))
+                } else {
+                    Some(s)
+                }
+            } else {
+                None
+            }
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn get_status(path: &Path) -> Vec<GitFileEntry> {
+    let output = git_cmd(path).args(["status", "--porcelain=v1"]).output();
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries = Vec::new();
+
+    for line in stdout.lines() {
+        if line.len() < 3 {
+            continue;
+        }
+        let bytes = line.as_bytes();
+        let index_status = bytes[0] as char;
+        let worktree_status = bytes[1] as char;
+        let file_path = &line[3..];
+
+        // Handle rename: "R  old -> new"
+        let display_path = if let Some(pos) = file_path.find(" -> ") {
+            file_path[pos + 4..].to_string()
+        } else {
+            file_path.to_string()
+        };
+
+        let mut entry = GitFileEntry {
+            path: display_path,
+            index_status,
+            worktree_status,
+            staged: index_status != ' ' && index_status != '?',
+        };
+        // Conflicts count as unstaged until `git add` marks them resolved
+        if is_conflicted(&entry) {
+            entry.staged = false;
+        }
+        entries.push(entry);
+    }
+
+    entries
+}
+
+fn get_log(path: &Path, count: usize) -> Vec<GitLogEntry> {
+    get_log_page(path, None, &LogFilter::default(), 0, count, None)
+}
+
+/// One page of the history of `rev` (HEAD when None) in topological order,
+/// newest first. `graph` continues the lanes of the previous page.
+fn get_log_page(
+    path: &Path,
+    rev: Option<&str>,
+    filter: &LogFilter,
+    skip: usize,
+    count: usize,
+    graph: Option<&mut LogGraph>,
+) -> Vec<GitLogEntry> {
+    let count_str = count.to_string();
+    let skip_str = skip.to_string();
+    let mut cmd = git_cmd(path);
+    // Fields are separated by 0x1f so subjects may contain anything.
+    // --parents rewrites parents past commits hidden by a path filter.
+    cmd.args([
+        "log",
+        "--topo-order",
+        "--parents",
+        "--format=%H%x1f%h%x1f%P%x1f%s%x1f%an%x1f%ar%x1f%D",
+        "-n",
+        &count_str,
+        "--skip",
+        &skip_str,
+    ]);
+    cmd.args(filter.args());
+    if let Some(rev) = rev {
+        if rev.starts_with('-') {
+            return Vec::new();
+        }
+        cmd.arg(rev);
+    }
+    cmd.arg("--");
+    if let Some(ref file) = filter.path {
+        cmd.arg(file);
+    }
+    let output = cmd.output();
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries = Vec::new();
+    let mut graph = graph;
+
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.splitn(7, '\u{1f}').collect();
+        if parts.len() < 6 {
+            continue;
+        }
+        let parents: Vec<String> = parts[2].split_whitespace().map(String::from).collect();
+        let graph_cells = match graph {
+            Some(ref mut g) => g.row(parts[0], &parents),
+            None => String::new(),
+        };
+        entries.push(GitLogEntry {
+            hash: parts[1].to_string(),
+            message: parts[3].to_string(),
+            author: parts[4].to_string(),
+            date: parts[5].to_string(),
+            refs: parts.get(6).unwrap_or(&"").to_string(),
+            graph: graph_cells,
+        });
+    }
+
+    entries
+}
+
+/// Files a commit changed, against its first parent
+fn get_commit_files(path: &Path, hash: &str) -> Vec<CommitFile> {
+    if !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Vec::new();
+    }
+    let output = git_cmd(path)
+        .args([
+            "diff-tree",
+            "--no-commit-id",
+            "-r",
+            "-M",
+            "--root",
+            "--diff-merges=first-parent",
+            "--name-status",
+            hash,
+        ])
+        .output();
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    // "M\tpath" or "R100\told\tnew"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let status = fields.next()?.chars().next()?;
+            let first = fields.next()?.to_string();
+            Some(match fields.next() {
+                Some(second) => CommitFile {
+                    status,
+                    path: second.to_string(),
+                    old_path: Some(first),
+                },
+                None => CommitFile {
+                    status,
+                    path: first,
+                    old_path: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Write a file's version before and after `hash` to scratch files for the
+/// file diff view. A side where the file does not exist is left missing.
+fn extract_commit_file(
+    path: &Path,
+    hash: &str,
+    file: &CommitFile,
+) -> Result<(PathBuf, PathBuf), String> {
+    if !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Invalid commit hash".to_string());
+    }
+    let dir = git_dir(path)
+        .ok_or("Not a git repository")?
+        .join("remotecc-diff");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let name = Path::new(&file.path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+
+    let old_path = file.old_path.as_deref().unwrap_or(&file.path);
+    let sides = [
+        (
+            format!("{}^:{}", hash, old_path),
+            format!("{}^_{}", hash, name),
+        ),
+        (
+            format!("{}:{}", hash, file.path),
+            format!("{}_{}", hash, name),
+        ),
+    ];
+    let mut targets = Vec::with_capacity(2);
+    for (object, target_name) in sides {
+        let target = dir.join(target_name);
+        let _ = std::fs::remove_file(&target);
+        let output = git_cmd(path)
+            .args(["show", &object])
+            .output()
+            .map_err(|e| e.to_string())?;
+        if output.status.success() {
+            std::fs::write(&target, output.stdout).map_err(|e| e.to_string())?;
+        }
+        targets.push(target);
+    }
+    let right = targets.pop().ok_or("Cannot read commit")?;
+    let left = targets.pop().ok_or("Cannot read commit")?;
+    Ok((left, right))
+}
+
+fn get_commit_diff(path: &Path, hash: &str) -> String {
+    // Validate hash to prevent command injection
+    if !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return String::new();
+    }
+    git_cmd(path)
+        .args(["show", "--stat", "--patch", hash])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+fn get_file_diff(path: &Path, file: &str, staged: bool) -> String {
+    let mut cmd = git_cmd(path);
+    cmd.arg("diff");
+    if staged {
+        cmd.arg("--cached");
+    }
+    cmd.args(["--", file]);
+
+    cmd.output()
         .ok()
-        .and_then(|o| {
-            if o.status.success() {
-                let s = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                if s.is_empty() {
-                    // detached HEAD
-                    git_cmd(path)
-                        .args(["rev-parse", "--short", "HEAD"])
-                        .output()
-                        .ok()
-                        .map(|o2| format!("({})", String::from_utf8_lossy(&o2.stdout).trim()))
-                } else {
-                    Some(s)
-                }
-            } else {
-                None
-            }
-        })
-        .unwrap_or_else(|| "unknown".to_string())
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
 }
 
-fn get_status(path: &Path) -> Vec<GitFileEntry> {
-    let output = git_cmd(path).args(["status", "--porcelain=v1"]).output();
+fn get_branches(path: &Path) -> Vec<GitBranchEntry> {
+    let output = git_cmd(path)
+        .args([
+            "branch",
+            "--format=%(HEAD)|%(refname:short)|%(objectname:short)|%(refname)",
+        ])
+        .output();
 
     let output = match output {
         Ok(o) if o.status.success() => o,
@@ -268,38 +2208,56 @@ fn get_status(path: &Path) -> Vec<GitFileEntry> {
     let mut entries = Vec::new();
 
     for line in stdout.lines() {
-        if line.len() < 3 {
-            continue;
+        let parts: Vec<&str> = line.splitn(4, '|').collect();
+        if parts.len() >= 2 {
+            let is_current = parts[0] == "*";
+            let name = parts[1].to_string();
+            let is_remote = parts.get(3).is_some_and(|r| r.starts_with("refs/remotes/"));
+
+            entries.push(GitBranchEntry {
+                name,
+                is_current,
+                is_remote,
+            });
         }
-        let bytes = line.as_bytes();
-        let index_status = bytes[0] as char;
-        let worktree_status = bytes[1] as char;
-        let file_path = &line[3..];
+    }
 
-        // Handle rename: "R  old -> new"
-        let display_path = if let Some(pos) = file_path.find(" -> ") {
-            file_path[pos + 4..].to_string()
-        } else {
-            file_path.to_string()
-        };
+    entries
+}
 
-        let staged = index_status != ' ' && index_status != '?';
+fn get_stashes(path: &Path) -> Vec<GitStashEntry> {
+    let output = git_cmd(path)
+        .args(["stash", "list", "--format=%gs|%cr"])
+        .output();
 
-        entries.push(GitFileEntry {
-            path: display_path,
-            index_status,
-            worktree_status,
-            staged,
-        });
-    }
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
 
-    entries
+    // `git stash list` prints stash@{0}, stash@{1}, ... in order
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let (message, date) = line.rsplit_once('|').unwrap_or((line, ""));
+            GitStashEntry {
+                index,
+                message: message.to_string(),
+                date: date.to_string(),
+            }
+        })
+        .collect()
 }
 
-fn get_log(path: &Path, count: usize) -> Vec<GitLogEntry> {
-    let count_str = count.to_string();
+fn get_tags(path: &Path) -> Vec<GitTagEntry> {
     let output = git_cmd(path)
-        .args(["log", "--format=%h|%s|%an|%ar|%D", "-n", &count_str])
+        .args([
+            "tag",
+            "--list",
+            "--sort=-creatordate",
+            "--format=%(refname:short)|%(objectname:short)|%(objecttype)|%(contents:subject)",
+        ])
         .output();
 
     let output = match output {
@@ -310,87 +2268,623 @@ fn get_log(path: &Path, count: usize) -> Vec<GitLogEntry> {
     let stdout = String::from_utf8_lossy(&output.stdout);
     let mut entries = Vec::new();
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.splitn(5, '|').collect();
-        if parts.len() >= 4 {
-            entries.push(GitLogEntry {
-                hash: parts[0].to_string(),
-                message: parts.get(1).unwrap_or(&"").to_string(),
-                author: parts.get(2).unwrap_or(&"").to_string(),
-                date: parts.get(3).unwrap_or(&"").to_string(),
-                refs: parts.get(4).unwrap_or(&"").to_string(),
-            });
-        }
+    for line in stdout.lines() {
+        let parts: Vec<&str> = line.splitn(4, '|').collect();
+        if parts.len() >= 3 {
+            entries.push(GitTagEntry {
+                name: parts[0].to_string(),
+                hash: parts[1].to_string(),
+                annotated: parts[2] == "tag",
+                subject: parts.get(3).unwrap_or(&"").to_string(),
+            });
+        }
+    }
+
+    entries
+}
+
+fn get_remotes(path: &Path) -> Vec<GitRemoteEntry> {
+    let output = git_cmd(path).args(["remote", "-v"]).output();
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+
+    // "origin\thttps://... (fetch)" — keep one entry per remote (fetch URL)
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries: Vec<GitRemoteEntry> = Vec::new();
+    for line in stdout.lines() {
+        let mut parts = line.split_whitespace();
+        let (name, url) = match (parts.next(), parts.next()) {
+            (Some(n), Some(u)) => (n, u),
+            _ => continue,
+        };
+        if !entries.iter().any(|e| e.name == name) {
+            entries.push(GitRemoteEntry {
+                name: name.to_string(),
+                url: url.to_string(),
+            });
+        }
+    }
+    entries
+}
+
+/// Worktrees of the repository followed by its submodules
+fn get_trees(path: &Path) -> Vec<GitTreeEntry> {
+    let mut trees = get_worktrees(path);
+    if let Some(root) = get_repo_root(path) {
+        trees.extend(get_submodules(&root));
+    }
+    trees
+}
+
+fn get_worktrees(path: &Path) -> Vec<GitTreeEntry> {
+    let output = match run_git(path, &["worktree", "list", "--porcelain"]) {
+        Ok(o) => o,
+        Err(_) => return Vec::new(),
+    };
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let current = get_repo_root(path).map(|r| canonical(&r));
+    let mut trees = parse_worktree_list(&output);
+    for tree in trees.iter_mut() {
+        tree.is_current = current.as_deref() == Some(canonical(&tree.path).as_path());
+        if tree.note != "bare" {
+            tree.changes = count_changes(&tree.path);
+        }
+    }
+    trees
+}
+
+/// Parse `git worktree list --porcelain`: blank-line separated records of
+/// "worktree", "HEAD", "branch"/"detached" and optional "bare"/"locked"/"prunable"
+fn parse_worktree_list(output: &str) -> Vec<GitTreeEntry> {
+    let mut trees = Vec::new();
+    for record in output.split("\n\n") {
+        let mut path = None;
+        let mut head = String::new();
+        let mut branch = None;
+        let mut notes = Vec::new();
+        for line in record.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "worktree" => path = Some(PathBuf::from(value)),
+                "HEAD" => head = value.chars().take(7).collect(),
+                "branch" => {
+                    branch = Some(
+                        value
+                            .strip_prefix("refs/heads/")
+                            .unwrap_or(value)
+                            .to_string(),
+                    )
+                }
+                "bare" | "locked" | "prunable" => notes.push(key),
+                _ => {}
+            }
+        }
+        let path = match path {
+            Some(p) => p,
+            None => continue,
+        };
+        trees.push(GitTreeEntry {
+            kind: TreeKind::Worktree,
+            name: path.to_string_lossy().to_string(),
+            path,
+            branch: branch.unwrap_or_else(|| format!("({})", head)),
+            changes: None,
+            is_current: false,
+            note: notes.join(", "),
+        });
+    }
+    trees
+}
+
+/// Submodules from `git submodule status`, run at the repository root
+fn get_submodules(root: &Path) -> Vec<GitTreeEntry> {
+    let output = match run_git(root, &["submodule", "status"]) {
+        Ok(o) => o,
+        Err(_) => return Vec::new(),
+    };
+    let mut trees = Vec::new();
+    // "<state><sha> <path>[ (<describe>)]" with state ' ', '-', '+' or 'U'
+    for line in output.lines() {
+        let mut chars = line.chars();
+        let state = match chars.next() {
+            Some(c) => c,
+            None => continue,
+        };
+        let (sha, rest) = match chars.as_str().split_once(' ') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let name = match rest.strip_suffix(')').and_then(|r| r.rsplit_once(" (")) {
+            Some((name, _)) => name,
+            None => rest,
+        };
+        let path = root.join(name);
+        let initialized = state != '-';
+        let note = match state {
+            '-' => "not initialized",
+            '+' => "checkout differs from index",
+            'U' => "merge conflict",
+            _ => "",
+        };
+        trees.push(GitTreeEntry {
+            kind: TreeKind::Submodule,
+            name: name.to_string(),
+            branch: if initialized {
+                get_current_branch(&path)
+            } else {
+                format!("({})", sha.chars().take(7).collect::<String>())
+            },
+            changes: if initialized {
+                count_changes(&path)
+            } else {
+                None
+            },
+            path,
+            is_current: false,
+            note: note.to_string(),
+        });
+    }
+    trees
+}
+
+/// Number of changed and untracked files in a checkout
+fn count_changes(path: &Path) -> Option<usize> {
+    if !path.is_dir() {
+        return None;
+    }
+    let output = git_cmd(path)
+        .args(["status", "--porcelain"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).lines().count())
+}
+
+/// Add a worktree at `dir`, relative to the root of the current worktree. An
+/// existing branch is checked out and a new one is created from HEAD; without
+/// a branch git names one after the directory.
+fn add_worktree(path: &Path, dir: &str, branch: Option<&str>) -> Result<String, String> {
+    if dir.is_empty() {
+        return Err("No path given".to_string());
+    }
+    let root = get_repo_root(path).unwrap_or_else(|| path.to_path_buf());
+    let target = root.join(dir);
+    let target_str = target.to_string_lossy().to_string();
+    let mut args = vec!["worktree", "add"];
+    match branch {
+        Some(b) if !is_valid_ref_name(b) => return Err(format!("Invalid branch name: {}", b)),
+        Some(b)
+            if run_git(
+                path,
+                &[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("refs/heads/{}", b),
+                ],
+            )
+            .is_ok() =>
+        {
+            args.extend([target_str.as_str(), b]);
+        }
+        Some(b) => args.extend(["-b", b, target_str.as_str()]),
+        None => args.push(target_str.as_str()),
+    }
+    run_git(path, &args)?;
+    Ok(format!("Added worktree {}", target_str))
+}
+
+fn remove_worktree(path: &Path, tree: &Path, force: bool) -> Result<(), String> {
+    let tree = tree.to_string_lossy();
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.push(&tree);
+    run_git(path, &args).map(|_| ())
+}
+
+fn get_upstream(path: &Path) -> Option<GitUpstream> {
+    let name = git_cmd(path)
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())?;
+
+    let counts = git_cmd(path)
+        .args(["rev-list", "--left-right", "--count", "HEAD...@{u}"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+    let mut parts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+
+    Some(GitUpstream {
+        name,
+        ahead: parts.next().unwrap_or(0),
+        behind: parts.next().unwrap_or(0),
+    })
+}
+
+/// Run a git command that needs a user identity (stash, annotated tags)
+fn run_git(path: &Path, args: &[&str]) -> Result<String, String> {
+    let output = git_commit_cmd(path)
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn stash_show(path: &Path, index: usize) -> String {
+    git_cmd(path)
+        .args(["stash", "show", "--stat", "--patch"])
+        .arg(format!("stash@{{{}}}", index))
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+fn tag_show(path: &Path, name: &str) -> String {
+    if name.starts_with('-') {
+        return String::new();
+    }
+    git_cmd(path)
+        .args(["show", "--stat", name, "--"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+/// Whether a status entry is an unresolved merge conflict
+fn is_conflicted(entry: &GitFileEntry) -> bool {
+    matches!(
+        (entry.index_status, entry.worktree_status),
+        ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D')
+    )
+}
+
+fn get_conflicts(path: &Path, status: &[GitFileEntry]) -> Vec<GitConflict> {
+    status
+        .iter()
+        .filter(|e| is_conflicted(e))
+        .map(|e| GitConflict {
+            path: e.path.clone(),
+            code: format!("{}{}", e.index_status, e.worktree_status),
+            markers: std::fs::read_to_string(path.join(&e.path))
+                .map(|text| text.lines().filter(|l| l.starts_with("<<<<<<<")).count())
+                .unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Write the base/ours/theirs index stages of `file` to scratch files for merging
+fn extract_conflict_stages(path: &Path, file: &str) -> Result<ConflictStages, String> {
+    let dir = git_dir(path)
+        .ok_or("Not a git repository")?
+        .join("remotecc-merge");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let name = Path::new(file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+
+    let mut stages = Vec::with_capacity(3);
+    for (stage, label) in [(1, "base"), (2, "ours"), (3, "theirs")] {
+        let output = git_cmd(path)
+            .arg("show")
+            .arg(format!(":{}:{}", stage, file))
+            .output()
+            .map_err(|e| e.to_string())?;
+        let content = if output.status.success() {
+            output.stdout
+        } else if stage == 1 {
+            // Added on both sides: no common ancestor
+            Vec::new()
+        } else {
+            return Err("Deleted on one side: use o or t to keep or delete it".to_string());
+        };
+        let target = dir.join(format!("{}_{}", label, name));
+        std::fs::write(&target, content).map_err(|e| e.to_string())?;
+        stages.push(target);
+    }
+
+    let mut stages = stages.into_iter();
+    match (stages.next(), stages.next(), stages.next()) {
+        (Some(base), Some(ours), Some(theirs)) => Ok(ConflictStages {
+            base,
+            ours,
+            theirs,
+            output: path.join(file),
+            file: file.to_string(),
+        }),
+        _ => Err("Cannot read conflict stages".to_string()),
+    }
+}
+
+/// Resolve a whole file with our or their version; a side that deleted it deletes it
+fn resolve_with_side(path: &Path, file: &str, ours: bool) -> Result<String, String> {
+    let (stage, side) = if ours { ("2", "ours") } else { ("3", "theirs") };
+    // "<mode> <object> <stage>\t<path>" per existing stage
+    let unmerged = run_git(path, &["ls-files", "--unmerged", "--", file])?;
+    let has_stage = unmerged
+        .lines()
+        .any(|l| l.split_whitespace().nth(2) == Some(stage));
+
+    if has_stage {
+        run_git(path, &["checkout", &format!("--{}", side), "--", file])?;
+        run_git(path, &["add", "--", file])?;
+        Ok(format!("Resolved {} with {}", file, side))
+    } else {
+        run_git(path, &["rm", "-q", "--", file])?;
+        Ok(format!("Deleted {} as in {}", file, side))
+    }
+}
+
+/// Record the worktree version of a conflicted file as resolved
+fn mark_resolved(path: &Path, file: &str) -> Result<(), String> {
+    if path.join(file).exists() {
+        run_git(path, &["add", "--", file])?;
+    } else {
+        run_git(path, &["rm", "-q", "--", file])?;
+    }
+    Ok(())
+}
+
+fn git_dir(path: &Path) -> Option<PathBuf> {
+    git_cmd(path)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim().to_string()))
+}
+
+/// Detect a rebase, cherry-pick, revert or merge that is in progress
+fn get_operation(path: &Path) -> Option<RepoOperation> {
+    let dir = git_dir(path)?;
+    let read_number = |file: PathBuf| -> usize {
+        std::fs::read_to_string(file)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0)
+    };
+
+    let merge_dir = dir.join("rebase-merge");
+    let apply_dir = dir.join("rebase-apply");
+    if merge_dir.is_dir() {
+        Some(RepoOperation::Rebase {
+            step: read_number(merge_dir.join("msgnum")),
+            total: read_number(merge_dir.join("end")),
+        })
+    } else if apply_dir.is_dir() {
+        Some(RepoOperation::Rebase {
+            step: read_number(apply_dir.join("next")),
+            total: read_number(apply_dir.join("last")),
+        })
+    } else if dir.join("CHERRY_PICK_HEAD").exists() {
+        Some(RepoOperation::CherryPick)
+    } else if dir.join("REVERT_HEAD").exists() {
+        Some(RepoOperation::Revert)
+    } else if dir.join("MERGE_HEAD").exists() {
+        Some(RepoOperation::Merge)
+    } else {
+        None
+    }
+}
+
+/// Short hash of the first parent, or None for a root commit
+fn commit_parent(path: &Path, hash: &str) -> Option<String> {
+    if !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    git_cmd(path)
+        .args(["rev-parse", "--verify", "--quiet", "--short"])
+        .arg(format!("{}^", hash))
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Commits between `base` (exclusive) and HEAD, oldest first, as rebase todo items
+fn get_rebase_items(path: &Path, base: Option<&str>) -> Result<Vec<RebaseItem>, String> {
+    let range = match base {
+        Some(b) if b.chars().all(|c| c.is_ascii_alphanumeric()) => format!("{}..HEAD", b),
+        Some(_) => return Err("Invalid commit hash".to_string()),
+        None => "HEAD".to_string(),
+    };
+
+    let merges = run_git(path, &["rev-list", "--merges", "--count", &range])?;
+    if merges.trim() != "0" {
+        return Err("Cannot rebase across merge commits".to_string());
+    }
+
+    let log = run_git(path, &["log", "--reverse", "--format=%h|%s", &range])?;
+    Ok(log
+        .lines()
+        .filter_map(|line| line.split_once('|'))
+        .map(|(hash, message)| RebaseItem {
+            action: RebaseAction::Pick,
+            hash: hash.to_string(),
+            message: message.to_string(),
+            new_message: None,
+        })
+        .collect())
+}
+
+/// Cherry-pick (oldest first) or revert (newest first) commits onto the current branch
+fn pick_commits(path: &Path, hashes: &[String], revert: bool) -> Result<String, String> {
+    if hashes.is_empty()
+        || !hashes
+            .iter()
+            .all(|h| h.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err("Invalid commit hash".to_string());
+    }
+    let mut args = if revert {
+        vec!["revert", "--no-edit"]
+    } else {
+        vec!["cherry-pick"]
+    };
+    args.extend(hashes.iter().map(|h| h.as_str()));
+    run_git(path, &args)
+}
+
+/// Continue, skip or abort the stopped operation (`step` is "--continue", "--skip" or "--abort")
+fn run_operation_step(path: &Path, op: RepoOperation, step: &str) -> Result<String, String> {
+    let output = git_commit_cmd(path)
+        .env("GIT_EDITOR", "true")
+        .args([op.command(), step])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(command_error(&output))
     }
-
-    entries
 }
 
-fn get_commit_diff(path: &Path, hash: &str) -> String {
-    // Validate hash to prevent command injection
-    if !hash.chars().all(|c| c.is_ascii_alphanumeric()) {
-        return String::new();
+/// stderr of a failed command, falling back to stdout (rebase reports conflicts there)
+fn command_error(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    } else {
+        stderr
     }
-    git_cmd(path)
-        .args(["show", "--stat", "--patch", hash])
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-        .unwrap_or_default()
 }
 
-fn get_file_diff(path: &Path, file: &str, staged: bool) -> String {
-    let mut cmd = git_cmd(path);
-    cmd.arg("diff");
-    if staged {
-        cmd.arg("--cached");
-    }
-    cmd.args(["--", file]);
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
 
-    cmd.output()
-        .ok()
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
-        .unwrap_or_default()
+/// Scratch directory for the generated todo and reword messages
+fn rebase_work_dir(path: &Path) -> Option<PathBuf> {
+    git_dir(path).map(|dir| dir.join("remotecc-rebase"))
 }
 
-fn get_branches(path: &Path) -> Vec<GitBranchEntry> {
-    let output = git_cmd(path)
-        .args([
-            "branch",
-            "--format=%(HEAD)|%(refname:short)|%(objectname:short)|%(refname)",
-        ])
-        .output();
+fn remove_rebase_work_dir(path: &Path) {
+    if let Some(dir) = rebase_work_dir(path) {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
 
-    let output = match output {
-        Ok(o) if o.status.success() => o,
-        _ => return Vec::new(),
+/// Run `git rebase -i` with the editor's todo in place of the one git generates
+fn run_interactive_rebase(
+    path: &Path,
+    editor: &RebaseEditor,
+    skip_hooks: bool,
+) -> Result<String, String> {
+    let work_dir = rebase_work_dir(path).ok_or("Not a git repository")?;
+    let _ = std::fs::remove_dir_all(&work_dir);
+    std::fs::create_dir_all(&work_dir).map_err(|e| e.to_string())?;
+
+    let (todo, messages) = editor.todo(&work_dir, skip_hooks);
+    for (file, message) in &messages {
+        std::fs::write(file, message).map_err(|e| e.to_string())?;
+    }
+    let todo_path = work_dir.join("git-rebase-todo");
+    std::fs::write(&todo_path, todo).map_err(|e| e.to_string())?;
+
+    // git runs the sequence editor as `$GIT_SEQUENCE_EDITOR <todo>`; copy ours over it.
+    // GIT_EDITOR=true accepts the combined message of squashes as is.
+    let mut cmd = git_commit_cmd(path);
+    cmd.args(["rebase", "-i"]);
+    match editor.base {
+        Some(ref base) => cmd.arg(base),
+        None => cmd.arg("--root"),
     };
+    let output = cmd
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+        )
+        .env("GIT_EDITOR", "true")
+        .output()
+        .map_err(|e| e.to_string())?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut entries = Vec::new();
+    if output.status.success() {
+        Ok(format!("Rebased {} commit(s)", editor.items.len()))
+    } else {
+        Err(command_error(&output))
+    }
+}
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.splitn(4, '|').collect();
-        if parts.len() >= 2 {
-            let is_current = parts[0] == "*";
-            let name = parts[1].to_string();
-            let is_remote = parts.get(3).is_some_and(|r| r.starts_with("refs/remotes/"));
+/// Reject names git would refuse or misread as options
+fn is_valid_ref_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && !name.contains("..")
+        && !name.ends_with(".lock")
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || "~^:?*[\\".contains(c) || c.is_control())
+}
 
-            entries.push(GitBranchEntry {
-                name,
-                is_current,
-                is_remote,
-            });
-        }
+/// Load the unstaged or staged diff of one file for the hunk view
+fn load_file_diff(path: &Path, file: &str, side: HunkSide) -> Result<FileDiff, String> {
+    let mut cmd = git_cmd(path);
+    cmd.args(["diff", "--no-color", "--no-ext-diff"]);
+    if side == HunkSide::Staged {
+        cmd.arg("--cached");
     }
+    cmd.args(["--", file]);
 
-    entries
+    let output = cmd.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    // Patches are built from this text, so it must round-trip exactly
+    let text =
+        String::from_utf8(output.stdout).map_err(|_| "Diff is not valid UTF-8".to_string())?;
+    Ok(FileDiff::parse(&text))
 }
 
-fn stage_all(path: &Path) {
-    let _ = git_cmd(path).args(["add", "-A"]).output();
+/// Apply a patch with `git apply`, to the index (`cached`) or the worktree
+fn apply_patch(path: &Path, patch: &str, cached: bool, reverse: bool) -> Result<(), String> {
+    let mut cmd = git_cmd(path);
+    cmd.arg("apply");
+    if cached {
+        cmd.arg("--cached");
+    }
+    if reverse {
+        cmd.arg("--reverse");
+    }
+    cmd.args(["--whitespace=nowarn", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(patch.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
 }
 
 fn stage_file(path: &Path, file: &str) -> Result<(), String> {
@@ -419,32 +2913,127 @@ fn unstage_file(path: &Path, file: &str) -> Result<(), String> {
     }
 }
 
-fn do_commit(path: &Path, message: &str) -> Result<String, String> {
-    let output = git_commit_cmd(path)
-        .args(["commit", "-m", message])
+/// Staged diff with a stat summary for a commit message draft, cut off at
+/// `DRAFT_DIFF_LIMIT` bytes
+fn get_staged_diff(path: &Path) -> String {
+    let stat = run_git(path, &["diff", "--cached", "--stat"]).unwrap_or_default();
+    if stat.is_empty() {
+        return String::new();
+    }
+    let diff = git_cmd(path)
+        .args(["diff", "--cached", "--no-color", "--no-ext-diff"])
         .output()
-        .map_err(|e| e.to_string())?;
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+    let mut text = format!("{}\n\n{}", stat, safe_prefix(&diff, DRAFT_DIFF_LIMIT));
+    if diff.len() > DRAFT_DIFF_LIMIT {
+        text.push_str("\n[diff truncated]\n");
+    }
+    text
+}
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+fn draft_prompt(diff: &str) -> String {
+    format!(
+        "Write a git commit message for the staged changes below. \
+         Reply with the commit message only: no code fences, quotes or explanations.\n\n{}",
+        diff
+    )
+}
+
+/// Strip code fences and surrounding blank lines a provider may add
+fn clean_draft(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .trim()
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("```"))
+        .map(|l| l.trim_end())
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+fn do_commit(path: &Path, message: &str, options: &CommitOptions) -> Result<String, String> {
+    run_commit(path, &[], message, options)
+}
+
+fn do_commit_amend(path: &Path, message: &str, options: &CommitOptions) -> Result<String, String> {
+    run_commit(path, &["--amend"], message, options)
+}
+
+/// Arguments after `git` for a commit with the given signing and hook options
+fn commit_args(extra: &[&str], message: &str, options: &CommitOptions) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    match options.signing {
+        CommitSigning::Gpg => args.extend(["-c".into(), "gpg.format=openpgp".into()]),
+        CommitSigning::Ssh => args.extend(["-c".into(), "gpg.format=ssh".into()]),
+        CommitSigning::Config | CommitSigning::Off => {}
     }
+    args.push("commit".to_string());
+    args.extend(extra.iter().map(|a| a.to_string()));
+    match options.signing {
+        CommitSigning::Gpg | CommitSigning::Ssh => args.push("-S".to_string()),
+        CommitSigning::Off => args.push("--no-gpg-sign".to_string()),
+        CommitSigning::Config => {}
+    }
+    if options.skip_hooks {
+        args.push("--no-verify".to_string());
+    }
+    args.extend(["-m".to_string(), message.to_string()]);
+    args
 }
 
-fn do_commit_amend(path: &Path, message: &str) -> Result<String, String> {
+/// Run a commit. On failure the error holds everything git and the hooks
+/// printed, stdout first.
+fn run_commit(
+    path: &Path,
+    extra: &[&str],
+    message: &str,
+    options: &CommitOptions,
+) -> Result<String, String> {
     let output = git_commit_cmd(path)
-        .args(["commit", "--amend", "-m", message])
+        .args(commit_args(extra, message, options))
+        .stdin(Stdio::null())
         .output()
         .map_err(|e| e.to_string())?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     } else {
-        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err([stdout.trim_end(), stderr.trim_end()]
+            .iter()
+            .filter(|s| !s.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n"))
     }
 }
 
+/// Executable hooks that run on commit (honours core.hooksPath)
+fn active_commit_hooks(path: &Path) -> Vec<&'static str> {
+    let hooks_dir = match run_git(path, &["rev-parse", "--git-path", "hooks"]) {
+        Ok(dir) => path.join(dir),
+        Err(_) => return Vec::new(),
+    };
+    ["pre-commit", "prepare-commit-msg", "commit-msg"]
+        .into_iter()
+        .filter(|hook| is_executable(&hooks_dir.join(hook)))
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
 fn checkout_branch(path: &Path, branch: &str) -> Result<(), String> {
     // Validate branch name
     if branch.contains("..") || branch.contains("~") || branch.starts_with('-') {
@@ -571,6 +3160,16 @@ pub fn draw(frame: &mut Frame, state: &mut GitScreenState, area: Rect, theme: &T
     draw_content(frame, state, layout[2], colors);
     draw_footer(frame, state, layout[3], colors);
 
+    // Output of a running or just finished fetch/pull/push
+    if state.job.is_some() {
+        draw_job_output(frame, state, layout[2], colors);
+    }
+
+    // Output of a hook that rejected a commit
+    if state.hook_output.is_some() {
+        draw_hook_output(frame, state, layout[2], colors);
+    }
+
     // Draw input dialog overlay
     if state.input_mode.is_some() {
         draw_input_dialog(frame, state, area, colors);
@@ -599,8 +3198,22 @@ fn draw_header(
     let max_w = area.width as usize;
     let path_str = state.repo_path.to_string_lossy();
 
-    // " [branch] path" — truncate path to fit remaining width
-    let prefix = format!(" [{}] ", state.branch_name);
+    // " [branch ↑ahead↓behind] path" — truncate path to fit remaining width
+    let tracking = match state.upstream {
+        Some(ref up) if up.ahead > 0 || up.behind > 0 => {
+            format!(" \u{2191}{}\u{2193}{}", up.ahead, up.behind)
+        }
+        _ => String::new(),
+    };
+    // Stopped rebase/cherry-pick/revert/merge and its conflict count
+    let operation = match state.operation {
+        Some(op) => match state.conflict_count() {
+            0 => format!("{} ", op.label()),
+            n => format!("{} ({} conflicts) ", op.label(), n),
+        },
+        None => String::new(),
+    };
+    let prefix = format!(" [{}{}] {}", state.branch_name, tracking, operation);
     let prefix_w = UnicodeWidthStr::width(prefix.as_str());
     let path_max = max_w.saturating_sub(prefix_w);
     let truncated_path = truncate_to_display_width(&path_str, path_max);
@@ -613,7 +3226,14 @@ fn draw_header(
                 .fg(colors.header_branch)
                 .add_modifier(Modifier::BOLD),
         ),
+        Span::styled(tracking, Style::default().fg(colors.header_branch)),
         Span::styled("] ", Style::default().fg(colors.header_path)),
+        Span::styled(
+            operation,
+            Style::default()
+                .fg(colors.file_deleted)
+                .add_modifier(Modifier::BOLD),
+        ),
         Span::styled(truncated_path, Style::default().fg(colors.header_path)),
     ];
     frame.render_widget(Paragraph::new(Line::from(spans)), area);
@@ -625,19 +3245,13 @@ fn draw_tab_bar(
     area: Rect,
     colors: &super::theme::GitScreenColors,
 ) {
-    let tabs = [
-        (GitTab::Commit, "1:Commit"),
-        (GitTab::Log, "2:Log"),
-        (GitTab::Branch, "3:Branch"),
-    ];
-
     let bg_style = Style::default().bg(colors.tab_bar_bg);
     let bg_block = Block::default().style(bg_style);
     frame.render_widget(bg_block, area);
 
     let mut spans = vec![Span::styled(" ", bg_style)];
-    for (tab, label) in &tabs {
-        let style = if *tab == state.current_tab {
+    for tab in GitTab::ALL {
+        let style = if tab == state.current_tab {
             Style::default()
                 .fg(colors.tab_active)
                 .bg(colors.tab_bar_bg)
@@ -647,7 +3261,18 @@ fn draw_tab_bar(
                 .fg(colors.tab_inactive)
                 .bg(colors.tab_bar_bg)
         };
-        spans.push(Span::styled(format!(" {} ", label), style));
+        let mut label = format!(" {}", tab.label());
+        if tab == GitTab::Log {
+            if let Some(ref branch) = state.log_ref {
+                label.push_str(&format!(" ({})", branch));
+            }
+            if !state.log_filter.is_empty() {
+                let filter = truncate_to_display_width(&state.log_filter.to_input(), 30);
+                label.push_str(&format!(" [{}]", filter));
+            }
+        }
+        label.push(' ');
+        spans.push(Span::styled(label, style));
     }
 
     frame.render_widget(Paragraph::new(Line::from(spans)), area);
@@ -663,11 +3288,19 @@ fn draw_content(
         GitTab::Commit => draw_commit_tab(frame, state, area, colors),
         GitTab::Log => draw_log_tab(frame, state, area, colors),
         GitTab::Branch => draw_branch_tab(frame, state, area, colors),
+        GitTab::Stash => draw_stash_tab(frame, state, area, colors),
+        GitTab::Tags => draw_tags_tab(frame, state, area, colors),
+        GitTab::Remotes => draw_remotes_tab(frame, state, area, colors),
+        GitTab::Trees => draw_trees_tab(frame, state, area, colors),
     }
 }
 
 fn file_status_style(entry: &GitFileEntry, colors: &super::theme::GitScreenColors) -> Style {
-    if entry.staged {
+    if is_conflicted(entry) {
+        Style::default()
+            .fg(colors.file_deleted)
+            .add_modifier(Modifier::BOLD)
+    } else if entry.staged {
         Style::default().fg(colors.file_staged)
     } else if entry.index_status == '?' {
         Style::default().fg(colors.file_untracked)
@@ -678,8 +3311,15 @@ fn file_status_style(entry: &GitFileEntry, colors: &super::theme::GitScreenColor
     }
 }
 
+/// Staged changes plus further unstaged changes in the worktree
+fn is_partially_staged(entry: &GitFileEntry) -> bool {
+    entry.staged && entry.worktree_status != ' ' && entry.worktree_status != '?'
+}
+
 fn file_status_char(entry: &GitFileEntry) -> &str {
-    if entry.staged {
+    if is_conflicted(entry) {
+        "U"
+    } else if entry.staged {
         match entry.index_status {
             'A' => "A",
             'D' => "D",
@@ -702,18 +3342,56 @@ fn draw_commit_tab(
     area: Rect,
     colors: &super::theme::GitScreenColors,
 ) {
+    if state.hunk_view.is_some() {
+        draw_hunk_view(frame, state, area, colors);
+        return;
+    }
+
     // If showing a diff detail, render it over the full area
     if state.log_detail.is_some() {
         draw_diff_detail(frame, state, area, colors, false);
         return;
     }
 
-    // Split: file list (top) + message input (bottom 3 lines)
+    // Banner with the continue/skip/abort keys while an operation is stopped
+    let area = match state.operation {
+        Some(op) => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(area);
+            let skip = if op.can_skip() { "  S: skip" } else { "" };
+            let text = format!(
+                " {} in progress: stage resolved files, then C: continue{}  A: abort",
+                op.label(),
+                skip
+            );
+            frame.render_widget(
+                Paragraph::new(Span::styled(
+                    truncate_to_display_width(&text, rows[0].width as usize),
+                    Style::default()
+                        .fg(colors.file_deleted)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                rows[0],
+            );
+            rows[1]
+        }
+        None => area,
+    };
+
+    if state.conflict_view {
+        draw_conflict_list(frame, state, area, colors);
+        return;
+    }
+
+    // Split: file list (top) + message input (bottom, grows with multi-line messages)
+    let message_lines = state.commit_message.split('\n').count().clamp(1, 8) as u16;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(3),    // file list
-            Constraint::Length(3), // commit message input
+            Constraint::Min(3),                    // file list
+            Constraint::Length(message_lines + 2), // commit message input
         ])
         .split(area);
 
@@ -748,7 +3426,13 @@ fn draw_commit_tab(
         {
             let is_selected = !state.commit_input_active && i == state.commit_selected;
             let status_char = file_status_char(entry);
-            let prefix = if entry.staged { "+" } else { " " };
+            let prefix = if is_partially_staged(entry) {
+                "~"
+            } else if entry.staged {
+                "+"
+            } else {
+                " "
+            };
             let text = format!(" {}[{}] {}", prefix, status_char, entry.path);
 
             let style = if is_selected {
@@ -791,55 +3475,146 @@ fn draw_commit_tab(
         Style::default().fg(colors.commit_input_border)
     };
 
+    let title = match state.draft {
+        Some(ref draft) => format!(
+            " Commit Message \u{2014} {} is drafting\u{2026} ",
+            draft.provider.display_name()
+        ),
+        None if state.message_drafted => " Commit Message (AI draft) ".to_string(),
+        None => " Commit Message ".to_string(),
+    };
+    let mut flags = Vec::new();
+    if state.commit_options.signing != CommitSigning::Config {
+        flags.push(format!("sign: {}", state.commit_options.signing.label()));
+    }
+    if state.commit_options.skip_hooks {
+        flags.push("hooks skipped".to_string());
+    }
+    let title = if flags.is_empty() {
+        title
+    } else {
+        format!("{}[{}] ", title, flags.join(", "))
+    };
     let input_block = Block::default()
         .borders(Borders::ALL)
         .border_style(border_style)
-        .title(" Commit Message ");
+        .title(title);
 
     let inner_width = input_area.width.saturating_sub(2) as usize; // border 2칸 제외
-    let display_text = if state.commit_message.is_empty() && !state.commit_input_active {
-        "Press Tab to enter commit message...".to_string()
-    } else {
-        truncate_to_display_width(&state.commit_message, inner_width)
-    };
-
-    let text_style = if state.commit_message.is_empty() && !state.commit_input_active {
+    let inner_height = input_area.height.saturating_sub(2) as usize;
+    let placeholder = state.commit_message.is_empty() && !state.commit_input_active;
+    let text_style = if placeholder {
         Style::default().fg(colors.footer_text)
     } else {
         Style::default().fg(colors.commit_input_text)
     };
+    // Keep the end of the message (where the cursor is) in view
+    let message_lines: Vec<&str> = if placeholder {
+        vec!["Press Tab to enter commit message, g for an AI draft..."]
+    } else {
+        state.commit_message.split('\n').collect()
+    };
+    let skip = message_lines.len().saturating_sub(inner_height);
+    let lines: Vec<Line> = message_lines
+        .iter()
+        .skip(skip)
+        .map(|l| {
+            Line::from(Span::styled(
+                truncate_to_display_width(l, inner_width),
+                text_style,
+            ))
+        })
+        .collect();
 
-    let input_paragraph = Paragraph::new(Span::styled(display_text, text_style)).block(input_block);
+    let input_paragraph = Paragraph::new(lines).block(input_block);
     frame.render_widget(input_paragraph, input_area);
 
     // Show cursor when input is active
-    if state.commit_input_active {
-        let cursor_x =
-            input_area.x + 1 + UnicodeWidthStr::width(state.commit_message.as_str()) as u16;
-        let cursor_y = input_area.y + 1;
+    if state.commit_input_active && state.draft.is_none() {
+        let last = message_lines.last().copied().unwrap_or("");
+        let cursor_x = input_area.x + 1 + UnicodeWidthStr::width(last) as u16;
+        let cursor_y = input_area.y + (message_lines.len() - skip) as u16;
         if cursor_x < input_area.x + input_area.width - 1 {
             frame.set_cursor_position((cursor_x, cursor_y));
         }
     }
 }
 
+fn draw_conflict_list(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    if state.conflicts.is_empty() {
+        let text = if state.operation.is_some() {
+            "  All conflicts resolved. Press C to continue"
+        } else {
+            "  No merge conflicts"
+        };
+        draw_empty(frame, area, text, colors);
+        return;
+    }
+
+    let rows = state
+        .conflicts
+        .iter()
+        .map(|c| {
+            let blocks = match c.markers {
+                0 => String::new(),
+                1 => "  (1 conflict block)".to_string(),
+                n => format!("  ({} conflict blocks)", n),
+            };
+            let text = format!(" {} {:<16} {}{}", c.code, c.describe(), c.path, blocks);
+            let style = if c.markers > 0 {
+                Style::default().fg(colors.file_deleted)
+            } else {
+                Style::default().fg(colors.file_modified)
+            };
+            (text, style)
+        })
+        .collect();
+    draw_list(
+        frame,
+        area,
+        rows,
+        state.conflict_selected,
+        &mut state.conflict_scroll,
+        colors,
+    );
+}
+
 fn draw_log_tab(
     frame: &mut Frame,
     state: &mut GitScreenState,
     area: Rect,
     colors: &super::theme::GitScreenColors,
 ) {
+    if state.rebase_editor.is_some() {
+        draw_rebase_editor(frame, state, area, colors);
+        return;
+    }
+
     if state.log_entries.is_empty() {
-        let msg = Paragraph::new(Line::from(Span::styled(
-            "  No commits",
-            Style::default().fg(colors.footer_text),
-        )));
-        frame.render_widget(msg, area);
+        let text = if state.log_filter.is_empty() {
+            "  No commits"
+        } else {
+            "  No commits match the filter (/ to change, Esc to clear)"
+        };
+        draw_empty(frame, area, text, colors);
         return;
     }
 
-    // If detail view is open, split view
-    if state.log_detail.is_some() {
+    // If detail view or the commit's files are open, split view
+    if state.log_files.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(area);
+
+        draw_log_list(frame, state, chunks[0], colors);
+        draw_commit_files(frame, state, chunks[1], colors);
+    } else if state.log_detail.is_some() {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
@@ -873,6 +3648,16 @@ fn draw_log_list(
     // Detect if files have been restored to a different commit
     let restored = get_index_matching_commit(&state.repo_path);
 
+    // Pad the graph column to the widest row on screen
+    let graph_width = state
+        .log_entries
+        .iter()
+        .skip(state.log_scroll)
+        .take(visible_height)
+        .map(|e| UnicodeWidthStr::width(e.graph.as_str()))
+        .max()
+        .unwrap_or(0);
+
     for (i, entry) in state
         .log_entries
         .iter()
@@ -882,12 +3667,30 @@ fn draw_log_list(
     {
         let is_selected = i == state.log_selected;
         let is_restored = restored.as_deref() == Some(&entry.hash);
-        let marker = if is_restored { ">" } else { " " };
+        let is_marked = state.log_marked.contains(&entry.hash);
+        let marker = if is_marked {
+            "*"
+        } else if is_restored {
+            ">"
+        } else {
+            " "
+        };
+
+        let graph = if graph_width > 0 {
+            format!("{} ", pad_to_display_width(&entry.graph, graph_width))
+        } else {
+            String::new()
+        };
+        let refs = if entry.refs.is_empty() {
+            String::new()
+        } else {
+            format!("({}) ", entry.refs)
+        };
 
         if is_selected {
             let text = format!(
-                "{}{} {} ({}, {})",
-                marker, entry.hash, entry.message, entry.author, entry.date
+                "{}{}{} {}{} ({}, {})",
+                marker, graph, entry.hash, refs, entry.message, entry.author, entry.date
             );
             let display = pad_to_display_width(&text, max_width);
             lines.push(Line::from(Span::styled(
@@ -897,45 +3700,169 @@ fn draw_log_list(
                     .bg(colors.selected_bg),
             )));
         } else {
-            let marker_style = if is_restored {
+            let marker_style = if is_marked {
+                Style::default()
+                    .fg(colors.file_modified)
+                    .add_modifier(Modifier::BOLD)
+            } else if is_restored {
                 Style::default().fg(colors.file_staged)
             } else {
                 Style::default()
             };
             let mut spans = vec![
                 Span::styled(marker, marker_style),
+                Span::styled(graph, Style::default().fg(colors.branch_normal)),
                 Span::styled(&entry.hash, Style::default().fg(colors.log_hash)),
                 Span::styled(" ", Style::default()),
+            ];
+            if !refs.is_empty() {
+                spans.push(Span::styled(
+                    refs,
+                    Style::default()
+                        .fg(colors.header_branch)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            spans.extend([
                 Span::styled(&entry.message, Style::default().fg(colors.log_message)),
                 Span::styled(" (", Style::default().fg(colors.log_date)),
                 Span::styled(&entry.author, Style::default().fg(colors.log_author)),
                 Span::styled(", ", Style::default().fg(colors.log_date)),
                 Span::styled(&entry.date, Style::default().fg(colors.log_date)),
                 Span::styled(")", Style::default().fg(colors.log_date)),
-            ];
+            ]);
 
             lines.push(Line::from(spans));
         }
     }
 
-    frame.render_widget(Paragraph::new(lines), area);
+    frame.render_widget(Paragraph::new(lines), area);
+
+    // Scrollbar
+    if state.log_entries.len() > visible_height {
+        let mut scrollbar_state =
+            ScrollbarState::new(state.log_entries.len()).position(state.log_scroll);
+        let scrollbar_area = Rect::new(
+            area.x + area.width.saturating_sub(1),
+            area.y,
+            1,
+            area.height,
+        );
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            scrollbar_area,
+            &mut scrollbar_state,
+        );
+    }
+}
+
+fn draw_commit_files(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let files = match state.log_files {
+        Some(ref mut f) => f,
+        None => return,
+    };
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(colors.border))
+        .title(format!(" Files in {} ", files.hash));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if files.files.is_empty() {
+        draw_empty(frame, inner, "  No file changes", colors);
+        return;
+    }
+    let rows = files
+        .files
+        .iter()
+        .map(|f| {
+            let text = match f.old_path {
+                Some(ref old) => format!(" {} {} \u{2192} {}", f.status, old, f.path),
+                None => format!(" {} {}", f.status, f.path),
+            };
+            let color = match f.status {
+                'A' => colors.diff_add,
+                'D' => colors.diff_remove,
+                _ => colors.file_modified,
+            };
+            (text, Style::default().fg(color))
+        })
+        .collect();
+    draw_list(
+        frame,
+        inner,
+        rows,
+        files.selected,
+        &mut files.scroll,
+        colors,
+    );
+}
+
+fn draw_rebase_editor(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let editor = match state.rebase_editor {
+        Some(ref mut e) => e,
+        None => return,
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(area);
+
+    let title = format!(
+        " Rebase {} commit(s) onto {} (applied top to bottom)",
+        editor.items.len(),
+        editor.base.as_deref().unwrap_or("root")
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            truncate_to_display_width(&title, chunks[0].width as usize),
+            Style::default()
+                .fg(colors.header_branch)
+                .add_modifier(Modifier::BOLD),
+        )),
+        chunks[0],
+    );
 
-    // Scrollbar
-    if state.log_entries.len() > visible_height {
-        let mut scrollbar_state =
-            ScrollbarState::new(state.log_entries.len()).position(state.log_scroll);
-        let scrollbar_area = Rect::new(
-            area.x + area.width.saturating_sub(1),
-            area.y,
-            1,
-            area.height,
-        );
-        frame.render_stateful_widget(
-            Scrollbar::new(ScrollbarOrientation::VerticalRight),
-            scrollbar_area,
-            &mut scrollbar_state,
-        );
-    }
+    let rows = editor
+        .items
+        .iter()
+        .map(|item| {
+            let message = match (item.action, &item.new_message) {
+                (RebaseAction::Reword, Some(new)) => format!("{} \u{2192} {}", item.message, new),
+                _ => item.message.clone(),
+            };
+            let text = format!(" {:<6} {} {}", item.action.label(), item.hash, message);
+            let style = match item.action {
+                RebaseAction::Pick => Style::default().fg(colors.log_message),
+                RebaseAction::Reword => Style::default().fg(colors.file_modified),
+                RebaseAction::Squash | RebaseAction::Fixup => {
+                    Style::default().fg(colors.file_staged)
+                }
+                RebaseAction::Drop => Style::default()
+                    .fg(colors.file_deleted)
+                    .add_modifier(Modifier::CROSSED_OUT),
+            };
+            (text, style)
+        })
+        .collect();
+    draw_list(
+        frame,
+        chunks[1],
+        rows,
+        editor.selected,
+        &mut editor.scroll,
+        colors,
+    );
 }
 
 fn draw_diff_detail(
@@ -1011,6 +3938,116 @@ fn draw_diff_detail(
     }
 }
 
+fn draw_hunk_view(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let view = match state.hunk_view {
+        Some(ref mut v) => v,
+        None => return,
+    };
+
+    // Title row + diff rows
+    let visible_height = (area.height as usize).saturating_sub(1);
+    if view.cursor < view.scroll {
+        view.scroll = view.cursor;
+    }
+    if visible_height > 0 && view.cursor >= view.scroll + visible_height {
+        view.scroll = view.cursor - visible_height + 1;
+    }
+
+    let max_width = area.width as usize;
+    let side_label = match view.side {
+        HunkSide::Unstaged => "unstaged",
+        HunkSide::Staged => "staged",
+    };
+    let hunk_pos = view
+        .locate(view.cursor)
+        .map(|(h, _)| format!("hunk {}/{}", h + 1, view.diff.hunks.len()))
+        .unwrap_or_default();
+    let selecting = if view.anchor.is_some() {
+        ", selecting lines"
+    } else {
+        ""
+    };
+    let title = format!(
+        " [{}] {} ({}{})",
+        side_label, view.file, hunk_pos, selecting
+    );
+
+    let mut lines = vec![Line::from(Span::styled(
+        truncate_to_display_width(&title, max_width),
+        Style::default()
+            .fg(colors.header_branch)
+            .add_modifier(Modifier::BOLD),
+    ))];
+
+    let current_hunk = view.locate(view.cursor).map(|(h, _)| h);
+    let mut row = 0;
+    for (h, hunk) in view.diff.hunks.iter().enumerate() {
+        let header = format!(
+            "@@ -{} +{} @@{}",
+            hunk.old_start, hunk.new_start, hunk.context
+        );
+        for text in std::iter::once(&header).chain(hunk.lines.iter()) {
+            if row >= view.scroll && row < view.scroll + visible_height {
+                let gutter = if current_hunk == Some(h) {
+                    "\u{2502}"
+                } else {
+                    " "
+                };
+                let display = format!("{}{}", gutter, text.trim_end_matches('\r'));
+                let display = pad_to_display_width(&display, max_width);
+                let style = if row == view.cursor {
+                    Style::default()
+                        .fg(colors.selected_text)
+                        .bg(colors.selected_bg)
+                } else {
+                    let base = if row == view.hunk_row(h) {
+                        Style::default()
+                            .fg(colors.diff_header)
+                            .add_modifier(Modifier::BOLD)
+                    } else if text.starts_with('+') {
+                        Style::default().fg(colors.diff_add)
+                    } else if text.starts_with('-') {
+                        Style::default().fg(colors.diff_remove)
+                    } else {
+                        Style::default().fg(colors.log_message)
+                    };
+                    if view.is_selected_row(row) {
+                        base.add_modifier(Modifier::REVERSED)
+                    } else {
+                        base
+                    }
+                };
+                lines.push(Line::from(Span::styled(display, style)));
+            }
+            row += 1;
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines), area);
+
+    // Scrollbar
+    let total = view.row_count();
+    if total > visible_height {
+        let mut scrollbar_state = ScrollbarState::new(total).position(view.scroll);
+        let scrollbar_area = Rect::new(
+            area.x + area.width.saturating_sub(1),
+            area.y + 1,
+            1,
+            area.height.saturating_sub(1),
+        );
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            scrollbar_area,
+            &mut scrollbar_state,
+        );
+    }
+}
+
 fn draw_branch_tab(
     frame: &mut Frame,
     state: &mut GitScreenState,
@@ -1090,6 +4127,394 @@ fn draw_branch_tab(
     }
 }
 
+/// Draw a scrolling list of pre-styled rows with the selected row highlighted
+fn draw_list(
+    frame: &mut Frame,
+    area: Rect,
+    rows: Vec<(String, Style)>,
+    selected: usize,
+    scroll: &mut usize,
+    colors: &super::theme::GitScreenColors,
+) {
+    let visible_height = area.height as usize;
+    if selected < *scroll {
+        *scroll = selected;
+    }
+    if visible_height > 0 && selected >= *scroll + visible_height {
+        *scroll = selected - visible_height + 1;
+    }
+
+    let max_width = area.width as usize;
+    let total = rows.len();
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .enumerate()
+        .skip(*scroll)
+        .take(visible_height)
+        .map(|(i, (text, style))| {
+            let style = if i == selected {
+                Style::default()
+                    .fg(colors.selected_text)
+                    .bg(colors.selected_bg)
+            } else {
+                style
+            };
+            Line::from(Span::styled(pad_to_display_width(&text, max_width), style))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+
+    // Scrollbar
+    if total > visible_height {
+        let mut scrollbar_state = ScrollbarState::new(total).position(*scroll);
+        let scrollbar_area = Rect::new(
+            area.x + area.width.saturating_sub(1),
+            area.y,
+            1,
+            area.height,
+        );
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            scrollbar_area,
+            &mut scrollbar_state,
+        );
+    }
+}
+
+fn draw_empty(frame: &mut Frame, area: Rect, text: &str, colors: &super::theme::GitScreenColors) {
+    let msg = Paragraph::new(Line::from(Span::styled(
+        text,
+        Style::default().fg(colors.footer_text),
+    )));
+    frame.render_widget(msg, area);
+}
+
+/// List on the left, detail (diff/show output) on the right when one is open
+fn split_for_detail(state: &GitScreenState, area: Rect) -> (Rect, Option<Rect>) {
+    if state.log_detail.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    }
+}
+
+fn draw_stash_tab(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    if state.stashes.is_empty() {
+        draw_empty(
+            frame,
+            area,
+            "  No stashes (n: stash current changes)",
+            colors,
+        );
+        return;
+    }
+    let (list_area, detail_area) = split_for_detail(state, area);
+    let rows = state
+        .stashes
+        .iter()
+        .map(|st| {
+            (
+                format!(" stash@{{{}}} {} ({})", st.index, st.message, st.date),
+                Style::default().fg(colors.log_message),
+            )
+        })
+        .collect();
+    draw_list(
+        frame,
+        list_area,
+        rows,
+        state.stash_selected,
+        &mut state.stash_scroll,
+        colors,
+    );
+    if let Some(detail_area) = detail_area {
+        draw_diff_detail(frame, state, detail_area, colors, true);
+    }
+}
+
+fn draw_tags_tab(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    if state.tags.is_empty() {
+        draw_empty(frame, area, "  No tags (n: create annotated tag)", colors);
+        return;
+    }
+    let (list_area, detail_area) = split_for_detail(state, area);
+    let rows = state
+        .tags
+        .iter()
+        .map(|tag| {
+            // Lightweight tags are marked with '*'
+            let kind = if tag.annotated { " " } else { "*" };
+            (
+                format!(" {}{} {} {}", kind, tag.name, tag.hash, tag.subject),
+                Style::default().fg(colors.branch_normal),
+            )
+        })
+        .collect();
+    draw_list(
+        frame,
+        list_area,
+        rows,
+        state.tag_selected,
+        &mut state.tag_scroll,
+        colors,
+    );
+    if let Some(detail_area) = detail_area {
+        draw_diff_detail(frame, state, detail_area, colors, true);
+    }
+}
+
+fn draw_remotes_tab(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(area);
+
+    // Tracking summary of the current branch
+    let tracking = match state.upstream {
+        Some(ref up) => {
+            let status = match (up.ahead, up.behind) {
+                (0, 0) => "up to date".to_string(),
+                (a, 0) => format!("ahead {}", a),
+                (0, b) => format!("behind {}", b),
+                (a, b) => format!("ahead {}, behind {}", a, b),
+            };
+            format!(" {} \u{2192} {} ({})", state.branch_name, up.name, status)
+        }
+        None => format!(" {} has no upstream branch", state.branch_name),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            truncate_to_display_width(&tracking, chunks[0].width as usize),
+            Style::default()
+                .fg(colors.header_branch)
+                .add_modifier(Modifier::BOLD),
+        ))),
+        chunks[0],
+    );
+
+    if state.remotes.is_empty() {
+        draw_empty(frame, chunks[1], "  No remotes configured", colors);
+        return;
+    }
+    let rows = state
+        .remotes
+        .iter()
+        .map(|remote| {
+            (
+                format!(" {}  {}", remote.name, remote.url),
+                Style::default().fg(colors.branch_normal),
+            )
+        })
+        .collect();
+    draw_list(
+        frame,
+        chunks[1],
+        rows,
+        state.remote_selected,
+        &mut state.remote_scroll,
+        colors,
+    );
+}
+
+fn draw_trees_tab(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    if state.trees.is_empty() {
+        draw_empty(frame, area, "  No worktrees found", colors);
+        return;
+    }
+    let name_width = state
+        .trees
+        .iter()
+        .map(|t| UnicodeWidthStr::width(t.name.as_str()))
+        .max()
+        .unwrap_or(0)
+        .min((area.width as usize) / 2);
+    let rows = state
+        .trees
+        .iter()
+        .map(|tree| {
+            let kind = match tree.kind {
+                TreeKind::Worktree => "wt ",
+                TreeKind::Submodule => "sub",
+            };
+            let current = if tree.is_current { '*' } else { ' ' };
+            let name = pad_to_display_width(
+                &truncate_to_display_width(&tree.name, name_width),
+                name_width,
+            );
+            let mut status = match tree.changes {
+                Some(0) => "clean".to_string(),
+                Some(n) => format!("{} changed", n),
+                None => String::new(),
+            };
+            if !tree.note.is_empty() {
+                if !status.is_empty() {
+                    status.push_str(", ");
+                }
+                status.push_str(&tree.note);
+            }
+            let style = if tree.is_current {
+                Style::default()
+                    .fg(colors.branch_current)
+                    .add_modifier(Modifier::BOLD)
+            } else if tree.changes.is_none() {
+                Style::default().fg(colors.log_date)
+            } else if tree.changes != Some(0) || !tree.note.is_empty() {
+                Style::default().fg(colors.file_modified)
+            } else {
+                Style::default().fg(colors.branch_normal)
+            };
+            (
+                format!(" {}{} {}  {}  {}", current, kind, name, tree.branch, status),
+                style,
+            )
+        })
+        .collect();
+    draw_list(
+        frame,
+        area,
+        rows,
+        state.tree_selected,
+        &mut state.tree_scroll,
+        colors,
+    );
+}
+
+fn draw_job_output(
+    frame: &mut Frame,
+    state: &GitScreenState,
+    content_area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let job = match state.job {
+        Some(ref j) => j,
+        None => return,
+    };
+    let height = (content_area.height / 2)
+        .clamp(3, 12)
+        .min(content_area.height);
+    let area = Rect::new(
+        content_area.x,
+        content_area.y + content_area.height - height,
+        content_area.width,
+        height,
+    );
+    let status = if job.running {
+        "running\u{2026}"
+    } else if job.succeeded {
+        "done (Esc: close)"
+    } else {
+        "failed (Esc: close)"
+    };
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(colors.border))
+        .title(format!(" {} \u{2014} {} ", job.title, status));
+    let inner = block.inner(area);
+    let max_width = inner.width as usize;
+    let lines: Vec<Line> = job
+        .lines
+        .iter()
+        .skip(job.lines.len().saturating_sub(inner.height as usize))
+        .map(|l| {
+            Line::from(Span::styled(
+                truncate_to_display_width(l, max_width),
+                Style::default().fg(colors.log_message),
+            ))
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().bg(colors.bg)),
+        area,
+    );
+}
+
+fn draw_hook_output(
+    frame: &mut Frame,
+    state: &mut GitScreenState,
+    content_area: Rect,
+    colors: &super::theme::GitScreenColors,
+) {
+    let output = match state.hook_output {
+        Some(ref mut o) => o,
+        None => return,
+    };
+    let height = (content_area.height * 2 / 3)
+        .clamp(3, 20)
+        .min(content_area.height);
+    let area = Rect::new(
+        content_area.x,
+        content_area.y + content_area.height - height,
+        content_area.width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(colors.file_deleted))
+        .title(format!(" {} ", output.title));
+    let inner = block.inner(area);
+    let visible = inner.height as usize;
+    output.scroll = output
+        .scroll
+        .min(output.lines.len().saturating_sub(visible));
+    let max_width = inner.width as usize;
+    let lines: Vec<Line> = output
+        .lines
+        .iter()
+        .skip(output.scroll)
+        .take(visible)
+        .map(|l| {
+            Line::from(Span::styled(
+                truncate_to_display_width(l, max_width),
+                Style::default().fg(colors.log_message),
+            ))
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().bg(colors.bg)),
+        area,
+    );
+    if output.lines.len() > visible {
+        let mut scrollbar_state = ScrollbarState::new(output.lines.len()).position(output.scroll);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            inner,
+            &mut scrollbar_state,
+        );
+    }
+}
+
 fn draw_footer(
     frame: &mut Frame,
     state: &GitScreenState,
@@ -1111,15 +4536,70 @@ fn draw_footer(
     }
 
     let shortcuts: Vec<(&str, &str)> = match state.current_tab {
+        _ if state.hook_output.is_some() => vec![
+            ("\u{2191}\u{2193}", "scroll "),
+            ("PgUp/Dn", "page "),
+            ("H", "skip hooks "),
+            ("Esc", "close"),
+        ],
         GitTab::Commit => {
-            if state.commit_input_active {
-                vec![("Enter", "commit "), ("Tab", "files "), ("Esc", "cancel")]
+            if state.draft.is_some() {
+                vec![("Esc", "cancel draft")]
+            } else if state.commit_input_active {
+                vec![
+                    ("Enter", "commit "),
+                    ("M-Enter", "newline "),
+                    ("^g", "AI draft "),
+                    ("Tab", "files "),
+                    ("Esc", "cancel"),
+                ]
+            } else if let Some(ref view) = state.hunk_view {
+                let apply = match view.side {
+                    HunkSide::Unstaged => "stage ",
+                    HunkSide::Staged => "unstage ",
+                };
+                let mut keys = vec![
+                    ("\u{2191}\u{2193}", "line "),
+                    ("n/p", "hunk "),
+                    ("Spc", apply),
+                    ("v", "lines "),
+                ];
+                if view.side == HunkSide::Unstaged {
+                    keys.push(("d", "iscard "));
+                }
+                keys.push(("Tab", "staged/unstaged "));
+                keys.push(("Esc", "close"));
+                keys
             } else if state.log_detail.is_some() {
                 vec![
                     ("\u{2191}\u{2193}", "nav "),
                     ("PgUp/Dn", "scroll "),
                     ("Esc", "close"),
                 ]
+            } else if state.conflict_view {
+                let mut keys = vec![
+                    ("\u{2191}\u{2193}", "nav "),
+                    ("Enter", "merge "),
+                    ("o", "urs "),
+                    ("t", "heirs "),
+                    ("a", "dd as resolved "),
+                ];
+                if state.operation.is_some() {
+                    keys.extend([("C", "ontinue "), ("A", "bort ")]);
+                }
+                keys.push(("Esc", "files"));
+                keys
+            } else if state.operation.is_some() || !state.conflicts.is_empty() {
+                vec![
+                    ("\u{2191}\u{2193}", "nav "),
+                    ("Spc", "stage "),
+                    ("Enter", "diff "),
+                    ("m", "erge conflicts "),
+                    ("C", "ontinue "),
+                    ("A", "bort "),
+                    ("Tab", "msg "),
+                    ("Esc", "back"),
+                ]
             } else {
                 vec![
                     ("\u{2191}\u{2193}", "nav "),
@@ -1127,39 +4607,109 @@ fn draw_footer(
                     ("Enter", "diff "),
                     ("^a", "ll "),
                     ("Tab", "msg "),
+                    ("g", "AI draft "),
                     ("a", "mend "),
+                    ("G", "sign "),
+                    ("H", "ooks "),
                     ("\u{2190}\u{2192}", "tab "),
                     ("Esc", "back"),
                 ]
             }
         }
         GitTab::Log => {
-            if state.log_detail.is_some() {
+            if state.log_files.is_some() {
+                vec![
+                    ("\u{2191}\u{2193}", "nav "),
+                    ("Enter", "diff file "),
+                    ("Esc", "close"),
+                ]
+            } else if state.log_detail.is_some() {
                 vec![
                     ("\u{2191}\u{2193}", "nav "),
                     ("\u{2190}\u{2192}", "commit "),
                     ("PgUp/Dn", "scroll "),
                     ("Esc", "close"),
                 ]
+            } else if state.rebase_editor.is_some() {
+                vec![
+                    ("\u{2191}\u{2193}", "nav "),
+                    ("J/K", "move "),
+                    ("p", "ick "),
+                    ("r", "eword "),
+                    ("s", "quash "),
+                    ("f", "ixup "),
+                    ("d", "rop "),
+                    ("Enter", "run "),
+                    ("Esc", "cancel"),
+                ]
             } else {
                 vec![
                     ("\u{2191}\u{2193}", "nav "),
+                    ("Spc", "mark "),
                     ("Enter", "detail "),
+                    ("f", "iles "),
+                    ("/", "filter "),
+                    ("c", "herry-pick "),
+                    ("v", "revert "),
+                    ("i", "rebase "),
                     ("r", "estore "),
-                    ("\u{2190}\u{2192}", "tab "),
                     ("Esc", "back"),
                 ]
             }
         }
         GitTab::Branch => vec![
             ("\u{2191}\u{2193}", "nav "),
-            ("Enter", "checkout "),
-            ("c", "heckout "),
+            ("Enter", "checkout "),
+            ("c", "heckout "),
+            ("l", "og "),
+            ("n", "ew "),
+            ("x", "del "),
+            ("\u{2190}\u{2192}", "tab "),
+            ("Esc", "back"),
+        ],
+        GitTab::Stash | GitTab::Tags if state.log_detail.is_some() => vec![
+            ("\u{2191}\u{2193}", "scroll "),
+            ("PgUp/Dn", "scroll "),
+            ("Esc", "close"),
+        ],
+        GitTab::Stash => vec![
+            ("\u{2191}\u{2193}", "nav "),
+            ("Enter", "show "),
+            ("a", "pply "),
+            ("p", "op "),
+            ("x", "drop "),
+            ("n", "ew "),
+            ("\u{2190}\u{2192}", "tab "),
+            ("Esc", "back"),
+        ],
+        GitTab::Tags => vec![
+            ("\u{2191}\u{2193}", "nav "),
+            ("Enter", "show "),
             ("n", "ew "),
             ("x", "del "),
             ("\u{2190}\u{2192}", "tab "),
             ("Esc", "back"),
         ],
+        GitTab::Remotes => vec![
+            ("\u{2191}\u{2193}", "nav "),
+            ("f", "etch "),
+            ("F", "etch all "),
+            ("l", "pull "),
+            ("p", "ush "),
+            ("\u{2190}\u{2192}", "tab "),
+            ("Esc", "back"),
+        ],
+        GitTab::Trees => vec![
+            ("\u{2191}\u{2193}", "nav "),
+            ("Enter", "open in panel "),
+            ("a", "dd worktree "),
+            ("x", "remove "),
+            ("p", "rune "),
+            ("i", "nit "),
+            ("u", "pdate "),
+            ("U", "pdate all "),
+            ("Esc", "back"),
+        ],
     };
 
     let mut spans = Vec::new();
@@ -1181,6 +4731,13 @@ fn draw_input_dialog(
     let title = match &state.input_mode {
         Some(InputMode::BranchCreate) => " New Branch Name ",
         Some(InputMode::CommitAmend) => " Amend Message ",
+        Some(InputMode::StashCreate) => " Stash Message ",
+        Some(InputMode::TagCreate) => " New Tag Name ",
+        Some(InputMode::TagMessage(_)) => " Tag Message ",
+        Some(InputMode::RebaseReword(_)) => " Reword Commit ",
+        Some(InputMode::LogFilter) => " Filter: author: path: since: until: text ",
+        Some(InputMode::WorktreeAdd) => " New Worktree Path ",
+        Some(InputMode::WorktreeBranch(_)) => " Worktree Branch (empty: from path) ",
         None => return,
     };
 
@@ -1228,6 +4785,65 @@ fn draw_confirm_dialog(
         Some(ConfirmAction::RestoreToCommit(hash)) => {
             (format!("Restore files to {}?", hash), " Restore ")
         }
+        Some(ConfirmAction::DiscardChanges(file)) => (
+            format!("Discard selected changes in {}?", file),
+            " Discard ",
+        ),
+        Some(ConfirmAction::StashDrop(index)) => {
+            (format!("Drop stash@{{{}}}?", index), " Drop Stash ")
+        }
+        Some(ConfirmAction::TagDelete(name)) => (format!("Delete tag '{}'?", name), " Delete "),
+        Some(ConfirmAction::MarkResolved(file)) => (
+            format!("{} still has conflict markers. Mark resolved?", file),
+            " Resolve ",
+        ),
+        Some(ConfirmAction::CherryPick(hashes)) => (
+            format!(
+                "Cherry-pick {} commit(s) onto {}?",
+                hashes.len(),
+                state.branch_name
+            ),
+            " Cherry-pick ",
+        ),
+        Some(ConfirmAction::Revert(hashes)) => (
+            format!(
+                "Revert {} commit(s) on {}?",
+                hashes.len(),
+                state.branch_name
+            ),
+            " Revert ",
+        ),
+        Some(ConfirmAction::RunRebase) => (
+            format!(
+                "Rewrite {} commit(s) on {}?",
+                state.rebase_editor.as_ref().map_or(0, |e| e.items.len()),
+                state.branch_name
+            ),
+            " Rebase ",
+        ),
+        Some(ConfirmAction::AbortOperation(op)) => (
+            format!("Abort {} and restore the branch?", op.command()),
+            " Abort ",
+        ),
+        Some(ConfirmAction::WorktreeRemove(path, changes)) => {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let msg = match changes {
+                0 => format!("Remove worktree '{}'?", name),
+                n => format!("Remove '{}' and discard {} change(s)?", name, n),
+            };
+            (msg, " Remove Worktree ")
+        }
+        Some(ConfirmAction::SkipHooks) => (
+            "Skip pre-commit and commit-msg hooks?".to_string(),
+            " Skip Hooks ",
+        ),
+        Some(ConfirmAction::CommitDraft) => (
+            "Commit with the AI-drafted message?".to_string(),
+            " Commit ",
+        ),
         None => return,
     };
     let cd = &theme.confirm_dialog;
@@ -1305,7 +4921,17 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
             && state.confirm_action.is_none()
             && state.input_mode.is_none()
             && !state.commit_input_active
-            && state.log_detail.is_none();
+            && state.log_detail.is_none()
+            && state.hunk_view.is_none()
+            && state.job.is_none()
+            && state.hook_output.is_none()
+            && !(state.current_tab == GitTab::Commit && state.conflict_view)
+            && !(state.current_tab == GitTab::Log
+                && (state.rebase_editor.is_some()
+                    || state.log_files.is_some()
+                    || state.log_ref.is_some()
+                    || !state.log_filter.is_empty()
+                    || !state.log_marked.is_empty()));
 
         if should_close {
             app.current_screen = Screen::FilePanel;
@@ -1329,59 +4955,113 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
         return;
     }
 
+    // Esc dismisses the output of a finished fetch/pull/push
+    if code == KeyCode::Esc && state.job.is_some() {
+        if state.is_job_running() {
+            state.show_msg("Wait for the git command to finish");
+        } else {
+            state.job = None;
+        }
+        return;
+    }
+
+    // Scroll or dismiss the output of a rejected commit
+    if state.hook_output.is_some() {
+        handle_hook_output_input(state, code);
+        return;
+    }
+
     // Handle commit message input
     if state.commit_input_active && state.current_tab == GitTab::Commit {
         handle_commit_input(state, code, modifiers);
         return;
     }
 
+    // Handle the hunk staging view in commit tab
+    if state.hunk_view.is_some() && state.current_tab == GitTab::Commit {
+        handle_hunk_view_input(state, code);
+        return;
+    }
+
     // Handle diff detail scrolling in log tab
     if state.log_detail.is_some() && state.current_tab == GitTab::Log {
         handle_log_detail_input(state, code);
         return;
     }
 
-    // Handle diff detail scrolling in commit tab
-    if state.log_detail.is_some() && state.current_tab == GitTab::Commit {
-        handle_status_diff_input(state, code);
+    // Handle the interactive rebase editor in log tab
+    if state.rebase_editor.is_some() && state.current_tab == GitTab::Log {
+        handle_rebase_editor_input(state, code, modifiers);
         return;
     }
 
-    // Common keys
-    match code {
-        KeyCode::Char('1') => {
-            state.current_tab = GitTab::Commit;
-            state.refresh_status();
+    // Continue/skip/abort a stopped cherry-pick, revert, rebase or merge
+    if let Some(op) = state.operation {
+        let step = match code {
+            KeyCode::Char('C') => Some("--continue"),
+            KeyCode::Char('S') if op.can_skip() => Some("--skip"),
+            KeyCode::Char('A') => {
+                state.confirm_action = Some(ConfirmAction::AbortOperation(op));
+                state.confirm_selected_button = 1;
+                return;
+            }
+            _ => None,
+        };
+        if let Some(step) = step {
+            let result = run_operation_step(&state.repo_path, op, step);
+            let done = format!("{} finished", op.command());
+            state.finish_sequence_step(result, &done);
             return;
         }
-        KeyCode::Char('2') => {
-            state.current_tab = GitTab::Log;
-            return;
+    }
+
+    // Files of a commit: the file diff view needs the app, so it is opened here
+    if state.log_files.is_some() && state.current_tab == GitTab::Log {
+        if let Some((left, right, name)) = handle_log_files_input(state, code) {
+            app.enter_diff_file_view(left, right, name, ReturnScreen::Screen(Screen::GitScreen));
+        }
+        return;
+    }
+
+    // Conflict list: the merge view needs the app, so it is opened here
+    if state.conflict_view && state.current_tab == GitTab::Commit {
+        if let Some(stages) = handle_conflict_input(state, code) {
+            app.enter_merge_file_view_into(
+                stages.base,
+                stages.ours,
+                stages.theirs,
+                stages.output,
+                format!("{} (left: ours, right: theirs)", stages.file),
+                ReturnScreen::Screen(Screen::GitScreen),
+            );
         }
-        KeyCode::Char('3') => {
-            state.current_tab = GitTab::Branch;
+        return;
+    }
+
+    // Handle diff detail scrolling in commit, stash and tags tabs
+    if state.log_detail.is_some()
+        && matches!(
+            state.current_tab,
+            GitTab::Commit | GitTab::Stash | GitTab::Tags
+        )
+    {
+        handle_status_diff_input(state, code);
+        return;
+    }
+
+    // Common keys
+    match code {
+        KeyCode::Char(c @ '1'..='7') => {
+            let index = c as usize - '1' as usize;
+            state.set_tab(GitTab::ALL[index]);
             return;
         }
         KeyCode::Left => {
-            state.current_tab = match state.current_tab {
-                GitTab::Commit => GitTab::Branch,
-                GitTab::Log => GitTab::Commit,
-                GitTab::Branch => GitTab::Log,
-            };
-            if matches!(state.current_tab, GitTab::Commit) {
-                state.refresh_status();
-            }
+            state.set_tab(state.current_tab.prev());
             return;
         }
         KeyCode::Right => {
-            state.current_tab = match state.current_tab {
-                GitTab::Commit => GitTab::Log,
-                GitTab::Log => GitTab::Branch,
-                GitTab::Branch => GitTab::Commit,
-            };
-            if matches!(state.current_tab, GitTab::Commit) {
-                state.refresh_status();
-            }
+            state.set_tab(state.current_tab.next());
             return;
         }
         _ => {}
@@ -1392,7 +5072,247 @@ pub fn handle_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
         GitTab::Commit => handle_commit_tab_input(state, code, modifiers),
         GitTab::Log => handle_log_input(state, code),
         GitTab::Branch => handle_branch_input(state, code),
+        GitTab::Stash => handle_stash_input(state, code),
+        GitTab::Tags => handle_tags_input(state, code),
+        GitTab::Remotes => handle_remotes_input(state, code),
+        GitTab::Trees => {
+            // Opening a checkout in a new panel needs the app
+            if let Some(path) = handle_trees_input(state, code) {
+                open_in_new_panel(app, path);
+            }
+        }
+    }
+}
+
+/// Leave the git screen and show `path` in a panel added next to the active one
+fn open_in_new_panel(app: &mut App, path: PathBuf) {
+    if app.panels.len() >= 10 {
+        if let Some(state) = app.git_screen_state.as_mut() {
+            state.show_msg("Too many panels open");
+        }
+        return;
+    }
+    app.current_screen = Screen::FilePanel;
+    app.git_screen_state = None;
+    app.refresh_panels();
+    app.add_panel();
+    app.goto_directory_with_focus(&path, None);
+}
+
+/// Move a list selection with the usual navigation keys. Returns true if the key was used.
+fn navigate_list(selected: &mut usize, len: usize, code: KeyCode) -> bool {
+    let last = len.saturating_sub(1);
+    match code {
+        KeyCode::Up => *selected = selected.saturating_sub(1),
+        KeyCode::Down => *selected = (*selected + 1).min(last),
+        KeyCode::Home => *selected = 0,
+        KeyCode::End => *selected = last,
+        KeyCode::PageUp => *selected = selected.saturating_sub(10),
+        KeyCode::PageDown => *selected = (*selected + 10).min(last),
+        _ => return false,
+    }
+    true
+}
+
+fn handle_stash_input(state: &mut GitScreenState, code: KeyCode) {
+    if code == KeyCode::Char('n') {
+        state.input_mode = Some(InputMode::StashCreate);
+        state.input_buffer.clear();
+        return;
+    }
+    if navigate_list(&mut state.stash_selected, state.stashes.len(), code) {
+        return;
+    }
+    let index = match state.stashes.get(state.stash_selected) {
+        Some(entry) => entry.index,
+        None => return,
+    };
+    let stash_ref = format!("stash@{{{}}}", index);
+
+    match code {
+        KeyCode::Enter => {
+            state.log_detail = Some(stash_show(&state.repo_path, index));
+            state.log_detail_scroll = 0;
+        }
+        KeyCode::Char('a') | KeyCode::Char('p') => {
+            let (op, done) = if code == KeyCode::Char('a') {
+                ("apply", "Applied")
+            } else {
+                ("pop", "Popped")
+            };
+            match run_git(&state.repo_path, &["stash", op, &stash_ref]) {
+                Ok(_) => {
+                    state.show_msg(&format!("{} {}", done, stash_ref));
+                    state.refresh_all();
+                }
+                Err(e) => {
+                    let short_err = e.lines().next().unwrap_or("Stash failed").to_string();
+                    state.show_msg(&short_err);
+                    // A conflicting apply still changes the worktree
+                    state.refresh_all();
+                }
+            }
+        }
+        KeyCode::Char('x') => {
+            state.confirm_action = Some(ConfirmAction::StashDrop(index));
+            state.confirm_selected_button = 1;
+        }
+        _ => {}
+    }
+}
+
+fn handle_tags_input(state: &mut GitScreenState, code: KeyCode) {
+    if code == KeyCode::Char('n') {
+        state.input_mode = Some(InputMode::TagCreate);
+        state.input_buffer.clear();
+        return;
+    }
+    if navigate_list(&mut state.tag_selected, state.tags.len(), code) {
+        return;
+    }
+    let name = match state.tags.get(state.tag_selected) {
+        Some(tag) => tag.name.clone(),
+        None => return,
+    };
+
+    match code {
+        KeyCode::Enter => {
+            state.log_detail = Some(tag_show(&state.repo_path, &name));
+            state.log_detail_scroll = 0;
+        }
+        KeyCode::Char('x') => {
+            state.confirm_action = Some(ConfirmAction::TagDelete(name));
+            state.confirm_selected_button = 1;
+        }
+        _ => {}
+    }
+}
+
+fn handle_remotes_input(state: &mut GitScreenState, code: KeyCode) {
+    if navigate_list(&mut state.remote_selected, state.remotes.len(), code) {
+        return;
+    }
+    let remote = state
+        .remotes
+        .get(state.remote_selected)
+        .map(|r| r.name.clone());
+
+    let args: Vec<&str> = match code {
+        KeyCode::Char('f') => match remote {
+            Some(ref name) => vec!["fetch", "--progress", name],
+            None => {
+                state.show_msg("No remotes configured");
+                return;
+            }
+        },
+        KeyCode::Char('F') => vec!["fetch", "--progress", "--all"],
+        KeyCode::Char('l') => vec!["pull", "--progress"],
+        KeyCode::Char('p') => {
+            if state.upstream.is_some() {
+                vec!["push", "--progress"]
+            } else {
+                // First push of a branch: publish it and set the upstream
+                let branch = state.branch_name.clone();
+                if branch.starts_with('(') || !is_valid_ref_name(&branch) {
+                    state.show_msg("Cannot push a detached HEAD");
+                    return;
+                }
+                match remote {
+                    Some(name) => {
+                        let args = ["push", "--progress", "-u", name.as_str(), branch.as_str()];
+                        state.start_job(args.iter().map(|a| a.to_string()).collect());
+                    }
+                    None => state.show_msg("No remotes configured"),
+                }
+                return;
+            }
+        }
+        _ => return,
+    };
+    state.start_job(args.iter().map(|a| a.to_string()).collect());
+}
+
+/// Keys of the trees tab. Returns the checkout to open in a new panel.
+fn handle_trees_input(state: &mut GitScreenState, code: KeyCode) -> Option<PathBuf> {
+    match code {
+        KeyCode::Char('a') => {
+            state.input_mode = Some(InputMode::WorktreeAdd);
+            state.input_buffer.clear();
+            return None;
+        }
+        KeyCode::Char('p') => {
+            match run_git(&state.repo_path, &["worktree", "prune"]) {
+                Ok(_) => state.show_msg("Pruned stale worktrees"),
+                Err(e) => {
+                    let short_err = e.lines().next().unwrap_or("Prune failed").to_string();
+                    state.show_msg(&short_err);
+                }
+            }
+            state.refresh_trees();
+            return None;
+        }
+        KeyCode::Char('U') => {
+            if state.trees.iter().any(|t| t.kind == TreeKind::Submodule) {
+                let args = ["submodule", "update", "--init", "--recursive", "--progress"];
+                state.start_job(args.iter().map(|a| a.to_string()).collect());
+            } else {
+                state.show_msg("No submodules");
+            }
+            return None;
+        }
+        _ => {}
+    }
+    if navigate_list(&mut state.tree_selected, state.trees.len(), code) {
+        return None;
+    }
+    let tree = state.trees.get(state.tree_selected)?.clone();
+
+    match code {
+        KeyCode::Enter | KeyCode::Char('o') => {
+            if tree.changes.is_some() {
+                return Some(tree.path);
+            }
+            state.show_msg(&format!("{} is not checked out", tree.name));
+        }
+        KeyCode::Char('x') => match tree.kind {
+            TreeKind::Worktree if tree.is_current => {
+                state.show_msg("Cannot remove the worktree in use")
+            }
+            TreeKind::Worktree => {
+                let changes = tree.changes.unwrap_or(0);
+                state.confirm_action = Some(ConfirmAction::WorktreeRemove(tree.path, changes));
+                state.confirm_selected_button = 1;
+            }
+            TreeKind::Submodule => state.show_msg("Only worktrees can be removed"),
+        },
+        KeyCode::Char('i') | KeyCode::Char('u') if tree.kind == TreeKind::Submodule => {
+            let path = tree.path.to_string_lossy().to_string();
+            if code == KeyCode::Char('i') {
+                match run_git(&state.repo_path, &["submodule", "init", "--", &path]) {
+                    Ok(_) => state.show_msg(&format!("Initialized {}", tree.name)),
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Init failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+                state.refresh_trees();
+            } else {
+                let args = [
+                    "submodule",
+                    "update",
+                    "--init",
+                    "--recursive",
+                    "--progress",
+                    "--",
+                    &path,
+                ];
+                state.start_job(args.iter().map(|a| a.to_string()).collect());
+            }
+        }
+        KeyCode::Char('i') | KeyCode::Char('u') => state.show_msg("Not a submodule"),
+        _ => {}
     }
+    None
 }
 
 fn handle_status_diff_input(state: &mut GitScreenState, code: KeyCode) {
@@ -1472,10 +5392,10 @@ fn handle_commit_tab_input(state: &mut GitScreenState, code: KeyCode, modifiers:
             }
         }
         KeyCode::Char(' ') => {
-            // Stage/unstage toggle
+            // Stage/unstage toggle (partially staged files get the rest staged)
             if let Some(entry) = state.status_files.get(state.commit_selected) {
                 let path = entry.path.clone();
-                if entry.staged {
+                if entry.staged && !is_partially_staged(entry) {
                     let _ = unstage_file(&state.repo_path, &path);
                 } else {
                     let _ = stage_file(&state.repo_path, &path);
@@ -1484,8 +5404,14 @@ fn handle_commit_tab_input(state: &mut GitScreenState, code: KeyCode, modifiers:
             }
         }
         KeyCode::Enter => {
-            // Show diff for selected file
-            if let Some(entry) = state.status_files.get(state.commit_selected) {
+            // Open the hunk view; untracked or binary files fall back to the plain view
+            if let Some(entry) = state.status_files.get(state.commit_selected).cloned() {
+                if entry.index_status != '?'
+                    && !is_conflicted(&entry)
+                    && state.open_hunk_view(&entry).is_ok()
+                {
+                    return;
+                }
                 let diff = get_file_diff(&state.repo_path, &entry.path, entry.staged);
                 if diff.is_empty() {
                     let full_path = state.repo_path.join(&entry.path);
@@ -1503,6 +5429,24 @@ fn handle_commit_tab_input(state: &mut GitScreenState, code: KeyCode, modifiers:
         KeyCode::Tab => {
             state.commit_input_active = true;
         }
+        KeyCode::Char('g') => {
+            state.start_draft();
+        }
+        KeyCode::Char('G') => {
+            state.commit_options.signing = state.commit_options.signing.next();
+            state.show_msg(&format!(
+                "Commit signing: {}",
+                state.commit_options.signing.label()
+            ));
+        }
+        KeyCode::Char('H') => toggle_skip_hooks(state),
+        KeyCode::Char('m') => {
+            if state.conflicts.is_empty() {
+                state.show_msg("No merge conflicts");
+            } else {
+                state.open_conflict_view();
+            }
+        }
         KeyCode::Char('a') => {
             // Amend mode
             state.input_mode = Some(InputMode::CommitAmend);
@@ -1520,40 +5464,238 @@ fn handle_commit_tab_input(state: &mut GitScreenState, code: KeyCode, modifiers:
     }
 }
 
-fn handle_commit_input(state: &mut GitScreenState, code: KeyCode, _modifiers: KeyModifiers) {
+fn handle_hunk_view_input(state: &mut GitScreenState, code: KeyCode) {
+    let view = match state.hunk_view {
+        Some(ref mut v) => v,
+        None => return,
+    };
+    let last_row = view.row_count().saturating_sub(1);
+
+    match code {
+        KeyCode::Esc => {
+            if view.anchor.is_some() {
+                view.anchor = None;
+            } else {
+                state.hunk_view = None;
+                state.refresh_status();
+            }
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            view.cursor = view.cursor.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            view.cursor = (view.cursor + 1).min(last_row);
+        }
+        KeyCode::PageUp => {
+            view.cursor = view.cursor.saturating_sub(20);
+        }
+        KeyCode::PageDown => {
+            view.cursor = (view.cursor + 20).min(last_row);
+        }
+        KeyCode::Home => {
+            view.cursor = 0;
+        }
+        KeyCode::End => {
+            view.cursor = last_row;
+        }
+        KeyCode::Char('n') => {
+            if let Some((h, _)) = view.locate(view.cursor) {
+                if h + 1 < view.diff.hunks.len() {
+                    view.cursor = view.hunk_row(h + 1);
+                    view.anchor = None;
+                }
+            }
+        }
+        KeyCode::Char('p') => {
+            if let Some((h, line)) = view.locate(view.cursor) {
+                // From inside a hunk go to its header first, like most pagers
+                let target = if line.is_some() || h == 0 { h } else { h - 1 };
+                view.cursor = view.hunk_row(target);
+                view.anchor = None;
+            }
+        }
+        KeyCode::Char('v') => {
+            view.anchor = match view.anchor {
+                Some(_) => None,
+                None => Some(view.cursor),
+            };
+        }
+        KeyCode::Tab => {
+            let other = match view.side {
+                HunkSide::Unstaged => HunkSide::Staged,
+                HunkSide::Staged => HunkSide::Unstaged,
+            };
+            state.reload_hunk_view(other);
+        }
+        KeyCode::Char(' ') => {
+            let side = view.side;
+            apply_hunk_selection(state, side, false);
+        }
+        KeyCode::Char('d') => {
+            if view.side == HunkSide::Unstaged {
+                let file = view.file.clone();
+                state.confirm_action = Some(ConfirmAction::DiscardChanges(file));
+                state.confirm_selected_button = 1;
+            } else {
+                state.show_msg("Unstage changes before discarding them");
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Stage, unstage or (with `discard`) drop the hunk or lines selected in the hunk view
+fn apply_hunk_selection(state: &mut GitScreenState, side: HunkSide, discard: bool) {
+    let (patch, count) = {
+        let view = match state.hunk_view {
+            Some(ref v) => v,
+            None => return,
+        };
+        let (hunk, lines) = match view.selection() {
+            Some(sel) => sel,
+            None => return,
+        };
+        if lines.is_some() && view.diff.is_new_or_deleted() {
+            state.show_msg("Only whole hunks can be applied for new or deleted files");
+            return;
+        }
+        let count = lines.as_ref().map(|l| l.len());
+        // Staging applies the worktree diff forward; unstaging and discarding reverse it
+        let reverse = side == HunkSide::Staged || discard;
+        match view.diff.hunk_patch(hunk, lines.as_deref(), reverse) {
+            Some(patch) => (patch, count),
+            None => {
+                state.show_msg("No changed lines selected");
+                return;
+            }
+        }
+    };
+
+    let result = if discard {
+        apply_patch(&state.repo_path, &patch, false, true)
+    } else {
+        apply_patch(&state.repo_path, &patch, true, side == HunkSide::Staged)
+    };
+    match result {
+        Ok(()) => {
+            let verb = match (discard, side) {
+                (true, _) => "Discarded",
+                (false, HunkSide::Unstaged) => "Staged",
+                (false, HunkSide::Staged) => "Unstaged",
+            };
+            let what = match count {
+                Some(1) => "1 line".to_string(),
+                Some(n) => format!("{} lines", n),
+                None => "hunk".to_string(),
+            };
+            state.show_msg(&format!("{} {}", verb, what));
+            state.reload_hunk_view(side);
+        }
+        Err(e) => {
+            let short_err = e.lines().next().unwrap_or("git apply failed").to_string();
+            state.show_msg(&short_err);
+        }
+    }
+}
+
+fn handle_commit_input(state: &mut GitScreenState, code: KeyCode, modifiers: KeyModifiers) {
+    // While a draft streams in, only cancelling is possible
+    if state.draft.is_some() {
+        if code == KeyCode::Esc {
+            state.cancel_draft();
+        } else {
+            state.show_msg("Drafting commit message (Esc: cancel)");
+        }
+        return;
+    }
     match code {
         KeyCode::Tab | KeyCode::Esc => {
             state.commit_input_active = false;
         }
+        KeyCode::Enter if modifiers.contains(KeyModifiers::ALT) => {
+            state.commit_message.push('\n');
+        }
         KeyCode::Enter => {
             if state.commit_message.trim().is_empty() {
                 state.show_msg("Commit message is empty");
-                return;
-            }
-            match do_commit(&state.repo_path, &state.commit_message) {
-                Ok(msg) => {
-                    let short_msg = msg.lines().next().unwrap_or("Committed").to_string();
-                    state.show_msg(&short_msg);
-                    state.commit_message.clear();
-                    state.commit_input_active = false;
-                    state.refresh_all();
-                }
-                Err(e) => {
-                    let short_err = e.lines().next().unwrap_or("Commit failed").to_string();
-                    state.show_msg(&short_err);
-                }
+                return;
+            }
+            // Never commit an AI draft without an explicit yes
+            if state.message_drafted {
+                state.confirm_action = Some(ConfirmAction::CommitDraft);
+                state.confirm_selected_button = 1;
+                return;
             }
+            commit_now(state);
+        }
+        KeyCode::Char('g') if modifiers.contains(KeyModifiers::CONTROL) => {
+            state.start_draft();
         }
         KeyCode::Char(c) => {
             state.commit_message.push(c);
         }
         KeyCode::Backspace => {
             state.commit_message.pop();
+            if state.commit_message.is_empty() {
+                state.message_drafted = false;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_hook_output_input(state: &mut GitScreenState, code: KeyCode) {
+    let output = match state.hook_output {
+        Some(ref mut o) => o,
+        None => return,
+    };
+    // Clamped to the visible height when drawn
+    let last = output.lines.len().saturating_sub(1);
+    match code {
+        KeyCode::Esc => state.hook_output = None,
+        KeyCode::Up | KeyCode::Char('k') => output.scroll = output.scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => output.scroll = (output.scroll + 1).min(last),
+        KeyCode::PageUp => output.scroll = output.scroll.saturating_sub(10),
+        KeyCode::PageDown => output.scroll = (output.scroll + 10).min(last),
+        KeyCode::Home => output.scroll = 0,
+        KeyCode::End => output.scroll = last,
+        KeyCode::Char('H') => {
+            state.hook_output = None;
+            toggle_skip_hooks(state);
         }
         _ => {}
     }
 }
 
+/// Turning hooks off bypasses the repository's checks, so it needs a yes
+fn toggle_skip_hooks(state: &mut GitScreenState) {
+    if state.commit_options.skip_hooks {
+        state.commit_options.skip_hooks = false;
+        state.show_msg("Commit hooks will run");
+    } else {
+        state.confirm_action = Some(ConfirmAction::SkipHooks);
+        state.confirm_selected_button = 1;
+    }
+}
+
+fn commit_now(state: &mut GitScreenState) {
+    match do_commit(
+        &state.repo_path,
+        &state.commit_message,
+        &state.commit_options,
+    ) {
+        Ok(msg) => {
+            let short_msg = msg.lines().next().unwrap_or("Committed").to_string();
+            state.show_msg(&short_msg);
+            state.commit_message.clear();
+            state.message_drafted = false;
+            state.commit_input_active = false;
+            state.refresh_all();
+        }
+        Err(e) => state.commit_failed(&e, "Commit"),
+    }
+}
+
 fn handle_log_input(state: &mut GitScreenState, code: KeyCode) {
     let len = state.log_entries.len();
     if len == 0 {
@@ -1595,8 +5737,183 @@ fn handle_log_input(state: &mut GitScreenState, code: KeyCode) {
                 state.confirm_selected_button = 1;
             }
         }
+        KeyCode::Char(' ') => {
+            // Mark/unmark for cherry-pick or revert, then move down
+            if let Some(entry) = state.log_entries.get(state.log_selected) {
+                let hash = entry.hash.clone();
+                if let Some(pos) = state.log_marked.iter().position(|h| *h == hash) {
+                    state.log_marked.remove(pos);
+                } else {
+                    state.log_marked.push(hash);
+                }
+                if state.log_selected + 1 < len {
+                    state.log_selected += 1;
+                }
+            }
+        }
+        KeyCode::Char('c') | KeyCode::Char('v') => {
+            if state.operation.is_some() {
+                state.show_msg("Finish the operation in progress first");
+                return;
+            }
+            let action = if code == KeyCode::Char('c') {
+                ConfirmAction::CherryPick(state.target_commits(true))
+            } else {
+                ConfirmAction::Revert(state.target_commits(false))
+            };
+            state.confirm_action = Some(action);
+            state.confirm_selected_button = 1;
+        }
+        KeyCode::Char('i') => {
+            if let Err(e) = state.open_rebase_editor() {
+                state.show_msg(&e);
+            }
+        }
+        KeyCode::Char('f') => {
+            if let Some(entry) = state.log_entries.get(state.log_selected) {
+                let hash = entry.hash.clone();
+                state.log_files = Some(CommitFiles {
+                    files: get_commit_files(&state.repo_path, &hash),
+                    hash,
+                    selected: 0,
+                    scroll: 0,
+                });
+                state.log_detail = None;
+            }
+        }
+        KeyCode::Char('/') => {
+            state.input_buffer = state.log_filter.to_input();
+            state.input_mode = Some(InputMode::LogFilter);
+        }
+        KeyCode::Esc => {
+            // Clear marks first, then the filter, then go back to the current branch's log
+            if !state.log_marked.is_empty() {
+                state.log_marked.clear();
+            } else if !state.log_filter.is_empty() {
+                state.set_log_filter(LogFilter::default());
+            } else if state.log_ref.is_some() {
+                state.set_log_ref(None);
+            }
+        }
+        _ => {}
+    }
+    state.load_more_log();
+}
+
+/// Keys of a commit's file list. Returns the two versions to open in the file diff view.
+fn handle_log_files_input(
+    state: &mut GitScreenState,
+    code: KeyCode,
+) -> Option<(PathBuf, PathBuf, String)> {
+    let files = state.log_files.as_mut()?;
+    if code == KeyCode::Esc {
+        state.log_files = None;
+        return None;
+    }
+    if navigate_list(&mut files.selected, files.files.len(), code) || code != KeyCode::Enter {
+        return None;
+    }
+    let hash = files.hash.clone();
+    let file = files.files.get(files.selected)?.clone();
+    match extract_commit_file(&state.repo_path, &hash, &file) {
+        Ok((left, right)) => Some((left, right, format!("{} @ {}", file.path, hash))),
+        Err(e) => {
+            state.show_msg(&e);
+            None
+        }
+    }
+}
+
+/// Keys of the conflict list. Returns the stages to open in the merge view.
+fn handle_conflict_input(state: &mut GitScreenState, code: KeyCode) -> Option<ConflictStages> {
+    if code == KeyCode::Esc {
+        state.conflict_view = false;
+        return None;
+    }
+    if navigate_list(&mut state.conflict_selected, state.conflicts.len(), code) {
+        return None;
+    }
+    let conflict = state.conflicts.get(state.conflict_selected)?.clone();
+
+    match code {
+        KeyCode::Enter => match extract_conflict_stages(&state.repo_path, &conflict.path) {
+            Ok(stages) => return Some(stages),
+            Err(e) => state.show_msg(&e),
+        },
+        KeyCode::Char('o') | KeyCode::Char('t') => {
+            let ours = code == KeyCode::Char('o');
+            match resolve_with_side(&state.repo_path, &conflict.path, ours) {
+                Ok(msg) => state.show_msg(&msg),
+                Err(e) => {
+                    let short_err = e.lines().next().unwrap_or("Resolve failed").to_string();
+                    state.show_msg(&short_err);
+                }
+            }
+            state.refresh_status();
+        }
+        KeyCode::Char('a') => {
+            if conflict.markers > 0 {
+                state.confirm_action = Some(ConfirmAction::MarkResolved(conflict.path));
+                state.confirm_selected_button = 1;
+            } else {
+                match mark_resolved(&state.repo_path, &conflict.path) {
+                    Ok(()) => state.show_msg(&format!("Marked {} resolved", conflict.path)),
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+                state.refresh_status();
+            }
+        }
         _ => {}
     }
+    None
+}
+
+fn handle_rebase_editor_input(state: &mut GitScreenState, code: KeyCode, modifiers: KeyModifiers) {
+    let editor = match state.rebase_editor {
+        Some(ref mut e) => e,
+        None => return,
+    };
+    let shift = modifiers.contains(KeyModifiers::SHIFT);
+
+    match code {
+        KeyCode::Esc => {
+            state.rebase_editor = None;
+        }
+        KeyCode::Up if shift => editor.move_selected(false),
+        KeyCode::Down if shift => editor.move_selected(true),
+        KeyCode::Char('K') => editor.move_selected(false),
+        KeyCode::Char('J') => editor.move_selected(true),
+        KeyCode::Char('k') => editor.selected = editor.selected.saturating_sub(1),
+        KeyCode::Char('j') => {
+            editor.selected = (editor.selected + 1).min(editor.items.len().saturating_sub(1))
+        }
+        KeyCode::Char('p') => editor.set_action(RebaseAction::Pick),
+        KeyCode::Char('s') => editor.set_action(RebaseAction::Squash),
+        KeyCode::Char('f') => editor.set_action(RebaseAction::Fixup),
+        KeyCode::Char('d') => editor.set_action(RebaseAction::Drop),
+        KeyCode::Char('r') => {
+            if let Some(item) = editor.items.get(editor.selected) {
+                state.input_buffer = item
+                    .new_message
+                    .clone()
+                    .unwrap_or_else(|| item.message.clone());
+                state.input_mode = Some(InputMode::RebaseReword(editor.selected));
+            }
+        }
+        KeyCode::Enter => match editor.validate() {
+            Ok(()) => {
+                state.confirm_action = Some(ConfirmAction::RunRebase);
+                state.confirm_selected_button = 1;
+            }
+            Err(e) => state.show_msg(&e),
+        },
+        _ => {
+            navigate_list(&mut editor.selected, editor.items.len(), code);
+        }
+    }
 }
 
 fn handle_log_detail_input(state: &mut GitScreenState, code: KeyCode) {
@@ -1639,6 +5956,7 @@ fn handle_log_detail_input(state: &mut GitScreenState, code: KeyCode) {
             if state.log_selected + 1 < state.log_entries.len() {
                 state.log_selected += 1;
             }
+            state.load_more_log();
             if let Some(entry) = state.log_entries.get(state.log_selected) {
                 let diff = get_commit_diff(&state.repo_path, &entry.hash);
                 state.log_detail = Some(diff);
@@ -1705,6 +6023,18 @@ fn handle_branch_input(state: &mut GitScreenState, code: KeyCode) {
             state.input_mode = Some(InputMode::BranchCreate);
             state.input_buffer.clear();
         }
+        KeyCode::Char('l') => {
+            // Show the branch's history in the log tab to cherry-pick from it
+            if let Some(branch) = state.branches.get(state.branch_selected) {
+                let log_ref = if branch.is_current {
+                    None
+                } else {
+                    Some(branch.name.clone())
+                };
+                state.set_log_ref(log_ref);
+                state.current_tab = GitTab::Log;
+            }
+        }
         KeyCode::Char('x') => {
             // Delete branch (force)
             if let Some(branch) = state.branches.get(state.branch_selected) {
@@ -1732,7 +6062,15 @@ fn handle_input_mode(state: &mut GitScreenState, code: KeyCode) {
         }
         KeyCode::Enter => {
             let input = state.input_buffer.clone();
-            if input.trim().is_empty() {
+            // Stash and tag messages may be left empty to use a default
+            let allows_empty = matches!(
+                state.input_mode,
+                Some(InputMode::StashCreate)
+                    | Some(InputMode::TagMessage(_))
+                    | Some(InputMode::LogFilter)
+                    | Some(InputMode::WorktreeBranch(_))
+            );
+            if input.trim().is_empty() && !allows_empty {
                 state.input_mode = None;
                 state.input_buffer.clear();
                 return;
@@ -1751,19 +6089,95 @@ fn handle_input_mode(state: &mut GitScreenState, code: KeyCode) {
                         }
                     }
                 }
-                Some(InputMode::CommitAmend) => {
-                    match do_commit_amend(&state.repo_path, input.trim()) {
+                Some(InputMode::StashCreate) => {
+                    let mut args = vec!["stash", "push"];
+                    if !input.trim().is_empty() {
+                        args.extend(["-m", input.trim()]);
+                    }
+                    match run_git(&state.repo_path, &args) {
                         Ok(msg) => {
-                            let short_msg = msg.lines().next().unwrap_or("Amended").to_string();
+                            let short_msg = msg.lines().next().unwrap_or("Stashed").to_string();
                             state.show_msg(&short_msg);
+                            state.stash_selected = 0;
+                            state.refresh_all();
+                        }
+                        Err(e) => {
+                            let short_err = e.lines().next().unwrap_or("Stash failed").to_string();
+                            state.show_msg(&short_err);
+                        }
+                    }
+                }
+                Some(InputMode::TagCreate) => {
+                    let name = input.trim().to_string();
+                    if is_valid_ref_name(&name) {
+                        // Ask for the annotation next
+                        state.input_mode = Some(InputMode::TagMessage(name));
+                    } else {
+                        state.show_msg("Invalid tag name");
+                    }
+                }
+                Some(InputMode::TagMessage(name)) => {
+                    let message = if input.trim().is_empty() {
+                        name.as_str()
+                    } else {
+                        input.trim()
+                    };
+                    match run_git(&state.repo_path, &["tag", "-a", &name, "-m", message]) {
+                        Ok(_) => {
+                            state.show_msg(&format!("Created tag {}", name));
                             state.refresh_all();
                         }
                         Err(e) => {
-                            let short_err = e.lines().next().unwrap_or("Amend failed").to_string();
+                            let short_err = e.lines().next().unwrap_or("Tag failed").to_string();
+                            state.show_msg(&short_err);
+                        }
+                    }
+                }
+                Some(InputMode::LogFilter) => {
+                    state.set_log_filter(LogFilter::parse(&input));
+                    if !state.log_filter.is_empty() {
+                        let n = state.log_entries.len();
+                        let more = if state.log_exhausted { "" } else { "+" };
+                        state.show_msg(&format!("{}{} matching commit(s)", n, more));
+                    }
+                }
+                Some(InputMode::RebaseReword(index)) => {
+                    if let Some(item) = state
+                        .rebase_editor
+                        .as_mut()
+                        .and_then(|e| e.items.get_mut(index))
+                    {
+                        item.action = RebaseAction::Reword;
+                        item.new_message = Some(input.trim().to_string());
+                    }
+                }
+                Some(InputMode::WorktreeAdd) => {
+                    // Ask for the branch next; the path may contain spaces
+                    state.input_mode = Some(InputMode::WorktreeBranch(input.trim().to_string()));
+                }
+                Some(InputMode::WorktreeBranch(dir)) => {
+                    let branch = Some(input.trim()).filter(|b| !b.is_empty());
+                    match add_worktree(&state.repo_path, &dir, branch) {
+                        Ok(msg) => {
+                            state.show_msg(&msg);
+                            state.refresh_trees();
+                        }
+                        Err(e) => {
+                            let short_err = e.lines().next().unwrap_or("Failed").to_string();
                             state.show_msg(&short_err);
                         }
                     }
                 }
+                Some(InputMode::CommitAmend) => {
+                    match do_commit_amend(&state.repo_path, input.trim(), &state.commit_options) {
+                        Ok(msg) => {
+                            let short_msg = msg.lines().next().unwrap_or("Amended").to_string();
+                            state.show_msg(&short_msg);
+                            state.refresh_all();
+                        }
+                        Err(e) => state.commit_failed(&e, "Amend"),
+                    }
+                }
                 None => {}
             }
             state.input_buffer.clear();
@@ -1793,6 +6207,87 @@ fn execute_confirm_action(state: &mut GitScreenState) {
                     }
                 }
             }
+            ConfirmAction::DiscardChanges(_) => {
+                apply_hunk_selection(state, HunkSide::Unstaged, true);
+            }
+            ConfirmAction::StashDrop(index) => {
+                let stash_ref = format!("stash@{{{}}}", index);
+                match run_git(&state.repo_path, &["stash", "drop", &stash_ref]) {
+                    Ok(_) => {
+                        state.show_msg(&format!("Dropped {}", stash_ref));
+                        state.refresh_all();
+                    }
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Drop failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+            }
+            ConfirmAction::TagDelete(name) => {
+                match run_git(&state.repo_path, &["tag", "-d", &name]) {
+                    Ok(_) => {
+                        state.show_msg(&format!("Deleted tag {}", name));
+                        state.refresh_all();
+                    }
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Delete failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+            }
+            ConfirmAction::MarkResolved(file) => {
+                match mark_resolved(&state.repo_path, &file) {
+                    Ok(()) => state.show_msg(&format!("Marked {} resolved", file)),
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+                state.refresh_status();
+            }
+            ConfirmAction::CherryPick(hashes) => {
+                let result = pick_commits(&state.repo_path, &hashes, false);
+                state.log_marked.clear();
+                let done = format!("Cherry-picked {} commit(s)", hashes.len());
+                state.finish_sequence_step(result, &done);
+            }
+            ConfirmAction::Revert(hashes) => {
+                let result = pick_commits(&state.repo_path, &hashes, true);
+                state.log_marked.clear();
+                let done = format!("Reverted {} commit(s)", hashes.len());
+                state.finish_sequence_step(result, &done);
+            }
+            ConfirmAction::RunRebase => {
+                if let Some(editor) = state.rebase_editor.take() {
+                    let result = run_interactive_rebase(
+                        &state.repo_path,
+                        &editor,
+                        state.commit_options.skip_hooks,
+                    );
+                    let done = result.clone().unwrap_or_default();
+                    state.finish_sequence_step(result, &done);
+                }
+            }
+            ConfirmAction::AbortOperation(op) => {
+                let result = run_operation_step(&state.repo_path, op, "--abort");
+                let done = format!("Aborted {}", op.command());
+                state.finish_sequence_step(result, &done);
+            }
+            ConfirmAction::CommitDraft => commit_now(state),
+            ConfirmAction::SkipHooks => {
+                state.commit_options.skip_hooks = true;
+                state.show_msg("Commits skip hooks (--no-verify) until H is pressed again");
+            }
+            ConfirmAction::WorktreeRemove(path, changes) => {
+                match remove_worktree(&state.repo_path, &path, changes > 0) {
+                    Ok(()) => state.show_msg(&format!("Removed worktree {}", path.display())),
+                    Err(e) => {
+                        let short_err = e.lines().next().unwrap_or("Remove failed").to_string();
+                        state.show_msg(&short_err);
+                    }
+                }
+                state.refresh_trees();
+            }
             ConfirmAction::RestoreToCommit(hash) => {
                 match restore_to_commit(&state.repo_path, &hash) {
                     Ok(msg) => {
@@ -1833,8 +6328,775 @@ fn handle_confirm_input(state: &mut GitScreenState, code: KeyCode) {
 
 pub fn handle_paste(state: &mut GitScreenState, text: &str) {
     if state.commit_input_active {
-        state.commit_message.push_str(text);
+        if state.draft.is_none() {
+            state.commit_message.push_str(text);
+        }
     } else if state.input_mode.is_some() {
         state.input_buffer.push_str(text);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::git_status::temp_repo;
+
+    const DIFF: &str = concat!(
+        "diff --git a/f.txt b/f.txt\n",
+        "index 1111111..2222222 100644\n",
+        "--- a/f.txt\n",
+        "+++ b/f.txt\n",
+        "@@ -1,4 +1,4 @@ fn main\n",
+        " one\n",
+        "-two\n",
+        "+TWO\n",
+        "+extra\n",
+        " three\n",
+        "-four\n",
+        "@@ -10,2 +10,3 @@\n",
+        " ten\n",
+        "+ten and a half\n",
+        " eleven\n",
+    );
+
+    #[test]
+    fn test_parse_file_diff() {
+        let diff = FileDiff::parse(DIFF);
+        assert_eq!(diff.header.len(), 4);
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(diff.hunks[0].old_start, 1);
+        assert_eq!(diff.hunks[0].context, " fn main");
+        assert_eq!(diff.hunks[0].lines.len(), 6);
+        assert_eq!(diff.hunks[1].new_start, 10);
+    }
+
+    #[test]
+    fn test_hunk_patch_selected_lines() {
+        let diff = FileDiff::parse(DIFF);
+
+        // Forward: unselected removal becomes context, unselected addition is dropped
+        let patch = diff.hunk_patch(0, Some(&[2]), false).unwrap_or_default();
+        assert!(patch.ends_with("@@ -1,4 +1,5 @@ fn main\n one\n two\n+TWO\n three\n four\n"));
+
+        // Reverse: unselected addition becomes context, unselected removal is dropped
+        let patch = diff.hunk_patch(0, Some(&[1]), true).unwrap_or_default();
+        assert!(patch.ends_with("@@ -1,5 +1,4 @@ fn main\n one\n-two\n TWO\n extra\n three\n"));
+
+        // Whole hunk keeps every change; context-only selections produce nothing
+        let patch = diff.hunk_patch(1, None, false).unwrap_or_default();
+        assert!(patch.starts_with("diff --git a/f.txt b/f.txt\n"));
+        assert!(patch.ends_with("@@ -10,2 +10,3 @@\n ten\n+ten and a half\n eleven\n"));
+        assert!(diff.hunk_patch(0, Some(&[0]), false).is_none());
+    }
+
+    #[test]
+    fn test_stage_lines_with_git_apply() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        let git = |args: &[&str]| git_commit_cmd(repo).args(args).output();
+        std::fs::write(repo.join("f.txt"), "a\nb\nc\n").unwrap();
+        git(&["add", "f.txt"]).unwrap();
+        git(&["commit", "-q", "-m", "init"]).unwrap();
+        std::fs::write(repo.join("f.txt"), "a\nB\nc\nd\n").unwrap();
+
+        let diff = load_file_diff(repo, "f.txt", HunkSide::Unstaged).unwrap();
+        assert_eq!(diff.hunks.len(), 1);
+        // Stage only the appended "d"
+        let d_line = diff.hunks[0].lines.iter().position(|l| l == "+d").unwrap();
+        let patch = diff.hunk_patch(0, Some(&[d_line]), false).unwrap();
+        apply_patch(repo, &patch, true, false).unwrap();
+
+        let staged = load_file_diff(repo, "f.txt", HunkSide::Staged).unwrap();
+        let staged_lines: Vec<&str> = staged.hunks[0].lines.iter().map(|l| l.as_str()).collect();
+        assert!(staged_lines.contains(&"+d"));
+        assert!(!staged_lines.contains(&"+B"));
+
+        // Unstage it again and discard the remaining worktree change
+        let patch = staged.hunk_patch(0, None, true).unwrap();
+        apply_patch(repo, &patch, true, true).unwrap();
+        assert!(load_file_diff(repo, "f.txt", HunkSide::Staged)
+            .unwrap()
+            .hunks
+            .is_empty());
+        let unstaged = load_file_diff(repo, "f.txt", HunkSide::Unstaged).unwrap();
+        let patch = unstaged.hunk_patch(0, None, true).unwrap();
+        apply_patch(repo, &patch, false, true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("f.txt")).unwrap(),
+            "a\nb\nc\n"
+        );
+    }
+
+    fn messages(msgs: Vec<GitJobMsg>) -> Vec<String> {
+        msgs.into_iter()
+            .map(|m| match m {
+                GitJobMsg::Line(l) => format!("L:{}", l),
+                GitJobMsg::Progress(l) => format!("P:{}", l),
+                GitJobMsg::Done(r) => format!("D:{:?}", r),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_line_splitter_progress_and_crlf() {
+        let mut splitter = LineSplitter::default();
+        let mut out = messages(splitter.feed(b"Receiving 10%\rReceiving 100%, done.\r"));
+        out.extend(messages(splitter.feed(b"\nFrom host\ntail")));
+        out.extend(messages(splitter.finish().into_iter().collect()));
+        assert_eq!(
+            out,
+            vec![
+                "P:Receiving 10%",
+                "L:Receiving 100%, done.",
+                "L:From host",
+                "L:tail"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_job_output_replaces_progress_lines() {
+        let (_tx, rx) = mpsc::channel();
+        let mut job = GitJob {
+            title: "git fetch".to_string(),
+            lines: Vec::new(),
+            running: true,
+            succeeded: false,
+            last_is_progress: false,
+            receiver: rx,
+        };
+        job.push_line("Counting 1%".to_string(), true);
+        job.push_line("Counting 50%".to_string(), true);
+        job.push_line("Counting 100%, done.".to_string(), false);
+        job.push_line("From origin".to_string(), false);
+        assert_eq!(job.lines, vec!["Counting 100%, done.", "From origin"]);
+    }
+
+    #[test]
+    fn test_stash_tag_and_job_in_repo() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::write(repo.join("f.txt"), "one\n").unwrap();
+        run_git(repo, &["add", "f.txt"]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "init"]).unwrap();
+
+        std::fs::write(repo.join("f.txt"), "two\n").unwrap();
+        run_git(repo, &["stash", "push", "-m", "work in progress"]).unwrap();
+        let stashes = get_stashes(repo);
+        assert_eq!(stashes.len(), 1);
+        assert!(stashes[0].message.ends_with("work in progress"));
+        assert!(stash_show(repo, 0).contains("+two"));
+
+        run_git(repo, &["tag", "-a", "v1.0", "-m", "first release"]).unwrap();
+        run_git(repo, &["tag", "light"]).unwrap();
+        let tags = get_tags(repo);
+        let v1 = tags.iter().find(|t| t.name == "v1.0").unwrap();
+        assert!(v1.annotated);
+        assert_eq!(v1.subject, "first release");
+        assert!(!tags.iter().find(|t| t.name == "light").unwrap().annotated);
+
+        assert!(get_remotes(repo).is_empty());
+        assert!(get_upstream(repo).is_none());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let mut job = GitJob::spawn(repo, vec!["log".to_string(), "--oneline".to_string()]);
+        while !job.poll() && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(job.succeeded);
+        assert_eq!(job.title, "git log --oneline");
+        assert!(job.lines.iter().any(|l| l.ends_with("init")));
+
+        // Publish to a bare remote, then commit once more to be one ahead
+        let remote_dir = tempfile::tempdir().unwrap();
+        run_git(remote_dir.path(), &["init", "-q", "--bare"]).unwrap();
+        let url = remote_dir.path().to_string_lossy().to_string();
+        run_git(repo, &["remote", "add", "origin", &url]).unwrap();
+        let branch = get_current_branch(repo);
+        let mut push = GitJob::spawn(
+            repo,
+            ["push", "--progress", "-u", "origin", branch.as_str()]
+                .iter()
+                .map(|a| a.to_string())
+                .collect(),
+        );
+        while !push.poll() && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(push.succeeded, "{:?}", push.lines);
+        std::fs::write(repo.join("g.txt"), "new\n").unwrap();
+        run_git(repo, &["add", "g.txt"]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "second"]).unwrap();
+        let upstream = get_upstream(repo).unwrap();
+        assert_eq!(upstream.name, format!("origin/{}", branch));
+        assert_eq!((upstream.ahead, upstream.behind), (1, 0));
+        assert_eq!(get_remotes(repo)[0].name, "origin");
+    }
+
+    fn item(action: RebaseAction, hash: &str, message: &str) -> RebaseItem {
+        RebaseItem {
+            action,
+            hash: hash.to_string(),
+            message: message.to_string(),
+            new_message: None,
+        }
+    }
+
+    #[test]
+    fn test_rebase_todo_and_validation() {
+        let mut editor = RebaseEditor {
+            base: Some("aaaa".to_string()),
+            items: vec![
+                item(RebaseAction::Pick, "b1", "first"),
+                item(RebaseAction::Pick, "c2", "second"),
+                item(RebaseAction::Pick, "d3", "third"),
+            ],
+            selected: 2,
+            scroll: 0,
+        };
+        editor.move_selected(false);
+        editor.set_action(RebaseAction::Fixup);
+        editor.items[0].action = RebaseAction::Reword;
+        editor.items[0].new_message = Some("better first".to_string());
+        editor.items[2].action = RebaseAction::Drop;
+
+        let (todo, messages) = editor.todo(Path::new("/tmp/work dir"), false);
+        assert_eq!(
+            todo,
+            concat!(
+                "pick b1 first\n",
+                "exec git commit --amend --only --quiet -F '/tmp/work dir/message-0'\n",
+                "fixup d3 third\n",
+                "drop c2 second\n",
+            )
+        );
+        // Hooks are skipped only when the commit options say so
+        let (todo, _) = editor.todo(Path::new("/w"), true);
+        assert!(
+            todo.contains("exec git commit --amend --only --no-verify --quiet -F '/w/message-0'\n")
+        );
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].1, "better first");
+        assert!(editor.validate().is_ok());
+
+        editor.items[0].action = RebaseAction::Drop;
+        assert!(editor.validate().is_err());
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_cherry_pick_conflict_and_interactive_rebase() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        let commit = |file: &str, text: &str, message: &str| {
+            std::fs::write(repo.join(file), text).unwrap();
+            run_git(repo, &["add", file]).unwrap();
+            run_git(repo, &["commit", "-q", "-m", message]).unwrap();
+        };
+        commit("f.txt", "base\n", "init");
+        let main = get_current_branch(repo);
+        run_git(repo, &["checkout", "-q", "-b", "topic"]).unwrap();
+        commit("f.txt", "topic\n", "topic change");
+        commit("g.txt", "g\n", "add g");
+        run_git(repo, &["checkout", "-q", &main]).unwrap();
+        commit("f.txt", "main\n", "main change");
+
+        // Cherry-pick both topic commits; the first one conflicts
+        let topic = get_log_page(repo, Some("topic"), &LogFilter::default(), 0, 10, None);
+        assert_eq!(topic[0].message, "add g");
+        let hashes: Vec<String> = topic[..2].iter().rev().map(|e| e.hash.clone()).collect();
+        assert!(pick_commits(repo, &hashes, false).is_err());
+        assert_eq!(get_operation(repo), Some(RepoOperation::CherryPick));
+        assert!(get_status(repo)
+            .iter()
+            .any(|e| is_conflicted(e) && !e.staged));
+
+        std::fs::write(repo.join("f.txt"), "resolved\n").unwrap();
+        run_git(repo, &["add", "f.txt"]).unwrap();
+        run_operation_step(repo, RepoOperation::CherryPick, "--continue").unwrap();
+        assert_eq!(get_operation(repo), None);
+        let log = get_log(repo, 10);
+        assert_eq!(log[0].message, "add g");
+        assert_eq!(log[1].message, "topic change");
+
+        // Reword "main change", squash "add g" into "topic change"
+        let base = commit_parent(repo, &log[2].hash);
+        let mut items = get_rebase_items(repo, base.as_deref()).unwrap();
+        assert_eq!(items.len(), 3);
+        items[0].action = RebaseAction::Reword;
+        items[0].new_message = Some("main change, reworded".to_string());
+        items[2].action = RebaseAction::Fixup;
+        let editor = RebaseEditor {
+            base,
+            items,
+            selected: 0,
+            scroll: 0,
+        };
+        run_interactive_rebase(repo, &editor, false).unwrap();
+        assert_eq!(get_operation(repo), None);
+        let log = get_log(repo, 10);
+        let messages: Vec<&str> = log.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["topic change", "main change, reworded", "init"]
+        );
+        assert!(repo.join("g.txt").exists());
+
+        // Reverting the squashed commit restores the main version
+        let hashes = vec![log[0].hash.clone()];
+        pick_commits(repo, &hashes, true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("f.txt")).unwrap(),
+            "main\n"
+        );
+        assert!(!repo.join("g.txt").exists());
+    }
+
+    #[test]
+    fn test_conflict_resolution_in_merge() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        let commit = |file: &str, text: &str, message: &str| {
+            std::fs::write(repo.join(file), text).unwrap();
+            run_git(repo, &["add", file]).unwrap();
+            run_git(repo, &["commit", "-q", "-m", message]).unwrap();
+        };
+        commit("a.txt", "one\ntwo\nthree\n", "init");
+        commit("b.txt", "b\n", "add b");
+        let main = get_current_branch(repo);
+        run_git(repo, &["checkout", "-q", "-b", "topic"]).unwrap();
+        commit("a.txt", "one\nTOPIC\nthree\n", "topic a");
+        commit("b.txt", "b topic\n", "topic b");
+        run_git(repo, &["checkout", "-q", &main]).unwrap();
+        commit("a.txt", "one\nMAIN\nthree\n", "main a");
+        commit("b.txt", "b main\n", "main b");
+
+        assert!(run_git(repo, &["merge", "-q", "topic"]).is_err());
+        assert_eq!(get_operation(repo), Some(RepoOperation::Merge));
+        let conflicts = get_conflicts(repo, &get_status(repo));
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].describe(), "both modified");
+        assert_eq!(conflicts[0].markers, 1);
+
+        // Merge a.txt from its stages, taking theirs for the conflicting block
+        let stages = extract_conflict_stages(repo, "a.txt").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&stages.ours).unwrap(),
+            "one\nMAIN\nthree\n"
+        );
+        let mut view = crate::ui::diff_file_view::DiffFileViewState::new_merge_into(
+            stages.base,
+            stages.ours,
+            stages.theirs,
+            stages.output,
+            stages.file,
+        );
+        let merge = view.merge.as_mut().unwrap();
+        assert_eq!(merge.conflict_count(), 1);
+        assert!(merge.choose(crate::ui::diff_file_view::MergeChoice::Right));
+        merge.save().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("a.txt")).unwrap(),
+            "one\nTOPIC\nthree\n"
+        );
+        mark_resolved(repo, "a.txt").unwrap();
+
+        resolve_with_side(repo, "b.txt", true).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("b.txt")).unwrap(),
+            "b main\n"
+        );
+        assert!(get_conflicts(repo, &get_status(repo)).is_empty());
+
+        run_operation_step(repo, RepoOperation::Merge, "--continue").unwrap();
+        assert_eq!(get_operation(repo), None);
+    }
+
+    #[test]
+    fn test_log_graph_rows() {
+        let mut graph = LogGraph::default();
+        let p = |hashes: &[&str]| -> Vec<String> { hashes.iter().map(|h| h.to_string()).collect() };
+        // m merges b2 (side branch) into a2; both fork from root r
+        let rows: Vec<String> = [
+            ("m", p(&["a2", "b2"])),
+            ("b2", p(&["r"])),
+            ("a2", p(&["r"])),
+            ("r", p(&[])),
+        ]
+        .iter()
+        .map(|(hash, parents)| graph.row(hash, parents))
+        .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "\u{25CF}\u{2500}\u{256E}",
+                "\u{2502} \u{25CF}",
+                "\u{25CF} \u{2502}",
+                "\u{25CF}\u{2500}\u{256F}",
+            ]
+        );
+        assert!(graph.lanes.is_empty());
+    }
+
+    #[test]
+    fn test_log_filter_parse() {
+        let filter = LogFilter::parse("author:alice since:\"2 weeks ago\" path:src/ fix crash");
+        assert_eq!(filter.author.as_deref(), Some("alice"));
+        assert_eq!(filter.since.as_deref(), Some("2 weeks ago"));
+        assert_eq!(filter.path.as_deref(), Some("src/"));
+        assert_eq!(filter.message.as_deref(), Some("fix crash"));
+        assert!(!filter.allows_graph());
+        assert_eq!(LogFilter::parse(&filter.to_input()), filter);
+        assert!(LogFilter::parse("  ").is_empty());
+        assert!(LogFilter::parse("path:docs").allows_graph());
+    }
+
+    #[test]
+    fn test_log_paging_filters_and_commit_files() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        for i in 0..5 {
+            let file = if i % 2 == 0 { "even.txt" } else { "odd.txt" };
+            std::fs::write(repo.join(file), format!("{}\n", i)).unwrap();
+            run_git(repo, &["add", file]).unwrap();
+            run_git(repo, &["commit", "-q", "-m", &format!("change {}", i)]).unwrap();
+        }
+        run_git(repo, &["mv", "odd.txt", "renamed.txt"]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "rename | with bar"]).unwrap();
+
+        let mut graph = LogGraph::default();
+        let filter = LogFilter::default();
+        let first = get_log_page(repo, None, &filter, 0, 4, Some(&mut graph));
+        let rest = get_log_page(repo, None, &filter, 4, 4, Some(&mut graph));
+        assert_eq!(first.len() + rest.len(), 6);
+        assert_eq!(first[0].message, "rename | with bar");
+        assert_eq!(rest.last().unwrap().message, "change 0");
+        assert!(first.iter().chain(&rest).all(|e| e.graph == "\u{25CF}"));
+
+        let by_path = get_log_page(repo, None, &LogFilter::parse("path:even.txt"), 0, 10, None);
+        assert_eq!(by_path.len(), 3);
+        let by_text = get_log_page(repo, None, &LogFilter::parse("CHANGE 3"), 0, 10, None);
+        assert_eq!(by_text.len(), 1);
+        let none = get_log_page(
+            repo,
+            None,
+            &LogFilter::parse("author:nobody-here"),
+            0,
+            10,
+            None,
+        );
+        assert!(none.is_empty());
+
+        let files = get_commit_files(repo, &first[0].hash);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, 'R');
+        assert_eq!(files[0].old_path.as_deref(), Some("odd.txt"));
+        let (left, right) = extract_commit_file(repo, &first[0].hash, &files[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(left).unwrap(), "3\n");
+        assert_eq!(std::fs::read_to_string(right).unwrap(), "3\n");
+
+        // The root commit has no "before" side
+        let root = rest.last().unwrap();
+        let files = get_commit_files(repo, &root.hash);
+        let (left, right) = extract_commit_file(repo, &root.hash, &files[0]).unwrap();
+        assert!(!left.exists());
+        assert_eq!(std::fs::read_to_string(right).unwrap(), "0\n");
+    }
+
+    #[test]
+    fn test_blame_and_file_history() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::create_dir(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/old.txt"), "one\ntwo\n").unwrap();
+        run_git(repo, &["add", "."]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "first"]).unwrap();
+        run_git(repo, &["mv", "src/old.txt", "src/new.txt"]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "rename"]).unwrap();
+        std::fs::write(repo.join("src/new.txt"), "one\nTWO\nthree\n").unwrap();
+        run_git(repo, &["commit", "-q", "-am", "edit"]).unwrap();
+        std::fs::write(repo.join("src/new.txt"), "one\nTWO\nthree\nfour\n").unwrap();
+
+        let src = repo.join("src");
+        let history = get_file_history(&src, "new.txt").unwrap();
+        let messages: Vec<&str> = history.iter().map(|r| r.entry.message.as_str()).collect();
+        assert_eq!(messages, vec!["edit", "rename", "first"]);
+        assert_eq!(history[0].path, "src/new.txt");
+        assert_eq!(history[2].path, "src/old.txt");
+
+        // Working copy blame: the last line is not committed yet
+        let blame = get_blame(&src, None, "new.txt").unwrap();
+        assert_eq!(blame.len(), 4);
+        assert_eq!(blame[0].hash, history[2].entry.hash);
+        assert_eq!(blame[1].hash, history[0].entry.hash);
+        assert!(blame[3].hash.is_empty());
+        assert!(!blame[0].author.is_empty());
+
+        let first = GitRevision {
+            repo: repo.to_path_buf(),
+            hash: history[2].entry.hash.clone(),
+            path: history[2].path.clone(),
+        };
+        let old = extract_revision(&first).unwrap();
+        assert_eq!(std::fs::read_to_string(&old).unwrap(), "one\ntwo\n");
+        assert!(old.to_string_lossy().ends_with("old.txt"));
+        let blame = get_blame(repo, Some(&first.hash), &first.path).unwrap();
+        assert_eq!(blame.len(), 2);
+        assert!(blame.iter().all(|b| b.hash == first.hash));
+
+        assert_eq!(format_age(1000, 1030), "now");
+        assert_eq!(format_age(0, 3 * 86400), "3d");
+        assert_eq!(format_age(0, 800 * 86400), "2y");
+    }
+
+    #[test]
+    fn test_ref_name_validation() {
+        assert!(is_valid_ref_name("v1.2.3"));
+        assert!(is_valid_ref_name("release/2024"));
+        assert!(!is_valid_ref_name("-v1"));
+        assert!(!is_valid_ref_name("a..b"));
+        assert!(!is_valid_ref_name("has space"));
+        assert!(!is_valid_ref_name("x~1"));
+    }
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "worktree /src/repo\nHEAD 1234567890abcdef\nbranch refs/heads/main\n\n\
+                      worktree /src/repo-fix\nHEAD abcdef1234567890\ndetached\nlocked\n\n\
+                      worktree /src/gone\nHEAD 1111111111111111\nbranch refs/heads/old\nprunable gitdir file points to non-existent location\n";
+        let trees = parse_worktree_list(output);
+        assert_eq!(trees.len(), 3);
+        assert_eq!(trees[0].path, PathBuf::from("/src/repo"));
+        assert_eq!(trees[0].branch, "main");
+        assert_eq!(trees[0].note, "");
+        assert_eq!(trees[1].branch, "(abcdef1)");
+        assert_eq!(trees[1].note, "locked");
+        assert_eq!(trees[2].note, "prunable");
+        assert!(trees.iter().all(|t| t.kind == TreeKind::Worktree));
+    }
+
+    #[test]
+    fn test_worktrees_and_submodules() {
+        let (repo_dir, lib_dir) = (temp_repo(), temp_repo());
+        let repo = repo_dir.path().canonicalize().unwrap();
+        let lib = lib_dir.path().canonicalize().unwrap();
+        for (path, file) in [(&repo, "a.txt"), (&lib, "lib.txt")] {
+            std::fs::write(path.join(file), "x\n").unwrap();
+            run_git(path, &["add", file]).unwrap();
+            run_git(path, &["commit", "-q", "-m", "init"]).unwrap();
+        }
+        let lib_url = lib.to_string_lossy().to_string();
+        let added = run_git(
+            &repo,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "-q",
+                &lib_url,
+                "deps/lib",
+            ],
+        );
+        if added.is_err() {
+            return; // submodules of local paths not supported by this git
+        }
+        run_git(&repo, &["commit", "-q", "-m", "add lib"]).unwrap();
+
+        // A new branch in a sibling directory, then one with local changes
+        let outside = tempfile::tempdir().unwrap();
+        let feature_path = outside.path().canonicalize().unwrap().join("feature");
+        add_worktree(&repo, &feature_path.to_string_lossy(), Some("topic")).unwrap();
+        assert!(add_worktree(&repo, "../bad", Some("bad~name")).is_err());
+        std::fs::write(feature_path.join("a.txt"), "changed\n").unwrap();
+        // Paths may contain spaces
+        let spaced = outside.path().canonicalize().unwrap().join("spaced dir");
+        add_worktree(&repo, &spaced.to_string_lossy(), Some("spaced")).unwrap();
+        assert!(spaced.join("a.txt").exists());
+        remove_worktree(&repo, &spaced, false).unwrap();
+
+        let trees = get_trees(&repo.join("deps"));
+        let worktrees: Vec<_> = trees
+            .iter()
+            .filter(|t| t.kind == TreeKind::Worktree)
+            .collect();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_current);
+        assert_eq!(worktrees[0].changes, Some(0));
+        let feature = worktrees[1];
+        assert!(!feature.is_current);
+        assert_eq!(feature.branch, "topic");
+        assert_eq!(feature.changes, Some(1));
+
+        let sub = trees
+            .iter()
+            .find(|t| t.kind == TreeKind::Submodule)
+            .unwrap();
+        assert_eq!(sub.name, "deps/lib");
+        assert_eq!(sub.path, repo.join("deps/lib"));
+        assert_eq!(sub.changes, Some(0));
+        assert_eq!(sub.note, "");
+
+        // The new worktree has the submodule registered but not checked out
+        let in_feature = get_submodules(&feature.path);
+        assert_eq!(in_feature.len(), 1);
+        assert_eq!(in_feature[0].note, "not initialized");
+        assert_eq!(in_feature[0].changes, None);
+
+        // Removing a dirty worktree needs --force
+        assert!(remove_worktree(&repo, &feature.path, false).is_err());
+        remove_worktree(&repo, &feature.path, true).unwrap();
+        assert_eq!(get_worktrees(&repo).len(), 1);
+    }
+
+    #[test]
+    fn test_clean_draft() {
+        assert_eq!(
+            clean_draft("\n```text\nfix(ui): keep cursor  \n\nBody line\n```\n"),
+            "fix(ui): keep cursor\n\nBody line"
+        );
+        assert_eq!(clean_draft("  feat: add x  "), "feat: add x");
+    }
+
+    #[test]
+    fn test_commit_draft_needs_confirmation() {
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::write(repo.join("f.txt"), "one\n").unwrap();
+        run_git(repo, &["add", "f.txt"]).unwrap();
+        run_git(repo, &["commit", "-q", "-m", "init"]).unwrap();
+        std::fs::write(repo.join("f.txt"), "two\n").unwrap();
+
+        let mut state = GitScreenState::new(repo.to_path_buf());
+        // Nothing staged: no request is sent
+        state.start_draft();
+        assert!(state.draft.is_none());
+        run_git(repo, &["add", "f.txt"]).unwrap();
+        let diff = get_staged_diff(repo);
+        assert!(diff.contains("f.txt | 2"));
+        assert!(diff.contains("+two"));
+
+        // Simulate a provider streaming its answer
+        let (tx, rx) = mpsc::channel();
+        state.commit_message = "typed".to_string();
+        state.draft = Some(CommitDraft {
+            provider: ProviderKind::Claude,
+            receiver: rx,
+            cancel: Arc::new(CancelToken::new()),
+            previous: std::mem::take(&mut state.commit_message),
+        });
+        state.commit_input_active = true;
+        tx.send(StreamMessage::Text {
+            content: "fix: change".to_string(),
+        })
+        .unwrap();
+        state.poll();
+        assert_eq!(state.commit_message, "fix: change");
+        // Keys other than Esc do not touch the streaming message
+        handle_commit_input(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(state.confirm_action.is_none());
+        tx.send(StreamMessage::Done {
+            result: "```\nfix: change f\n\nUpdate the text.\n```".to_string(),
+            session_id: None,
+        })
+        .unwrap();
+        state.poll();
+        assert!(state.draft.is_none());
+        assert!(state.message_drafted);
+        assert_eq!(state.commit_message, "fix: change f\n\nUpdate the text.");
+
+        // Enter asks first; declining leaves the commit undone
+        handle_commit_input(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(matches!(
+            state.confirm_action,
+            Some(ConfirmAction::CommitDraft)
+        ));
+        handle_confirm_input(&mut state, KeyCode::Char('n'));
+        assert_eq!(get_log(repo, 10).len(), 1);
+
+        handle_commit_input(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        handle_confirm_input(&mut state, KeyCode::Char('y'));
+        let log = get_log(repo, 10);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].message, "fix: change f");
+        assert!(!state.message_drafted);
+        assert!(state.commit_message.is_empty());
+
+        // A failed draft restores the previous message
+        let (tx, rx) = mpsc::channel();
+        state.commit_message = "mine".to_string();
+        state.draft = Some(CommitDraft {
+            provider: ProviderKind::Codex,
+            receiver: rx,
+            cancel: Arc::new(CancelToken::new()),
+            previous: std::mem::take(&mut state.commit_message),
+        });
+        drop(tx);
+        state.poll();
+        assert!(state.draft.is_none());
+        assert_eq!(state.commit_message, "mine");
+        assert!(!state.message_drafted);
+    }
+
+    #[test]
+    fn test_commit_args_signing_and_hooks() {
+        let mut options = CommitOptions::default();
+        assert_eq!(commit_args(&[], "m", &options), ["commit", "-m", "m"]);
+        options.signing = CommitSigning::Ssh;
+        options.skip_hooks = true;
+        assert_eq!(
+            commit_args(&["--amend"], "m", &options),
+            [
+                "-c",
+                "gpg.format=ssh",
+                "commit",
+                "--amend",
+                "-S",
+                "--no-verify",
+                "-m",
+                "m"
+            ]
+        );
+        options.signing = CommitSigning::Gpg;
+        assert_eq!(commit_args(&[], "m", &options)[1], "gpg.format=openpgp");
+        options.signing = CommitSigning::Off;
+        assert!(commit_args(&[], "m", &options).contains(&"--no-gpg-sign".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_hook_output_and_skip() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_repo();
+        let repo = dir.path();
+        let hook = repo.join(".git/hooks/pre-commit");
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\necho 'lint: 2 problems'\necho 'a.rs:1 bad' >&2\nexit 1\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(active_commit_hooks(repo), ["pre-commit"]);
+
+        std::fs::write(repo.join("a.rs"), "x\n").unwrap();
+        run_git(repo, &["add", "a.rs"]).unwrap();
+        let mut state = GitScreenState::new(repo.to_path_buf());
+        state.commit_message = "add a".to_string();
+        state.commit_input_active = true;
+        handle_commit_input(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        let output = state.hook_output.as_ref().unwrap();
+        assert!(output.title.contains("pre-commit"));
+        assert_eq!(output.lines, ["lint: 2 problems", "a.rs:1 bad"]);
+        assert!(get_log(repo, 10).is_empty());
+
+        // Skipping hooks needs a confirmation, then the commit goes through
+        handle_hook_output_input(&mut state, KeyCode::Char('H'));
+        assert!(state.hook_output.is_none());
+        assert!(!state.commit_options.skip_hooks);
+        handle_confirm_input(&mut state, KeyCode::Char('y'));
+        assert!(state.commit_options.skip_hooks);
+        handle_commit_input(&mut state, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(state.hook_output.is_none());
+        assert_eq!(get_log(repo, 10)[0].message, "add a");
+    }
+}
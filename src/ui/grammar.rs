@@ -0,0 +1,887 @@
+//! Data-driven syntax definitions.
+//!
+//! A [`Grammar`] lists a language's comment and string delimiters, its
+//! keywords and regex rules; [`SyntaxHighlighter`](super::syntax::SyntaxHighlighter)
+//! tokenizes lines with it. Dockerfile, Makefile, Nix, Terraform, Lua, Zig and
+//! Elixir are built in this way, and users add languages by dropping grammar
+//! files into `~/.remotecc/syntaxes/`: either Sublime Text `.sublime-syntax`
+//! files or `.json` files in the format below.
+//!
+//! ```json
+//! {
+//!   "name": "Haskell",
+//!   "lsp_id": "haskell",
+//!   "extensions": ["hs", "lhs"],
+//!   "interpreters": ["runghc"],
+//!   "line_comment": "--",
+//!   "block_comment": ["{-", "-}"],
+//!   "keywords": ["module", "where", "import", "let", "in", "case", "of"],
+//!   "rules": [{ "pattern": "\\b[A-Z]\\w*", "token": "type" }]
+//! }
+//! ```
+//!
+//! Rules are matched against the whole line, so `^` anchors at its start; a
+//! match is used where it begins outside a comment or string, before the
+//! built-in number, word and operator scanning.
+//!
+//! Of a `.sublime-syntax` file only what fits that model is used: the `match`
+//! rules of `main` and the contexts it includes, with `{{variables}}`
+//! substituted and each rule's scope mapped to a token type. Pushed contexts
+//! aren't tracked; one whose `meta_scope` is a comment or string and that opens
+//! and closes on plain text becomes the comment or string delimiters instead.
+//! Oniguruma-only regex syntax (look-behind, `\h`, ...) is not supported.
+//!
+//! Grammar files are read once, on first use; files that don't parse and rules
+//! with an invalid regex are reported once on the editor's message line.
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_yaml_ng::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use super::syntax::{Language, TokenType};
+use crate::config::Settings;
+
+/// A regex whose matches get one token type
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrammarRule {
+    pub pattern: String,
+    pub token: TokenType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Grammar {
+    pub name: String,
+    /// Language Server Protocol id; the name in lowercase when not given
+    #[serde(default)]
+    pub lsp_id: Option<String>,
+    /// File extensions, without the dot
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Exact file names, e.g. `Makefile`
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    /// Interpreters named by a `#!` line, e.g. `lua`
+    #[serde(default)]
+    pub interpreters: Vec<String>,
+    #[serde(default)]
+    pub line_comment: Option<String>,
+    #[serde(default)]
+    pub block_comment: Option<(String, String)>,
+    /// Strings that may span lines, e.g. `"""` heredocs
+    #[serde(default)]
+    pub block_string: Option<(String, String)>,
+    /// Characters opening and closing a one-line string
+    #[serde(default = "default_quotes")]
+    pub quotes: String,
+    #[serde(default = "default_operators")]
+    pub operators: String,
+    /// Keywords and types match regardless of case
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<String>,
+    #[serde(default)]
+    pub constants: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<GrammarRule>,
+    /// `rules` compiled; ones with an invalid pattern are left out
+    #[serde(skip)]
+    pub compiled: Vec<(Regex, TokenType)>,
+}
+
+fn default_quotes() -> String {
+    "\"'".to_string()
+}
+
+fn default_operators() -> String {
+    "+-*/%=<>!&|^~?:".to_string()
+}
+
+impl Default for Grammar {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            lsp_id: None,
+            extensions: Vec::new(),
+            filenames: Vec::new(),
+            interpreters: Vec::new(),
+            line_comment: None,
+            block_comment: None,
+            block_string: None,
+            quotes: default_quotes(),
+            operators: default_operators(),
+            case_insensitive: false,
+            keywords: Vec::new(),
+            types: Vec::new(),
+            constants: Vec::new(),
+            rules: Vec::new(),
+            compiled: Vec::new(),
+        }
+    }
+}
+
+impl Grammar {
+    /// Parse a grammar file's JSON and compile its rules. Rules with an
+    /// invalid pattern are left out and returned as errors.
+    pub fn from_json(json: &str) -> Result<(Self, Vec<String>), String> {
+        let mut grammar: Grammar = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if grammar.name.is_empty() {
+            return Err("no name".to_string());
+        }
+        let errors = grammar.compile();
+        Ok((grammar, errors))
+    }
+
+    /// Read the regex subset of a Sublime Text `.sublime-syntax` file (see the
+    /// module doc); `file_name` names it when the file doesn't
+    pub fn from_sublime_syntax(yaml: &str, file_name: &str) -> Result<(Self, Vec<String>), String> {
+        let syntax: SublimeSyntax = serde_yaml_ng::from_str(yaml).map_err(|e| e.to_string())?;
+        if !syntax.contexts.contains_key("main") {
+            return Err("no main context".to_string());
+        }
+        let mut grammar = Grammar {
+            name: syntax.name.clone().unwrap_or_else(|| file_name.to_string()),
+            lsp_id: syntax
+                .scope
+                .as_deref()
+                .and_then(|s| s.rsplit('.').next())
+                .map(str::to_string),
+            extensions: syntax.file_extensions.clone(),
+            // Strings only come from the syntax's own contexts
+            quotes: String::new(),
+            ..Default::default()
+        };
+        let mut visited = HashSet::new();
+        syntax.collect(&mut grammar, "main", &mut visited);
+        let errors = grammar.compile();
+        Ok((grammar, errors))
+    }
+
+    /// Compile the rules, returning why the invalid ones were left out
+    fn compile(&mut self) -> Vec<String> {
+        if self.lsp_id.is_none() {
+            self.lsp_id = Some(self.name.to_lowercase());
+        }
+        let mut errors = Vec::new();
+        self.compiled = self
+            .rules
+            .iter()
+            .filter_map(|r| match Regex::new(&r.pattern) {
+                Ok(re) => Some((re, r.token)),
+                Err(_) => {
+                    errors.push(format!("invalid pattern `{}`", r.pattern));
+                    None
+                }
+            })
+            .collect();
+        if self.case_insensitive {
+            for word in self
+                .keywords
+                .iter_mut()
+                .chain(self.types.iter_mut())
+                .chain(self.constants.iter_mut())
+            {
+                *word = word.to_lowercase();
+            }
+        }
+        errors
+    }
+
+    pub fn lsp_id(&self) -> &str {
+        self.lsp_id.as_deref().unwrap_or("plaintext")
+    }
+
+    /// Token type of an identifier, by the word lists
+    pub fn classify(&self, word: &str) -> Option<TokenType> {
+        let folded;
+        let word = if self.case_insensitive {
+            folded = word.to_lowercase();
+            folded.as_str()
+        } else {
+            word
+        };
+        let listed = |list: &[String]| list.iter().any(|w| w == word);
+        if listed(&self.keywords) {
+            Some(TokenType::Keyword)
+        } else if listed(&self.types) {
+            Some(TokenType::Type)
+        } else if listed(&self.constants) {
+            Some(TokenType::Constant)
+        } else {
+            None
+        }
+    }
+}
+
+/// The parts of a `.sublime-syntax` file that are read
+#[derive(Debug, Deserialize)]
+struct SublimeSyntax {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
+    file_extensions: Vec<String>,
+    #[serde(default)]
+    variables: HashMap<String, String>,
+    #[serde(default)]
+    contexts: HashMap<String, Vec<Value>>,
+}
+
+impl SublimeSyntax {
+    /// Add the rules of context `name` and of the contexts it includes
+    fn collect(&self, grammar: &mut Grammar, name: &str, visited: &mut HashSet<String>) {
+        if !visited.insert(name.to_string()) {
+            return;
+        }
+        let Some(items) = self.contexts.get(name) else {
+            return;
+        };
+        for item in items.iter().filter_map(Value::as_mapping) {
+            if let Some(include) = str_field(item, "include") {
+                self.collect(grammar, include, visited);
+                continue;
+            }
+            let Some(pattern) = str_field(item, "match") else {
+                continue;
+            };
+            let pattern = self.expand(pattern);
+            let pushed = item.get("push").or_else(|| item.get("set"));
+            if let Some(target) = pushed.and_then(|p| self.context_items(p)) {
+                if self.add_delimiters(grammar, &pattern, target) {
+                    continue;
+                }
+            }
+            if let Some(token) = str_field(item, "scope").and_then(scope_token) {
+                grammar.rules.push(GrammarRule { pattern, token });
+            }
+        }
+    }
+
+    /// Items of a pushed context: named, or written inline
+    fn context_items<'a>(&'a self, target: &'a Value) -> Option<&'a [Value]> {
+        match target {
+            Value::String(name) => self.contexts.get(name).map(Vec::as_slice),
+            Value::Sequence(items) if items.iter().all(Value::is_mapping) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Turn a pushed comment or string context opened by `open` into the
+    /// grammar's delimiters. False when it isn't one or doesn't fit.
+    fn add_delimiters(&self, grammar: &mut Grammar, open: &str, context: &[Value]) -> bool {
+        let maps: Vec<&Mapping> = context.iter().filter_map(Value::as_mapping).collect();
+        let Some(meta) = maps.iter().find_map(|m| str_field(m, "meta_scope")) else {
+            return false;
+        };
+        let Some(open) = literal(open) else {
+            return false;
+        };
+        let close = maps
+            .iter()
+            .find(|m| m.get("pop").is_some_and(|p| p.as_bool() == Some(true)))
+            .and_then(|m| str_field(m, "match"))
+            .map(|p| self.expand(p));
+        let Some(close) = close else {
+            return false;
+        };
+        let to_line_end = matches!(close.as_str(), "$" | "$\\n?" | "\\n" | "$\\n");
+        match scope_token(meta) {
+            Some(TokenType::Comment) if to_line_end => {
+                grammar.line_comment.get_or_insert(open);
+            }
+            Some(TokenType::Comment) => match literal(&close) {
+                Some(close) => {
+                    grammar.block_comment.get_or_insert((open, close));
+                }
+                None => return false,
+            },
+            Some(TokenType::String) => match literal(&close) {
+                Some(close) if close == open && open.chars().count() == 1 => {
+                    grammar.quotes.push_str(&open);
+                }
+                Some(close) => {
+                    grammar.block_string.get_or_insert((open, close));
+                }
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Substitute `{{variables}}`, which may refer to other variables
+    fn expand(&self, pattern: &str) -> String {
+        let mut pattern = pattern.to_string();
+        for _ in 0..10 {
+            if !pattern.contains("{{") {
+                break;
+            }
+            for (name, value) in &self.variables {
+                pattern = pattern.replace(&format!("{{{{{}}}}}", name), value);
+            }
+        }
+        pattern
+    }
+}
+
+fn str_field<'a>(map: &'a Mapping, key: &str) -> Option<&'a str> {
+    map.get(key).and_then(Value::as_str)
+}
+
+/// The text a regex matches when it is plain text with escaped punctuation
+fn literal(pattern: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                c if c.is_ascii_punctuation() => text.push(c),
+                _ => return None,
+            },
+            '.' | '^' | '$' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' => {
+                return None
+            }
+            c => text.push(c),
+        }
+    }
+    (!text.is_empty()).then_some(text)
+}
+
+/// Token type of a Sublime/TextMate scope name, by its first scope's prefix
+fn scope_token(scope: &str) -> Option<TokenType> {
+    let scope = scope.split_whitespace().next()?;
+    let starts = |prefix: &str| scope == prefix || scope.starts_with(&format!("{}.", prefix));
+    let token = if starts("comment") {
+        TokenType::Comment
+    } else if starts("string") {
+        TokenType::String
+    } else if starts("constant.numeric") {
+        TokenType::Number
+    } else if starts("constant.character.escape") {
+        TokenType::String
+    } else if starts("constant") || starts("support.constant") {
+        TokenType::Constant
+    } else if starts("keyword.operator") {
+        TokenType::Operator
+    } else if starts("keyword") || starts("storage.modifier") {
+        TokenType::Keyword
+    } else if starts("storage.type")
+        || starts("entity.name.type")
+        || starts("entity.name.class")
+        || starts("entity.other.inherited-class")
+        || starts("support.type")
+        || starts("support.class")
+    {
+        TokenType::Type
+    } else if starts("storage") {
+        TokenType::Keyword
+    } else if starts("entity.name.function")
+        || starts("support.function")
+        || starts("variable.function")
+    {
+        TokenType::Function
+    } else if starts("entity.other.attribute-name") || starts("entity.name.tag") {
+        TokenType::Attribute
+    } else if starts("meta.preprocessor") || starts("entity.name.macro") {
+        TokenType::Macro
+    } else if starts("variable") {
+        TokenType::Variable
+    } else if starts("punctuation.section") {
+        TokenType::Bracket
+    } else {
+        return None;
+    };
+    Some(token)
+}
+
+/// Grammars of the `*.json` and `*.sublime-syntax` files in `dir`, in file
+/// name order, and the errors found in them as "file: error". Files that don't
+/// parse are skipped.
+pub fn load_grammars(dir: &Path) -> (Vec<Grammar>, Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e == "json" || e == "sublime-syntax")
+        })
+        .collect();
+    paths.sort();
+
+    let mut grammars = Vec::new();
+    let mut errors = Vec::new();
+    for path in &paths {
+        let file = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let stem = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let loaded = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                if path.extension().is_some_and(|e| e == "json") {
+                    Grammar::from_json(&text)
+                } else {
+                    Grammar::from_sublime_syntax(&text, &stem)
+                }
+            });
+        match loaded {
+            Ok((grammar, rule_errors)) => {
+                errors.extend(rule_errors.into_iter().map(|e| format!("{}: {}", file, e)));
+                grammars.push(grammar);
+            }
+            Err(e) => errors.push(format!("{}: {}", file, e)),
+        }
+    }
+    (grammars, errors)
+}
+
+/// Grammars from ~/.remotecc/syntaxes and their errors, read on first use
+fn user_grammar_files() -> &'static (Vec<Grammar>, Vec<String>) {
+    static USER: OnceLock<(Vec<Grammar>, Vec<String>)> = OnceLock::new();
+    USER.get_or_init(|| {
+        Settings::syntaxes_dir()
+            .map(|d| load_grammars(&d))
+            .unwrap_or_default()
+    })
+}
+
+/// Grammars from ~/.remotecc/syntaxes, read on first use
+pub fn user_grammars() -> &'static [Grammar] {
+    &user_grammar_files().0
+}
+
+/// Summary of the errors in ~/.remotecc/syntaxes, the first time it's asked for
+pub fn take_load_errors() -> Option<String> {
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    let errors = &user_grammar_files().1;
+    if errors.is_empty() || REPORTED.swap(true, Ordering::Relaxed) {
+        return None;
+    }
+    Some(load_errors_message(errors))
+}
+
+fn load_errors_message(errors: &[String]) -> String {
+    let mut message = format!("Syntax file error: {}", errors[0]);
+    if errors.len() > 1 {
+        message.push_str(&format!(" (+{} more)", errors.len() - 1));
+    }
+    message
+}
+
+/// Grammar of a built-in language highlighted by one
+pub fn builtin_grammar(language: Language) -> Option<&'static Grammar> {
+    static BUILTIN: OnceLock<Vec<(Language, Grammar)>> = OnceLock::new();
+    BUILTIN
+        .get_or_init(builtin_grammars)
+        .iter()
+        .find(|(l, _)| *l == language)
+        .map(|(_, g)| g)
+}
+
+fn words(list: &[&str]) -> Vec<String> {
+    list.iter().map(|w| w.to_string()).collect()
+}
+
+fn rules(list: &[(&str, TokenType)]) -> Vec<GrammarRule> {
+    list.iter()
+        .map(|(pattern, token)| GrammarRule {
+            pattern: pattern.to_string(),
+            token: *token,
+        })
+        .collect()
+}
+
+fn builtin_grammars() -> Vec<(Language, Grammar)> {
+    let pair = |s: &str, e: &str| Some((s.to_string(), e.to_string()));
+    let mut grammars = vec![
+        (
+            Language::Dockerfile,
+            Grammar {
+                name: "Dockerfile".into(),
+                line_comment: Some("#".into()),
+                case_insensitive: true,
+                keywords: words(&[
+                    "from",
+                    "run",
+                    "cmd",
+                    "label",
+                    "maintainer",
+                    "expose",
+                    "env",
+                    "add",
+                    "copy",
+                    "entrypoint",
+                    "volume",
+                    "user",
+                    "workdir",
+                    "arg",
+                    "onbuild",
+                    "stopsignal",
+                    "healthcheck",
+                    "shell",
+                    "as",
+                ]),
+                rules: rules(&[
+                    (r"\$\{[^}]*\}|\$\w+", TokenType::Variable),
+                    (r"--[\w-]+", TokenType::Attribute),
+                ]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Makefile,
+            Grammar {
+                name: "Makefile".into(),
+                line_comment: Some("#".into()),
+                keywords: words(&[
+                    "ifeq", "ifneq", "ifdef", "ifndef", "else", "endif", "include", "sinclude",
+                    "define", "endef", "export", "unexport", "override", "vpath", "private",
+                ]),
+                rules: rules(&[
+                    // Targets: `name other: prerequisites`, not `VAR := value`
+                    (
+                        r"^[\w./%-]+(?:[ \t]+[\w./%-]+)*[ \t]*::?(?:[ \t]|$)",
+                        TokenType::Function,
+                    ),
+                    (r"\$\([^)]*\)|\$\{[^}]*\}|\$[@<^+?*%]", TokenType::Variable),
+                    (r"^\.[A-Z_]+", TokenType::Macro),
+                ]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Nix,
+            Grammar {
+                name: "Nix".into(),
+                line_comment: Some("#".into()),
+                block_comment: pair("/*", "*/"),
+                block_string: pair("''", "''"),
+                quotes: "\"".into(),
+                keywords: words(&[
+                    "let", "in", "with", "rec", "inherit", "if", "then", "else", "assert", "or",
+                ]),
+                constants: words(&["true", "false", "null"]),
+                rules: rules(&[
+                    (r"\bbuiltins\.[\w'-]+|\bimport\b", TokenType::Function),
+                    (r"\.{0,2}/[\w./+-]+|<[\w./+-]+>", TokenType::String),
+                    (r"\$\{", TokenType::Operator),
+                ]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Terraform,
+            Grammar {
+                name: "Terraform".into(),
+                line_comment: Some("#".into()),
+                block_comment: pair("/*", "*/"),
+                quotes: "\"".into(),
+                keywords: words(&[
+                    "resource",
+                    "data",
+                    "variable",
+                    "output",
+                    "module",
+                    "provider",
+                    "terraform",
+                    "locals",
+                    "backend",
+                    "required_providers",
+                    "dynamic",
+                    "for_each",
+                    "count",
+                    "depends_on",
+                    "lifecycle",
+                    "for",
+                    "in",
+                    "if",
+                ]),
+                types: words(&[
+                    "string", "number", "bool", "list", "map", "set", "object", "tuple", "any",
+                ]),
+                constants: words(&["true", "false", "null"]),
+                rules: rules(&[
+                    (r"//.*", TokenType::Comment),
+                    (r"<<-?\w+", TokenType::String),
+                ]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Lua,
+            Grammar {
+                name: "Lua".into(),
+                line_comment: Some("--".into()),
+                block_comment: pair("--[[", "]]"),
+                block_string: pair("[[", "]]"),
+                keywords: words(&[
+                    "and", "break", "do", "else", "elseif", "end", "for", "function", "goto", "if",
+                    "in", "local", "not", "or", "repeat", "return", "then", "until", "while",
+                ]),
+                constants: words(&["true", "false", "nil", "self"]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Zig,
+            Grammar {
+                name: "Zig".into(),
+                line_comment: Some("//".into()),
+                keywords: words(&[
+                    "addrspace",
+                    "align",
+                    "allowzero",
+                    "and",
+                    "anyframe",
+                    "asm",
+                    "async",
+                    "await",
+                    "break",
+                    "catch",
+                    "comptime",
+                    "const",
+                    "continue",
+                    "defer",
+                    "else",
+                    "enum",
+                    "errdefer",
+                    "error",
+                    "export",
+                    "extern",
+                    "fn",
+                    "for",
+                    "if",
+                    "inline",
+                    "noalias",
+                    "noinline",
+                    "nosuspend",
+                    "opaque",
+                    "or",
+                    "orelse",
+                    "packed",
+                    "pub",
+                    "resume",
+                    "return",
+                    "linksection",
+                    "struct",
+                    "suspend",
+                    "switch",
+                    "test",
+                    "threadlocal",
+                    "try",
+                    "union",
+                    "unreachable",
+                    "usingnamespace",
+                    "var",
+                    "volatile",
+                    "while",
+                ]),
+                types: words(&[
+                    "isize",
+                    "usize",
+                    "c_int",
+                    "c_uint",
+                    "c_long",
+                    "c_ulong",
+                    "c_char",
+                    "f16",
+                    "f32",
+                    "f64",
+                    "f80",
+                    "f128",
+                    "bool",
+                    "void",
+                    "noreturn",
+                    "type",
+                    "anyerror",
+                    "anytype",
+                    "anyopaque",
+                    "comptime_int",
+                    "comptime_float",
+                ]),
+                constants: words(&["true", "false", "null", "undefined"]),
+                rules: rules(&[
+                    (r"\\\\.*", TokenType::String),
+                    (r"@\w+", TokenType::Macro),
+                    (r"\b[iu]\d+\b", TokenType::Type),
+                ]),
+                ..Default::default()
+            },
+        ),
+        (
+            Language::Elixir,
+            Grammar {
+                name: "Elixir".into(),
+                line_comment: Some("#".into()),
+                block_string: pair("\"\"\"", "\"\"\""),
+                keywords: words(&[
+                    "def",
+                    "defp",
+                    "defmodule",
+                    "defmacro",
+                    "defmacrop",
+                    "defstruct",
+                    "defprotocol",
+                    "defimpl",
+                    "defdelegate",
+                    "defguard",
+                    "defexception",
+                    "do",
+                    "end",
+                    "if",
+                    "else",
+                    "unless",
+                    "case",
+                    "cond",
+                    "fn",
+                    "when",
+                    "with",
+                    "for",
+                    "import",
+                    "alias",
+                    "require",
+                    "use",
+                    "quote",
+                    "unquote",
+                    "receive",
+                    "try",
+                    "catch",
+                    "rescue",
+                    "after",
+                    "raise",
+                    "in",
+                    "and",
+                    "or",
+                    "not",
+                ]),
+                constants: words(&["true", "false", "nil"]),
+                rules: rules(&[
+                    (r":[a-zA-Z_]\w*[?!]?", TokenType::Constant),
+                    (r"@\w+", TokenType::Attribute),
+                    (r"~[a-zA-Z]", TokenType::Macro),
+                    (r"\b[A-Z]\w*", TokenType::Type),
+                ]),
+                ..Default::default()
+            },
+        ),
+    ];
+    for (_, grammar) in &mut grammars {
+        grammar.compile();
+    }
+    grammars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_grammars() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("haskell.json"),
+            r#"{
+                "name": "Haskell",
+                "extensions": ["hs"],
+                "line_comment": "--",
+                "case_insensitive": true,
+                "keywords": ["Where"],
+                "rules": [
+                    { "pattern": "\\b[A-Z]\\w*", "token": "type" },
+                    { "pattern": "(unclosed", "token": "string" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+        fs::write(dir.path().join("notes.txt"), "{}").unwrap();
+
+        let (grammars, errors) = load_grammars(dir.path());
+        assert_eq!(grammars.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("broken.json: "));
+        assert_eq!(errors[1], "haskell.json: invalid pattern `(unclosed`");
+        assert_eq!(
+            load_errors_message(&errors),
+            format!("Syntax file error: {} (+1 more)", errors[0])
+        );
+        let haskell = &grammars[0];
+        assert_eq!(haskell.name, "Haskell");
+        assert_eq!(haskell.lsp_id(), "haskell");
+        assert_eq!(haskell.quotes, "\"'");
+        // The invalid pattern is left out
+        assert_eq!(haskell.compiled.len(), 1);
+        assert_eq!(haskell.classify("WHERE"), Some(TokenType::Keyword));
+        assert_eq!(haskell.classify("main"), None);
+
+        assert!(load_grammars(Path::new("/nonexistent/syntaxes"))
+            .0
+            .is_empty());
+        assert!(builtin_grammar(Language::Zig).is_some());
+        assert!(builtin_grammar(Language::Rust).is_none());
+    }
+
+    #[test]
+    fn test_from_sublime_syntax() {
+        let yaml = r#"%YAML 1.2
+---
+file_extensions: [toy]
+scope: source.toy
+variables:
+  ident: '[a-z_]\w*'
+  call: '{{ident}}(?=\()'
+contexts:
+  main:
+    - include: comments
+    - match: '"'
+      push: string
+    - match: \b(if|else)\b
+      scope: keyword.control.toy
+    - match: '{{call}}'
+      scope: variable.function.toy
+    - match: (?<=\.)\w+
+      scope: variable.other.member.toy
+    - match: \bx\b
+      scope: meta.unmapped.toy
+  comments:
+    - match: '#'
+      push:
+        - meta_scope: comment.line.toy
+        - match: $\n?
+          pop: true
+  string:
+    - meta_scope: string.quoted.double.toy
+    - match: '"'
+      pop: true
+"#;
+        let (toy, errors) = Grammar::from_sublime_syntax(yaml, "toy").unwrap();
+        assert_eq!(toy.name, "toy");
+        assert_eq!(toy.lsp_id(), "toy");
+        assert_eq!(toy.extensions, vec!["toy"]);
+        assert_eq!(toy.line_comment.as_deref(), Some("#"));
+        assert_eq!(toy.quotes, "\"");
+        // Look-around patterns are reported and left out
+        assert_eq!(errors.len(), 2);
+        assert_eq!(toy.rules.len(), 3);
+        assert_eq!(toy.rules[0].token, TokenType::Keyword);
+        assert_eq!(toy.rules[1].pattern, "[a-z_]\\w*(?=\\()");
+        assert_eq!(toy.rules[1].token, TokenType::Function);
+        assert_eq!(toy.compiled.len(), 1);
+
+        assert!(Grammar::from_sublime_syntax("name: X\ncontexts: {}", "x").is_err());
+    }
+}
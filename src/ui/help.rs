@@ -259,6 +259,7 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
     lines.push(pk(PanelAction::EncryptAll, "Encrypt all files (AES-256)"));
     lines.push(pk(PanelAction::DecryptAll, "Decrypt .cokacenc files"));
     lines.push(pk(PanelAction::Search, "Find/search files"));
+    lines.push(pk(PanelAction::FileHistory, "Git history of file"));
     #[cfg(target_os = "macos")]
     {
         lines.push(pk(PanelAction::OpenInFinder, "Open folder in Finder"));
@@ -306,6 +307,14 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
     lines.push(ek(EditorAction::Paste, "Paste"));
     lines.push(ek(EditorAction::SelectNextOccurrence, "Select word"));
     lines.push(ek(EditorAction::SelectLine, "Select line"));
+    lines.push(ek(
+        EditorAction::SelectAllOccurrences,
+        "Select all occurrences",
+    ));
+    lines.push(ek(EditorAction::AddCursorAbove, "Add cursor above"));
+    lines.push(ek(EditorAction::AddCursorBelow, "Add cursor below"));
+    lines.push(key_line("Alt+Shift+Arrows", "Block (column) selection"));
+    lines.push(key_line("Alt+Drag", "Block selection with the mouse"));
     lines.push(ek(EditorAction::DeleteLine, "Delete line"));
     lines.push(ek(EditorAction::DuplicateLine, "Duplicate line"));
     lines.push(ek(EditorAction::ToggleComment, "Toggle comment"));
@@ -317,7 +326,22 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
     lines.push(ek(EditorAction::Replace, "Find and replace"));
     lines.push(ek(EditorAction::GotoLine, "Go to line"));
     lines.push(ek(EditorAction::Save, "Save file"));
-    lines.push(ek(EditorAction::Exit, "Close editor"));
+    lines.push(ek(EditorAction::FileHistory, "Git history of file"));
+    lines.push(ek(EditorAction::OpenFile, "Open file in a new buffer"));
+    lines.push(ek(EditorAction::NextBuffer, "Next buffer"));
+    lines.push(ek(EditorAction::PrevBuffer, "Previous buffer"));
+    lines.push(ek(EditorAction::BufferList, "List open buffers"));
+    lines.push(ek(EditorAction::CloseBuffer, "Close buffer"));
+    lines.push(ek(EditorAction::SplitHorizontal, "Split top and bottom"));
+    lines.push(ek(EditorAction::SplitVertical, "Split side by side"));
+    lines.push(ek(EditorAction::FocusOtherSplit, "Focus the other split"));
+    lines.push(ek(EditorAction::CloseSplit, "Close the other split"));
+    lines.push(ek(EditorAction::LspHover, "Type and docs at cursor (LSP)"));
+    lines.push(ek(EditorAction::LspDefinition, "Go to definition (LSP)"));
+    lines.push(ek(EditorAction::LspCompletion, "Complete word (LSP)"));
+    lines.push(ek(EditorAction::LspFormat, "Format document (LSP)"));
+    lines.push(ek(EditorAction::NextDiagnostic, "Next diagnostic (LSP)"));
+    lines.push(ek(EditorAction::Exit, "Close editor (all buffers)"));
     lines.push(Line::from(""));
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -437,6 +461,13 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
             hint_style,
         ),
     ]));
+    lines.push(Line::from(vec![
+        Span::styled("  ".to_string(), desc_style),
+        Span::styled(
+            "3 selected dirs/files: three-way merge (base, left, right in mark order)".to_string(),
+            hint_style,
+        ),
+    ]));
     lines.push(dsk(DiffScreenAction::MoveUp, "Move cursor up"));
     lines.push(dsk(DiffScreenAction::MoveDown, "Move cursor down"));
     lines.push(dsk(DiffScreenAction::PageUp, "Page up"));
@@ -457,6 +488,38 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
     lines.push(dsk(DiffScreenAction::CollapseDir, "Collapse directory"));
     lines.push(dsk(DiffScreenAction::ExpandAll, "Expand all"));
     lines.push(dsk(DiffScreenAction::CollapseAll, "Collapse all"));
+    lines.push(dsk(
+        DiffScreenAction::SyncToRight,
+        "Copy selected differences left → right",
+    ));
+    lines.push(dsk(
+        DiffScreenAction::SyncToLeft,
+        "Copy selected differences right → left",
+    ));
+    lines.push(dsk(
+        DiffScreenAction::DeleteExtra,
+        "Delete selected one-side-only items",
+    ));
+    lines.push(dsk(
+        DiffScreenAction::MirrorToRight,
+        "Mirror whole tree left → right",
+    ));
+    lines.push(dsk(
+        DiffScreenAction::MirrorToLeft,
+        "Mirror whole tree right → left",
+    ));
+    lines.push(Line::from(vec![
+        Span::styled("  ".to_string(), desc_style),
+        Span::styled(
+            "Sync shows a dry-run summary first; one side may be a remote panel".to_string(),
+            hint_style,
+        ),
+    ]));
+    lines.push(dsk(
+        DiffScreenAction::ExportPatch,
+        "Export unified patch (selection or all)",
+    ));
+    lines.push(dsk(DiffScreenAction::ExportReport, "Export HTML report"));
     lines.push(dsk(DiffScreenAction::Close, "Return to file panel"));
     lines.push(Line::from(""));
 
@@ -484,6 +547,54 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
         DiffFileViewAction::PrevChange,
         "Jump to previous change",
     ));
+    lines.push(dfk(
+        DiffFileViewAction::ToggleMergeView,
+        "Three-way: toggle merged result",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::TakeLeft,
+        "Three-way: take left hunk",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::TakeRight,
+        "Three-way: take right hunk",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::TakeBoth,
+        "Three-way: take both hunks",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::TakeBase,
+        "Three-way: take base hunk",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::EditChunk,
+        "Three-way: edit current hunk",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::ToggleIgnoreWhitespace,
+        "Toggle ignore whitespace",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::ToggleIgnoreCase,
+        "Toggle ignore case",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::ToggleInlineMode,
+        "Word / character highlighting",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::CopyToRight,
+        "Copy current hunk to right",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::CopyToLeft,
+        "Copy current hunk to left",
+    ));
+    lines.push(dfk(
+        DiffFileViewAction::Save,
+        "Save edited files / merged result",
+    ));
     lines.push(dfk(DiffFileViewAction::Close, "Return to diff screen"));
     lines.push(Line::from(""));
 
@@ -504,6 +615,13 @@ fn build_help_content(theme: &Theme, kb: &Keybindings) -> Vec<Line<'static>> {
         Span::styled("  ".to_string(), desc_style),
         Span::styled("Themes: ~/.remotecc/themes/".to_string(), hint_style),
     ]));
+    lines.push(Line::from(vec![
+        Span::styled("  ".to_string(), desc_style),
+        Span::styled(
+            "Syntaxes: ~/.remotecc/syntaxes/ (*.sublime-syntax, *.json; see README)".to_string(),
+            hint_style,
+        ),
+    ]));
     lines.push(Line::from(""));
 
     // ═══════════════════════════════════════════════════════════════════════
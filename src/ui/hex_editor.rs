@@ -0,0 +1,669 @@
+//! Byte editor behind the viewer's hex mode.
+//!
+//! [`HexEditor`] owns the bytes of the file while it is open: bytes are
+//! overwritten or inserted from the hex or the ASCII column, every change is
+//! one [`ByteEdit`] on the undo stack, and saving goes through the same atomic
+//! write as the text editor. Reverting undoes or redoes back to the last save,
+//! so the viewer gets the bytes on disk back without keeping a second copy.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use std::path::PathBuf;
+
+use super::file_editor::write_atomically;
+use super::theme::Theme;
+
+const ROW_BYTES: usize = 16;
+
+/// Rows of the value inspector under the bytes
+pub const INSPECTOR_ROWS: usize = 4;
+
+/// Column the cursor types into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexColumn {
+    Hex,
+    Ascii,
+}
+
+/// Replacement of `old` at `offset` by `new`; an insert has no old bytes and
+/// a deletion no new ones
+#[derive(Debug, Clone)]
+struct ByteEdit {
+    id: u64,
+    offset: usize,
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct HexEditor {
+    path: PathBuf,
+    data: Vec<u8>,
+    /// Byte under the cursor; `data.len()` appends
+    pub cursor: usize,
+    /// The high nibble of the byte under the cursor was just typed
+    low_nibble: bool,
+    pub column: HexColumn,
+    /// Typing inserts bytes instead of overwriting them
+    pub insert: bool,
+    /// First row on screen
+    pub scroll: usize,
+    undo_stack: Vec<ByteEdit>,
+    redo_stack: Vec<ByteEdit>,
+    next_id: u64,
+    /// Id of the newest undo entry when the file was last saved (0: none)
+    saved_id: u64,
+    /// Open "find bytes" prompt
+    pub find_input: Option<String>,
+    find_pattern: Vec<u8>,
+    /// Set by a first Esc with unsaved changes; a second one discards them
+    confirm_discard: bool,
+    pub message: Option<String>,
+}
+
+impl HexEditor {
+    pub fn new(path: PathBuf, data: Vec<u8>) -> Self {
+        Self {
+            path,
+            data,
+            cursor: 0,
+            low_nibble: false,
+            column: HexColumn::Hex,
+            insert: false,
+            scroll: 0,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            next_id: 1,
+            saved_id: 0,
+            find_input: None,
+            find_pattern: Vec::new(),
+            confirm_discard: false,
+            message: None,
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.undo_stack.last().map_or(0, |e| e.id) != self.saved_id
+    }
+
+    /// Go back to the bytes last saved (or loaded) and hand them back
+    pub fn revert(mut self) -> Vec<u8> {
+        let saved_id = self.saved_id;
+        if self.redo_stack.iter().any(|e| e.id == saved_id) {
+            // Undone past the save
+            while self.is_modified() && self.redo() {}
+        } else if saved_id == 0 || self.undo_stack.iter().any(|e| e.id == saved_id) {
+            while self.is_modified() && self.undo() {}
+        } else if let Ok(data) = std::fs::read(&self.path) {
+            // The save was undone and then replaced by other edits
+            return data;
+        }
+        self.data
+    }
+
+    /// Replace `old_len` bytes at `offset` with `new`, as one undoable change
+    fn apply(&mut self, offset: usize, old_len: usize, new: Vec<u8>) {
+        let old: Vec<u8> = self
+            .data
+            .splice(offset..offset + old_len, new.iter().copied())
+            .collect();
+        self.undo_stack.push(ByteEdit {
+            id: self.next_id,
+            offset,
+            old,
+            new,
+        });
+        self.next_id += 1;
+        self.redo_stack.clear();
+    }
+
+    /// Type a character into the current column. Returns false when it is
+    /// not a hex digit (hex column) or not ASCII (ASCII column).
+    pub fn type_char(&mut self, c: char) -> bool {
+        let end = self.cursor >= self.data.len();
+        match self.column {
+            HexColumn::Hex => {
+                let digit = match c.to_digit(16) {
+                    Some(d) => d as u8,
+                    None => return false,
+                };
+                if self.low_nibble {
+                    // Complete the byte the high nibble started, in the same edit
+                    let byte = (self.data[self.cursor] & 0xF0) | digit;
+                    self.data[self.cursor] = byte;
+                    if let Some(edit) = self.undo_stack.last_mut() {
+                        edit.new = vec![byte];
+                        edit.id = self.next_id;
+                        self.next_id += 1;
+                    }
+                    self.low_nibble = false;
+                    self.cursor += 1;
+                } else {
+                    let high = digit << 4;
+                    if self.insert || end {
+                        self.apply(self.cursor, 0, vec![high]);
+                    } else {
+                        let byte = (self.data[self.cursor] & 0x0F) | high;
+                        self.apply(self.cursor, 1, vec![byte]);
+                    }
+                    self.low_nibble = true;
+                }
+            }
+            HexColumn::Ascii => {
+                if !c.is_ascii() || c.is_ascii_control() {
+                    return false;
+                }
+                let old_len = if self.insert || end { 0 } else { 1 };
+                self.apply(self.cursor, old_len, vec![c as u8]);
+                self.cursor += 1;
+            }
+        }
+        self.confirm_discard = false;
+        true
+    }
+
+    /// Delete the byte under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.data.len() {
+            self.apply(self.cursor, 1, Vec::new());
+        }
+        self.low_nibble = false;
+    }
+
+    /// Delete the byte before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.apply(self.cursor, 1, Vec::new());
+        }
+        self.low_nibble = false;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let edit = match self.undo_stack.pop() {
+            Some(e) => e,
+            None => return false,
+        };
+        self.data.splice(
+            edit.offset..edit.offset + edit.new.len(),
+            edit.old.iter().copied(),
+        );
+        self.cursor = edit.offset.min(self.data.len());
+        self.low_nibble = false;
+        self.redo_stack.push(edit);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let edit = match self.redo_stack.pop() {
+            Some(e) => e,
+            None => return false,
+        };
+        self.data.splice(
+            edit.offset..edit.offset + edit.old.len(),
+            edit.new.iter().copied(),
+        );
+        self.cursor = (edit.offset + edit.new.len()).min(self.data.len());
+        self.low_nibble = false;
+        self.undo_stack.push(edit);
+        true
+    }
+
+    /// Move the cursor by `delta` bytes, clamped to the data
+    pub fn move_by(&mut self, delta: isize) {
+        self.move_to(self.cursor.saturating_add_signed(delta));
+    }
+
+    pub fn move_to(&mut self, offset: usize) {
+        self.cursor = offset.min(self.data.len());
+        self.low_nibble = false;
+    }
+
+    /// Write the bytes back to the file
+    pub fn save(&mut self) -> Result<(), String> {
+        write_atomically(&self.path, &self.data)?;
+        self.saved_id = self.undo_stack.last().map_or(0, |e| e.id);
+        self.confirm_discard = false;
+        Ok(())
+    }
+
+    /// Find `pattern` after (or before) the cursor, wrapping around the end
+    pub fn find(&mut self, forward: bool) -> bool {
+        let pattern = &self.find_pattern;
+        if pattern.is_empty() || pattern.len() > self.data.len() {
+            return false;
+        }
+        let last = self.data.len() - pattern.len();
+        let at = |i: usize| self.data[i..i + pattern.len()] == pattern[..];
+        let found = if forward {
+            let start = (self.cursor + 1).min(last + 1);
+            (start..=last).chain(0..start).find(|&i| at(i))
+        } else {
+            let start = self.cursor.min(last + 1);
+            (0..start)
+                .rev()
+                .chain((start..=last).rev())
+                .find(|&i| at(i))
+        };
+        match found {
+            Some(offset) => {
+                self.move_to(offset);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Values of the bytes at the cursor: (type, little endian, big endian)
+    pub fn inspect(&self) -> Vec<(&'static str, String, String)> {
+        let bytes = &self.data[self.cursor.min(self.data.len())..];
+        fn read<const N: usize>(bytes: &[u8]) -> Option<([u8; N], [u8; N])> {
+            let le: [u8; N] = bytes.get(..N)?.try_into().ok()?;
+            let mut be = le;
+            be.reverse();
+            Some((le, be))
+        }
+        fn pair<const N: usize, T: ToString>(
+            bytes: &[u8],
+            convert: fn([u8; N]) -> T,
+        ) -> (String, String) {
+            match read::<N>(bytes) {
+                // `be` is reversed, so reading it as little endian gives the big endian value
+                Some((le, be)) => (convert(le).to_string(), convert(be).to_string()),
+                None => ("-".to_string(), "-".to_string()),
+            }
+        }
+        let rows: [(&'static str, (String, String)); 8] = [
+            ("u8", pair::<1, _>(bytes, u8::from_le_bytes)),
+            ("i8", pair::<1, _>(bytes, i8::from_le_bytes)),
+            ("u16", pair::<2, _>(bytes, u16::from_le_bytes)),
+            ("i16", pair::<2, _>(bytes, i16::from_le_bytes)),
+            ("u32", pair::<4, _>(bytes, u32::from_le_bytes)),
+            ("i32", pair::<4, _>(bytes, i32::from_le_bytes)),
+            ("f32", pair::<4, _>(bytes, f32::from_le_bytes)),
+            ("f64", pair::<8, _>(bytes, f64::from_le_bytes)),
+        ];
+        rows.into_iter()
+            .map(|(name, (le, be))| (name, le, be))
+            .collect()
+    }
+
+    /// Keep the cursor row within `height` rows on screen
+    fn scroll_to_cursor(&mut self, height: usize) {
+        let row = self.cursor / ROW_BYTES;
+        if row < self.scroll {
+            self.scroll = row;
+        } else if height > 0 && row >= self.scroll + height {
+            self.scroll = row + 1 - height;
+        }
+    }
+}
+
+/// Bytes of a search: hex digits (spaces allowed) or a quoted string
+pub fn parse_byte_pattern(input: &str) -> Result<Vec<u8>, String> {
+    let input = input.trim();
+    if let Some(text) = input.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return Err("Enter whole bytes in hex (DE AD BE EF) or \"text\"".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("'{}' is not a hex byte", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+fn find_or_report(editor: &mut HexEditor, forward: bool) {
+    if !editor.find(forward) {
+        editor.message = Some("Not found".to_string());
+    }
+}
+
+/// Handle a key. Returns true when the editor should be closed.
+pub fn handle_key(
+    editor: &mut HexEditor,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+    height: usize,
+) -> bool {
+    editor.message = None;
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+    // Find prompt
+    if let Some(ref mut input) = editor.find_input {
+        match code {
+            KeyCode::Esc => editor.find_input = None,
+            KeyCode::Enter => match parse_byte_pattern(input) {
+                Ok(pattern) => {
+                    editor.find_pattern = pattern;
+                    editor.find_input = None;
+                    find_or_report(editor, true);
+                }
+                Err(e) => editor.message = Some(e),
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !ctrl => input.push(c),
+            _ => {}
+        }
+        editor.scroll_to_cursor(height);
+        return false;
+    }
+
+    let row = ROW_BYTES as isize;
+    match code {
+        KeyCode::Esc => {
+            if editor.is_modified() && !editor.confirm_discard {
+                editor.confirm_discard = true;
+                editor.message =
+                    Some("Unsaved changes: Esc again to discard them, Ctrl+S to save".to_string());
+                return false;
+            }
+            return true;
+        }
+        KeyCode::Char('s') if ctrl => match editor.save() {
+            Ok(()) => editor.message = Some("Saved".to_string()),
+            Err(e) => editor.message = Some(e),
+        },
+        KeyCode::Char('z') if ctrl => {
+            editor.undo();
+        }
+        KeyCode::Char('y') if ctrl => {
+            editor.redo();
+        }
+        KeyCode::Char('f') if ctrl => editor.find_input = Some(String::new()),
+        KeyCode::Char('n') if ctrl => find_or_report(editor, true),
+        KeyCode::F(3) => find_or_report(editor, !modifiers.contains(KeyModifiers::SHIFT)),
+        KeyCode::Char('p') if ctrl => find_or_report(editor, false),
+        KeyCode::Tab | KeyCode::BackTab => {
+            editor.column = match editor.column {
+                HexColumn::Hex => HexColumn::Ascii,
+                HexColumn::Ascii => HexColumn::Hex,
+            };
+            editor.low_nibble = false;
+        }
+        KeyCode::Insert => editor.insert = !editor.insert,
+        KeyCode::Left => editor.move_by(-1),
+        KeyCode::Right => editor.move_by(1),
+        KeyCode::Up => editor.move_by(-row),
+        KeyCode::Down => editor.move_by(row),
+        KeyCode::PageUp => editor.move_by(-row * height.max(1) as isize),
+        KeyCode::PageDown => editor.move_by(row * height.max(1) as isize),
+        KeyCode::Home if ctrl => editor.move_to(0),
+        KeyCode::End if ctrl => editor.move_to(editor.data.len()),
+        KeyCode::Home => editor.move_to(editor.cursor / ROW_BYTES * ROW_BYTES),
+        KeyCode::End => editor.move_to(editor.cursor / ROW_BYTES * ROW_BYTES + ROW_BYTES - 1),
+        KeyCode::Delete => editor.delete(),
+        KeyCode::Backspace => editor.backspace(),
+        KeyCode::Char(c) if !ctrl => {
+            let typed = editor.type_char(c);
+            if !typed && editor.column == HexColumn::Hex {
+                editor.message = Some("Type hex digits, or Tab to the ASCII column".to_string());
+            }
+        }
+        _ => {}
+    }
+    editor.scroll_to_cursor(height);
+    false
+}
+
+/// Draw the bytes in `area` and the inspector below them. `footer` is the
+/// row for prompts and key hints.
+pub fn draw(frame: &mut Frame, editor: &mut HexEditor, area: Rect, footer: Rect, theme: &Theme) {
+    let inspector_rows = if area.height as usize > INSPECTOR_ROWS + 2 {
+        INSPECTOR_ROWS
+    } else {
+        0
+    };
+    let height = area.height as usize - inspector_rows;
+    editor.scroll_to_cursor(height);
+
+    let cursor_style = Style::default()
+        .fg(theme.viewer.search_cursor_fg)
+        .bg(theme.viewer.search_cursor_bg)
+        .add_modifier(Modifier::BOLD);
+    // The same byte in the other column
+    let mirror_style = Style::default()
+        .fg(theme.viewer.search_match_other_fg)
+        .bg(theme.viewer.search_match_other_bg);
+    let (hex_cursor, ascii_cursor) = match editor.column {
+        HexColumn::Hex => (cursor_style, mirror_style),
+        HexColumn::Ascii => (mirror_style, cursor_style),
+    };
+
+    let len = editor.data.len();
+    for i in 0..height {
+        let start = (editor.scroll + i) * ROW_BYTES;
+        // One row past the end holds the append position
+        if start > len || (start == len && !len.is_multiple_of(ROW_BYTES)) {
+            break;
+        }
+        let end = (start + ROW_BYTES).min(len);
+        let mut spans = vec![Span::styled(
+            format!("{:08X}  ", start),
+            Style::default().fg(theme.viewer.hex_offset),
+        )];
+        let mut ascii = vec![Span::styled(
+            "|",
+            Style::default().fg(theme.viewer.hex_ascii),
+        )];
+        for offset in start..start + ROW_BYTES {
+            let at_cursor = offset == editor.cursor;
+            let separator = if offset - start == 8 { "  " } else { " " };
+            if offset > start {
+                spans.push(Span::raw(separator));
+            }
+            if offset < end {
+                let byte = editor.data[offset];
+                let hex = format!("{:02X}", byte);
+                if at_cursor && editor.low_nibble && editor.column == HexColumn::Hex {
+                    // Show which nibble is typed next
+                    spans.push(Span::styled(hex[..1].to_string(), mirror_style));
+                    spans.push(Span::styled(hex[1..].to_string(), cursor_style));
+                } else {
+                    let style = if at_cursor {
+                        hex_cursor
+                    } else {
+                        Style::default().fg(theme.viewer.hex_bytes)
+                    };
+                    spans.push(Span::styled(hex, style));
+                }
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                let style = if at_cursor {
+                    ascii_cursor
+                } else {
+                    Style::default().fg(theme.viewer.hex_ascii)
+                };
+                ascii.push(Span::styled(c.to_string(), style));
+            } else if at_cursor {
+                spans.push(Span::styled("__", hex_cursor));
+                ascii.push(Span::styled("_", ascii_cursor));
+            } else {
+                spans.push(Span::raw("  "));
+            }
+        }
+        ascii.push(Span::styled(
+            "|",
+            Style::default().fg(theme.viewer.hex_ascii),
+        ));
+        spans.push(Span::raw("  "));
+        spans.extend(ascii);
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)),
+            Rect::new(area.x, area.y + i as u16, area.width, 1),
+        );
+    }
+
+    // Inspector: two values per row
+    let values = editor.inspect();
+    for (i, pair) in values.chunks(2).take(inspector_rows).enumerate() {
+        let mut spans = Vec::new();
+        for (name, le, be) in pair {
+            spans.push(Span::styled(format!(" {:<4}", name), theme.header_style()));
+            spans.push(Span::styled(
+                format!("LE {:<14} BE {:<14} ", truncate(le), truncate(be)),
+                theme.dim_style(),
+            ));
+        }
+        let y = area.y + (height + i) as u16;
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).style(theme.status_bar_style()),
+            Rect::new(area.x, y, area.width, 1),
+        );
+    }
+
+    // Footer
+    let line = if let Some(ref input) = editor.find_input {
+        Line::from(vec![
+            Span::styled("Find bytes (hex or \"text\"): ", theme.header_style()),
+            Span::styled(input.clone(), theme.normal_style()),
+            Span::styled(" ", cursor_style),
+        ])
+    } else if let Some(ref msg) = editor.message {
+        Line::from(Span::styled(format!(" {}", msg), theme.header_style()))
+    } else {
+        let mut spans = vec![Span::styled(
+            format!(
+                "{:08X} {}{} ",
+                editor.cursor,
+                if editor.insert { "INS" } else { "OVR" },
+                if editor.is_modified() { " [+]" } else { "" }
+            ),
+            theme.header_style(),
+        )];
+        for (key, rest) in [
+            ("^S", "save "),
+            ("^Z", "undo "),
+            ("^Y", "redo "),
+            ("^F", "find "),
+            ("Tab", " hex/ascii "),
+            ("Ins", " insert "),
+            ("Esc", " close"),
+        ] {
+            spans.push(Span::styled(key, theme.header_style()));
+            spans.push(Span::styled(rest, theme.dim_style()));
+        }
+        Line::from(spans)
+    };
+    frame.render_widget(Paragraph::new(line).style(theme.status_bar_style()), footer);
+}
+
+/// Floats can print very long; the inspector cuts them to its column width
+fn truncate(value: &str) -> &str {
+    value.get(..14).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overwrite_insert_delete_and_undo() {
+        let mut editor = HexEditor::new(PathBuf::new(), vec![0x00, 0x11, 0x22]);
+        // Two hex digits make one byte and one undo step
+        assert!(editor.type_char('a'));
+        assert!(editor.type_char('B'));
+        assert!(!editor.type_char('g'));
+        assert_eq!(editor.data, [0xAB, 0x11, 0x22]);
+        assert_eq!(editor.cursor, 1);
+
+        editor.insert = true;
+        editor.column = HexColumn::Ascii;
+        assert!(editor.type_char('Z'));
+        assert_eq!(editor.data, [0xAB, b'Z', 0x11, 0x22]);
+
+        editor.move_to(3);
+        editor.delete();
+        editor.backspace();
+        assert_eq!(editor.data, [0xAB, b'Z']);
+
+        // Typing at the end appends
+        editor.insert = false;
+        editor.move_to(2);
+        editor.type_char('!');
+        assert_eq!(editor.data, [0xAB, b'Z', b'!']);
+
+        while editor.undo() {}
+        assert_eq!(editor.data, [0x00, 0x11, 0x22]);
+        assert!(!editor.is_modified());
+        assert!(editor.redo());
+        assert_eq!(editor.data, [0xAB, 0x11, 0x22]);
+        assert!(editor.is_modified());
+        assert_eq!(editor.revert(), [0x00, 0x11, 0x22]);
+    }
+
+    #[test]
+    fn test_find_and_inspect() {
+        assert_eq!(parse_byte_pattern("de ad BE"), Ok(vec![0xDE, 0xAD, 0xBE]));
+        assert_eq!(parse_byte_pattern("\"PK\""), Ok(b"PK".to_vec()));
+        assert!(parse_byte_pattern("abc").is_err());
+        assert!(parse_byte_pattern("zz").is_err());
+
+        let mut data = vec![0u8; 40];
+        data[4..8].copy_from_slice(&1.5f32.to_le_bytes());
+        data[20..22].copy_from_slice(&[0xDE, 0xAD]);
+        data[30..32].copy_from_slice(&[0xDE, 0xAD]);
+        let mut editor = HexEditor::new(PathBuf::new(), data);
+        editor.find_pattern = vec![0xDE, 0xAD];
+        assert!(editor.find(true));
+        assert_eq!(editor.cursor, 20);
+        assert!(editor.find(true));
+        assert_eq!(editor.cursor, 30);
+        // Wraps around
+        assert!(editor.find(true));
+        assert_eq!(editor.cursor, 20);
+        assert!(editor.find(false));
+        assert_eq!(editor.cursor, 30);
+
+        let values = editor.inspect();
+        assert_eq!(values[2], ("u16", "44510".to_string(), "57005".to_string()));
+        editor.move_to(4);
+        let values = editor.inspect();
+        assert_eq!(values[6].1, "1.5");
+        editor.move_to(39);
+        assert_eq!(editor.inspect()[4].1, "-");
+    }
+
+    #[test]
+    fn test_save_writes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, [1u8, 2, 3]).unwrap();
+        let mut editor = HexEditor::new(path.clone(), std::fs::read(&path).unwrap());
+        editor.delete();
+        assert!(editor.is_modified());
+        editor.save().unwrap();
+        assert!(!editor.is_modified());
+        assert_eq!(std::fs::read(&path).unwrap(), [2, 3]);
+        // Undo after saving is an unsaved change again
+        editor.undo();
+        assert!(editor.is_modified());
+        assert_eq!(editor.data, [1, 2, 3]);
+        // Closing goes back to what was saved, not to what was loaded
+        assert_eq!(editor.revert(), [2, 3]);
+
+        let mut editor = HexEditor::new(path.clone(), std::fs::read(&path).unwrap());
+        editor.delete();
+        editor.save().unwrap();
+        editor.undo();
+        editor.column = HexColumn::Ascii;
+        editor.type_char('x');
+        assert_eq!(editor.revert(), [3]);
+    }
+}
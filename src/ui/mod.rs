@@ -6,11 +6,14 @@ pub mod dialogs;
 pub mod diff_file_view;
 pub mod diff_screen;
 pub mod draw;
+pub mod editor_workspace;
 pub mod file_editor;
 pub mod file_info;
 pub mod file_viewer;
 pub mod git_screen;
+pub mod grammar;
 pub mod help;
+pub mod hex_editor;
 pub mod image_viewer;
 pub mod panel;
 pub mod process_manager;
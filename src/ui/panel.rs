@@ -11,6 +11,7 @@ use super::{
     app::{PanelState, SortBy, SortOrder},
     theme::Theme,
 };
+use crate::services::git_status::GitFileStatus;
 use crate::utils::format::{format_size, pad_to_display_width, truncate_to_display_width};
 
 pub fn draw(
@@ -85,6 +86,9 @@ pub fn draw(
     let min_columns: u16 = 10 + 12 + 4; // size + date + padding
     let type_col_total: usize = 10; // 2 + 6 + 2 (padding + type + padding)
 
+    // Git status badges get their own column inside repositories
+    let git_column = panel.git_status.root().is_some();
+
     // Calculate max file name width (including marker and icon = 2 chars)
     let max_name_display_width = panel
         .files
@@ -94,7 +98,8 @@ pub fn draw(
             name.width() + 2 // +2 for marker and icon
         })
         .max()
-        .unwrap_or(0);
+        .unwrap_or(0)
+        + if git_column { GIT_BADGE_WIDTH } else { 0 };
 
     let (name_col, type_col, size_col, date_col) = if inner.width > min_columns {
         let available_for_name = (inner.width - min_columns) as usize;
@@ -163,6 +168,13 @@ pub fn draw(
     panel.scroll_offset = start_index;
 
     let visible_files = panel.files.iter().skip(start_index).take(visible_height);
+    let columns = FileColumns {
+        name_width: name_col,
+        type_width: type_col,
+        size_width: size_col,
+        date_width: date_col,
+        git_column,
+    };
 
     for (i, file) in visible_files.enumerate() {
         let actual_index = start_index + i;
@@ -170,16 +182,7 @@ pub fn draw(
         let is_marked = panel.selected_files.contains(&file.name);
         let show_cursor = is_cursor && is_active;
 
-        let line = create_file_line(
-            file,
-            show_cursor,
-            is_marked,
-            name_col,
-            type_col,
-            size_col,
-            date_col,
-            theme,
-        );
+        let line = create_file_line(file, show_cursor, is_marked, &columns, theme);
 
         let paragraph = if show_cursor {
             let cursor_bg = if is_marked {
@@ -377,16 +380,50 @@ fn create_header_line(
     ])
 }
 
-fn create_file_line(
-    file: &super::app::FileItem,
-    is_cursor: bool,
-    is_marked: bool,
+/// Width of the git status badge column
+const GIT_BADGE_WIDTH: usize = 2;
+
+fn git_badge_color(status: GitFileStatus, theme: &Theme) -> Color {
+    match status {
+        GitFileStatus::Modified => theme.git_screen.file_modified,
+        GitFileStatus::Staged => theme.git_screen.file_staged,
+        GitFileStatus::Untracked => theme.git_screen.file_untracked,
+        GitFileStatus::Conflicted => theme.git_screen.file_deleted,
+        GitFileStatus::Ignored => theme.panel.date_text,
+    }
+}
+
+/// Column widths of the file list, the same for every row
+struct FileColumns {
     name_width: usize,
     type_width: usize,
     size_width: usize,
     date_width: usize,
+    /// Show the git status badge, carved out of the name column
+    git_column: bool,
+}
+
+fn create_file_line(
+    file: &super::app::FileItem,
+    is_cursor: bool,
+    is_marked: bool,
+    columns: &FileColumns,
     theme: &Theme,
 ) -> Line<'static> {
+    let FileColumns {
+        name_width,
+        type_width,
+        size_width,
+        date_width,
+        git_column,
+    } = *columns;
+    // The badge column is carved out of the name column
+    let (name_width, badge_width) = if git_column && name_width > GIT_BADGE_WIDTH + 4 {
+        (name_width - GIT_BADGE_WIDTH, GIT_BADGE_WIDTH)
+    } else {
+        (name_width, 0)
+    };
+
     let marker = if is_marked { "✻" } else { " " };
     let icon = if file.is_symlink {
         theme.chars.symlink.to_string()
@@ -514,10 +551,25 @@ fn create_file_line(
         theme.dim_style()
     };
 
-    Line::from(vec![
-        Span::styled(name_col, name_style),
+    let mut spans = vec![Span::styled(name_col, name_style)];
+    if badge_width > 0 {
+        let badge = match file.git_status {
+            Some(status) => format!("{:>width$}", status.badge(), width = badge_width),
+            None => " ".repeat(badge_width),
+        };
+        let badge_style = match file.git_status {
+            Some(_) if is_cursor => name_style.add_modifier(Modifier::BOLD),
+            Some(status) => Style::default()
+                .fg(git_badge_color(status, theme))
+                .add_modifier(Modifier::BOLD),
+            None => name_style,
+        };
+        spans.push(Span::styled(badge, badge_style));
+    }
+    spans.extend([
         Span::styled(type_col_str, other_style),
         Span::styled(size_col, other_style),
         Span::styled(date_col, other_style),
-    ])
+    ]);
+    Line::from(spans)
 }
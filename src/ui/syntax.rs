@@ -1,10 +1,15 @@
 use ratatui::style::{Modifier, Style};
+use regex::Regex;
+use serde::Deserialize;
 use std::path::Path;
+use std::sync::OnceLock;
 
+use crate::ui::grammar::{self, Grammar};
 use crate::ui::theme::SyntaxColors;
 
-/// 토큰 유형
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 토큰 유형 (문법 파일에서는 snake_case 이름)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TokenType {
     Keyword,
     Type,
@@ -45,10 +50,190 @@ pub enum Language {
     Php,
     Swift,
     Kotlin,
+    Dockerfile,
+    Makefile,
+    Nix,
+    Terraform,
+    Lua,
+    Zig,
+    Elixir,
     Plain,
+    /// ~/.remotecc/syntaxes 문법 파일로 정의된 언어 (`grammar::user_grammars()`의 인덱스)
+    User(usize),
 }
 
+/// 모드라인을 찾는 파일 앞뒤 라인 수
+const MODELINE_LINES: usize = 5;
+
 impl Language {
+    /// 내장 언어 목록 (Plain 제외)
+    pub const BUILTIN: [Language; 28] = [
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::C,
+        Language::Cpp,
+        Language::Java,
+        Language::Go,
+        Language::Html,
+        Language::Css,
+        Language::Json,
+        Language::Yaml,
+        Language::Toml,
+        Language::Markdown,
+        Language::Shell,
+        Language::Sql,
+        Language::Xml,
+        Language::Ruby,
+        Language::Php,
+        Language::Swift,
+        Language::Kotlin,
+        Language::Dockerfile,
+        Language::Makefile,
+        Language::Nix,
+        Language::Terraform,
+        Language::Lua,
+        Language::Zig,
+        Language::Elixir,
+    ];
+
+    /// 파일 이름과 내용으로 언어 감지
+    /// (모드라인, shebang, 파일 이름, 확장자 순으로 확인)
+    pub fn detect<S: AsRef<str>>(path: &Path, lines: &[S]) -> Self {
+        let tail_start = lines
+            .len()
+            .saturating_sub(MODELINE_LINES)
+            .max(MODELINE_LINES);
+        let modeline = lines
+            .iter()
+            .take(MODELINE_LINES)
+            .chain(lines.iter().skip(tail_start))
+            .find_map(|l| Self::from_modeline(l.as_ref()));
+        if let Some(language) = modeline {
+            return language;
+        }
+
+        if let Some(language) = lines
+            .first()
+            .and_then(|l| l.as_ref().strip_prefix("#!"))
+            .and_then(Self::from_interpreter)
+        {
+            return language;
+        }
+
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if let Some(language) = Self::from_file_name(file_name) {
+            return language;
+        }
+
+        Self::from_extension(path)
+    }
+
+    /// vim (`vim: ft=python`) 또는 emacs (`-*- mode: python -*-`) 모드라인의 언어
+    fn from_modeline(line: &str) -> Option<Self> {
+        static MODELINES: OnceLock<Vec<Regex>> = OnceLock::new();
+        let patterns = MODELINES.get_or_init(|| {
+            [
+                r"(?:^|\s)(?:vi|vim|ex):.*?\b(?:ft|filetype|syntax)=([\w+#-]+)",
+                r"-\*-.*?\bmode:\s*([\w+#-]+)",
+                r"-\*-\s*([\w+#-]+)\s*-\*-",
+            ]
+            .iter()
+            .filter_map(|p| Regex::new(p).ok())
+            .collect()
+        });
+        let name = patterns
+            .iter()
+            .find_map(|re| re.captures(line))
+            .and_then(|c| c.get(1))?
+            .as_str();
+        // emacs의 `makefile-gmake`, `sh-mode` 같은 이름은 앞부분으로도 찾는다
+        Self::from_name(name).or_else(|| Self::from_name(name.split('-').next()?))
+    }
+
+    /// `#!` 뒤의 인터프리터로 언어 감지 (`/usr/bin/env python3` 등)
+    pub fn from_interpreter(shebang: &str) -> Option<Self> {
+        let mut args = shebang.split_whitespace();
+        let mut program = args.next()?.rsplit('/').next()?;
+        if program == "env" {
+            // env의 옵션(-S 등)과 VAR=value 지정은 건너뛴다
+            program = args.find(|a| !a.starts_with('-') && !a.contains('='))?;
+            program = program.rsplit('/').next()?;
+        }
+        // python3.11 → python
+        let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+        if let Some(index) = grammar::user_grammars()
+            .iter()
+            .position(|g| g.interpreters.iter().any(|i| i == name || i == program))
+        {
+            return Some(Language::User(index));
+        }
+
+        match name {
+            "python" | "pypy" => Some(Language::Python),
+            "node" | "nodejs" | "deno" | "bun" => Some(Language::JavaScript),
+            "ts-node" | "tsx" => Some(Language::TypeScript),
+            "sh" | "bash" | "zsh" | "fish" | "dash" | "ksh" | "ash" => Some(Language::Shell),
+            "ruby" => Some(Language::Ruby),
+            "php" => Some(Language::Php),
+            "lua" | "luajit" => Some(Language::Lua),
+            "elixir" => Some(Language::Elixir),
+            "make" | "gmake" => Some(Language::Makefile),
+            "swift" => Some(Language::Swift),
+            "nix-shell" => Some(Language::Nix),
+            _ => None,
+        }
+    }
+
+    /// 확장자 없이 이름으로 알아보는 파일
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        if let Some(index) = grammar::user_grammars()
+            .iter()
+            .position(|g| g.filenames.iter().any(|f| f == file_name))
+        {
+            return Some(Language::User(index));
+        }
+
+        match file_name {
+            "Makefile" | "makefile" | "GNUmakefile" => Some(Language::Makefile),
+            "Dockerfile" | "Containerfile" => Some(Language::Dockerfile),
+            "Rakefile" | "Gemfile" => Some(Language::Ruby),
+            ".bashrc" | ".bash_profile" | ".zshrc" | ".profile" => Some(Language::Shell),
+            _ if file_name.starts_with("Dockerfile.") => Some(Language::Dockerfile),
+            _ => None,
+        }
+    }
+
+    /// 언어 이름, language id 또는 확장자로 언어 찾기 (대소문자 무시)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if let Some(index) = grammar::user_grammars().iter().position(|g| {
+            g.name.to_lowercase() == name || g.lsp_id() == name || g.extensions.contains(&name)
+        }) {
+            return Some(Language::User(index));
+        }
+        if let Some(language) = Self::BUILTIN
+            .into_iter()
+            .find(|l| l.name().to_lowercase() == name || l.lsp_id() == name)
+        {
+            return Some(language);
+        }
+        match name.as_str() {
+            "make" => Some(Language::Makefile),
+            "docker" => Some(Language::Dockerfile),
+            "text" | "plain" | "plaintext" => Some(Language::Plain),
+            _ => match Self::from_extension(Path::new(&format!("file.{}", name))) {
+                Language::Plain => None,
+                language => Some(language),
+            },
+        }
+    }
+
     /// 파일 확장자로 언어 감지
     pub fn from_extension(path: &Path) -> Self {
         let ext = path
@@ -57,6 +242,13 @@ impl Language {
             .map(|e| e.to_lowercase())
             .unwrap_or_default();
 
+        if let Some(index) = grammar::user_grammars()
+            .iter()
+            .position(|g| g.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext)))
+        {
+            return Language::User(index);
+        }
+
         match ext.as_str() {
             "rs" => Language::Rust,
             "py" | "pyw" | "pyi" => Language::Python,
@@ -79,6 +271,13 @@ impl Language {
             "php" | "php3" | "php4" | "php5" | "phtml" => Language::Php,
             "swift" => Language::Swift,
             "kt" | "kts" => Language::Kotlin,
+            "dockerfile" | "containerfile" => Language::Dockerfile,
+            "mk" | "mak" => Language::Makefile,
+            "nix" => Language::Nix,
+            "tf" | "tfvars" | "hcl" => Language::Terraform,
+            "lua" => Language::Lua,
+            "zig" => Language::Zig,
+            "ex" | "exs" => Language::Elixir,
             _ => Language::Plain,
         }
     }
@@ -107,7 +306,63 @@ impl Language {
             Language::Php => "PHP",
             Language::Swift => "Swift",
             Language::Kotlin => "Kotlin",
+            Language::Dockerfile => "Dockerfile",
+            Language::Makefile => "Makefile",
+            Language::Nix => "Nix",
+            Language::Terraform => "Terraform",
+            Language::Lua => "Lua",
+            Language::Zig => "Zig",
+            Language::Elixir => "Elixir",
             Language::Plain => "Plain",
+            Language::User(index) => grammar::user_grammars()
+                .get(*index)
+                .map_or("Plain", |g| g.name.as_str()),
+        }
+    }
+
+    /// Language Server Protocol의 language id (Settings의 lsp_servers 키)
+    pub fn lsp_id(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Java => "java",
+            Language::Go => "go",
+            Language::Html => "html",
+            Language::Css => "css",
+            Language::Json => "json",
+            Language::Yaml => "yaml",
+            Language::Toml => "toml",
+            Language::Markdown => "markdown",
+            Language::Shell => "shellscript",
+            Language::Sql => "sql",
+            Language::Xml => "xml",
+            Language::Ruby => "ruby",
+            Language::Php => "php",
+            Language::Swift => "swift",
+            Language::Kotlin => "kotlin",
+            Language::Dockerfile => "dockerfile",
+            Language::Makefile => "makefile",
+            Language::Nix => "nix",
+            Language::Terraform => "terraform",
+            Language::Lua => "lua",
+            Language::Zig => "zig",
+            Language::Elixir => "elixir",
+            Language::Plain => "plaintext",
+            Language::User(index) => grammar::user_grammars()
+                .get(*index)
+                .map_or("plaintext", |g| g.lsp_id()),
+        }
+    }
+
+    /// 문법 정의로 강조하는 언어의 Grammar
+    pub fn grammar(&self) -> Option<&'static Grammar> {
+        match self {
+            Language::User(index) => grammar::user_grammars().get(*index),
+            _ => grammar::builtin_grammar(*self),
         }
     }
 }
@@ -186,6 +441,20 @@ impl SyntaxHighlighter {
             Language::Php => self.tokenize_php(line),
             Language::Swift => self.tokenize_swift(line),
             Language::Markdown => self.tokenize_markdown(line),
+            Language::Dockerfile
+            | Language::Makefile
+            | Language::Nix
+            | Language::Terraform
+            | Language::Lua
+            | Language::Zig
+            | Language::Elixir
+            | Language::User(_) => match self.language.grammar() {
+                Some(grammar) => self.tokenize_grammar(line, grammar),
+                None => vec![Token {
+                    text: line.to_string(),
+                    token_type: TokenType::Normal,
+                }],
+            },
             Language::Plain => vec![Token {
                 text: line.to_string(),
                 token_type: TokenType::Normal,
@@ -2806,6 +3075,228 @@ impl SyntaxHighlighter {
         tokens
     }
 
+    // 문법 정의(Grammar)로 토큰화
+    fn tokenize_grammar(&mut self, line: &str, grammar: &Grammar) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let chars: Vec<char> = line.chars().collect();
+        // 문자 인덱스별 바이트 오프셋 (마지막은 라인 길이)
+        let offsets: Vec<usize> = line
+            .char_indices()
+            .map(|(b, _)| b)
+            .chain(std::iter::once(line.len()))
+            .collect();
+        let char_at = |byte: usize| offsets.partition_point(|&b| b < byte);
+        let text = |from: usize, to: usize| line[offsets[from]..offsets[to]].to_string();
+        let mut i = 0;
+
+        // 멀티라인 주석/문자열 계속
+        let open_block = if self.in_multiline_comment {
+            grammar
+                .block_comment
+                .as_ref()
+                .map(|(_, end)| (end.as_str(), TokenType::Comment))
+        } else if self.in_multiline_string {
+            grammar
+                .block_string
+                .as_ref()
+                .map(|(_, end)| (end.as_str(), TokenType::String))
+        } else {
+            None
+        };
+        self.in_multiline_comment = false;
+        self.in_multiline_string = false;
+        if let Some((end, token_type)) = open_block {
+            match line.find(end) {
+                Some(idx) => {
+                    let byte_end = idx + end.len();
+                    tokens.push(Token {
+                        text: line[..byte_end].to_string(),
+                        token_type,
+                    });
+                    i = char_at(byte_end);
+                }
+                None => {
+                    match token_type {
+                        TokenType::Comment => self.in_multiline_comment = true,
+                        _ => self.in_multiline_string = true,
+                    }
+                    tokens.push(Token {
+                        text: line.to_string(),
+                        token_type,
+                    });
+                    return tokens;
+                }
+            }
+        }
+
+        // 규칙 매치: 시작 문자 인덱스 → (끝 문자 인덱스, 토큰 타입), 앞선 규칙 우선
+        let mut rule_at: Vec<Option<(usize, TokenType)>> = vec![None; chars.len()];
+        for (re, token_type) in &grammar.compiled {
+            for m in re.find_iter(line).filter(|m| !m.is_empty()) {
+                let start = char_at(m.start());
+                if rule_at[start].is_none() {
+                    rule_at[start] = Some((char_at(m.end()), *token_type));
+                }
+            }
+        }
+
+        while i < chars.len() {
+            let rest = &line[offsets[i]..];
+
+            // 블록 주석 시작 (Lua의 --[[ 처럼 라인 주석과 겹칠 수 있어 먼저 확인)
+            if let Some((open, close)) = &grammar.block_comment {
+                if rest.starts_with(open.as_str()) {
+                    let start = i;
+                    i = match block_end(line, offsets[i], open, close) {
+                        Some(byte_end) => char_at(byte_end),
+                        None => {
+                            self.in_multiline_comment = true;
+                            chars.len()
+                        }
+                    };
+                    tokens.push(Token {
+                        text: text(start, i),
+                        token_type: TokenType::Comment,
+                    });
+                    continue;
+                }
+            }
+
+            // 라인 주석
+            if let Some(comment) = &grammar.line_comment {
+                if rest.starts_with(comment.as_str()) {
+                    tokens.push(Token {
+                        text: rest.to_string(),
+                        token_type: TokenType::Comment,
+                    });
+                    break;
+                }
+            }
+
+            // 블록 문자열 시작
+            if let Some((open, close)) = &grammar.block_string {
+                if rest.starts_with(open.as_str()) {
+                    let start = i;
+                    i = match block_end(line, offsets[i], open, close) {
+                        Some(byte_end) => char_at(byte_end),
+                        None => {
+                            self.in_multiline_string = true;
+                            chars.len()
+                        }
+                    };
+                    tokens.push(Token {
+                        text: text(start, i),
+                        token_type: TokenType::String,
+                    });
+                    continue;
+                }
+            }
+
+            // 문법 규칙
+            if let Some((end, token_type)) = rule_at[i] {
+                tokens.push(Token {
+                    text: text(i, end),
+                    token_type,
+                });
+                i = end;
+                continue;
+            }
+
+            // 문자열
+            if grammar.quotes.contains(chars[i]) {
+                let quote = chars[i];
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if i < chars.len() {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    text: text(start, i),
+                    token_type: TokenType::String,
+                });
+                continue;
+            }
+
+            // 숫자
+            if chars[i].is_ascii_digit() {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+                {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    text: text(start, i),
+                    token_type: TokenType::Number,
+                });
+                continue;
+            }
+
+            // 식별자/키워드
+            if chars[i].is_alphabetic() || chars[i] == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word = text(start, i);
+                let token_type =
+                    grammar
+                        .classify(&word)
+                        .unwrap_or(if i < chars.len() && chars[i] == '(' {
+                            TokenType::Function
+                        } else {
+                            TokenType::Variable
+                        });
+                tokens.push(Token {
+                    text: word,
+                    token_type,
+                });
+                continue;
+            }
+
+            // 연산자
+            if grammar.operators.contains(chars[i]) {
+                let start = i;
+                while i < chars.len() && grammar.operators.contains(chars[i]) {
+                    i += 1;
+                    if i - start >= 3 {
+                        break;
+                    }
+                }
+                tokens.push(Token {
+                    text: text(start, i),
+                    token_type: TokenType::Operator,
+                });
+                continue;
+            }
+
+            // 괄호
+            if "()[]{}".contains(chars[i]) {
+                tokens.push(Token {
+                    text: chars[i].to_string(),
+                    token_type: TokenType::Bracket,
+                });
+                i += 1;
+                continue;
+            }
+
+            // 기타 (공백, 세미콜론, 콤마 등)
+            tokens.push(Token {
+                text: chars[i].to_string(),
+                token_type: TokenType::Normal,
+            });
+            i += 1;
+        }
+
+        tokens
+    }
+
     // C-like 언어 공통 토큰화
     fn tokenize_c_like(
         &mut self,
@@ -3076,6 +3567,12 @@ impl SyntaxHighlighter {
     }
 }
 
+/// `from` 바이트 위치에서 `open`으로 시작하는 블록이 같은 라인에서 끝나면 그 끝 바이트 위치
+fn block_end(line: &str, from: usize, open: &str, close: &str) -> Option<usize> {
+    let body = from + open.len();
+    line[body..].find(close).map(|idx| body + idx + close.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3105,6 +3602,133 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detection_by_name_shebang_and_modeline() {
+        let none: &[&str] = &[];
+        assert_eq!(
+            Language::detect(Path::new("/src/Dockerfile"), none),
+            Language::Dockerfile
+        );
+        assert_eq!(
+            Language::detect(Path::new("Dockerfile.dev"), none),
+            Language::Dockerfile
+        );
+        assert_eq!(
+            Language::detect(Path::new("GNUmakefile"), none),
+            Language::Makefile
+        );
+        assert_eq!(
+            Language::detect(Path::new("main.tf"), none),
+            Language::Terraform
+        );
+        assert_eq!(Language::detect(Path::new("init.lua"), none), Language::Lua);
+        assert_eq!(
+            Language::detect(Path::new("mix.exs"), none),
+            Language::Elixir
+        );
+
+        assert_eq!(
+            Language::detect(Path::new("run"), &["#!/usr/bin/env -S python3.11 -u"]),
+            Language::Python
+        );
+        assert_eq!(
+            Language::detect(Path::new("build"), &["#!/bin/bash", "set -e"]),
+            Language::Shell
+        );
+        assert_eq!(
+            Language::detect(Path::new("tool"), &["#!/usr/local/bin/luajit"]),
+            Language::Lua
+        );
+
+        // 모드라인이 shebang과 확장자보다 우선
+        assert_eq!(
+            Language::detect(Path::new("a.txt"), &["#!/bin/sh", "# vim: set ft=zig :"]),
+            Language::Zig
+        );
+        assert_eq!(
+            Language::detect(Path::new("a.conf"), &["# -*- mode: nix -*-"]),
+            Language::Nix
+        );
+        assert_eq!(
+            Language::detect(Path::new("rules"), &["# -*- makefile-gmake -*-"]),
+            Language::Makefile
+        );
+        let mut lines = vec!["x"; 20];
+        lines.push("// vim: filetype=c++");
+        assert_eq!(Language::detect(Path::new("a.h"), &lines), Language::Cpp);
+        assert_eq!(
+            Language::detect(Path::new("a.txt"), &["# Dockerfile: set ft=docker"]),
+            Language::Plain
+        );
+    }
+
+    #[test]
+    fn test_grammar_tokenization() {
+        let colors = crate::ui::theme::Theme::default().syntax;
+        let has = |tokens: &[Token], text: &str, token_type: TokenType| {
+            tokens
+                .iter()
+                .any(|t| t.text == text && t.token_type == token_type)
+        };
+
+        let mut docker = SyntaxHighlighter::new(Language::Dockerfile, colors);
+        let tokens = docker.tokenize_line("from rust:1.75 AS build");
+        assert!(has(&tokens, "from", TokenType::Keyword));
+        assert!(has(&tokens, "AS", TokenType::Keyword));
+        let tokens = docker.tokenize_line("COPY --from=build ${APP} /app # done");
+        assert!(has(&tokens, "--from", TokenType::Attribute));
+        assert!(has(&tokens, "${APP}", TokenType::Variable));
+        assert!(has(&tokens, "# done", TokenType::Comment));
+
+        let mut make = SyntaxHighlighter::new(Language::Makefile, colors);
+        let tokens = make.tokenize_line("all: build $(OUT)");
+        // 타겟 규칙은 `:=` 와 구분하려고 콜론 뒤 공백까지 매치한다
+        assert!(has(&tokens, "all: ", TokenType::Function));
+        assert!(has(&tokens, "$(OUT)", TokenType::Variable));
+        let tokens = make.tokenize_line("CC := gcc");
+        assert!(!tokens.iter().any(|t| t.token_type == TokenType::Function));
+
+        // Lua 블록 주석은 라인 주석보다 먼저, 여러 줄에 걸쳐 이어진다
+        let mut lua = SyntaxHighlighter::new(Language::Lua, colors);
+        let tokens = lua.tokenize_line("local x = 1 --[[ start");
+        assert!(has(&tokens, "local", TokenType::Keyword));
+        assert!(has(&tokens, "--[[ start", TokenType::Comment));
+        let tokens = lua.tokenize_line("still comment ]] return nil");
+        assert!(has(&tokens, "still comment ]]", TokenType::Comment));
+        assert!(has(&tokens, "return", TokenType::Keyword));
+        assert!(has(&tokens, "nil", TokenType::Constant));
+        let tokens = lua.tokenize_line("-- plain comment");
+        assert!(has(&tokens, "-- plain comment", TokenType::Comment));
+
+        let mut zig = SyntaxHighlighter::new(Language::Zig, colors);
+        let tokens = zig.tokenize_line("const x: u8 = @intCast(y);");
+        assert!(has(&tokens, "const", TokenType::Keyword));
+        assert!(has(&tokens, "u8", TokenType::Type));
+        assert!(has(&tokens, "@intCast", TokenType::Macro));
+
+        let mut elixir = SyntaxHighlighter::new(Language::Elixir, colors);
+        let tokens = elixir.tokenize_line("defmodule Foo do @doc \"\"\"");
+        assert!(has(&tokens, "defmodule", TokenType::Keyword));
+        assert!(has(&tokens, "Foo", TokenType::Type));
+        assert!(has(&tokens, "@doc", TokenType::Attribute));
+        let tokens = elixir.tokenize_line("  docs \"\"\" :ok");
+        assert!(has(&tokens, "  docs \"\"\"", TokenType::String));
+        assert!(has(&tokens, ":ok", TokenType::Constant));
+
+        let mut nix = SyntaxHighlighter::new(Language::Nix, colors);
+        let tokens = nix.tokenize_line("let src = ./src; in true");
+        assert!(has(&tokens, "let", TokenType::Keyword));
+        assert!(has(&tokens, "./src", TokenType::String));
+        assert!(has(&tokens, "true", TokenType::Constant));
+
+        let mut tf = SyntaxHighlighter::new(Language::Terraform, colors);
+        let tokens = tf.tokenize_line("variable \"region\" { type = string } // note");
+        assert!(has(&tokens, "variable", TokenType::Keyword));
+        assert!(has(&tokens, "\"region\"", TokenType::String));
+        assert!(has(&tokens, "string", TokenType::Type));
+        assert!(has(&tokens, "// note", TokenType::Comment));
+    }
+
     #[test]
     fn test_rust_tokenization() {
         let colors = crate::ui::theme::Theme::default().syntax;
@@ -0,0 +1,221 @@
+//! Text encodings of files opened in the viewer and editor.
+//!
+//! [`TextEncoding::detect`] looks for a byte order mark first, then valid
+//! UTF-8, then whichever of CP949 (EUC-KR) and Shift-JIS decodes the bytes
+//! cleanly into the most Hangul or Kana, and falls back to ISO-8859-1, which
+//! maps every byte to a character and so always round-trips.
+
+use encoding_rs::{Encoding, EUC_KR, SHIFT_JIS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark, kept on save
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// EUC-KR with the CP949 (Unified Hangul Code) extensions
+    EucKr,
+    ShiftJis,
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Every encoding, in the order the convert command cycles through them
+    pub const ALL: [TextEncoding; 7] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::EucKr,
+        TextEncoding::ShiftJis,
+        TextEncoding::Latin1,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 BOM",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::EucKr => "EUC-KR",
+            TextEncoding::ShiftJis => "Shift-JIS",
+            TextEncoding::Latin1 => "ISO-8859-1",
+        }
+    }
+
+    /// The encoding after this one in [`TextEncoding::ALL`]
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&e| e == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16Le => b"\xFF\xFE",
+            TextEncoding::Utf16Be => b"\xFE\xFF",
+            _ => b"",
+        }
+    }
+
+    /// True when `bytes` start with the byte order mark of a UTF-16 encoding
+    pub fn has_utf16_bom(bytes: &[u8]) -> bool {
+        bytes.starts_with(TextEncoding::Utf16Le.bom())
+            || bytes.starts_with(TextEncoding::Utf16Be.bom())
+    }
+
+    /// Guess the encoding of `bytes`
+    pub fn detect(bytes: &[u8]) -> Self {
+        for encoding in [
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            if bytes.starts_with(encoding.bom()) {
+                return encoding;
+            }
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return TextEncoding::Utf8;
+        }
+        // A legacy multi-byte encoding must decode without errors, and is
+        // scored by the characters of its own script
+        let score = |encoding: &'static Encoding, script: fn(char) -> bool| {
+            encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .map(|text| text.chars().filter(|&c| script(c)).count())
+        };
+        let korean = score(EUC_KR, is_hangul).unwrap_or(0);
+        let japanese = score(SHIFT_JIS, is_kana).unwrap_or(0);
+        if korean > 0 && korean >= japanese {
+            TextEncoding::EucKr
+        } else if japanese > 0 {
+            TextEncoding::ShiftJis
+        } else {
+            TextEncoding::Latin1
+        }
+    }
+
+    /// Decode `bytes` (without the byte order mark). Malformed sequences
+    /// become U+FFFD; the second value is true when there were any.
+    pub fn decode(self, bytes: &[u8]) -> (String, bool) {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => match String::from_utf8_lossy(bytes) {
+                std::borrow::Cow::Borrowed(text) => (text.to_string(), false),
+                std::borrow::Cow::Owned(text) => (text, true),
+            },
+            TextEncoding::Latin1 => (bytes.iter().map(|&b| b as char).collect(), false),
+            _ => {
+                let (text, had_errors) = self.encoding_rs().decode_without_bom_handling(bytes);
+                (text.into_owned(), had_errors)
+            }
+        }
+    }
+
+    /// Encode `text`, with the byte order mark if the encoding has one.
+    /// Fails when `text` has characters the encoding cannot represent.
+    pub fn encode(self, text: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = self.bom().to_vec();
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => bytes.extend_from_slice(text.as_bytes()),
+            TextEncoding::Utf16Le => {
+                bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+            }
+            TextEncoding::Utf16Be => {
+                bytes.extend(text.encode_utf16().flat_map(|u| u.to_be_bytes()));
+            }
+            TextEncoding::Latin1 => {
+                for c in text.chars() {
+                    let b = u8::try_from(c).map_err(|_| self.unmappable(c))?;
+                    bytes.push(b);
+                }
+            }
+            TextEncoding::EucKr | TextEncoding::ShiftJis => {
+                let (encoded, _, had_errors) = self.encoding_rs().encode(text);
+                if had_errors {
+                    // Report the first character that does not survive a round trip
+                    let bad = text
+                        .chars()
+                        .find(|c| self.encoding_rs().encode(&c.to_string()).2)
+                        .unwrap_or('?');
+                    return Err(self.unmappable(bad));
+                }
+                bytes.extend_from_slice(&encoded);
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn unmappable(self, c: char) -> String {
+        format!(
+            "'{}' (U+{:04X}) cannot be saved as {}",
+            c,
+            c as u32,
+            self.name()
+        )
+    }
+
+    fn encoding_rs(self) -> &'static Encoding {
+        match self {
+            TextEncoding::EucKr => EUC_KR,
+            TextEncoding::ShiftJis => SHIFT_JIS,
+            TextEncoding::Utf16Le => encoding_rs::UTF_16LE,
+            TextEncoding::Utf16Be => encoding_rs::UTF_16BE,
+            TextEncoding::Latin1 => encoding_rs::WINDOWS_1252,
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => encoding_rs::UTF_8,
+        }
+    }
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c, '\u{AC00}'..='\u{D7A3}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}')
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}' | '\u{FF66}'..='\u{FF9F}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_round_trip() {
+        let samples = [
+            (TextEncoding::Utf8, "plain ascii\nand 한글"),
+            (TextEncoding::Utf8Bom, "bom\n첫 줄"),
+            (TextEncoding::Utf16Le, "utf-16 le\n日本語"),
+            (TextEncoding::Utf16Be, "utf-16 be\n한국어"),
+            (TextEncoding::EucKr, "안녕하세요, 세계!\n두 번째 줄"),
+            (TextEncoding::ShiftJis, "こんにちは、世界\nカタカナ"),
+            (
+                TextEncoding::Latin1,
+                "caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e",
+            ),
+        ];
+        for (encoding, text) in samples {
+            let bytes = encoding.encode(text).unwrap();
+            assert_eq!(TextEncoding::detect(&bytes), encoding, "{}", text);
+            let (decoded, had_errors) = encoding.decode(&bytes);
+            assert_eq!(decoded, text);
+            assert!(!had_errors);
+            // Saving again gives the same bytes
+            assert_eq!(encoding.encode(&decoded).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_unmappable_characters() {
+        let err = TextEncoding::Latin1.encode("naïve 한").unwrap_err();
+        assert!(err.contains("U+D55C"), "{}", err);
+        assert!(TextEncoding::EucKr.encode("emoji 😀").is_err());
+        assert!(TextEncoding::ShiftJis.encode("한글").is_err());
+        assert!(TextEncoding::Utf16Le.encode("😀").is_ok());
+        // Any byte sequence survives as ISO-8859-1
+        let bytes: Vec<u8> = (0..=255).collect();
+        let (text, _) = TextEncoding::Latin1.decode(&bytes);
+        assert_eq!(TextEncoding::Latin1.encode(&text).unwrap(), bytes);
+    }
+}